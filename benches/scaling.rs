@@ -8,6 +8,7 @@
 //!   grid_resolution_scaling -- step() at fixed particle count, varying grid resolution
 //!   sand_sheared          -- step() on pre-deformed sand (50 warm-up steps before measuring)
 //!   p2g                   -- scatter_particles_to_grid in isolation
+//!   p2g_parallel          -- scatter_particles_to_grid_parallel (SimConfig::parallel_p2g)
 //!   g2p                   -- gather_grid_to_particles in isolation
 //!   kirchhoff             -- kirchhoff_stress per material (NeoHookean / Sand / Fluid / Snow)
 //!   update_particle       -- plasticity update per material
@@ -148,6 +149,31 @@ fn bench_p2g(c: &mut Criterion) {
     group.finish();
 }
 
+// â”€â”€ p2g_parallel â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€
+
+fn bench_p2g_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("p2g_parallel");
+    for &target in &[2500usize, 5000, 12000] {
+        let mut fx = TransferFixture::new(target);
+        let mut scratch = emerge::transfer::P2GScratch::new();
+        let n = fx.n;
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            b.iter(|| {
+                fx.grid.clear();
+                emerge::transfer::scatter_particles_to_grid_parallel(
+                    &fx.particles,
+                    &mut fx.grid,
+                    &mut scratch,
+                    &fx.registry,
+                    fx.config.dt,
+                    fx.n,
+                );
+            });
+        });
+    }
+    group.finish();
+}
+
 // â”€â”€ g2p â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€â”€
 
 fn bench_g2p(c: &mut Criterion) {
@@ -689,6 +715,7 @@ criterion_group!(
     bench_grid_resolution_scaling,
     bench_sand_sheared,
     bench_p2g,
+    bench_p2g_parallel,
     bench_g2p,
     bench_kirchhoff,
     bench_update_particle,
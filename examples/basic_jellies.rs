@@ -3,13 +3,13 @@ extern crate emerge_engine as emerge;
 use egui_wgpu::ScreenDescriptor;
 use emerge::render::{ColorMode, Renderer};
 use emerge::{
-    CorotatedMaterial, NeoHookeanMaterial, SimConfig, Simulation, SlipBoundary, SpawnRegion,
-    ViscoelasticMaterial,
+    Constraint, ConstraintHandle, CorotatedMaterial, NeoHookeanMaterial, SimConfig, Simulation,
+    SlipBoundary, SpawnRegion, Spring, ViscoelasticMaterial,
 };
 use glam::{IVec2, Vec2};
 /// CPU elastic solids -- NeoHookean / Corotated / Viscoelastic, three-blob comparison.
 ///
-///   G  toggle ByPhysics/ByMaterial  |  LMB push  RMB pull  |  R reset  Q quit
+///   G  toggle ByPhysics/ByMaterial  |  LMB drag  |  R reset  Q quit
 ///   cargo run --example basic_jellies --features "render"
 use std::sync::Arc;
 use winit::application::ApplicationHandler;
@@ -24,6 +24,14 @@ const MAT_NEO: u32 = 0;
 const MAT_COR: u32 = 1;
 const MAT_VIS: u32 = 2;
 
+/// Particles within this many cells of the cursor are grabbed on LMB.
+const GRAB_RADIUS: f32 = 3.0;
+/// Stiff enough to lead the jelly, soft enough to let it wobble.
+const GRAB_SPRING: Spring = Spring {
+    stiffness: 40.0,
+    damping: 4.0,
+};
+
 const SIGMA_NEO: [f32; 3] = [0.05, 0.55, 0.60];
 const SIGMA_COR: [f32; 3] = [0.10, 0.45, 0.50];
 const SIGMA_VIS: [f32; 3] = [0.08, 0.35, 0.45];
@@ -72,7 +80,8 @@ struct State {
     p: Params,
     cursor_pos: [f32; 2],
     lmb: bool,
-    rmb: bool,
+    /// Drag springs of the current grab, with each particle's offset from the cursor.
+    grab: Vec<(ConstraintHandle, Vec2)>,
     physics_colors: bool,
     frame: u64,
     fps_timer: std::time::Instant,
@@ -110,7 +119,9 @@ fn make_sim(p: &Params) -> Simulation {
                 p.vis_viscosity,
             )),
         )
-        .with_boundary(Box::new(SlipBoundary::new(config.boundary_thickness)));
+        .with_boundary(Box::new(SlipBoundary::new(config.boundary_thickness)))
+        // Stable IDs let the drag springs follow their particles.
+        .with_particle_ids();
     let _ = solver.add_body(spawn(Vec2::new(32.0, 50.0), MAT_COR));
     let _ = solver.add_body(spawn(Vec2::new(50.0, 50.0), MAT_VIS));
     solver
@@ -184,7 +195,7 @@ impl State {
         );
 
         println!(
-            "jellies: {} particles  G=colors  LMB/RMB=push/pull  R=reset  Q=quit",
+            "jellies: {} particles  G=colors  LMB=drag  R=reset  Q=quit",
             sim.particles().len()
         );
         Self {
@@ -200,7 +211,7 @@ impl State {
             p,
             cursor_pos: [0.0; 2],
             lmb: false,
-            rmb: false,
+            grab: Vec::new(),
             physics_colors: true,
             frame: 0,
             fps_timer: std::time::Instant::now(),
@@ -229,9 +240,44 @@ impl State {
 
     fn reset(&mut self) {
         self.sim = make_sim(&self.p);
+        self.grab.clear();
         self.frame = 0;
     }
 
+    /// LMB grabs the particles under the cursor with point springs and drags
+    /// them along; releasing drops the springs.
+    fn drag(&mut self) {
+        let cursor = self.cursor_grid();
+        if !self.lmb {
+            for (handle, _) in self.grab.drain(..) {
+                self.sim.constraints_mut().remove(handle);
+            }
+            return;
+        }
+        if self.grab.is_empty() {
+            let grabbed: Vec<_> = self
+                .sim
+                .particles_near(cursor, GRAB_RADIUS)
+                .filter_map(|i| Some((self.sim.id_of(i)?, self.sim.particles().x[i])))
+                .collect();
+            for (particle, x) in grabbed {
+                let handle = self.sim.constraints_mut().add(Constraint::PointSpring {
+                    particle,
+                    target: x,
+                    spring: GRAB_SPRING,
+                });
+                self.grab.push((handle, x - cursor));
+            }
+        }
+        for &(handle, offset) in &self.grab {
+            if let Some(Constraint::PointSpring { target, .. }) =
+                self.sim.constraints_mut().get_mut(handle)
+            {
+                *target = cursor + offset;
+            }
+        }
+    }
+
     fn update_and_render(&mut self, window: &Window) {
         // Push live params to solver
         self.sim.set_gravity(Vec2::new(0.0, self.p.gravity));
@@ -253,10 +299,7 @@ impl State {
             )),
         );
 
-        if self.lmb || self.rmb {
-            let mag = if self.lmb { 2.0 } else { -2.0 };
-            self.sim.apply_radial_impulse(self.cursor_grid(), 6.0, mag);
-        }
+        self.drag();
         self.sim.step();
         self.frame += 1;
         self.fps_frames += 1;
@@ -307,7 +350,7 @@ impl State {
                     ui.add(egui::Slider::new(&mut p.vis_mu, 1.0..=400.0).text("mu"));
                     ui.add(egui::Slider::new(&mut p.vis_viscosity, 0.0..=5.0).text("viscosity"));
                     ui.separator();
-                    ui.label("LMB push  RMB pull  G colors  R reset");
+                    ui.label("LMB drag  G colors  R reset");
                     if ui.button("Reset").clicked() {
                         reset = true;
                     }
@@ -389,11 +432,11 @@ impl ApplicationHandler for App {
             WindowEvent::CursorMoved { position, .. } => {
                 s.cursor_pos = [position.x as f32, position.y as f32];
             }
-            WindowEvent::MouseInput { state, button, .. } => match button {
-                MouseButton::Left => s.lmb = state == ElementState::Pressed,
-                MouseButton::Right => s.rmb = state == ElementState::Pressed,
-                _ => {}
-            },
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => s.lmb = state == ElementState::Pressed,
             WindowEvent::KeyboardInput {
                 event:
                     KeyEvent {
@@ -585,6 +585,8 @@ impl State {
             Mode::Force if self.lmb || self.rmb => {
                 // Real radial impulse, same call basic_showcase_gpu's push/pull uses --
                 // no new mechanic, just exposed as a second selectable tool here.
+                // Not a constraint drag like basic_jellies: constraints are CPU-only
+                // and GpuSimulation has no constraint pass.
                 let mag = if self.lmb { 3.0 } else { -3.0 };
                 self.sim.apply_radial_impulse(self.cursor_grid(), 5.0, mag);
             }
@@ -0,0 +1,398 @@
+//! `emerge`: run scene files headless, for regression jobs and experiments.
+//!
+//! ```text
+//! emerge run <scene> [--steps N] [--log FILE] [--log-every N] [--out DIR]
+//!                    [--checkpoint-every N] [--dump-every N] [--vtk-every N]
+//!                    [--keep-going]
+//! emerge sweep <scene>... [--steps N] [--set KEY=V1,V2,...]
+//! emerge inspect <scene> [--checkpoint FILE]
+//! ```
+//! `run` steps one scene, writing `FrameLogger` NDJSON to `--log`, and
+//! checkpoints (`checkpoint_<step>.ckpt`) and particle CSV dumps
+//! (`particles_<step>.csv`) into `--out`; `--vtk-every` adds ParaView
+//! frames (`particles_<step>.vtu`, `grid_<step>.vti`) listed in
+//! `particles.pvd` and `grid.pvd`. It stops at the first frame
+//! `evaluate_stability` flags, unless `--keep-going`. `sweep` runs every
+//! scene once per `--set` value of a `[config]` key and prints one line per
+//! run; a value the scene cannot take fails that run only, and the sweep
+//! exits 2 once the rest have run. `inspect` summarizes a scene, optionally
+//! restored from a checkpoint.
+//!
+//! Exit status: 0 when every frame was healthy, 1 when one was not, 2 on a
+//! usage, scene or I/O error.
+extern crate emerge_engine as emerge;
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::Instant;
+
+use emerge::vtk::{self, PvdCollection};
+use emerge::{
+    FrameLogger, Scene, Simulation, StabilityStatus, StabilityThresholds, evaluate_stability,
+    per_material_stats,
+};
+
+const USAGE: &str = "\
+usage:
+  emerge run <scene> [--steps N] [--log FILE] [--log-every N] [--out DIR]
+                     [--checkpoint-every N] [--dump-every N] [--vtk-every N]
+                     [--keep-going]
+  emerge sweep <scene>... [--steps N] [--set KEY=V1,V2,...]
+  emerge inspect <scene> [--checkpoint FILE]";
+
+const DEFAULT_STEPS: u64 = 100;
+
+type CliResult = Result<ExitCode, Box<dyn Error>>;
+
+/// Positional arguments, `--option value` pairs and `--flag`s.
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+    flags: HashSet<String>,
+}
+
+impl Args {
+    /// Split `args`, accepting only the listed option and flag names.
+    fn parse(
+        mut args: impl Iterator<Item = String>,
+        options: &[&str],
+        flags: &[&str],
+    ) -> Result<Self, String> {
+        let mut parsed = Self {
+            positional: Vec::new(),
+            options: HashMap::new(),
+            flags: HashSet::new(),
+        };
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg);
+                continue;
+            };
+            if flags.contains(&name) {
+                parsed.flags.insert(name.to_string());
+            } else if options.contains(&name) {
+                let value = args.next().ok_or(format!("`--{name}` needs a value"))?;
+                parsed.options.insert(name.to_string(), value);
+            } else {
+                return Err(format!("unknown option `{arg}`"));
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn number<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.option(name) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("`--{name}` expects a whole number, got `{value}`")),
+            None => Ok(default),
+        }
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    /// The single positional argument of a command taking one scene.
+    fn scene_path(&self, command: &str) -> Result<&str, String> {
+        match &self.positional[..] {
+            [path] => Ok(path),
+            _ => Err(format!("`{command}` takes exactly one scene file")),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+    let result = match command.as_deref() {
+        Some("run") => Args::parse(
+            args,
+            &[
+                "steps",
+                "log",
+                "log-every",
+                "out",
+                "checkpoint-every",
+                "dump-every",
+                "vtk-every",
+            ],
+            &["keep-going"],
+        )
+        .map_err(Into::into)
+        .and_then(run),
+        Some("sweep") => Args::parse(args, &["steps", "set"], &[])
+            .map_err(Into::into)
+            .and_then(sweep),
+        Some("inspect") => Args::parse(args, &["checkpoint"], &[])
+            .map_err(Into::into)
+            .and_then(inspect),
+        Some("help" | "--help" | "-h") => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Some(other) => Err(format!("unknown command `{other}`\n{USAGE}").into()),
+        None => Err(USAGE.into()),
+    };
+    result.unwrap_or_else(|err| {
+        eprintln!("emerge: {err}");
+        ExitCode::from(2)
+    })
+}
+
+/// `step` is a multiple of `every` (`0` means never).
+fn due(step: u64, every: u64) -> bool {
+    every > 0 && step.is_multiple_of(every)
+}
+
+fn exit_code(healthy: bool) -> ExitCode {
+    if healthy {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    }
+}
+
+fn issues(status: StabilityStatus) -> String {
+    status.issue_labels().join(",")
+}
+
+/// `Scene::read`, with the path on I/O errors too (parse errors carry it).
+fn read_scene(path: &str) -> Result<Scene, String> {
+    Scene::read(path).map_err(|err| match err.kind() {
+        io::ErrorKind::InvalidData => err.to_string(),
+        _ => format!("{path}: {err}"),
+    })
+}
+
+/// `Scene::build` after `Scene::check`, so a bad scene is an error rather
+/// than a panic.
+fn build(scene: &Scene) -> Result<Simulation, String> {
+    scene.check()?;
+    Ok(scene.build())
+}
+
+/// Material ID → name pairs, as `FrameLogger::log` takes them.
+fn labels(scene: &Scene) -> Vec<(u32, &str)> {
+    (0u32..)
+        .zip(&scene.materials)
+        .map(|(id, (name, _))| (id, name.as_str()))
+        .collect()
+}
+
+fn run(args: Args) -> CliResult {
+    let path = args.scene_path("run")?;
+    let steps = args.number("steps", DEFAULT_STEPS)?;
+    let log_every = args.number("log-every", 1)?;
+    let checkpoint_every = args.number("checkpoint-every", 0)?;
+    let dump_every = args.number("dump-every", 0)?;
+    let vtk_every = args.number("vtk-every", 0)?;
+    let out = PathBuf::from(args.option("out").unwrap_or("."));
+    if checkpoint_every > 0 || dump_every > 0 || vtk_every > 0 {
+        fs::create_dir_all(&out)?;
+    }
+    let mut logger = args.option("log").map(FrameLogger::open).transpose()?;
+
+    let scene = read_scene(path)?;
+    let labels = labels(&scene);
+    let mut sim = build(&scene).map_err(|err| format!("{path}: {err}"))?;
+    let thresholds = StabilityThresholds::default();
+    let started = Instant::now();
+    let mut healthy = true;
+    let mut particle_series = PvdCollection::new();
+    let mut grid_series = PvdCollection::new();
+    for step in 1..=steps {
+        sim.step();
+        let snapshot = sim.diagnostics_snapshot();
+        if let Some(logger) = &mut logger
+            && due(step, log_every)
+        {
+            let stats = per_material_stats(sim.particles());
+            logger.log(step, sim.config().dt, &stats, &snapshot, &labels, &[]);
+        }
+        if due(step, checkpoint_every) {
+            sim.save_checkpoint(out.join(format!("checkpoint_{step:06}.ckpt")))?;
+        }
+        if due(step, dump_every) {
+            dump_particles(&sim, &out.join(format!("particles_{step:06}.csv")))?;
+        }
+        if due(step, vtk_every) {
+            // Rewrite the collections every frame so an aborted run still opens.
+            let time = step as f32 * sim.config().dt;
+            let particles = format!("particles_{step:06}.vtu");
+            let grid = format!("grid_{step:06}.vti");
+            vtk::save_vtu(sim.particles(), out.join(&particles))?;
+            vtk::save_vti(&sim.field_sampler(), out.join(&grid))?;
+            particle_series.add(time, particles);
+            grid_series.add(time, grid);
+            particle_series.save(out.join("particles.pvd"))?;
+            grid_series.save(out.join("grid.pvd"))?;
+        }
+        let status = evaluate_stability(&snapshot, &thresholds);
+        if !status.healthy() {
+            eprintln!("{path}: step {step} unhealthy [{}]", issues(status));
+            healthy = false;
+            if !args.flag("keep-going") {
+                return Ok(exit_code(false));
+            }
+        }
+    }
+    let snapshot = sim.diagnostics_snapshot();
+    println!(
+        "{path}: {steps} steps in {:.2} s, {} particles, ke {:.4}",
+        started.elapsed().as_secs_f32(),
+        snapshot.particle_count,
+        snapshot.total_kinetic_energy,
+    );
+    Ok(exit_code(healthy))
+}
+
+fn sweep(args: Args) -> CliResult {
+    if args.positional.is_empty() {
+        return Err("`sweep` needs at least one scene file".into());
+    }
+    let steps = args.number("steps", DEFAULT_STEPS)?;
+    let set = match args.option("set") {
+        Some(set) => {
+            let (key, values) = set.split_once('=').ok_or("`--set` expects KEY=V1,V2,...")?;
+            Some((key, values.split(',').collect::<Vec<_>>()))
+        }
+        None => None,
+    };
+
+    let thresholds = StabilityThresholds::default();
+    let mut healthy = true;
+    let mut errors = false;
+    for path in &args.positional {
+        // An unreadable scene fails like a bad value: the other files still run.
+        let base = match read_scene(path) {
+            Ok(scene) => scene,
+            Err(err) => {
+                errors = true;
+                println!("{path}: failed: {err}");
+                continue;
+            }
+        };
+        let runs: Vec<(String, Result<Scene, String>)> = match &set {
+            Some((key, values)) => values
+                .iter()
+                .map(|value| {
+                    let mut scene = base.clone();
+                    let set = scene.set_config(key, value).map_err(|err| err.message);
+                    (format!("{path} {key}={value}"), set.map(|()| scene))
+                })
+                .collect(),
+            None => vec![(path.clone(), Ok(base))],
+        };
+        for (label, scene) in runs {
+            // A bad value fails its own run; the rest of the sweep goes on.
+            let started = Instant::now();
+            let mut sim = match scene.and_then(|scene| build(&scene)) {
+                Ok(sim) => sim,
+                Err(err) => {
+                    errors = true;
+                    println!("{label}: failed: {err}");
+                    continue;
+                }
+            };
+            let mut failure = None;
+            for step in 1..=steps {
+                sim.step();
+                let status = evaluate_stability(&sim.diagnostics_snapshot(), &thresholds);
+                if !status.healthy() {
+                    failure = Some((step, status));
+                    break;
+                }
+            }
+            let elapsed = started.elapsed().as_secs_f32();
+            match failure {
+                Some((step, status)) => {
+                    healthy = false;
+                    println!("{label}: unhealthy at step {step} [{}]", issues(status));
+                }
+                None => println!(
+                    "{label}: ok, {steps} steps in {elapsed:.2} s, ke {:.4}",
+                    sim.diagnostics_snapshot().total_kinetic_energy
+                ),
+            }
+        }
+    }
+    if errors {
+        return Ok(ExitCode::from(2));
+    }
+    Ok(exit_code(healthy))
+}
+
+fn inspect(args: Args) -> CliResult {
+    let path = args.scene_path("inspect")?;
+    let scene = read_scene(path)?;
+    let mut sim = build(&scene).map_err(|err| format!("{path}: {err}"))?;
+    if let Some(checkpoint) = args.option("checkpoint") {
+        sim.load_checkpoint(checkpoint)?;
+    }
+    let config = sim.config();
+    let dims = config.grid_dims();
+    let snapshot = sim.diagnostics_snapshot();
+    println!("{path}");
+    println!(
+        "  grid        {} x {} cells, dx {} m, dt {} ({} s)",
+        dims.x, dims.y, config.dx_meters, config.dt, config.dt_seconds
+    );
+    let counts = sim.material_particle_counts();
+    for (id, (name, material)) in (0u32..).zip(&scene.materials) {
+        println!(
+            "  material {id}  {name} ({}), {} particles",
+            material.model(),
+            counts.get(&id).copied().unwrap_or(0)
+        );
+    }
+    println!(
+        "  setup       {} boundaries, {} fields, {} scalar fields, {} phase rules, thermal {}",
+        scene.boundaries.len(),
+        scene.fields.len(),
+        scene.scalar_fields.len(),
+        scene.phase_rules.len(),
+        if scene.thermal.is_some() { "on" } else { "off" },
+    );
+    println!(
+        "  particles   {} in {} bodies, mass {:.4}, frame {}",
+        snapshot.particle_count,
+        scene.bodies.len(),
+        snapshot.total_particle_mass,
+        snapshot.frame_index,
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// One CSV row per particle: position, velocity, mass, material, tag,
+/// temperature and volume ratio `J = det F`.
+fn dump_particles(sim: &Simulation, path: &Path) -> io::Result<()> {
+    let p = sim.particles();
+    let mut w = BufWriter::new(File::create(path)?);
+    writeln!(w, "x,y,vx,vy,mass,material,tag,temperature,j")?;
+    for i in 0..p.len() {
+        writeln!(
+            w,
+            "{},{},{},{},{},{},{},{},{}",
+            p.x[i].x,
+            p.x[i].y,
+            p.v[i].x,
+            p.v[i].y,
+            p.mass[i],
+            p.material_id[i],
+            p.user_tag[i],
+            p.temperature[i],
+            p.deformation_gradient[i].determinant(),
+        )?;
+    }
+    w.flush()
+}
@@ -16,12 +16,12 @@
 //! orders of magnitude larger than MPM's wave-speed CFL (~0.002s).
 //! Thermal CFL is never the bottleneck; no separate substep needed.
 
-use glam::IVec2;
+use glam::{IVec2, UVec2};
 
 use crate::{grid::kernel::quadratic_weights, particle::Particles};
 
 /// Configuration for grid-based thermal diffusion.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct ThermalConfig {
     /// Thermal conductivity k in W/(m·K).
     ///
@@ -77,11 +77,12 @@ impl ThermalConfig {
 
 /// Grid-based Fourier heat diffusion.
 ///
-/// Add to `Simulation` via `solver.with_thermal(ThermalDiffusion::new(config, grid_res))`.
+/// Add to `Simulation` via `solver.with_thermal(ThermalDiffusion::new(config, grid_res))`,
+/// or `ThermalDiffusion::with_dims(config, config.grid_dims())` on a non-square grid.
 /// Applied once per MPM substep, after force fields, before state projection.
 pub struct ThermalDiffusion {
     pub config: ThermalConfig,
-    grid_res: usize,
+    dims: UVec2,
     // Preallocated scratch buffers — no per-substep heap allocation.
     grid_work: Vec<f32>, // dual-use: P2G scatter (Σ w·m·T), then Laplacian output (T_new)
     grid_mass: Vec<f32>, // Σ (w · mass) per cell
@@ -90,22 +91,31 @@ pub struct ThermalDiffusion {
 
 impl ThermalDiffusion {
     pub fn new(config: ThermalConfig, grid_res: usize) -> Self {
-        let n = grid_res * grid_res;
+        Self::with_dims(config, UVec2::splat(grid_res as u32))
+    }
+
+    /// Same as `new` on a `dims.x × dims.y` grid (`SimConfig::grid_dims`).
+    pub fn with_dims(config: ThermalConfig, dims: UVec2) -> Self {
+        let n = (dims.x * dims.y) as usize;
         Self {
             config,
-            grid_res,
+            dims,
             grid_work: vec![0.0; n],
             grid_mass: vec![0.0; n],
             grid_temp: vec![0.0; n],
         }
     }
 
+    /// Grid dimensions this model was created with.
+    pub fn grid_dims(&self) -> UVec2 {
+        self.dims
+    }
+
     /// Apply one thermal substep. Call from `Simulation::do_substep` after force fields.
     ///
     /// `sub_dt`: substep duration in seconds.
     pub fn apply(&mut self, particles: &mut Particles, sub_dt: f32) {
-        let n = self.grid_res * self.grid_res;
-        let res = self.grid_res as i32;
+        let n = self.grid_work.len();
 
         // --- Clear scratch (grid_work = P2G scatter, grid_mass = weights) ---
         for i in 0..n {
@@ -123,10 +133,9 @@ impl ThermalDiffusion {
                 for gy in 0i32..3 {
                     let weight = w.wx[gx as usize] * w.wy[gy as usize];
                     let cell = w.base_cell + IVec2::new(gx - 1, gy - 1);
-                    if cell.x < 0 || cell.y < 0 || cell.x >= res || cell.y >= res {
+                    let Some(idx) = super::stencil::cell_index(cell, self.dims) else {
                         continue;
-                    }
-                    let idx = (cell.x * res + cell.y) as usize;
+                    };
                     let mw = weight * mass;
                     self.grid_work[idx] += mw * temperature;
                     self.grid_mass[idx] += mw;
@@ -150,7 +159,7 @@ impl ThermalDiffusion {
         super::stencil::laplacian_step(
             &self.grid_temp,
             &mut self.grid_work,
-            self.grid_res,
+            self.dims,
             alpha_dt,
             self.config.ambient,
         );
@@ -168,10 +177,9 @@ impl ThermalDiffusion {
                 for gy in 0i32..3 {
                     let weight = w.wx[gx as usize] * w.wy[gy as usize];
                     let cell = w.base_cell + IVec2::new(gx - 1, gy - 1);
-                    if cell.x < 0 || cell.y < 0 || cell.x >= res || cell.y >= res {
+                    let Some(idx) = super::stencil::cell_index(cell, self.dims) else {
                         continue;
-                    }
-                    let idx = (cell.x * res + cell.y) as usize;
+                    };
                     delta += weight * (self.grid_work[idx] - self.grid_temp[idx]);
                     w_sum += weight;
                 }
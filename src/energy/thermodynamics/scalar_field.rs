@@ -23,7 +23,7 @@
 //! `set` receives the **delta** (Δφ), not the new absolute value — this
 //! preserves per-particle state not captured by the grid (sparse regions, edges).
 
-use glam::IVec2;
+use glam::{IVec2, UVec2};
 
 use crate::{
     grid::kernel::quadratic_weights,
@@ -63,7 +63,7 @@ pub struct ScalarDiffusionField {
     /// Use for fire emitting heat, creatures emitting pheromone, Turing patterns, etc.
     pub source: Option<fn(&Particle, f32) -> f32>,
 
-    grid_res: usize,
+    dims: UVec2,
     grid_mass: Vec<f32>, // Σ(w · mass)          — cleared each step
     grid_norm: Vec<f32>, // φ_grid (pre-Laplacian) — needed for G2P delta
     grid_work: Vec<f32>, // dual-use: P2G scatter buffer, then Laplacian output
@@ -74,7 +74,7 @@ pub struct ScalarDiffusionField {
 }
 
 /// Configuration for a scalar diffusion field.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct ScalarDiffusionConfig {
     /// Diffusivity D in grid-units²/s.
     ///
@@ -110,13 +110,23 @@ impl ScalarDiffusionField {
         set: fn(&mut Particle, f32),
         grid_res: usize,
     ) -> Self {
-        let n = grid_res * grid_res;
+        Self::with_dims(config, get, set, UVec2::splat(grid_res as u32))
+    }
+
+    /// Same as `new` on a `dims.x × dims.y` grid (`SimConfig::grid_dims`).
+    pub fn with_dims(
+        config: ScalarDiffusionConfig,
+        get: fn(&Particle) -> f32,
+        set: fn(&mut Particle, f32),
+        dims: UVec2,
+    ) -> Self {
+        let n = (dims.x * dims.y) as usize;
         Self {
             config,
             get,
             set,
             source: None,
-            grid_res,
+            dims,
             grid_mass: vec![0.0; n],
             grid_norm: vec![0.0; n],
             grid_work: vec![0.0; n],
@@ -137,23 +147,22 @@ impl ScalarDiffusionField {
 
     /// Read-only view of the post-step scalar field on the grid.
     ///
-    /// Layout: `phi[x * grid_res + y]`.  Valid after the first call to `apply()`.
+    /// Layout: `phi[x * height + y]` (`height = grid_dims().y`).  Valid after the first call to `apply()`.
     /// Use with `ChemotaxisField::sync_from` to drive gradient-following forces.
     pub fn current_phi(&self) -> &[f32] {
         &self.grid_work
     }
 
-    /// Grid resolution this field was created with.
-    pub fn grid_res(&self) -> usize {
-        self.grid_res
+    /// Grid dimensions this field was created with.
+    pub fn grid_dims(&self) -> UVec2 {
+        self.dims
     }
 
     /// Apply one substep of diffusion to the particle set.
     ///
     /// Call once per MPM substep, after force fields.
     pub fn apply(&mut self, particles: &mut Particles, sub_dt: f32) {
-        let n = self.grid_res * self.grid_res;
-        let res = self.grid_res as i32;
+        let n = self.grid_work.len();
 
         // --- Source injection: φ += S(p)·dt before scattering ---
         if let Some(src) = self.source {
@@ -181,10 +190,9 @@ impl ScalarDiffusionField {
                 for gy in 0i32..3 {
                     let weight = w.wx[gx as usize] * w.wy[gy as usize];
                     let cell = w.base_cell + IVec2::new(gx - 1, gy - 1);
-                    if cell.x < 0 || cell.y < 0 || cell.x >= res || cell.y >= res {
+                    let Some(idx) = super::stencil::cell_index(cell, self.dims) else {
                         continue;
-                    }
-                    let idx = (cell.x * res + cell.y) as usize;
+                    };
                     let mw = weight * p.mass;
                     self.grid_work[idx] += mw * phi;
                     self.grid_mass[idx] += mw;
@@ -208,7 +216,7 @@ impl ScalarDiffusionField {
         super::stencil::laplacian_step(
             &self.grid_norm,
             &mut self.grid_work,
-            self.grid_res,
+            self.dims,
             d_dt,
             self.config.ambient,
         );
@@ -234,10 +242,9 @@ impl ScalarDiffusionField {
                 for gy in 0i32..3 {
                     let weight = w.wx[gx as usize] * w.wy[gy as usize];
                     let cell = w.base_cell + IVec2::new(gx - 1, gy - 1);
-                    if cell.x < 0 || cell.y < 0 || cell.x >= res || cell.y >= res {
+                    let Some(idx) = super::stencil::cell_index(cell, self.dims) else {
                         continue;
-                    }
-                    let idx = (cell.x * res + cell.y) as usize;
+                    };
                     delta += weight * (self.grid_work[idx] - self.grid_norm[idx]);
                     w_sum += weight;
                 }
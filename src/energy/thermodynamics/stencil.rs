@@ -7,39 +7,38 @@
 //! Newton-cooling-to-ambient) is real, not accidental duplication — see
 //! each module's own docs. Only the stencil itself was hand-copied.
 
+use glam::{IVec2, UVec2};
+
+/// Flat index of `cell` in a column-major `dims` grid (`x * dims.y + y`), or
+/// `None` off-grid — the scalar fields' P2G/G2P stencils skip those nodes.
+#[inline]
+pub(crate) fn cell_index(cell: IVec2, dims: UVec2) -> Option<usize> {
+    let in_bounds = cell.cmpge(IVec2::ZERO).all() && cell.as_uvec2().cmplt(dims).all();
+    in_bounds.then(|| (cell.x as u32 * dims.y + cell.y as u32) as usize)
+}
+
 /// Applies one explicit-Euler diffusion step: `grid_out[c] = grid_in[c] +
 /// diffusivity_dt * laplacian(grid_in, c)`.
 ///
 /// Off-grid neighbors (domain edges) are treated as `ambient` — a Dirichlet
-/// boundary condition. Column-major layout: `idx = x * grid_res + y`,
+/// boundary condition. Column-major layout: `idx = x * dims.y + y`,
 /// matching the mechanics grid.
 pub(crate) fn laplacian_step(
     grid_in: &[f32],
     grid_out: &mut [f32],
-    grid_res: usize,
+    dims: UVec2,
     diffusivity_dt: f32,
     ambient: f32,
 ) {
-    for x in 0..grid_res {
-        for y in 0..grid_res {
-            let c = x * grid_res + y;
+    let (nx, ny) = (dims.x as usize, dims.y as usize);
+    for x in 0..nx {
+        for y in 0..ny {
+            let c = x * ny + y;
             let t_c = grid_in[c];
-            let t_xm = if x > 0 {
-                grid_in[c - grid_res]
-            } else {
-                ambient
-            };
-            let t_xp = if x + 1 < grid_res {
-                grid_in[c + grid_res]
-            } else {
-                ambient
-            };
+            let t_xm = if x > 0 { grid_in[c - ny] } else { ambient };
+            let t_xp = if x + 1 < nx { grid_in[c + ny] } else { ambient };
             let t_ym = if y > 0 { grid_in[c - 1] } else { ambient };
-            let t_yp = if y + 1 < grid_res {
-                grid_in[c + 1]
-            } else {
-                ambient
-            };
+            let t_yp = if y + 1 < ny { grid_in[c + 1] } else { ambient };
             let laplacian = t_xm + t_xp + t_ym + t_yp - 4.0 * t_c;
             grid_out[c] = t_c + diffusivity_dt * laplacian;
         }
@@ -1,6 +1,7 @@
 use glam::Vec2;
 
-use super::{BoundaryCondition, apply_coulomb_wall, clamp_position_inside_grid};
+use super::{BoundaryCondition, apply_coulomb_wall, clamp_position_inside_grid, wall_bands};
+use crate::grid::GridDomain;
 
 /// Grid-level Coulomb wall boundary.
 ///
@@ -39,27 +40,37 @@ impl FrictionBoundary {
 
 impl BoundaryCondition for FrictionBoundary {
     fn apply_to_grid_velocity(&self, cell_index: usize, grid_res: usize, velocity: &mut Vec2) {
-        let t = self.thickness;
-        let hi = grid_res.saturating_sub(t + 1);
-        let x = cell_index / grid_res;
-        let y = cell_index % grid_res;
+        self.apply_to_grid_velocity_in(cell_index, GridDomain::square(grid_res), velocity);
+    }
+
+    fn clamp_particle_position(&self, position: Vec2, grid_res: usize) -> Vec2 {
+        self.clamp_particle_position_in(position, GridDomain::square(grid_res))
+    }
+
+    fn apply_to_grid_velocity_in(
+        &self,
+        cell_index: usize,
+        domain: GridDomain,
+        velocity: &mut Vec2,
+    ) {
+        let walls = wall_bands(self.thickness, cell_index, domain);
         let mu = self.friction_coefficient;
 
-        if x < t {
+        if walls.left {
             apply_coulomb_wall(velocity, Vec2::X, mu);
         }
-        if x > hi {
+        if walls.right {
             apply_coulomb_wall(velocity, Vec2::NEG_X, mu);
         }
-        if y < t {
+        if walls.bottom {
             apply_coulomb_wall(velocity, Vec2::Y, mu);
         }
-        if y > hi {
+        if walls.top {
             apply_coulomb_wall(velocity, Vec2::NEG_Y, mu);
         }
     }
 
-    fn clamp_particle_position(&self, position: Vec2, grid_res: usize) -> Vec2 {
-        clamp_position_inside_grid(self.thickness, position, grid_res)
+    fn clamp_particle_position_in(&self, position: Vec2, domain: GridDomain) -> Vec2 {
+        clamp_position_inside_grid(self.thickness, position, domain)
     }
 }
@@ -2,6 +2,7 @@ use glam::Vec2;
 
 use super::BoundaryCondition;
 use super::friction::FrictionBoundary;
+use crate::grid::GridDomain;
 use crate::particle::Particles;
 
 /// Coulomb wall friction whose EFFECTIVE grip is modulated by each particle's own
@@ -69,6 +70,20 @@ impl BoundaryCondition for GripFrictionBoundary {
         self.inner.clamp_particle_position(position, grid_res)
     }
 
+    fn apply_to_grid_velocity_in(
+        &self,
+        cell_index: usize,
+        domain: GridDomain,
+        velocity: &mut Vec2,
+    ) {
+        self.inner
+            .apply_to_grid_velocity_in(cell_index, domain, velocity);
+    }
+
+    fn clamp_particle_position_in(&self, position: Vec2, domain: GridDomain) -> Vec2 {
+        self.inner.clamp_particle_position_in(position, domain)
+    }
+
     fn post_g2p_particle(&self, particles: &mut Particles, i: usize, _grid_res: usize, _dt: f32) {
         if self.grip_gain <= 0.0 {
             return;
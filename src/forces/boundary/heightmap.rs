@@ -1,6 +1,7 @@
 use glam::Vec2;
 
-use super::BoundaryCondition;
+use super::{BoundaryCondition, clamp_position_inside_grid, wall_bands};
+use crate::grid::GridDomain;
 
 /// Heightmap terrain boundary — arbitrary ground profile + outer box walls.
 ///
@@ -10,10 +11,11 @@ use super::BoundaryCondition;
 /// tangential (horizontal) velocity component at the surface.
 ///
 /// Outer axis-aligned walls are always enforced (same as `SlipBoundary`), so the
-/// heightmap sits inside the standard simulation domain.
+/// heightmap sits inside the standard simulation domain — except on periodic
+/// axes, where there are no walls and an x-periodic terrain tiles seamlessly.
 ///
 /// # Coordinate convention
-/// Y increases upward. `heights[0]` is the left column, `heights[grid_res-1]` is the right.
+/// Y increases upward. `heights[0]` is the left column, `heights[width-1]` is the right (one per grid column).
 /// Heights beyond the array length clamp to the last value.
 ///
 /// # Usage
@@ -61,19 +63,30 @@ impl HeightmapBoundary {
 
 impl BoundaryCondition for HeightmapBoundary {
     fn apply_to_grid_velocity(&self, cell_index: usize, grid_res: usize, velocity: &mut Vec2) {
-        let x = cell_index / grid_res;
-        let y = cell_index % grid_res;
-        let t = self.wall_thickness;
-        let hi = grid_res.saturating_sub(t + 1);
+        self.apply_to_grid_velocity_in(cell_index, GridDomain::square(grid_res), velocity);
+    }
+
+    fn clamp_particle_position(&self, position: Vec2, grid_res: usize) -> Vec2 {
+        self.clamp_particle_position_in(position, GridDomain::square(grid_res))
+    }
+
+    fn apply_to_grid_velocity_in(
+        &self,
+        cell_index: usize,
+        domain: GridDomain,
+        velocity: &mut Vec2,
+    ) {
+        let (x, y) = domain.cell_of(cell_index);
+        let walls = wall_bands(self.wall_thickness, cell_index, domain);
 
         // Outer box walls — standard slip (no-penetration, free tangential).
-        if x < t {
+        if walls.left {
             velocity.x = velocity.x.max(0.0);
         }
-        if x > hi {
+        if walls.right {
             velocity.x = velocity.x.min(0.0);
         }
-        if y > hi {
+        if walls.top {
             velocity.y = velocity.y.min(0.0);
         }
 
@@ -98,14 +111,17 @@ impl BoundaryCondition for HeightmapBoundary {
         }
     }
 
-    fn clamp_particle_position(&self, position: Vec2, grid_res: usize) -> Vec2 {
+    fn clamp_particle_position_in(&self, position: Vec2, domain: GridDomain) -> Vec2 {
         // Outer walls.
-        let wall_min = self.wall_thickness.saturating_sub(1) as f32;
-        let wall_max = grid_res.saturating_sub(self.wall_thickness) as f32;
-        let mut pos = position.clamp(Vec2::splat(wall_min), Vec2::splat(wall_max));
+        let mut pos = clamp_position_inside_grid(self.wall_thickness, position, domain);
 
-        // Terrain: push particles above the surface.
-        let x_col = (pos.x as usize).min(grid_res.saturating_sub(1));
+        // Terrain: push particles above the surface. On a periodic x axis the
+        // terrain tiles, so the column wraps instead of clamping.
+        let x_col = if domain.periodic.x {
+            (pos.x.floor() as i64).rem_euclid(domain.width() as i64) as usize
+        } else {
+            (pos.x as usize).min(domain.width().saturating_sub(1))
+        };
         let terrain_h = self.height_at(x_col);
         if pos.y < terrain_h + 1.0 {
             pos.y = terrain_h + 1.0;
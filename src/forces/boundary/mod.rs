@@ -1,12 +1,13 @@
-//! Boundary conditions: the `BoundaryCondition` trait plus 6 real models,
+//! Boundary conditions: the `BoundaryCondition` trait plus 7 real models,
 //! one per file (mirrors the `materials/` one-model-per-file pattern).
 //!
-//! Shared helpers (`apply_coulomb_wall`, `apply_slip_wall_velocity`,
+//! Shared helpers (`apply_coulomb_wall`, `wall_bands`, `apply_slip_wall_velocity`,
 //! `clamp_position_inside_grid`) and their direct unit tests live here,
 //! since they're genuinely shared math, not any one model's own logic.
 
 use glam::Vec2;
 
+use crate::grid::GridDomain;
 use crate::particle::Particles;
 
 mod friction;
@@ -14,6 +15,7 @@ mod grip_friction;
 mod heightmap;
 mod predictive;
 mod ratchet_friction;
+mod sdf_collider;
 mod slip;
 
 pub use friction::FrictionBoundary;
@@ -21,6 +23,7 @@ pub use grip_friction::GripFrictionBoundary;
 pub use heightmap::HeightmapBoundary;
 pub use predictive::PredictiveBoundary;
 pub use ratchet_friction::RatchetFrictionBoundary;
+pub use sdf_collider::{SdfCollider, SdfColliderBoundary, SdfShape};
 pub use slip::SlipBoundary;
 
 pub trait BoundaryCondition: Send + Sync + core::fmt::Debug {
@@ -29,8 +32,38 @@ pub trait BoundaryCondition: Send + Sync + core::fmt::Debug {
     /// Not a physical force — last-resort domain enforcement so particles never escape the grid.
     /// Proper no-penetration physics lives in `apply_to_grid_velocity`.
     fn clamp_particle_position(&self, position: Vec2, grid_res: usize) -> Vec2;
+    /// `apply_to_grid_velocity` on an arbitrary `GridDomain` — non-square
+    /// (`SimConfig::grid_res_y`) and/or periodic on some axes, where walls must
+    /// not exist. This is what the solver calls. The default forwards to
+    /// `apply_to_grid_velocity` with `grid_res = domain.height()`, under which
+    /// `cell_index / grid_res` and `cell_index % grid_res` still decode the node's
+    /// (x, y); it is only exact for square, non-periodic domains, so debug
+    /// builds assert that. Boundaries with domain walls override it (every
+    /// built-in one does).
+    fn apply_to_grid_velocity_in(
+        &self,
+        cell_index: usize,
+        domain: GridDomain,
+        velocity: &mut Vec2,
+    ) {
+        debug_assert_square_closed(self, domain);
+        self.apply_to_grid_velocity(cell_index, domain.height(), velocity);
+    }
+    /// `clamp_particle_position` counterpart of `apply_to_grid_velocity_in`:
+    /// clamps to each axis' own extent and leaves periodic axes unclamped (the
+    /// solver wraps them). Same default forwarding and debug assertion.
+    fn clamp_particle_position_in(&self, position: Vec2, domain: GridDomain) -> Vec2 {
+        debug_assert_square_closed(self, domain);
+        self.clamp_particle_position(position, domain.height())
+    }
+    /// `grid_res` is the grid width (`SimConfig::grid_res`).
     fn post_g2p_particle(&self, _particles: &mut Particles, _i: usize, _grid_res: usize, _dt: f32) {
     }
+    /// Called once at the start of every substep with that substep's `dt`, before
+    /// P2G. Lets a moving boundary (e.g. `SdfColliderBoundary`'s kinematic
+    /// colliders) integrate its own pose so the grid sees it where it really is.
+    /// Static boundaries ignore it.
+    fn advance(&self, _dt: f32) {}
 }
 
 /// Delegating impl so an `Arc<T>` can be boxed as a `BoundaryCondition` directly —
@@ -47,9 +80,26 @@ impl<T: BoundaryCondition + ?Sized> BoundaryCondition for std::sync::Arc<T> {
         (**self).clamp_particle_position(position, grid_res)
     }
 
+    fn apply_to_grid_velocity_in(
+        &self,
+        cell_index: usize,
+        domain: GridDomain,
+        velocity: &mut Vec2,
+    ) {
+        (**self).apply_to_grid_velocity_in(cell_index, domain, velocity);
+    }
+
+    fn clamp_particle_position_in(&self, position: Vec2, domain: GridDomain) -> Vec2 {
+        (**self).clamp_particle_position_in(position, domain)
+    }
+
     fn post_g2p_particle(&self, particles: &mut Particles, i: usize, grid_res: usize, dt: f32) {
         (**self).post_g2p_particle(particles, i, grid_res, dt);
     }
+
+    fn advance(&self, dt: f32) {
+        (**self).advance(dt);
+    }
 }
 
 /// Apply Coulomb wall friction along one wall face.
@@ -75,27 +125,59 @@ pub(crate) fn apply_coulomb_wall(velocity: &mut Vec2, outward_normal: Vec2, mu:
     };
 }
 
+/// The default `_in` methods only see `grid_res`, which cannot describe a
+/// non-square or periodic domain.
+#[inline]
+fn debug_assert_square_closed<B: BoundaryCondition + ?Sized>(boundary: &B, domain: GridDomain) {
+    debug_assert!(
+        domain.width() == domain.height() && !domain.periodic.any(),
+        "{boundary:?} only implements the square-grid methods; override \
+         `apply_to_grid_velocity_in` / `clamp_particle_position_in` to run on {domain:?}"
+    );
+}
+
+/// Which wall bands of a `thickness`-cell box the node at `cell_index` lies in.
+/// Each axis uses its own extent; periodic axes have no walls, so their bands
+/// are never set.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct WallBands {
+    pub left: bool,
+    pub right: bool,
+    pub bottom: bool,
+    pub top: bool,
+}
+
+pub(crate) fn wall_bands(thickness: usize, cell_index: usize, domain: GridDomain) -> WallBands {
+    let (x, y) = domain.cell_of(cell_index);
+    let hi_x = domain.width().saturating_sub(thickness + 1);
+    let hi_y = domain.height().saturating_sub(thickness + 1);
+    WallBands {
+        left: !domain.periodic.x && x < thickness,
+        right: !domain.periodic.x && x > hi_x,
+        bottom: !domain.periodic.y && y < thickness,
+        top: !domain.periodic.y && y > hi_y,
+    }
+}
+
 pub(crate) fn apply_slip_wall_velocity(
     thickness: usize,
     cell_index: usize,
-    grid_res: usize,
+    domain: GridDomain,
     velocity: &mut Vec2,
 ) {
-    let hi = grid_res - (thickness + 1);
-    let x = cell_index / grid_res;
-    let y = cell_index % grid_res;
+    let walls = wall_bands(thickness, cell_index, domain);
     // Only block the inward component — let outward (escape) velocity pass through.
     // Standard MPM slip: no-penetration, free tangential slip.
-    if x < thickness {
+    if walls.left {
         velocity.x = velocity.x.max(0.0);
     }
-    if x > hi {
+    if walls.right {
         velocity.x = velocity.x.min(0.0);
     }
-    if y < thickness {
+    if walls.bottom {
         velocity.y = velocity.y.max(0.0);
     }
-    if y > hi {
+    if walls.top {
         velocity.y = velocity.y.min(0.0);
     }
 }
@@ -103,11 +185,14 @@ pub(crate) fn apply_slip_wall_velocity(
 pub(crate) fn clamp_position_inside_grid(
     thickness: usize,
     position: Vec2,
-    grid_res: usize,
+    domain: GridDomain,
 ) -> Vec2 {
-    let min = thickness.saturating_sub(1) as f32;
-    let max = grid_res.saturating_sub(thickness) as f32;
-    position.clamp(Vec2::splat(min), Vec2::splat(max))
+    let min = Vec2::splat(thickness.saturating_sub(1) as f32);
+    let max = domain
+        .dims
+        .saturating_sub(glam::UVec2::splat(thickness as u32))
+        .as_vec2();
+    Vec2::select(domain.periodic, position, position.clamp(min, max))
 }
 
 #[cfg(test)]
@@ -124,7 +209,12 @@ mod boundary_physics_tests {
         // x=0 (left wall zone), y=32 (mid-grid, clear of every other wall) --
         // isolates the left wall's check alone, avoids corner-cell double-hits.
         let mut v = Vec2::new(-3.0, 7.5); // moving into the wall, tangential=7.5
-        apply_slip_wall_velocity(2, /* cell_index for x=0,y=32 */ 32, 64, &mut v);
+        apply_slip_wall_velocity(
+            2,
+            /* cell_index for x=0,y=32 */ 32,
+            GridDomain::square(64),
+            &mut v,
+        );
         assert_eq!(
             v.y, 7.5,
             "tangential (Y) component must pass through exactly unchanged"
@@ -143,7 +233,12 @@ mod boundary_physics_tests {
         // x=0 (left wall zone), y=32 (mid-grid, clear of every other wall) --
         // isolates the left wall's check alone, avoids corner-cell double-hits.
         let mut v = Vec2::new(4.0, -2.0); // moving AWAY from the left wall
-        apply_slip_wall_velocity(2, /* cell_index for x=0,y=32 */ 32, 64, &mut v);
+        apply_slip_wall_velocity(
+            2,
+            /* cell_index for x=0,y=32 */ 32,
+            GridDomain::square(64),
+            &mut v,
+        );
         assert_eq!(
             v,
             Vec2::new(4.0, -2.0),
@@ -160,7 +255,7 @@ mod boundary_physics_tests {
         apply_coulomb_wall(&mut v_friction, Vec2::X, 0.0);
 
         let mut v_slip = Vec2::new(-3.0, 7.5);
-        apply_slip_wall_velocity(2, 0, 64, &mut v_slip);
+        apply_slip_wall_velocity(2, 0, GridDomain::square(64), &mut v_slip);
 
         assert_eq!(
             v_friction.y, v_slip.y,
@@ -208,6 +303,23 @@ mod boundary_physics_tests {
         );
     }
 
+    /// Walls sit at each axis' own far edge on a non-square grid, and periodic
+    /// axes have none at all.
+    #[test]
+    fn wall_bands_follow_each_axis_extent_and_skip_periodic_axes() {
+        let domain = GridDomain::new(glam::UVec2::new(64, 16));
+        // (x=40, y=14): inside in x, in the top band of a 16-tall grid.
+        let walls = wall_bands(2, 40 * 16 + 14, domain);
+        assert!(walls.top && !walls.left && !walls.right && !walls.bottom);
+        // (x=63, y=8): right band only.
+        let walls = wall_bands(2, 63 * 16 + 8, domain);
+        assert!(walls.right && !walls.top);
+        let periodic_x = domain.with_periodic(glam::BVec2::new(true, false));
+        assert!(!wall_bands(2, 63 * 16 + 8, periodic_x).right);
+        let p = clamp_position_inside_grid(2, Vec2::new(70.0, 30.0), periodic_x);
+        assert_eq!(p, Vec2::new(70.0, 14.0));
+    }
+
     /// Outward-moving velocity must be completely untouched by Coulomb friction
     /// too, same as the slip wall -- friction only applies to genuine impacts.
     #[test]
@@ -220,4 +332,26 @@ mod boundary_physics_tests {
             "outward velocity must be completely untouched"
         );
     }
+
+    /// A boundary that only implements the square-grid methods must not be
+    /// handed a domain it cannot index: the default `_in` forwarding would
+    /// silently mis-decode cells, so debug builds stop there.
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "only implements the square-grid methods")]
+    fn default_in_methods_reject_non_square_domains() {
+        #[derive(Debug)]
+        struct SquareOnly;
+
+        impl BoundaryCondition for SquareOnly {
+            fn apply_to_grid_velocity(&self, _: usize, _: usize, _: &mut Vec2) {}
+            fn clamp_particle_position(&self, position: Vec2, _: usize) -> Vec2 {
+                position
+            }
+        }
+
+        let domain = GridDomain::new(glam::UVec2::new(64, 16));
+        let mut velocity = Vec2::ZERO;
+        SquareOnly.apply_to_grid_velocity_in(0, domain, &mut velocity);
+    }
 }
@@ -1,6 +1,7 @@
 use glam::Vec2;
 
 use super::{BoundaryCondition, apply_slip_wall_velocity};
+use crate::grid::GridDomain;
 
 /// Grid-level slip boundary with a tighter inner keep-out zone.
 ///
@@ -25,12 +26,25 @@ impl PredictiveBoundary {
 
 impl BoundaryCondition for PredictiveBoundary {
     fn apply_to_grid_velocity(&self, cell_index: usize, grid_res: usize, velocity: &mut Vec2) {
-        apply_slip_wall_velocity(self.thickness, cell_index, grid_res, velocity);
+        self.apply_to_grid_velocity_in(cell_index, GridDomain::square(grid_res), velocity);
     }
 
     fn clamp_particle_position(&self, position: Vec2, grid_res: usize) -> Vec2 {
-        let min = self.predictive_wall_min;
-        let max = (grid_res as f32 - 1.0) - self.predictive_wall_min;
-        position.clamp(Vec2::splat(min), Vec2::splat(max))
+        self.clamp_particle_position_in(position, GridDomain::square(grid_res))
+    }
+
+    fn apply_to_grid_velocity_in(
+        &self,
+        cell_index: usize,
+        domain: GridDomain,
+        velocity: &mut Vec2,
+    ) {
+        apply_slip_wall_velocity(self.thickness, cell_index, domain, velocity);
+    }
+
+    fn clamp_particle_position_in(&self, position: Vec2, domain: GridDomain) -> Vec2 {
+        let min = Vec2::splat(self.predictive_wall_min);
+        let max = (domain.dims.as_vec2() - 1.0) - self.predictive_wall_min;
+        Vec2::select(domain.periodic, position, position.clamp(min, max))
     }
 }
@@ -1,6 +1,7 @@
 use glam::Vec2;
 
-use super::{BoundaryCondition, apply_coulomb_wall, clamp_position_inside_grid};
+use super::{BoundaryCondition, apply_coulomb_wall, clamp_position_inside_grid, wall_bands};
+use crate::grid::GridDomain;
 
 /// Directional (anisotropic) Coulomb floor friction — a real "ratchet" mechanism,
 /// not a phase-gated one.
@@ -120,28 +121,38 @@ impl RatchetFrictionBoundary {
 
 impl BoundaryCondition for RatchetFrictionBoundary {
     fn apply_to_grid_velocity(&self, cell_index: usize, grid_res: usize, velocity: &mut Vec2) {
-        let t = self.thickness;
-        let hi = grid_res.saturating_sub(t + 1);
-        let x = cell_index / grid_res;
-        let y = cell_index % grid_res;
+        self.apply_to_grid_velocity_in(cell_index, GridDomain::square(grid_res), velocity);
+    }
+
+    fn clamp_particle_position(&self, position: Vec2, grid_res: usize) -> Vec2 {
+        self.clamp_particle_position_in(position, GridDomain::square(grid_res))
+    }
+
+    fn apply_to_grid_velocity_in(
+        &self,
+        cell_index: usize,
+        domain: GridDomain,
+        velocity: &mut Vec2,
+    ) {
+        let walls = wall_bands(self.thickness, cell_index, domain);
 
         // Side and ceiling walls: plain symmetric slip+friction, same as
         // FrictionBoundary — the ratchet only applies to the floor, where a
         // resting/crawling body actually spends its contact time.
         let mu_side = 0.5 * (self.mu_easy() + self.mu_resist());
-        if x < t {
+        if walls.left {
             apply_coulomb_wall(velocity, Vec2::X, mu_side);
         }
-        if x > hi {
+        if walls.right {
             apply_coulomb_wall(velocity, Vec2::NEG_X, mu_side);
         }
-        if y > hi {
+        if walls.top {
             apply_coulomb_wall(velocity, Vec2::NEG_Y, mu_side);
         }
 
         // Floor: directional friction. Tangential (horizontal) motion aligned
         // with the LIVE easy_direction gets mu_easy; opposing motion gets mu_resist.
-        if y < t {
+        if walls.bottom {
             let v_n_scalar = velocity.dot(Vec2::Y);
             if v_n_scalar < 0.0 {
                 let easy_direction = self.easy_direction();
@@ -157,7 +168,7 @@ impl BoundaryCondition for RatchetFrictionBoundary {
         }
     }
 
-    fn clamp_particle_position(&self, position: Vec2, grid_res: usize) -> Vec2 {
-        clamp_position_inside_grid(self.thickness, position, grid_res)
+    fn clamp_particle_position_in(&self, position: Vec2, domain: GridDomain) -> Vec2 {
+        clamp_position_inside_grid(self.thickness, position, domain)
     }
 }
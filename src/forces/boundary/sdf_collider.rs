@@ -0,0 +1,537 @@
+use std::sync::RwLock;
+
+use glam::{Mat2, Vec2};
+
+use super::{BoundaryCondition, apply_coulomb_wall};
+use crate::grid::GridDomain;
+use crate::particle::Particles;
+
+/// Collider shape in its own local frame (origin = collider position, +X = the
+/// collider's rotation). Signed distance is negative inside, positive outside.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq)]
+pub enum SdfShape {
+    /// Disk of `radius` grid cells centered on the origin.
+    Circle { radius: f32 },
+    /// Segment from `(-half_length, 0)` to `(half_length, 0)` inflated by `radius`.
+    Capsule { half_length: f32, radius: f32 },
+    /// Convex polygon, vertices in either winding order. Build via
+    /// `SdfShape::convex_polygon`, which checks convexity.
+    ConvexPolygon { vertices: Vec<Vec2> },
+    /// Union of shapes sharing one rigid frame (an L-shaped paddle, a mixer's
+    /// blades). Distance is the minimum over the parts; the normal comes from
+    /// whichever part is closest.
+    Union(Vec<SdfShape>),
+}
+
+impl SdfShape {
+    pub fn circle(radius: f32) -> Self {
+        assert!(radius > 0.0, "circle radius must be positive");
+        Self::Circle { radius }
+    }
+
+    pub fn capsule(half_length: f32, radius: f32) -> Self {
+        assert!(
+            half_length >= 0.0 && radius > 0.0,
+            "capsule needs half_length >= 0 and radius > 0"
+        );
+        Self::Capsule {
+            half_length,
+            radius,
+        }
+    }
+
+    /// Axis-aligned (in the local frame) box of half-extents `half`.
+    pub fn rect(half: Vec2) -> Self {
+        Self::convex_polygon(vec![
+            Vec2::new(-half.x, -half.y),
+            Vec2::new(half.x, -half.y),
+            Vec2::new(half.x, half.y),
+            Vec2::new(-half.x, half.y),
+        ])
+    }
+
+    pub fn convex_polygon(vertices: Vec<Vec2>) -> Self {
+        assert!(vertices.len() >= 3, "polygon needs at least 3 vertices");
+        let n = vertices.len();
+        let mut sign = 0.0f32;
+        for i in 0..n {
+            let a = vertices[i];
+            let b = vertices[(i + 1) % n];
+            let c = vertices[(i + 2) % n];
+            let cross = (b - a).perp_dot(c - b);
+            if cross.abs() > f32::EPSILON {
+                assert!(
+                    sign == 0.0 || cross.signum() == sign,
+                    "SdfShape::convex_polygon: vertices are not convex"
+                );
+                sign = cross.signum();
+            }
+        }
+        assert!(sign != 0.0, "SdfShape::convex_polygon: degenerate polygon");
+        Self::ConvexPolygon { vertices }
+    }
+
+    pub fn union(parts: Vec<SdfShape>) -> Self {
+        assert!(!parts.is_empty(), "union needs at least one part");
+        Self::Union(parts)
+    }
+
+    /// Signed distance and outward unit normal at local point `p`.
+    pub fn distance_and_normal(&self, p: Vec2) -> (f32, Vec2) {
+        match self {
+            Self::Circle { radius } => {
+                let len = p.length();
+                (len - radius, normal_or_up(p, len))
+            }
+            Self::Capsule {
+                half_length,
+                radius,
+            } => {
+                let closest = Vec2::new(p.x.clamp(-half_length, *half_length), 0.0);
+                let d = p - closest;
+                let len = d.length();
+                (len - radius, normal_or_up(d, len))
+            }
+            Self::ConvexPolygon { vertices } => polygon_distance(vertices, p),
+            Self::Union(parts) => parts
+                .iter()
+                .map(|s| s.distance_and_normal(p))
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .unwrap_or((f32::INFINITY, Vec2::Y)),
+        }
+    }
+}
+
+/// Degenerate case (point exactly on the medial axis of a circle/capsule): any
+/// direction is a valid normal, +Y is as good as any and keeps the math finite.
+fn normal_or_up(d: Vec2, len: f32) -> Vec2 {
+    if len > f32::EPSILON { d / len } else { Vec2::Y }
+}
+
+/// Exact polygon SDF: distance to the closest edge, sign from an even-odd
+/// crossing test (orientation-agnostic, so vertex winding doesn't matter).
+fn polygon_distance(vertices: &[Vec2], p: Vec2) -> (f32, Vec2) {
+    let n = vertices.len();
+    let mut best_d2 = f32::INFINITY;
+    let mut best_delta = Vec2::ZERO;
+    let mut best_edge_normal = Vec2::Y;
+    let mut inside = false;
+    for i in 0..n {
+        let a = vertices[i];
+        let b = vertices[(i + 1) % n];
+        let e = b - a;
+        let t = ((p - a).dot(e) / e.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+        let delta = p - (a + e * t);
+        let d2 = delta.length_squared();
+        if d2 < best_d2 {
+            best_d2 = d2;
+            best_delta = delta;
+            best_edge_normal = e.perp().normalize_or_zero();
+        }
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * e.x {
+            inside = !inside;
+        }
+    }
+    let dist = best_d2.sqrt();
+    let sign = if inside { -1.0 } else { 1.0 };
+    let normal = if dist > f32::EPSILON {
+        sign * best_delta / dist
+    } else {
+        // Exactly on an edge: fall back to the edge's own normal, oriented
+        // away from the polygon's vertex centroid.
+        let centroid = vertices.iter().copied().sum::<Vec2>() / n as f32;
+        let outward = p - centroid;
+        if best_edge_normal.dot(outward) < 0.0 {
+            -best_edge_normal
+        } else {
+            best_edge_normal
+        }
+    };
+    (sign * dist, normal)
+}
+
+/// One rigid, kinematic obstacle: a shape plus a prescribed pose and velocity.
+///
+/// "Kinematic" means the material never pushes back — the collider moves exactly
+/// as prescribed, at `linear_velocity` / `angular_velocity` (radians/s, CCW),
+/// and grid nodes inside it are driven toward its rigid-body velocity field
+/// `v(x) = linear_velocity + angular_velocity × (x − position)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SdfCollider {
+    pub shape: SdfShape,
+    pub position: Vec2,
+    /// Rotation of the local frame, radians CCW.
+    pub rotation: f32,
+    pub linear_velocity: Vec2,
+    pub angular_velocity: f32,
+    /// Coulomb friction coefficient µ between material and this collider.
+    /// 0.0 = slip, same convention as `FrictionBoundary`.
+    pub friction: f32,
+}
+
+impl SdfCollider {
+    /// Static, frictionless collider at `position`.
+    pub fn new(shape: SdfShape, position: Vec2) -> Self {
+        Self {
+            shape,
+            position,
+            rotation: 0.0,
+            linear_velocity: Vec2::ZERO,
+            angular_velocity: 0.0,
+            friction: 0.0,
+        }
+    }
+
+    pub fn rotation(mut self, radians: f32) -> Self {
+        self.rotation = radians;
+        self
+    }
+
+    pub fn velocity(mut self, linear: Vec2) -> Self {
+        self.linear_velocity = linear;
+        self
+    }
+
+    pub fn angular_velocity(mut self, radians_per_s: f32) -> Self {
+        self.angular_velocity = radians_per_s;
+        self
+    }
+
+    pub fn friction(mut self, mu: f32) -> Self {
+        assert!(mu >= 0.0, "collider friction must be non-negative");
+        self.friction = mu;
+        self
+    }
+
+    /// World-space signed distance and outward unit normal at `p`.
+    pub fn distance_and_normal(&self, p: Vec2) -> (f32, Vec2) {
+        let rot = Mat2::from_angle(self.rotation);
+        let local = rot.transpose() * (p - self.position);
+        let (d, n) = self.shape.distance_and_normal(local);
+        (d, rot * n)
+    }
+
+    /// Rigid-body velocity of the collider's material point coincident with `p`.
+    pub fn velocity_at(&self, p: Vec2) -> Vec2 {
+        self.linear_velocity + self.angular_velocity * (p - self.position).perp()
+    }
+
+    fn advance(&mut self, dt: f32) {
+        self.position += self.linear_velocity * dt;
+        self.rotation += self.angular_velocity * dt;
+    }
+}
+
+/// Arbitrary static and moving obstacles described as signed distance fields.
+///
+/// Grid nodes within `skin` of a collider surface get the collider's velocity
+/// as the no-penetration reference: the node's velocity RELATIVE to the collider
+/// loses its inward normal component and has Coulomb friction applied to the
+/// tangential part (same `apply_coulomb_wall` math as `FrictionBoundary`, just
+/// with the SDF normal and a moving wall). Particles that still end up inside
+/// after G2P are projected back to the surface along the normal, and their
+/// relative velocity gets the same Coulomb treatment in `post_g2p_particle`.
+///
+/// Collider-only: does NOT enforce the outer box walls. Stack it with the
+/// simulation's wall boundary (`with_boundary` appends, the default
+/// `SlipBoundary` stays).
+///
+/// Poses advance automatically once per substep from each collider's velocity
+/// (`BoundaryCondition::advance`). For game-controlled props, wrap it in an
+/// `Arc`, install a clone with `with_boundary(Box::new(arc.clone()))`, and call
+/// `set_velocity`/`set_pose` from the game loop — same shared-instance pattern as
+/// `RatchetFrictionBoundary::set_easy_direction`.
+///
+/// ```rust,no_run
+/// # extern crate emerge_engine as emerge;
+/// use emerge::{SdfCollider, SdfColliderBoundary, SdfShape};
+/// use glam::Vec2;
+/// // A rotating two-blade mixer in the middle of a 64-cell domain.
+/// let blades = SdfShape::union(vec![
+///     SdfShape::capsule(10.0, 1.0),
+///     SdfShape::rect(Vec2::new(1.0, 10.0)),
+/// ]);
+/// let mixer = SdfColliderBoundary::new(vec![
+///     SdfCollider::new(blades, Vec2::splat(32.0)).angular_velocity(1.5).friction(0.3),
+/// ]);
+/// ```
+#[derive(Debug)]
+pub struct SdfColliderBoundary {
+    colliders: RwLock<Vec<SdfCollider>>,
+    /// Band (grid cells) outside each surface where grid nodes are still
+    /// constrained. 1.0 (default) covers the node layer a particle sitting on
+    /// the surface scatters into, so material stops AT the surface rather than
+    /// half a cell inside it.
+    pub skin: f32,
+}
+
+impl SdfColliderBoundary {
+    pub fn new(colliders: Vec<SdfCollider>) -> Self {
+        Self {
+            colliders: RwLock::new(colliders),
+            skin: 1.0,
+        }
+    }
+
+    pub fn with_skin(mut self, skin: f32) -> Self {
+        assert!(skin >= 0.0, "skin must be non-negative");
+        self.skin = skin;
+        self
+    }
+
+    /// Append a collider; returns its index for later `set_*` calls.
+    pub fn add_collider(&self, collider: SdfCollider) -> usize {
+        let mut colliders = self.write();
+        colliders.push(collider);
+        colliders.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Snapshot of collider `i` (current pose included), if it exists.
+    pub fn collider(&self, i: usize) -> Option<SdfCollider> {
+        self.read().get(i).cloned()
+    }
+
+    /// Snapshot of every collider, poses and velocities included. Moving
+    /// poses are simulation state a checkpoint cannot reach (boundaries are
+    /// trait objects): save this next to it and `set_colliders` after restoring.
+    pub fn colliders(&self) -> Vec<SdfCollider> {
+        self.read().clone()
+    }
+
+    /// Replace every collider, e.g. with a `colliders` snapshot.
+    pub fn set_colliders(&self, colliders: Vec<SdfCollider>) {
+        *self.write() = colliders;
+    }
+
+    /// Teleport collider `i`. Velocity is unchanged — for smooth scripted motion
+    /// prefer `set_velocity` so grid nodes see the real surface speed.
+    pub fn set_pose(&self, i: usize, position: Vec2, rotation: f32) {
+        if let Some(c) = self.write().get_mut(i) {
+            c.position = position;
+            c.rotation = rotation;
+        }
+    }
+
+    pub fn set_velocity(&self, i: usize, linear: Vec2, angular: f32) {
+        if let Some(c) = self.write().get_mut(i) {
+            c.linear_velocity = linear;
+            c.angular_velocity = angular;
+        }
+    }
+
+    /// Collider with the smallest signed distance at `p`, with that distance
+    /// and normal. `None` when there are no colliders.
+    fn nearest(colliders: &[SdfCollider], p: Vec2) -> Option<(&SdfCollider, f32, Vec2)> {
+        colliders
+            .iter()
+            .map(|c| {
+                let (d, n) = c.distance_and_normal(p);
+                (c, d, n)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    // A poisoned lock only means a game-loop thread panicked mid-`set_*`; the
+    // collider list itself is always in a valid state, so keep simulating.
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Vec<SdfCollider>> {
+        self.colliders.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Vec<SdfCollider>> {
+        self.colliders.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Distance below which a particle counts as touching a collider in
+/// `post_g2p_particle` — covers the rounding left by the position projection.
+const SURFACE_TOLERANCE: f32 = 1.0e-3;
+
+/// Coulomb contact against a moving surface: the same `apply_coulomb_wall`
+/// rule, applied to the velocity relative to the surface.
+fn apply_moving_coulomb(velocity: &mut Vec2, surface_velocity: Vec2, normal: Vec2, mu: f32) {
+    let mut relative = *velocity - surface_velocity;
+    apply_coulomb_wall(&mut relative, normal, mu);
+    *velocity = surface_velocity + relative;
+}
+
+impl BoundaryCondition for SdfColliderBoundary {
+    fn apply_to_grid_velocity(&self, cell_index: usize, grid_res: usize, velocity: &mut Vec2) {
+        self.apply_to_grid_velocity_in(cell_index, GridDomain::square(grid_res), velocity);
+    }
+
+    fn clamp_particle_position(&self, position: Vec2, grid_res: usize) -> Vec2 {
+        self.clamp_particle_position_in(position, GridDomain::square(grid_res))
+    }
+
+    /// Colliders live in grid space and do not wrap: on a periodic axis a
+    /// collider near one edge is not seen from the other.
+    fn apply_to_grid_velocity_in(
+        &self,
+        cell_index: usize,
+        domain: GridDomain,
+        velocity: &mut Vec2,
+    ) {
+        // Node position: cell center, matching `quadratic_weights`' `cell_pos + 0.5`.
+        let (x, y) = domain.cell_of(cell_index);
+        let node = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+        let colliders = self.read();
+        for c in colliders.iter() {
+            let (d, n) = c.distance_and_normal(node);
+            if d < self.skin {
+                apply_moving_coulomb(velocity, c.velocity_at(node), n, c.friction);
+            }
+        }
+    }
+
+    fn clamp_particle_position_in(&self, position: Vec2, _domain: GridDomain) -> Vec2 {
+        let colliders = self.read();
+        let mut pos = position;
+        // One projection per collider, in order: enough for separated obstacles.
+        // A point wedged between two overlapping colliders is resolved by
+        // whichever comes last — acceptable for a last-resort clamp.
+        for c in colliders.iter() {
+            let (d, n) = c.distance_and_normal(pos);
+            if d < 0.0 {
+                pos -= d * n;
+            }
+        }
+        pos
+    }
+
+    fn post_g2p_particle(&self, particles: &mut Particles, i: usize, _grid_res: usize, _dt: f32) {
+        let colliders = self.read();
+        let x = particles.x[i];
+        if let Some((c, d, n)) = Self::nearest(&colliders, x)
+            && d < SURFACE_TOLERANCE
+        {
+            // Only particles the position clamp just put on (or left touching) a
+            // surface: the grid already handled everything with a real gap.
+            apply_moving_coulomb(&mut particles.v[i], c.velocity_at(x), n, c.friction);
+        }
+    }
+
+    fn advance(&self, dt: f32) {
+        for c in self.write().iter_mut() {
+            c.advance(dt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1.0e-4
+    }
+
+    #[test]
+    fn shape_distances_and_normals_are_exact() {
+        let (d, n) = SdfShape::circle(2.0).distance_and_normal(Vec2::new(3.0, 0.0));
+        assert!(close(d, 1.0) && n.abs_diff_eq(Vec2::X, 1.0e-5));
+
+        let capsule = SdfShape::capsule(3.0, 1.0);
+        let (d, n) = capsule.distance_and_normal(Vec2::new(1.0, -0.5));
+        assert!(close(d, -0.5) && n.abs_diff_eq(Vec2::NEG_Y, 1.0e-5));
+        let (d, _) = capsule.distance_and_normal(Vec2::new(5.0, 0.0));
+        assert!(close(d, 1.0));
+
+        let square = SdfShape::rect(Vec2::splat(1.0));
+        let (d, n) = square.distance_and_normal(Vec2::new(0.0, 0.75));
+        assert!(close(d, -0.25) && n.abs_diff_eq(Vec2::Y, 1.0e-5));
+        let (d, _) = square.distance_and_normal(Vec2::new(2.0, 2.0));
+        assert!(close(d, 2.0f32.sqrt()));
+
+        let union = SdfShape::union(vec![
+            SdfShape::circle(1.0),
+            SdfShape::rect(Vec2::splat(0.5)),
+        ]);
+        let (d, _) = union.distance_and_normal(Vec2::new(0.0, 3.0));
+        assert!(close(d, 2.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "not convex")]
+    fn concave_polygon_is_rejected() {
+        SdfShape::convex_polygon(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(1.0, 0.5),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+        ]);
+    }
+
+    #[test]
+    fn rotated_collider_transforms_distance_and_normal() {
+        let c = SdfCollider::new(SdfShape::capsule(4.0, 1.0), Vec2::splat(10.0))
+            .rotation(std::f32::consts::FRAC_PI_2);
+        // Capsule now runs along Y; a point 3 cells to its right is 2 cells out.
+        let (d, n) = c.distance_and_normal(Vec2::new(13.0, 12.0));
+        assert!(close(d, 2.0) && n.abs_diff_eq(Vec2::X, 1.0e-5));
+    }
+
+    #[test]
+    fn grid_node_inside_moving_collider_takes_its_normal_velocity() {
+        // Collider moving +X at 2 cells/s; material at rest just inside its right face.
+        let boundary = SdfColliderBoundary::new(vec![
+            SdfCollider::new(SdfShape::rect(Vec2::splat(4.0)), Vec2::splat(16.0))
+                .velocity(Vec2::new(2.0, 0.0)),
+        ]);
+        let (x, y) = (19usize, 16usize); // node center (19.5, 16.5): 0.5 inside
+        let mut v = Vec2::ZERO;
+        boundary.apply_to_grid_velocity(x * 32 + y, 32, &mut v);
+        assert!(close(v.x, 2.0), "pushed along with the face, got {v:?}");
+        assert!(
+            close(v.y, 0.0),
+            "frictionless: no tangential drag, got {v:?}"
+        );
+    }
+
+    #[test]
+    fn rotating_collider_drags_tangentially_with_friction() {
+        let boundary = SdfColliderBoundary::new(vec![
+            SdfCollider::new(SdfShape::circle(4.0), Vec2::splat(16.0))
+                .angular_velocity(1.0)
+                .friction(10.0),
+        ]);
+        // Node at (20.5, 16.5): r = (4.5, 0.5), inside the skin. Material moving
+        // INTO the disk so friction has a normal load to work with.
+        let node = Vec2::new(20.5, 16.5);
+        let mut v = Vec2::new(-3.0, 0.0);
+        boundary.apply_to_grid_velocity(20 * 32 + 16, 32, &mut v);
+        let surface_v = (node - Vec2::splat(16.0)).perp();
+        let n = (node - Vec2::splat(16.0)).normalize();
+        assert!(
+            (v - surface_v).dot(n) >= -1.0e-5,
+            "no relative inflow allowed"
+        );
+        // High µ: sticks to the surface entirely.
+        assert!(
+            v.abs_diff_eq(surface_v, 1.0e-4),
+            "got {v:?} vs {surface_v:?}"
+        );
+    }
+
+    #[test]
+    fn particles_are_projected_out_and_poses_advance() {
+        let boundary = SdfColliderBoundary::new(vec![
+            SdfCollider::new(SdfShape::circle(3.0), Vec2::splat(10.0)).velocity(Vec2::X),
+        ]);
+        let p = boundary.clamp_particle_position(Vec2::new(11.0, 10.0), 32);
+        assert!(close(p.x, 13.0) && close(p.y, 10.0), "got {p:?}");
+
+        boundary.advance(0.5);
+        let c = boundary.collider(0).unwrap();
+        assert!(c.position.abs_diff_eq(Vec2::new(10.5, 10.0), 1.0e-6));
+    }
+}
@@ -1,6 +1,7 @@
 use glam::Vec2;
 
 use super::{BoundaryCondition, apply_slip_wall_velocity, clamp_position_inside_grid};
+use crate::grid::GridDomain;
 
 #[derive(Debug, Clone, Copy)]
 pub struct SlipBoundary {
@@ -15,10 +16,23 @@ impl SlipBoundary {
 
 impl BoundaryCondition for SlipBoundary {
     fn apply_to_grid_velocity(&self, cell_index: usize, grid_res: usize, velocity: &mut Vec2) {
-        apply_slip_wall_velocity(self.thickness, cell_index, grid_res, velocity);
+        self.apply_to_grid_velocity_in(cell_index, GridDomain::square(grid_res), velocity);
     }
 
     fn clamp_particle_position(&self, position: Vec2, grid_res: usize) -> Vec2 {
-        clamp_position_inside_grid(self.thickness, position, grid_res)
+        self.clamp_particle_position_in(position, GridDomain::square(grid_res))
+    }
+
+    fn apply_to_grid_velocity_in(
+        &self,
+        cell_index: usize,
+        domain: GridDomain,
+        velocity: &mut Vec2,
+    ) {
+        apply_slip_wall_velocity(self.thickness, cell_index, domain, velocity);
+    }
+
+    fn clamp_particle_position_in(&self, position: Vec2, domain: GridDomain) -> Vec2 {
+        clamp_position_inside_grid(self.thickness, position, domain)
     }
 }
@@ -48,6 +48,18 @@ impl BuoyancyField {
 }
 
 impl Field for BuoyancyField {
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        let slot = match name {
+            "fluid_density" => &mut self.fluid_density,
+            "gravity.x" => &mut self.gravity.x,
+            "gravity.y" => &mut self.gravity.y,
+            "min_density" => &mut self.min_density,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
+
     fn acceleration(&self, particles: &Particles, i: usize) -> Vec2 {
         let rho = particles.density[i].max(self.min_density);
         -self.gravity * (self.fluid_density / rho)
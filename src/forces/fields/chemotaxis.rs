@@ -11,7 +11,7 @@
 //! Gradient is estimated by central finite differences on the stored grid snapshot:
 //!   ∂φ/∂x ≈ (φ[ix+1,iy] − φ[ix−1,iy]) / 2
 //!   ∂φ/∂y ≈ (φ[ix,iy+1] − φ[ix,iy−1]) / 2
-//! where (ix,iy) = floor(particle position). Grid layout: φ[x*res+y].
+//! where (ix,iy) = floor(particle position). Grid layout: φ[x*height+y].
 //!
 //! # Usage
 //! ```rust,no_run
@@ -39,7 +39,7 @@
 //! Keller & Segel 1970, "Initiation of slime mold aggregation viewed as an instability".
 //! PDE: ∂ρ/∂t = ∇·(D∇ρ − χ·ρ·∇φ)  →  particle force: a = χ·∇φ.
 
-use glam::Vec2;
+use glam::{UVec2, Vec2};
 
 use crate::fields::Field;
 use crate::particle::Particles;
@@ -50,9 +50,9 @@ use crate::thermodynamics::ScalarDiffusionField;
 /// Call `sync_from(&scalar_field)` once per substep (after `scalar_field.apply()`)
 /// to update the internal snapshot, then register with the solver as a `Field`.
 pub struct ChemotaxisField {
-    /// Grid resolution — must match the ScalarDiffusionField and MPM solver.
-    grid_res: usize,
-    /// Snapshot of φ on the grid. Layout: phi[x*grid_res+y].
+    /// Grid dimensions — must match the ScalarDiffusionField and MPM solver.
+    dims: UVec2,
+    /// Snapshot of φ on the grid. Layout: phi[x*dims.y+y].
     phi: Vec<f32>,
     /// Chemotactic sensitivity χ (grid-units/s² per φ-unit).
     /// Positive = move up gradient (attraction). Negative = move away (repulsion).
@@ -68,9 +68,14 @@ impl ChemotaxisField {
     /// - `grid_res`: must match `ScalarDiffusionField` and `Simulation` grid resolution.
     /// - `sensitivity`: χ — positive for attraction, negative for repulsion.
     pub fn new(grid_res: usize, sensitivity: f32) -> Self {
+        Self::with_dims(UVec2::splat(grid_res as u32), sensitivity)
+    }
+
+    /// Same as `new` on a `dims.x × dims.y` grid (`SimConfig::grid_dims`).
+    pub fn with_dims(dims: UVec2, sensitivity: f32) -> Self {
         Self {
-            grid_res,
-            phi: vec![0.0; grid_res * grid_res],
+            dims,
+            phi: vec![0.0; (dims.x * dims.y) as usize],
             sensitivity,
             material_filter: None,
         }
@@ -88,20 +93,20 @@ impl ChemotaxisField {
     /// the just-computed diffusion step.
     pub fn sync_from(&mut self, source: &ScalarDiffusionField) {
         let src = source.current_phi();
-        let n = self.grid_res * self.grid_res;
-        debug_assert_eq!(src.len(), n, "ChemotaxisField grid_res mismatch");
+        let n = self.phi.len();
+        debug_assert_eq!(src.len(), n, "ChemotaxisField grid dims mismatch");
         self.phi[..n].copy_from_slice(&src[..n]);
     }
 
     /// Estimate ∇φ at grid coordinate (x, y) using central differences.
     fn gradient_at(&self, x: i32, y: i32) -> Vec2 {
-        let res = self.grid_res as i32;
+        let (nx, ny) = (self.dims.x as i32, self.dims.y as i32);
 
         let idx = |xi: i32, yi: i32| -> f32 {
-            if xi < 0 || yi < 0 || xi >= res || yi >= res {
+            if xi < 0 || yi < 0 || xi >= nx || yi >= ny {
                 return 0.0;
             }
-            self.phi[(xi * res + yi) as usize]
+            self.phi[(xi * ny + yi) as usize]
         };
 
         let dphidx = (idx(x + 1, y) - idx(x - 1, y)) * 0.5;
@@ -111,6 +116,15 @@ impl ChemotaxisField {
 }
 
 impl Field for ChemotaxisField {
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        let slot = match name {
+            "sensitivity" => &mut self.sensitivity,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
+
     fn prepare(&mut self, _particles: &Particles) {
         // Gradient is computed on-demand from the snapshot; no pre-computation needed.
     }
@@ -42,6 +42,18 @@ impl RadialConfinementField {
 }
 
 impl Field for RadialConfinementField {
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        let slot = match name {
+            "center.x" => &mut self.center.x,
+            "center.y" => &mut self.center.y,
+            "radius" => &mut self.radius,
+            "stiffness" => &mut self.stiffness,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
+
     fn acceleration(&self, particles: &Particles, i: usize) -> Vec2 {
         let r_vec = particles.x[i] - self.center;
         let dist = r_vec.length();
@@ -79,6 +91,19 @@ impl AabbConfinementField {
 }
 
 impl Field for AabbConfinementField {
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        let slot = match name {
+            "min.x" => &mut self.min.x,
+            "min.y" => &mut self.min.y,
+            "max.x" => &mut self.max.x,
+            "max.y" => &mut self.max.y,
+            "stiffness" => &mut self.stiffness,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
+
     fn acceleration(&self, particles: &Particles, i: usize) -> Vec2 {
         let p = particles.x[i];
         let mut acc = Vec2::ZERO;
@@ -90,6 +90,16 @@ impl CoulombField {
 }
 
 impl Field for CoulombField {
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        let slot = match name {
+            "coulomb_constant" => &mut self.coulomb_constant,
+            "softening" => &mut self.softening,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
+
     fn acceleration(&self, particles: &Particles, i: usize) -> Vec2 {
         let q_particle = match self.material_charges.get(&particles.material_id[i]) {
             Some(&q) if q.abs() > f32::EPSILON => q,
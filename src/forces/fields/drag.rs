@@ -67,6 +67,17 @@ impl LinearDragField {
 }
 
 impl Field for LinearDragField {
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        let slot = match name {
+            "target_velocity.x" => &mut self.target_velocity.x,
+            "target_velocity.y" => &mut self.target_velocity.y,
+            "drag_coefficient" => &mut self.drag_coefficient,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
+
     fn acceleration(&self, particles: &Particles, i: usize) -> Vec2 {
         let material_id = particles.material_id[i];
         if self.material_mask != Self::ALL_MATERIALS && self.material_mask & (1 << material_id) == 0
@@ -121,6 +132,15 @@ impl SpatialDragField {
 }
 
 impl Field for SpatialDragField {
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        let slot = match name {
+            "drag_coefficient" => &mut self.drag_coefficient,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
+
     fn acceleration(&self, particles: &Particles, i: usize) -> Vec2 {
         let material_id = particles.material_id[i];
         if self.material_mask != LinearDragField::ALL_MATERIALS
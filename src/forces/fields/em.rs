@@ -53,6 +53,16 @@ impl UniformElectricField {
 }
 
 impl Field for UniformElectricField {
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        let slot = match name {
+            "field.x" => &mut self.field.x,
+            "field.y" => &mut self.field.y,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
+
     fn acceleration(&self, particles: &Particles, i: usize) -> Vec2 {
         let q = match self.material_charges.get(&particles.material_id[i]) {
             Some(&q) if q.abs() > f32::EPSILON => q,
@@ -23,4 +23,13 @@ pub trait Field: Send + Sync {
 
     /// Return the acceleration (in grid-units/s²) applied to particle `i` this substep.
     fn acceleration(&self, particles: &Particles, i: usize) -> Vec2;
+
+    /// Set the scalar parameter `name` to `value` between substeps — how a
+    /// `Timeline` animates a named force field. Names are the field's own
+    /// public member names, with `.x` / `.y` for vector components
+    /// (`"drag_coefficient"`, `"target_velocity.x"`). Returns `false` for a
+    /// name the field does not expose; the default exposes none.
+    fn set_parameter(&mut self, _name: &str, _value: f32) -> bool {
+        false
+    }
 }
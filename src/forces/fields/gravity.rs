@@ -77,6 +77,16 @@ impl GravityWellField {
 }
 
 impl Field for GravityWellField {
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        let slot = match name {
+            "gravitational_constant" => &mut self.gravitational_constant,
+            "softening" => &mut self.softening,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
+
     fn acceleration(&self, particles: &Particles, i: usize) -> Vec2 {
         let mut acc = Vec2::ZERO;
         let eps2 = self.softening * self.softening;
@@ -329,6 +329,17 @@ impl NBodyGravityField {
 }
 
 impl Field for NBodyGravityField {
+    fn set_parameter(&mut self, name: &str, value: f32) -> bool {
+        let slot = match name {
+            "gravitational_constant" => &mut self.gravitational_constant,
+            "softening" => &mut self.softening,
+            "theta" => &mut self.theta,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
+
     fn prepare(&mut self, particles: &crate::particle::Particles) {
         self.snapshot.clear();
         self.snapshot.extend(
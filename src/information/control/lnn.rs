@@ -1,9 +1,10 @@
 // RESOLVED (was a TODO to move this into LP's mpm crate): the decision went the
 // other way. LP's crates/mpm was retired back into a single crate (2026-07-01),
 // and ARCHITECTURE.md §7 documents `control::Lnn` as a deliberate exception that
-// lives in emerge: it does not participate in the substep loop at all — a
+// lives in emerge: it does not participate in the substep loop by itself — a
 // standalone ODE the caller integrates, writing its output into
-// `Particle::activation`/`activation_dir` between steps. emerge supplies the
+// `Particle::activation`/`activation_dir`, either between steps or from a
+// `SubstepHook` (`solver::hook`) at the physics rate. emerge supplies the
 // controller math; it has no opinion on when or whether it runs.
 
 /// Liquid Time-constant Network (LNN) — Hasani, Lechner, Amini, Rus, Grosu,
@@ -29,6 +30,24 @@
 ///     p.activation = act;
 /// }
 /// ```
+///
+/// To actually run at the sub-step rate, drive it from a `SubstepHook`
+/// (here segment `k` is the particles tagged `k + 1`):
+/// ```ignore
+/// struct Muscles(Lnn);
+/// impl SubstepHook for Muscles {
+///     fn pre_p2g(&mut self, particles: &mut Particles, info: SubstepInfo) {
+///         self.0.step(info.sub_dt);
+///         let acts: Vec<f32> = self.0.activations().collect();
+///         for i in 0..particles.len() {
+///             if let Some(&a) = acts.get((particles.user_tag[i] as usize).wrapping_sub(1)) {
+///                 particles.activation[i] = a;
+///             }
+///         }
+///     }
+/// }
+/// sim.add_substep_hook(Box::new(Muscles(Lnn::traveling_wave(4, 1.0))));
+/// ```
 #[derive(Debug, Clone)]
 pub struct Lnn {
     /// Neuron states xᵢ ∈ ℝ.  Persist between steps — carry oscillator memory.
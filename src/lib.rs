@@ -8,7 +8,8 @@
 //   │                    SpawnRegion, query, density, cutoff), grid (Grid, Cell,
 //   │                    kernel), transfer (P2G/G2P transfer kernels)
 //   ├── matter/          Matter domain: particle (Particle struct), materials/
-//   │                    (MaterialModel trait, constitutive models, MaterialRegistry)
+//   │                    (MaterialModel trait, constitutive models, MaterialRegistry),
+//   │                    rigid (two-way coupled RigidBody)
 //   ├── forces/          Forces domain: boundary (BoundaryCondition + impls),
 //   │                    fields (Field trait + impls: gravity, Coulomb, EM, confinement)
 //   ├── information/     Information domain: control (Lnn), measures (entropy/MI) [experimental]
@@ -19,7 +20,8 @@
 //   Systems domain -- pure orchestration, no IRL counterpart (feature-gated where relevant)
 //   ├── systems::diagnostics  health monitoring, plugin-based stats collection
 //   ├── systems::gpu          GpuSimulation + WGSL shaders        [feature = "gpu"]
-//   └── systems::render       Instanced particle debug draw    [feature = "render"]
+//   ├── systems::render       Instanced particle debug draw    [feature = "render"]
+//   └── systems::vtk          VTU/VTI/PVD export for ParaView
 //
 //   Extended physics (experimental, not part of LP-stable API)
 //   ├── forces::electromagnetics  E/B field-query math       [feature = "experimental"]
@@ -57,6 +59,7 @@ pub use systems::diagnostics;
 pub use systems::gpu;
 #[cfg(feature = "render")]
 pub use systems::render;
+pub use systems::vtk;
 
 // ── Prelude — common imports for LP/game consumers ───────────────────────────
 pub mod prelude;
@@ -66,10 +69,31 @@ pub mod prelude;
 
 // Solver core
 pub use grid::{Cell, DirectionalContactGrip, Grid};
-pub use particle::{Particle, Particles};
+pub use particle::{PIN_ANCHOR, PIN_KINEMATIC, Particle, Particles};
 pub use solver::Simulation;
-pub use solver::config::{SimConfig, SpawnRegion, SpawnShape};
-pub use solver::handle::{MaterialHandle, ParticleGroup};
+pub use solver::config::{CompositeShape, SimConfig, SpawnRegion, SpawnShape};
+pub use solver::constraint::{Constraint, ConstraintHandle, ConstraintSet, Spring};
+pub use solver::contour::{Contour, ContourFilter, ContourOptions, surface_height};
+pub use solver::emitter::{
+    EmissionRate, Emitter, EmitterHandle, RegionShape, Sink, SinkAction, SinkHandle,
+};
+pub use solver::events::{ParticleEvent, ParticleEventKind};
+pub use solver::fragments::{BodySplit, Fragment, FragmentLabels, FragmentOptions};
+pub use solver::handle::{MaterialHandle, ParticleGroup, ParticleId};
+pub use solver::hook::{SubstepHook, SubstepInfo};
+pub use solver::import::{Bitmap, ImportedPoint, Palette, PointSet};
+pub use solver::raycast::{RayHit, RaySurface, RaycastOptions};
+pub use solver::reaction::{BoundaryReactions, WallReaction};
+pub use solver::sampler::{FieldSample, FieldSampler};
+pub use solver::scene::{Scene, SceneError};
+pub use solver::stress::StressState;
+pub use solver::timeline::{Interpolation, Timeline, Track};
+
+// Rigid bodies
+pub use matter::rigid::{RigidBody, RigidBodyHandle};
+
+// Passive tracers
+pub use matter::tracer::Tracers;
 
 // Materials
 pub use materials::{
@@ -85,7 +109,8 @@ pub use materials::{
 // Boundary conditions
 pub use boundary::{
     BoundaryCondition, FrictionBoundary, GripFrictionBoundary, HeightmapBoundary,
-    PredictiveBoundary, RatchetFrictionBoundary, SlipBoundary,
+    PredictiveBoundary, RatchetFrictionBoundary, SdfCollider, SdfColliderBoundary, SdfShape,
+    SlipBoundary,
 };
 
 // Force fields
@@ -98,7 +123,7 @@ pub use fields::{
 
 // State queries + density export for rendering
 pub use control::Lnn;
-pub use solver::density::compute_density_grid;
+pub use solver::density::{compute_density_grid, compute_density_grid_in};
 pub use solver::query::BodyState;
 
 /// Build a `Vec<Particle>` from a `SpawnRegion` — the primary way to construct
@@ -122,9 +147,33 @@ pub use solver::query::BodyState;
 pub fn build_particles(config: &SimConfig, spawn: SpawnRegion) -> Vec<Particle> {
     use crate::solver::LcgRng;
     let mut rng = LcgRng::new(spawn.rng_seed);
-    let mut particles = crate::solver::initialize_particles(config, spawn, &mut rng);
+    let mut particles = crate::solver::initialize_particles(config, &spawn, &mut rng);
     if spawn.precompute_initial_volumes {
-        estimate_particle_volumes(&mut particles, config.grid_res);
+        estimate_volumes_on(&mut particles, Grid::with_domain(config.grid_domain()));
+    }
+    particles
+}
+
+/// Build a `Vec<Particle>` at imported `points` (CSV, PLY or a painted
+/// bitmap — see `solver::import`), the `build_particles` counterpart for
+/// shapes a `SpawnRegion` cannot describe.
+///
+/// `spawn` supplies what the points leave unset: `material_id`, mass
+/// (`mass_override` or `SimConfig::particle_mass`), jitter (a fraction of
+/// `spacing`), random velocity, initial `F`, and whether to precompute
+/// volumes. Its box, centre, shape, rotation and spin are ignored. Add the
+/// result to a CPU simulation with `Simulation::add_particles`, after
+/// `Simulation::check_particles` when the points came from a file.
+pub fn build_particles_from_points(
+    config: &SimConfig,
+    spawn: SpawnRegion,
+    points: &PointSet,
+) -> Vec<Particle> {
+    use crate::solver::LcgRng;
+    let mut rng = LcgRng::new(spawn.rng_seed);
+    let mut particles = crate::solver::import::initialize_points(config, &spawn, points, &mut rng);
+    if spawn.precompute_initial_volumes {
+        estimate_volumes_on(&mut particles, Grid::with_domain(config.grid_domain()));
     }
     particles
 }
@@ -136,9 +185,12 @@ pub fn build_particles(config: &SimConfig, spawn: SpawnRegion) -> Vec<Particle>
 /// inside `Simulation::spawn_region`. Without it, initial particle density is geometric
 /// (`mass / spacing²`) which can cause a pressure spike on the first substep.
 pub fn estimate_particle_volumes(particles: &mut Vec<Particle>, grid_res: usize) {
+    estimate_volumes_on(particles, Grid::new(grid_res));
+}
+
+fn estimate_volumes_on(particles: &mut Vec<Particle>, mut grid: Grid) {
     use crate::solver::density::estimate_particle_volumes as density_estimate;
     let mut soa = Particles::from(std::mem::take(particles));
-    let mut grid = Grid::new(grid_res);
     let n = soa.len();
     density_estimate(&mut soa, &mut grid, n, true);
     *particles = soa.to_vec();
@@ -45,7 +45,7 @@ use crate::SimConfig;
 ///
 /// Default constitutive model: `NeoHookeanMaterial`.
 /// For corotated linear elasticity use `CorotatedMaterial::from_physical`.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Elastic {
     /// Young's modulus `[Pa]`
     pub e_pa: f32,
@@ -59,7 +59,7 @@ pub struct Elastic {
 ///
 /// Pick the yield criterion via [`PlasticityModel`].
 /// Default constitutive model dispatched from `model` field.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Elastoplastic {
     pub elastic: Elastic,
     pub model: PlasticityModel,
@@ -67,7 +67,7 @@ pub struct Elastoplastic {
 
 /// Plastic yield criterion for [`Elastoplastic`].
 #[non_exhaustive]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PlasticityModel {
     /// Volumetric snow plasticity (Stomakhin 2013).
     /// Hardening ξ=10, critical compression θ_c=0.025, critical stretch θ_s=0.0075.
@@ -117,7 +117,7 @@ pub enum PlasticityModel {
 /// The material deforms elastically AND dissipates energy simultaneously.
 /// Creep under constant stress eventually stops (spring limits deformation).
 /// → `ViscoelasticMaterial`
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Viscoelastic {
     pub elastic: Elastic,
     /// Dynamic viscosity η `[Pa·s]`
@@ -130,7 +130,7 @@ pub struct Viscoelastic {
 ///
 /// Use for wet terrain substrates, saturated granular flows, biological cell matrices.
 /// Distinct from `Fluid` (no elastic restoring force) and `Elastoplastic` (no EOS bulk pressure).
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct FluidGranular {
     /// Rest density `[kg/m³]`
     pub rho_kg_m3: f32,
@@ -225,7 +225,7 @@ impl FluidGranular {
 ///
 /// - `yield_stress_pa = None`  → Newtonian (flow at any stress) → `NewtonianFluidMaterial`
 /// - `yield_stress_pa = Some(τ₀)` → Bingham viscoplastic (rigid plug below τ₀) → `BinghamFluidMaterial`
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Fluid {
     pub rho_kg_m3: f32,
     /// Dynamic (shear) viscosity η `[Pa·s]`
@@ -2,6 +2,8 @@
 //!
 //! `materials` — constitutive models, `MaterialModel` trait, `MaterialRegistry`.
 //! `particle` — the `Particle` struct, the per-particle state every model reads/writes.
+//! `rigid` — `RigidBody`, two-way coupled rigid bodies exchanging momentum with the grid.
+//! `tracer` — `Tracers`, massless passive markers advected by the grid velocity.
 //!
 //! Part of the emerge/LP domain taxonomy (matter/forces/energy/information/
 //! spacetime/organism/systems) -- see `project_domain_taxonomy` design notes.
@@ -12,3 +14,5 @@
 
 pub mod materials;
 pub mod particle;
+pub mod rigid;
+pub mod tracer;
@@ -101,6 +101,13 @@ pub struct Particle {
     /// measured live -- terrain centroid crept y=3.8->7.1 over one foothold-seeking
     /// locomotion prototype run); a thin pinned "bedrock" layer under the free top layer anchors the whole
     /// body while the top layer still deforms naturally underfoot.
+    ///
+    /// [`PIN_KINEMATIC`] instead makes the particle kinematic: the same branches skip
+    /// it, but G2P keeps its own `v` as a prescribed velocity and advances `x` with it,
+    /// so it follows a scripted trajectory (dragged handle, piston, shaking plate) while
+    /// its mass and momentum still push neighbours around. CPU `Simulation` only -- the
+    /// GPU path treats every nonzero value as an anchor. Set through
+    /// `Simulation::set_kinematic_velocity` and friends (see `solver::kinematic`).
     pub pinned: u32,
     /// Generic second scalar carrier -- for any `ScalarDiffusionField`-shaped quantity
     /// (resource/grass level, pheromone concentration, nutrients, morphogen) that needs
@@ -143,6 +150,12 @@ pub struct Particle {
 // forgets to update the WGSL side or the padding.
 const _: () = assert!(std::mem::size_of::<Particle>() == 128);
 
+/// `Particle::pinned` value of a fixed anchor (`v = 0`). Any nonzero value other than
+/// [`PIN_KINEMATIC`] anchors the same way.
+pub const PIN_ANCHOR: u32 = 1;
+/// `Particle::pinned` value of a kinematic particle that moves with its prescribed `v`.
+pub const PIN_KINEMATIC: u32 = 2;
+
 impl Particle {
     /// All-zero particle with identity deformation gradient. Useful in tests and tooling.
     pub fn zeroed() -> Self {
@@ -240,6 +253,12 @@ pub struct Particles {
     /// True when the particle is in the sleeping partition and skipped by P2G/G2P.
     /// Do not write directly — use `Simulation::wake` / `Simulation::sleep`.
     pub sleeping: Vec<bool>,
+
+    // ── Identity — not in the hot path ───────────────────────────────────────
+    /// Stable particle ID, `0` = unassigned. Like `sleeping`, not part of the
+    /// AoS [`Particle`] view: `swap`/`retain` carry it, `push` starts it at 0.
+    /// Do not write directly — assigned by `Simulation::enable_particle_ids`.
+    pub id: Vec<u64>,
 }
 
 impl Particles {
@@ -268,6 +287,7 @@ impl Particles {
             pinned: Vec::new(),
             scalar_field: Vec::new(),
             sleeping: Vec::new(),
+            id: Vec::new(),
         }
     }
 
@@ -296,6 +316,7 @@ impl Particles {
             pinned: Vec::with_capacity(cap),
             scalar_field: Vec::with_capacity(cap),
             sleeping: Vec::with_capacity(cap),
+            id: Vec::with_capacity(cap),
         }
     }
 
@@ -396,6 +417,7 @@ impl Particles {
         // live GPU particles (sleeping state included) into this SoA. Freshly-spawned
         // particles always have sleeping=0 already, so this is a no-op for that path.
         self.sleeping.push(p.sleeping != 0);
+        self.id.push(0);
     }
 
     /// Swap all SoA fields for indices `a` and `b`. Used by sleep/wake partition logic.
@@ -426,6 +448,7 @@ impl Particles {
         self.pinned.swap(a, b);
         self.scalar_field.swap(a, b);
         self.sleeping.swap(a, b);
+        self.id.swap(a, b);
     }
 
     /// Rotate `[start..end]` so that `[mid..end]` precedes `[start..mid]`.
@@ -462,7 +485,8 @@ impl Particles {
     }
 
     /// Remove particles where `pred` returns `false`. Stable (preserves order). O(N).
-    pub fn retain<F: Fn(&Particle) -> bool>(&mut self, pred: F) {
+    /// `pred` is called exactly once per particle, in index order.
+    pub fn retain<F: FnMut(&Particle) -> bool>(&mut self, mut pred: F) {
         let n = self.len();
         let mut write = 0;
         for read in 0..n {
@@ -470,8 +494,9 @@ impl Particles {
             if pred(&p) {
                 if write != read {
                     self.set(write, p);
-                    // sleeping is not part of the AoS Particle view — copy explicitly.
+                    // sleeping and id are not part of the AoS Particle view — copy explicitly.
                     self.sleeping[write] = self.sleeping[read];
+                    self.id[write] = self.id[read];
                 }
                 write += 1;
             }
@@ -498,6 +523,7 @@ impl Particles {
         self.pinned.truncate(write);
         self.scalar_field.truncate(write);
         self.sleeping.truncate(write);
+        self.id.truncate(write);
     }
 
     /// Apply `f` to every particle, writing all changes back.
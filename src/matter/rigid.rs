@@ -0,0 +1,495 @@
+//! Two-way coupled rigid bodies: rocks, crates, logs and tools that push on
+//! the continuum and get pushed back.
+//!
+//! A `RigidBody` is an `SdfShape` (the same shapes `SdfColliderBoundary` uses)
+//! with mass, inertia and a dynamic pose. Each substep, after the grid velocity
+//! update and the boundary conditions, every grid node within `COUPLING_SKIN`
+//! of a body surface that is moving INTO the body exchanges an impulse with it:
+//! the normal part removes the approach velocity, the tangential part is
+//! Coulomb friction capped at `friction × normal impulse` — the same contact
+//! rule `SdfColliderBoundary` applies against a kinematic collider, but with the
+//! body's finite mass and inertia in the effective-mass denominator, so the
+//! node and the body share the momentum instead of the node losing it.
+//!
+//! Impulses are applied node by node, updating the body's velocity after each
+//! (one sequential-impulse sweep in grid insertion order). That keeps the
+//! exchange momentum-conserving and stable for any body/material mass ratio — a
+//! 1-kg crate in a lake and a boulder on a sand pile go through the same code —
+//! and deterministic, since the dirty-cell order is.
+//!
+//! Scope: bodies collide with the continuum and with the domain walls
+//! (`SimConfig::boundary_thickness`, a positional clamp with Coulomb friction on
+//! the translational velocity). On periodic axes there are no walls: the body
+//! wraps like the particles do, and nodes/particles are measured at their image
+//! nearest the body, so a body straddling the seam couples with both sides. Body-body contact and contact with other
+//! `BoundaryCondition`s (heightmaps, SDF colliders) are not modelled; terrain
+//! made of particles works, since it is just more continuum.
+
+use glam::{Mat2, Vec2};
+
+use crate::boundary::SdfShape;
+use crate::grid::{Grid, GridDomain};
+use crate::particle::Particles;
+
+/// Band (grid cells) outside a body surface where grid nodes still couple —
+/// same role and value as `SdfColliderBoundary::skin`: covers the node layer a
+/// particle resting on the surface scatters into.
+const COUPLING_SKIN: f32 = 1.0;
+
+/// Typed index of a body registered with `Simulation::add_rigid_body`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RigidBodyHandle(pub usize);
+
+/// A dynamic rigid body. Local frame origin = `position` = center of mass;
+/// `rotation` is radians CCW. Velocities and forces are in grid units
+/// (cells, cells/s), like every particle quantity. The shape, mass and
+/// inertia change only through `set_shape` / `set_mass_properties`, which keep
+/// the cached bounding radius and the positive-mass invariant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RigidBody {
+    shape: SdfShape,
+    pub position: Vec2,
+    pub rotation: f32,
+    pub linear_velocity: Vec2,
+    /// Radians/s, CCW.
+    pub angular_velocity: f32,
+    mass: f32,
+    /// Moment of inertia about `position`.
+    inertia: f32,
+    /// Coulomb friction coefficient µ against material and the domain walls.
+    pub friction: f32,
+    /// Net force the continuum exerted on the body over the last `step()`
+    /// (accumulated impulse / step time). Excludes gravity and wall contact.
+    continuum_force: Vec2,
+    continuum_torque: f32,
+    step_impulse: Vec2,
+    step_angular_impulse: f32,
+    /// Radius of the shape's bounding circle around `position` — a cheap
+    /// reject before the exact SDF evaluation per node/particle.
+    bounding_radius: f32,
+}
+
+impl RigidBody {
+    /// Body of uniform `density` (mass per cell²). Mass and inertia come from
+    /// the shape's area and second moment about the local origin; for
+    /// asymmetric polygons place the vertices so the origin is the centroid.
+    /// `Union` parts are summed as if disjoint (overlap counts twice).
+    pub fn new(shape: SdfShape, position: Vec2, density: f32) -> Self {
+        assert!(density > 0.0, "rigid body density must be positive");
+        let mass = density * area(&shape);
+        let inertia = density * second_moment(&shape);
+        let bounding_radius = bounding_radius(&shape);
+        Self {
+            shape,
+            position,
+            rotation: 0.0,
+            linear_velocity: Vec2::ZERO,
+            angular_velocity: 0.0,
+            mass,
+            inertia,
+            friction: 0.0,
+            continuum_force: Vec2::ZERO,
+            continuum_torque: 0.0,
+            step_impulse: Vec2::ZERO,
+            step_angular_impulse: 0.0,
+            bounding_radius,
+        }
+    }
+
+    pub fn rotation(mut self, radians: f32) -> Self {
+        self.rotation = radians;
+        self
+    }
+
+    pub fn velocity(mut self, linear: Vec2) -> Self {
+        self.linear_velocity = linear;
+        self
+    }
+
+    pub fn angular_velocity(mut self, radians_per_s: f32) -> Self {
+        self.angular_velocity = radians_per_s;
+        self
+    }
+
+    pub fn friction(mut self, mu: f32) -> Self {
+        assert!(mu >= 0.0, "rigid body friction must be non-negative");
+        self.friction = mu;
+        self
+    }
+
+    /// Override the computed mass and inertia (e.g. a hollow crate).
+    pub fn mass_properties(mut self, mass: f32, inertia: f32) -> Self {
+        self.set_mass_properties(mass, inertia);
+        self
+    }
+
+    pub fn shape(&self) -> &SdfShape {
+        &self.shape
+    }
+
+    /// Swap the shape in place, keeping mass and inertia (set those with
+    /// `set_mass_properties` if they should follow the new shape).
+    pub fn set_shape(&mut self, shape: SdfShape) {
+        self.bounding_radius = bounding_radius(&shape);
+        self.shape = shape;
+    }
+
+    pub fn mass(&self) -> f32 {
+        self.mass
+    }
+
+    /// Moment of inertia about `position`.
+    pub fn inertia(&self) -> f32 {
+        self.inertia
+    }
+
+    /// In-place form of `mass_properties`, for a body already in the simulation.
+    pub fn set_mass_properties(&mut self, mass: f32, inertia: f32) {
+        assert!(
+            mass > 0.0 && inertia > 0.0,
+            "rigid body mass and inertia must be positive"
+        );
+        self.mass = mass;
+        self.inertia = inertia;
+    }
+
+    /// `(position, rotation)`.
+    pub fn pose(&self) -> (Vec2, f32) {
+        (self.position, self.rotation)
+    }
+
+    /// Net force from the continuum, averaged over the last `step()`.
+    pub fn continuum_force(&self) -> Vec2 {
+        self.continuum_force
+    }
+
+    /// Net torque (CCW positive) about `position` from the continuum,
+    /// averaged over the last `step()`.
+    pub fn continuum_torque(&self) -> f32 {
+        self.continuum_torque
+    }
+
+    /// World-space signed distance and outward unit normal at `p`.
+    pub fn distance_and_normal(&self, p: Vec2) -> (f32, Vec2) {
+        let rot = Mat2::from_angle(self.rotation);
+        let local = rot.transpose() * (p - self.position);
+        let (d, n) = self.shape.distance_and_normal(local);
+        (d, rot * n)
+    }
+
+    /// Velocity of the body's material point coincident with `p`.
+    pub fn velocity_at(&self, p: Vec2) -> Vec2 {
+        self.linear_velocity + self.angular_velocity * (p - self.position).perp()
+    }
+
+    /// Farthest extent of the shape from `position` along world direction `dir`.
+    fn extent_along(&self, dir: Vec2) -> f32 {
+        let local = Mat2::from_angle(self.rotation).transpose() * dir;
+        support(&self.shape, local)
+    }
+
+    /// Cheap superset test: could `p` be within `margin` of the surface?
+    pub(crate) fn may_touch(&self, p: Vec2, margin: f32) -> bool {
+        let reach = self.bounding_radius + margin;
+        (p - self.position).length_squared() <= reach * reach
+    }
+
+    /// The periodic image of `p` nearest the body (`p` itself when no axis wraps).
+    pub(crate) fn image_near(&self, p: Vec2, domain: GridDomain) -> Vec2 {
+        if !domain.periodic.any() {
+            return p;
+        }
+        self.position + domain.nearest_image(p - self.position)
+    }
+
+    pub(crate) fn begin_step(&mut self) {
+        self.step_impulse = Vec2::ZERO;
+        self.step_angular_impulse = 0.0;
+    }
+
+    pub(crate) fn end_step(&mut self, elapsed: f32) {
+        if elapsed > 0.0 {
+            self.continuum_force = self.step_impulse / elapsed;
+            self.continuum_torque = self.step_angular_impulse / elapsed;
+        }
+    }
+
+    /// True while any surface point moves faster than `threshold` — a moving
+    /// body must wake the sleeping material it runs into.
+    pub(crate) fn is_moving(&self, threshold: f32) -> bool {
+        self.linear_velocity.length() > threshold
+            || self.angular_velocity.abs() * self.bounding_radius > threshold
+    }
+
+    /// Gravity, then momentum exchange with every coupled grid node.
+    /// Call after the grid holds velocities (post `update_velocities` + BCs).
+    pub(crate) fn couple_to_grid(&mut self, grid: &mut Grid, sub_dt: f32, gravity: Vec2) {
+        self.linear_velocity += gravity * sub_dt;
+        let inv_mass = 1.0 / self.mass;
+        let inv_inertia = 1.0 / self.inertia;
+        let domain = grid.domain();
+        for (idx, cell) in grid.active_cells_with_index_mut() {
+            if cell.mass <= 0.0 {
+                continue;
+            }
+            // Node position: cell center, matching `quadratic_weights`' `cell_pos + 0.5`.
+            let (x, y) = domain.cell_of(idx);
+            let node = self.image_near(Vec2::new(x as f32 + 0.5, y as f32 + 0.5), domain);
+            if !self.may_touch(node, COUPLING_SKIN) {
+                continue;
+            }
+            let (d, n) = self.distance_and_normal(node);
+            if d >= COUPLING_SKIN {
+                continue;
+            }
+            let r = node - self.position;
+            let relative = cell.momentum - self.velocity_at(node);
+            let v_n = relative.dot(n);
+            if v_n >= 0.0 {
+                continue;
+            }
+            // Inverse effective mass of the node/body pair along `dir`.
+            let inv_eff =
+                |dir: Vec2| 1.0 / cell.mass + inv_mass + r.perp_dot(dir).powi(2) * inv_inertia;
+            let j_n = -v_n / inv_eff(n);
+            let mut impulse = j_n * n;
+            let tangential = relative - v_n * n;
+            let v_t = tangential.length();
+            if v_t > f32::EPSILON {
+                let t = tangential / v_t;
+                let j_t = (v_t / inv_eff(t)).min(self.friction * j_n);
+                impulse -= j_t * t;
+            }
+            cell.momentum += impulse / cell.mass;
+            self.linear_velocity -= impulse * inv_mass;
+            self.angular_velocity -= r.perp_dot(impulse) * inv_inertia;
+            self.step_impulse -= impulse;
+            self.step_angular_impulse -= r.perp_dot(impulse);
+        }
+    }
+
+    /// Advance the pose and clamp it inside the `[lo, hi]` box (the walls),
+    /// wrapping it instead on the domain's periodic axes.
+    pub(crate) fn integrate(&mut self, sub_dt: f32, lo: Vec2, hi: Vec2, domain: GridDomain) {
+        self.position += self.linear_velocity * sub_dt;
+        self.rotation += self.angular_velocity * sub_dt;
+        self.position = domain.wrap_position(self.position);
+        let axes = [
+            (Vec2::X, domain.periodic.x, lo.x, hi.x),
+            (Vec2::Y, domain.periodic.y, lo.y, hi.y),
+        ];
+        for (axis, wraps, lo, hi) in axes {
+            if wraps {
+                continue;
+            }
+            let tangent = axis.perp();
+            for (sign, wall) in [(-1.0, lo), (1.0, hi)] {
+                let reach = self.extent_along(axis * sign);
+                let penetration = sign * (self.position.dot(axis) - wall) + reach;
+                if penetration <= 0.0 {
+                    continue;
+                }
+                self.position -= axis * (sign * penetration);
+                let v_n = self.linear_velocity.dot(axis) * sign;
+                if v_n > 0.0 {
+                    let v_t = self.linear_velocity.dot(tangent);
+                    let damped = (v_t.abs() - self.friction * v_n).max(0.0) * v_t.signum();
+                    self.linear_velocity = tangent * damped;
+                }
+            }
+        }
+    }
+
+    /// Push active particles that ended up inside the body back to its
+    /// surface, removing their approach velocity relative to the body.
+    pub(crate) fn project_particles(
+        &self,
+        particles: &mut Particles,
+        active_count: usize,
+        domain: GridDomain,
+    ) {
+        for i in 0..active_count {
+            let x = self.image_near(particles.x[i], domain);
+            if !self.may_touch(x, 0.0) {
+                continue;
+            }
+            let (d, n) = self.distance_and_normal(x);
+            if d >= 0.0 {
+                continue;
+            }
+            particles.x[i] = domain.wrap_position(x - d * n);
+            let relative = particles.v[i] - self.velocity_at(x);
+            let v_n = relative.dot(n);
+            if v_n < 0.0 {
+                particles.v[i] -= v_n * n;
+            }
+        }
+    }
+}
+
+fn area(shape: &SdfShape) -> f32 {
+    match shape {
+        SdfShape::Circle { radius } => std::f32::consts::PI * radius * radius,
+        SdfShape::Capsule {
+            half_length,
+            radius,
+        } => 4.0 * half_length * radius + std::f32::consts::PI * radius * radius,
+        SdfShape::ConvexPolygon { vertices } => polygon_moments(vertices).0,
+        SdfShape::Union(parts) => parts.iter().map(area).sum(),
+    }
+}
+
+/// ∫ |x|² dA about the local origin (inertia per unit density).
+fn second_moment(shape: &SdfShape) -> f32 {
+    match shape {
+        SdfShape::Circle { radius } => 0.5 * std::f32::consts::PI * radius.powi(4),
+        SdfShape::Capsule {
+            half_length,
+            radius,
+        } => {
+            // Box 2h × 2r plus two half-disks whose centroids sit 4r/3π past
+            // the segment ends (parallel-axis theorem on each half).
+            let (h, r) = (*half_length, *radius);
+            let pi = std::f32::consts::PI;
+            let box_area = 4.0 * h * r;
+            let box_moment = box_area * (4.0 * h * h + 4.0 * r * r) / 12.0;
+            let half_disk_area = 0.5 * pi * r * r;
+            let c = 4.0 * r / (3.0 * pi);
+            let about_own_centroid = 0.25 * pi * r.powi(4) - half_disk_area * c * c;
+            let caps = 2.0 * (about_own_centroid + half_disk_area * (h + c).powi(2));
+            box_moment + caps
+        }
+        SdfShape::ConvexPolygon { vertices } => polygon_moments(vertices).1,
+        SdfShape::Union(parts) => parts.iter().map(second_moment).sum(),
+    }
+}
+
+/// `(area, ∫ |x|² dA about the origin)` of a simple polygon, either winding.
+fn polygon_moments(vertices: &[Vec2]) -> (f32, f32) {
+    let n = vertices.len();
+    let mut twice_area = 0.0;
+    let mut moment = 0.0;
+    for i in 0..n {
+        let a = vertices[i];
+        let b = vertices[(i + 1) % n];
+        let cross = a.perp_dot(b);
+        twice_area += cross;
+        moment += cross * (a.dot(a) + a.dot(b) + b.dot(b));
+    }
+    ((twice_area * 0.5).abs(), (moment / 12.0).abs())
+}
+
+/// Support function: max over the shape of `p · dir`, `dir` unit, local frame.
+fn support(shape: &SdfShape, dir: Vec2) -> f32 {
+    match shape {
+        SdfShape::Circle { radius } => *radius,
+        SdfShape::Capsule {
+            half_length,
+            radius,
+        } => half_length * dir.x.abs() + radius,
+        SdfShape::ConvexPolygon { vertices } => vertices
+            .iter()
+            .map(|v| v.dot(dir))
+            .fold(f32::NEG_INFINITY, f32::max),
+        SdfShape::Union(parts) => parts
+            .iter()
+            .map(|s| support(s, dir))
+            .fold(f32::NEG_INFINITY, f32::max),
+    }
+}
+
+fn bounding_radius(shape: &SdfShape) -> f32 {
+    match shape {
+        SdfShape::Circle { radius } => *radius,
+        SdfShape::Capsule {
+            half_length,
+            radius,
+        } => half_length + radius,
+        SdfShape::ConvexPolygon { vertices } => {
+            vertices.iter().map(|v| v.length()).fold(0.0, f32::max)
+        }
+        SdfShape::Union(parts) => parts.iter().map(bounding_radius).fold(0.0, f32::max),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mass_properties_match_closed_forms() {
+        let disk = RigidBody::new(SdfShape::circle(2.0), Vec2::ZERO, 3.0);
+        let pi = std::f32::consts::PI;
+        assert!((disk.mass - 3.0 * pi * 4.0).abs() < 1.0e-3);
+        assert!((disk.inertia - 0.5 * disk.mass * 4.0).abs() < 1.0e-3);
+
+        // Rectangle 4 × 2: I = m (w² + h²) / 12.
+        let rect = RigidBody::new(SdfShape::rect(Vec2::new(2.0, 1.0)), Vec2::ZERO, 1.0);
+        assert!((rect.mass - 8.0).abs() < 1.0e-4);
+        assert!((rect.inertia - 8.0 * (16.0 + 4.0) / 12.0).abs() < 1.0e-3);
+
+        // A zero-length capsule is a disk.
+        let pill = RigidBody::new(SdfShape::capsule(0.0, 2.0), Vec2::ZERO, 3.0);
+        assert!((pill.mass - disk.mass).abs() < 1.0e-3);
+        assert!((pill.inertia - disk.inertia).abs() < 1.0e-2);
+    }
+
+    #[test]
+    fn set_shape_refreshes_the_bounding_radius() {
+        let mut body = RigidBody::new(SdfShape::circle(1.0), Vec2::ZERO, 1.0).angular_velocity(1.0);
+        assert!(!body.may_touch(Vec2::new(3.5, 0.0), 0.0));
+        assert!(!body.is_moving(2.0));
+
+        body.set_shape(SdfShape::circle(4.0));
+        assert!(body.may_touch(Vec2::new(3.5, 0.0), 0.0));
+        assert!(body.is_moving(2.0));
+        assert_eq!(body.mass(), std::f32::consts::PI);
+    }
+
+    #[test]
+    #[should_panic(expected = "mass and inertia must be positive")]
+    fn zero_mass_is_rejected() {
+        RigidBody::new(SdfShape::circle(1.0), Vec2::ZERO, 1.0).set_mass_properties(0.0, 1.0);
+    }
+
+    #[test]
+    fn grid_exchange_conserves_momentum_and_stops_approach() {
+        let mut grid = Grid::new(16);
+        // One node just inside the left face of a box, moving right into it.
+        grid.add_mass_momentum(glam::IVec2::new(5, 7), 2.0, Vec2::new(4.0, 0.0));
+        grid.update_velocities(0.1, Vec2::ZERO);
+        let mut body = RigidBody::new(SdfShape::rect(Vec2::splat(2.0)), Vec2::new(8.0, 7.5), 0.25);
+        let before = 2.0 * Vec2::new(2.0, 0.0) + body.mass * body.linear_velocity;
+
+        body.couple_to_grid(&mut grid, 0.1, Vec2::ZERO);
+        let v_node = grid.velocity_at(glam::IVec2::new(5, 7));
+        let after = 2.0 * v_node + body.mass * body.linear_velocity;
+
+        assert!((before - after).length() < 1.0e-4, "{before} vs {after}");
+        assert!(body.linear_velocity.x > 0.0, "body must be pushed +X");
+        assert!(
+            (v_node - body.velocity_at(Vec2::new(5.5, 7.5))).x <= 1.0e-5,
+            "node must no longer approach the body"
+        );
+    }
+
+    #[test]
+    fn walls_clamp_a_rotated_box() {
+        let mut body = RigidBody::new(SdfShape::rect(Vec2::splat(1.0)), Vec2::new(8.0, 3.0), 1.0)
+            .rotation(std::f32::consts::FRAC_PI_4)
+            .velocity(Vec2::new(1.0, -5.0));
+        body.integrate(
+            0.1,
+            Vec2::splat(3.0),
+            Vec2::splat(13.0),
+            GridDomain::square(16),
+        );
+        // Diagonal half-extent √2: the corner must rest on y = 3.
+        assert!((body.position.y - (3.0 + std::f32::consts::SQRT_2)).abs() < 1.0e-4);
+        assert_eq!(body.linear_velocity.y, 0.0);
+        assert!(
+            body.linear_velocity.x > 0.0,
+            "frictionless wall keeps tangential speed"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Massless passive tracers: smoke wisps, dust, leaf litter, streaklines.
+//!
+//! A tracer is only a position and a velocity. It is advected by the grid
+//! velocity after each G2P and is never scattered in P2G, so it has no mass,
+//! no stress and no effect on the simulation — tracers are cheap enough to use
+//! by the hundred thousand. They live in their own [`Tracers`] store next to
+//! `Particles`, managed through `Simulation::add_tracers` / `remove_tracers`.
+//!
+//! Advection is RK2 (midpoint) through the same quadratic B-spline gather G2P
+//! uses. A tracer with `response_time` τ > 0 lags the flow: its velocity
+//! relaxes toward the flow velocity as `1 − exp(−dt/τ)` per substep (Stokes
+//! drag on a small heavy particle); τ = 0 follows the flow exactly. Where there
+//! is no material the grid velocity is zero, so tracers come to rest outside
+//! the continuum.
+
+use glam::{IVec2, Vec2};
+use rayon::prelude::*;
+
+use crate::grid::kernel::quadratic_weights;
+use crate::grid::{Grid, GridDomain};
+
+/// Structure-of-arrays tracer store. The arrays are private and exposed as
+/// slices, so the count changes only through `push` / `retain` and the history
+/// ring stays sized to it.
+#[derive(Clone, Debug, Default)]
+pub struct Tracers {
+    x: Vec<Vec2>,
+    v: Vec<Vec2>,
+    /// Drag lag τ in seconds; 0 = ideal tracer. See the module doc.
+    response_time: Vec<f32>,
+    /// Ring buffer of past positions, `history_len` slots per tracer.
+    history: Vec<Vec2>,
+    history_len: usize,
+    /// Slot the next `record_history` writes (shared by every tracer).
+    history_head: usize,
+    /// Positions recorded so far, capped at `history_len`.
+    history_count: usize,
+}
+
+impl Tracers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    pub fn x(&self) -> &[Vec2] {
+        &self.x
+    }
+
+    pub fn x_mut(&mut self) -> &mut [Vec2] {
+        &mut self.x
+    }
+
+    pub fn v(&self) -> &[Vec2] {
+        &self.v
+    }
+
+    pub fn v_mut(&mut self) -> &mut [Vec2] {
+        &mut self.v
+    }
+
+    /// Drag lag τ in seconds; 0 = ideal tracer. See the module doc.
+    pub fn response_time(&self) -> &[f32] {
+        &self.response_time
+    }
+
+    pub fn response_time_mut(&mut self) -> &mut [f32] {
+        &mut self.response_time
+    }
+
+    /// Append a tracer at rest. Its history starts filled with `x`.
+    pub fn push(&mut self, x: Vec2, response_time: f32) {
+        self.x.push(x);
+        self.v.push(Vec2::ZERO);
+        self.response_time.push(response_time.max(0.0));
+        self.history
+            .extend(std::iter::repeat_n(x, self.history_len));
+    }
+
+    /// Keep tracers whose position satisfies `keep`. Stable (preserves order).
+    pub fn retain(&mut self, mut keep: impl FnMut(Vec2) -> bool) {
+        let len = self.history_len;
+        let mut write = 0;
+        for read in 0..self.len() {
+            if !keep(self.x[read]) {
+                continue;
+            }
+            if write != read {
+                self.x[write] = self.x[read];
+                self.v[write] = self.v[read];
+                self.response_time[write] = self.response_time[read];
+                self.history
+                    .copy_within(read * len..(read + 1) * len, write * len);
+            }
+            write += 1;
+        }
+        self.x.truncate(write);
+        self.v.truncate(write);
+        self.response_time.truncate(write);
+        self.history.truncate(write * len);
+    }
+
+    pub fn clear(&mut self) {
+        self.retain(|_| false);
+    }
+
+    /// Keep the last `len` positions of every tracer (0 disables history).
+    /// Discards what was recorded so far.
+    pub fn set_history_len(&mut self, len: usize) {
+        self.history_len = len;
+        self.history_head = 0;
+        self.history_count = 0;
+        self.history = self
+            .x
+            .iter()
+            .flat_map(|&x| std::iter::repeat_n(x, len))
+            .collect();
+    }
+
+    pub fn history_len(&self) -> usize {
+        self.history_len
+    }
+
+    /// Recorded positions of tracer `i`, oldest first (at most `history_len`,
+    /// one per `Simulation::step`). Empty while history is disabled.
+    pub fn history(&self, i: usize) -> impl Iterator<Item = Vec2> + '_ {
+        let len = self.history_len;
+        let ring = &self.history[i * len..(i + 1) * len];
+        let start = (self.history_head + len - self.history_count) % len.max(1);
+        (0..self.history_count).map(move |k| ring[(start + k) % len])
+    }
+
+    /// Append every tracer's current position to its history.
+    pub(crate) fn record_history(&mut self) {
+        let len = self.history_len;
+        if len == 0 {
+            return;
+        }
+        for (i, &x) in self.x.iter().enumerate() {
+            self.history[i * len + self.history_head] = x;
+        }
+        self.history_head = (self.history_head + 1) % len;
+        self.history_count = (self.history_count + 1).min(len);
+    }
+
+    /// One substep of advection through `grid`'s post-update node velocities,
+    /// then the same wrap/clamp particles get: periodic axes wrap, the others
+    /// stay inside the `boundary_thickness` walls.
+    pub(crate) fn advect(&mut self, grid: &Grid, sub_dt: f32, boundary_thickness: usize) {
+        let domain = grid.domain();
+        let lo = Vec2::splat(boundary_thickness.saturating_sub(1) as f32);
+        let hi = (domain.dims.as_vec2() - boundary_thickness as f32).max(lo);
+        self.x
+            .par_iter_mut()
+            .zip(self.v.par_iter_mut())
+            .zip(self.response_time.par_iter())
+            .for_each(|((x, v), &tau)| {
+                let u0 = grid_velocity(grid, *x);
+                let u = grid_velocity(grid, *x + 0.5 * sub_dt * u0);
+                if tau > 0.0 {
+                    *v += (u - *v) * (1.0 - (-sub_dt / tau).exp());
+                } else {
+                    *v = u;
+                }
+                *x = confine(*x + sub_dt * *v, domain, lo, hi);
+            });
+    }
+}
+
+/// G2P's gather of node velocity at `x`.
+fn grid_velocity(grid: &Grid, x: Vec2) -> Vec2 {
+    let w = quadratic_weights(x);
+    let mut u = Vec2::ZERO;
+    for gx in 0..3 {
+        for gy in 0..3 {
+            let cell = w.base_cell + IVec2::new(gx as i32 - 1, gy as i32 - 1);
+            u += w.wx[gx] * w.wy[gy] * grid.velocity_at(cell);
+        }
+    }
+    u
+}
+
+fn confine(x: Vec2, domain: GridDomain, lo: Vec2, hi: Vec2) -> Vec2 {
+    let wrapped = domain.wrap_position(x);
+    Vec2::select(domain.periodic, wrapped, x.clamp(lo, hi))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retain_keeps_order_and_each_tracers_history() {
+        let mut tracers = Tracers::new();
+        tracers.set_history_len(3);
+        for k in 0..4 {
+            tracers.push(Vec2::splat(k as f32), 0.0);
+        }
+        for step in 1..=4 {
+            for x in tracers.x_mut() {
+                x.y += 1.0;
+            }
+            if step == 2 {
+                tracers.push(Vec2::new(9.0, 9.0), 0.0);
+            }
+            tracers.record_history();
+        }
+        tracers.retain(|x| x.x != 1.0);
+
+        assert_eq!(tracers.len(), 4);
+        assert_eq!(tracers.x[0], Vec2::new(0.0, 4.0));
+        assert_eq!(tracers.x[1], Vec2::new(2.0, 6.0));
+        let trail: Vec<Vec2> = tracers.history(1).collect();
+        assert_eq!(trail, [4.0, 5.0, 6.0].map(|y| Vec2::new(2.0, y)));
+        // Pushed mid-run: starts from its spawn point, then follows along.
+        let late: Vec<Vec2> = tracers.history(3).collect();
+        assert_eq!(late, [9.0, 10.0, 11.0].map(|y| Vec2::new(9.0, y)));
+
+        tracers.set_history_len(0);
+        assert_eq!(tracers.history(0).count(), 0);
+    }
+}
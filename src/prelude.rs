@@ -13,13 +13,27 @@ pub use crate::{
     ActivationStatsPlugin,
     // Materials — all twelve (*Material types only)
     BinghamFluidMaterial,
+    // Particle import from point sets and painted bitmaps
+    Bitmap,
+    // Connected fragments and body-split detection
+    BodySplit,
     // Queries + density field export
     BodyState,
     // Boundary conditions
     BoundaryCondition,
+    // Boundary reaction forces and wall pressure
+    BoundaryReactions,
     BrittleProps,
     BuoyancyField,
     ChemotaxisField,
+    // Soft springs and distance limits
+    Constraint,
+    ConstraintHandle,
+    ConstraintSet,
+    // Surface contours from the density grid
+    Contour,
+    ContourFilter,
+    ContourOptions,
     CorotatedMaterial,
     CoulombField,
     DiagnosticsFrame,
@@ -30,25 +44,37 @@ pub use crate::{
     // Physical property families + trait
     Elastic,
     Elastoplastic,
+    // Continuous sources / drains
+    EmissionRate,
+    Emitter,
+    EmitterHandle,
     // Force fields
     Field,
+    // Continuum field sampling at arbitrary points
+    FieldSample,
+    FieldSampler,
     // Runtime
     FixedStepConfig,
     FixedStepController,
 
     Fluid,
     FluidGranular,
+    Fragment,
+    FragmentLabels,
+    FragmentOptions,
     // Per-material stats + logging
     FrameLogger,
     FrictionBoundary,
     FromSI,
     GranularFluidMaterial,
     GravityWellField,
+    Grid,
     // Directional/phase-gated grip boundaries (shipped with the ratchet
     // locomotion work) -- were missing from the prelude despite its own doc
     // claiming full boundary-condition coverage; fixed 2026-07-08.
     GripFrictionBoundary,
     HeightmapBoundary,
+    Interpolation,
 
     // Creature locomotion controller
     Lnn,
@@ -62,44 +88,81 @@ pub use crate::{
     NaccMaterial,
     NeoHookeanMaterial,
     NewtonianFluidMaterial,
+    Palette,
     Particle,
+    // Lifecycle / phase-change event queue
+    ParticleEvent,
+    ParticleEventKind,
+
     ParticleGroup,
+    // Stable per-particle identity
+    ParticleId,
     ParticleMass,
     Particles,
-
     PlasticityModel,
+    PointSet,
     PredictiveBoundary,
     RadialConfinementField,
     RankineMaterial,
     RatchetFrictionBoundary,
+    // Ray casting against the continuum
+    RayHit,
+    RaySurface,
+    RaycastOptions,
+    RegionShape,
+    // Two-way coupled rigid bodies
+    RigidBody,
+    RigidBodyHandle,
     RollingPlugin,
     ScalarDiffusionConfig,
     ScalarDiffusionField,
+    // Declarative scene files
+    Scene,
+    SceneError,
+    SdfCollider,
+    SdfColliderBoundary,
+    SdfShape,
 
     SimConfig,
     SimSnapshot,
-
     // Solver
     Simulation,
+    Sink,
+    SinkAction,
+    SinkHandle,
+
     SlipBoundary,
     SpawnRegion,
     SpawnShape,
+    Spring,
     StabilityStatus,
     StabilityThresholds,
     StepTiming,
     StomakhinMaterial,
+    // Per-particle stress and strain export
+    StressState,
+    // Substep hooks (+ the grid `post_grid_update` edits)
+    SubstepHook,
+    SubstepInfo,
     // Thermodynamics
     ThermalConfig,
     ThermalDiffusion,
     ThermalStatsPlugin,
+    // Keyframed parameter animation
+    Timeline,
+    // Passive flow-visualization tracers
+    Tracers,
+    Track,
     UniformElectricField,
 
     Viscoelastic,
     ViscoelasticMaterial,
     VonMisesMaterial,
+    WallReaction,
     WithLatentHeat,
     // Particle construction helpers
     build_particles,
+    build_particles_from_points,
     collect_snapshot,
     collect_snapshot_particles_only,
     evaluate_stability,
@@ -4,7 +4,7 @@ use glam::{IVec2, Vec2};
 
 use super::contact_normal::fit_contact_normal_lr;
 use super::directional_grip::DirectionalContactGrip;
-use super::{FxU32BuildHasher, Grid, flat_index};
+use super::{FxU32BuildHasher, Grid};
 
 /// Second velocity field for multi-field frictional contact (Bardenhagen, Guilkey,
 /// Roessig, Brackbill 2001) — see `Particle::contact_group`'s doc for the full
@@ -39,7 +39,7 @@ impl Grid {
     /// `add_mass_momentum` call for the SAME particle — this is a second, separate
     /// accumulator, not a replacement. OOB silently ignored.
     pub fn add_grip_mass_momentum(&mut self, cell_pos: IVec2, mass: f32, momentum: Vec2) {
-        let Some(idx) = flat_index(cell_pos, self.resolution) else {
+        let Some(idx) = self.key(cell_pos) else {
             return;
         };
         match self.contact_cells.entry(idx) {
@@ -72,7 +72,7 @@ impl Grid {
     /// contact-active, so this is deliberately not merged into
     /// `scatter_particles_to_grid` itself. OOB silently ignored.
     pub fn add_contact_point(&mut self, cell_pos: IVec2, position: Vec2, label: f32) {
-        let Some(idx) = flat_index(cell_pos, self.resolution) else {
+        let Some(idx) = self.key(cell_pos) else {
             return;
         };
         if let Some(cell) = self.contact_cells.get_mut(&idx) {
@@ -80,12 +80,53 @@ impl Grid {
         }
     }
 
+    /// Flat index of `cell_pos` if it is contact-active this substep — the same
+    /// test `add_contact_point` makes, split out so the parallel point-cloud
+    /// gather can decide read-only which points to keep before appending them.
+    pub(crate) fn contact_cell_index(&self, cell_pos: IVec2) -> Option<u32> {
+        self.key(cell_pos)
+            .filter(|idx| self.contact_cells.contains_key(idx))
+    }
+
+    /// Append a point to an already-resolved contact cell (see `contact_cell_index`).
+    pub(crate) fn push_contact_point(&mut self, idx: u32, position: Vec2, label: f32) {
+        if let Some(cell) = self.contact_cells.get_mut(&idx) {
+            cell.points.push((position, label));
+        }
+    }
+
+    /// Grip-field half of `Grid::merge_from`. Only the P2G accumulators are
+    /// merged -- point clouds and resolved velocities are filled in later,
+    /// on the merged grid itself.
+    pub(super) fn merge_contact_from(&mut self, other: &Grid) {
+        for &idx in &other.contact_dirty {
+            let Some(src) = other.contact_cells.get(&idx) else {
+                continue;
+            };
+            match self.contact_cells.entry(idx) {
+                std::collections::hash_map::Entry::Occupied(mut e) => {
+                    let cell = e.get_mut();
+                    cell.grip_mass += src.grip_mass;
+                    cell.grip_momentum += src.grip_momentum;
+                }
+                std::collections::hash_map::Entry::Vacant(e) => {
+                    self.contact_dirty.push(idx);
+                    e.insert(ContactCell {
+                        grip_mass: src.grip_mass,
+                        grip_momentum: src.grip_momentum,
+                        ..ContactCell::default()
+                    });
+                }
+            }
+        }
+    }
+
     /// Resolved "grip" field velocity at `cell_pos` — valid after `resolve_contact()`.
     /// Falls back to the ordinary total velocity when no contact was ever registered
     /// at this node (e.g. a grip particle whose kernel briefly touches a cell that no
     /// OTHER grip particle reaches, so there's no real second field to speak of).
     pub fn grip_velocity_at(&self, cell_pos: IVec2) -> Vec2 {
-        let Some(idx) = flat_index(cell_pos, self.resolution) else {
+        let Some(idx) = self.key(cell_pos) else {
             return Vec2::ZERO;
         };
         self.contact_cells
@@ -99,7 +140,7 @@ impl Grid {
     /// this is what makes routing G2P through this function safe everywhere, not just
     /// near contact.
     pub fn rest_velocity_at(&self, cell_pos: IVec2) -> Vec2 {
-        let Some(idx) = flat_index(cell_pos, self.resolution) else {
+        let Some(idx) = self.key(cell_pos) else {
             return Vec2::ZERO;
         };
         self.contact_cells
@@ -111,7 +152,7 @@ impl Grid {
     /// `grip_mass_gradient_normal` below — a tiny, deliberately local helper, not a
     /// public query (there's no meaningful "grip mass" outside contact resolution).
     fn grip_mass_at(&self, cell_pos: IVec2) -> f32 {
-        flat_index(cell_pos, self.resolution)
+        self.key(cell_pos)
             .and_then(|idx| self.contact_cells.get(&idx))
             .map_or(0.0, |c| c.grip_mass)
     }
@@ -126,8 +167,8 @@ impl Grid {
     /// best. Returns `None` when there's no real local gradient (deep inside a
     /// well-mixed interior, matching the old code's own "no gradient" case).
     fn grip_mass_gradient_normal(&self, idx: u32) -> Option<Vec2> {
-        let x = (idx as usize / self.resolution) as i32;
-        let y = (idx as usize % self.resolution) as i32;
+        let (x, y) = self.domain.cell_of(idx as usize);
+        let (x, y) = (x as i32, y as i32);
         let m = |dx: i32, dy: i32| self.grip_mass_at(IVec2::new(x + dx, y + dy));
         let grad_x = (m(1, -1) + 2.0 * m(1, 0) + m(1, 1)) - (m(-1, -1) + 2.0 * m(-1, 0) + m(-1, 1));
         let grad_y = (m(-1, 1) + 2.0 * m(0, 1) + m(1, 1)) - (m(-1, -1) + 2.0 * m(0, -1) + m(1, -1));
@@ -286,10 +327,8 @@ impl Grid {
             }
         };
         for &idx in &self.contact_dirty {
-            let node_pos = Vec2::new(
-                (idx as usize / self.resolution) as f32,
-                (idx as usize % self.resolution) as f32,
-            );
+            let (node_x, node_y) = self.domain.cell_of(idx as usize);
+            let node_pos = Vec2::new(node_x as f32, node_y as f32);
             let Some(&total) = self.cells.get(&idx) else {
                 continue;
             };
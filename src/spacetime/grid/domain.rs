@@ -0,0 +1,83 @@
+//! `GridDomain`: extent and topology of the simulation grid — the per-axis
+//! cell counts (`SimConfig::grid_res` / `grid_res_y`) and the axes that wrap
+//! around (`SimConfig::periodic_x` / `periodic_y`).
+//!
+//! One value shared by `Grid`, the boundary conditions, the spatial hash and
+//! the density export, so they all agree on indexing: node `(x, y)` lives at
+//! flat index `x * height + y` (column-major — on a square grid this is the
+//! old `x * grid_res + y`).
+
+use glam::{BVec2, IVec2, UVec2, Vec2};
+
+use super::kernel;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GridDomain {
+    /// Cells along x (width) and y (height).
+    pub dims: UVec2,
+    /// Axes on which the domain wraps around instead of ending in a wall.
+    pub periodic: BVec2,
+}
+
+impl GridDomain {
+    pub fn new(dims: UVec2) -> Self {
+        Self {
+            dims,
+            periodic: BVec2::FALSE,
+        }
+    }
+
+    /// `res × res`, no periodic axes — the classic domain.
+    pub fn square(res: usize) -> Self {
+        Self::new(UVec2::splat(res as u32))
+    }
+
+    pub fn with_periodic(mut self, periodic: BVec2) -> Self {
+        self.periodic = periodic;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.dims.x as usize
+    }
+
+    pub fn height(&self) -> usize {
+        self.dims.y as usize
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    /// `(x, y)` of the node at `index` — inverse of `flat_index`.
+    #[inline]
+    pub fn cell_of(&self, index: usize) -> (usize, usize) {
+        (index / self.height(), index % self.height())
+    }
+
+    /// Flat index of `cell`, wrapped first on periodic axes; `None` outside the domain.
+    #[inline]
+    pub fn flat_index(&self, cell: IVec2) -> Option<u32> {
+        let cell = kernel::wrap_cell(cell, self.dims, self.periodic);
+        if cell.x < 0 || cell.y < 0 {
+            return None;
+        }
+        let (x, y) = (cell.x as usize, cell.y as usize);
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        Some((x * self.height() + y) as u32)
+    }
+
+    /// See `kernel::wrap_position`.
+    #[inline]
+    pub fn wrap_position(&self, position: Vec2) -> Vec2 {
+        kernel::wrap_position(position, self.dims, self.periodic)
+    }
+
+    /// See `kernel::nearest_image`.
+    #[inline]
+    pub fn nearest_image(&self, d: Vec2) -> Vec2 {
+        kernel::nearest_image(d, self.dims, self.periodic)
+    }
+}
@@ -1,4 +1,4 @@
-use glam::{IVec2, Vec2};
+use glam::{BVec2, IVec2, UVec2, Vec2};
 
 #[derive(Clone, Copy, Debug)]
 pub struct QuadraticWeights {
@@ -24,6 +24,38 @@ pub fn axis_weights(d: f32) -> [f32; 3] {
     [w0, w1, w2]
 }
 
+/// Wraps a stencil node back into `[0, dims)` on the `periodic` axes, so
+/// the 3×3 stencil of a particle next to the seam reaches the nodes on the far
+/// side. Non-periodic axes pass through untouched (and stay out-of-bounds if
+/// they were). The stencil's weights and `cell_dist` still use the unwrapped
+/// node — only the storage slot wraps.
+#[inline]
+pub fn wrap_cell(cell: IVec2, dims: UVec2, periodic: BVec2) -> IVec2 {
+    let wrapped = cell.rem_euclid(dims.as_ivec2());
+    IVec2::select(periodic, wrapped, cell)
+}
+
+/// Wraps a particle position into `[0, dims)` on the `periodic` axes —
+/// the position counterpart of `wrap_cell`, applied after G2P advection.
+#[inline]
+pub fn wrap_position(position: Vec2, dims: UVec2, periodic: BVec2) -> Vec2 {
+    let extent = dims.as_vec2();
+    let mut wrapped = position - (position / extent).floor() * extent;
+    // `p - floor(p / L) * L` can round up to exactly L for tiny negative p.
+    wrapped = Vec2::select(wrapped.cmpge(extent), Vec2::ZERO, wrapped);
+    Vec2::select(periodic, wrapped, position)
+}
+
+/// Shortest representative of the displacement `d` on the `periodic` axes
+/// (minimum image: each wrapped component lands in `[-dims/2, dims/2]`). Distance
+/// tests against anything near the seam must go through this.
+#[inline]
+pub fn nearest_image(d: Vec2, dims: UVec2, periodic: BVec2) -> Vec2 {
+    let extent = dims.as_vec2();
+    let wrapped = d - (d / extent).round() * extent;
+    Vec2::select(periodic, wrapped, d)
+}
+
 /// Analytic derivative `[dw0/dd, dw1/dd, dw2/dd]` of `axis_weights` w.r.t.
 /// the fractional offset `d` -- the base building block for differentiating
 /// through the kernel's own dependence on particle position, the last
@@ -65,6 +97,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn wrap_cell_and_position_only_touch_periodic_axes() {
+        let dims = UVec2::new(32, 16);
+        let periodic = BVec2::new(true, false);
+        assert_eq!(
+            wrap_cell(IVec2::new(-1, -1), dims, periodic),
+            IVec2::new(31, -1)
+        );
+        assert_eq!(
+            wrap_cell(IVec2::new(33, 40), dims, periodic),
+            IVec2::new(1, 40)
+        );
+        // Each axis wraps by its own extent.
+        assert_eq!(
+            wrap_cell(IVec2::new(-1, 17), dims, BVec2::TRUE),
+            IVec2::new(31, 1)
+        );
+        let p = wrap_position(Vec2::new(32.25, 40.0), dims, periodic);
+        assert_eq!(p, Vec2::new(0.25, 40.0));
+        let q = wrap_position(Vec2::new(-1.0e-9, 5.0), dims, periodic);
+        assert!(
+            (0.0..32.0).contains(&q.x),
+            "wrapped x={} escaped [0, 32)",
+            q.x
+        );
+        let d = nearest_image(Vec2::new(30.0, 30.0), dims, periodic);
+        assert_eq!(d, Vec2::new(-2.0, 30.0));
+    }
+
     #[test]
     fn axis_weights_derivative_matches_finite_difference() {
         let h = 1.0e-3_f32;
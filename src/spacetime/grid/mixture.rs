@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use glam::{IVec2, Vec2};
 
-use super::{FxU32BuildHasher, Grid, flat_index};
+use super::{FxU32BuildHasher, Grid};
 
 /// Two-phase mixture coupling cell (Tampubolon et al. 2017 — see `MixturePhase`'s
 /// own doc). Only allocated at nodes touched by at least one `MixturePhase::Solid`
@@ -40,7 +40,7 @@ impl Grid {
         mass: f32,
         momentum: Vec2,
     ) {
-        let Some(idx) = flat_index(cell_pos, self.resolution) else {
+        let Some(idx) = self.key(cell_pos) else {
             return;
         };
         use crate::materials::MixturePhase;
@@ -76,12 +76,40 @@ impl Grid {
         }
     }
 
+    /// Mixture half of `Grid::merge_from` — sums both phases' P2G accumulators.
+    pub(super) fn merge_mixture_from(&mut self, other: &Grid) {
+        for &idx in &other.mixture_dirty {
+            let Some(src) = other.mixture_cells.get(&idx) else {
+                continue;
+            };
+            match self.mixture_cells.entry(idx) {
+                std::collections::hash_map::Entry::Occupied(mut e) => {
+                    let cell = e.get_mut();
+                    cell.solid_mass += src.solid_mass;
+                    cell.solid_momentum += src.solid_momentum;
+                    cell.fluid_mass += src.fluid_mass;
+                    cell.fluid_momentum += src.fluid_momentum;
+                }
+                std::collections::hash_map::Entry::Vacant(e) => {
+                    self.mixture_dirty.push(idx);
+                    e.insert(MixtureCell {
+                        solid_mass: src.solid_mass,
+                        solid_momentum: src.solid_momentum,
+                        fluid_mass: src.fluid_mass,
+                        fluid_momentum: src.fluid_momentum,
+                        ..MixtureCell::default()
+                    });
+                }
+            }
+        }
+    }
+
     /// Resolved solid-phase velocity at `cell_pos` — valid after
     /// `resolve_mixture_coupling()`. Falls back to the ordinary total velocity
     /// when no mixture coupling was ever registered at this node, same
     /// convention as `grip_velocity_at`.
     pub fn resolved_solid_velocity_at(&self, cell_pos: IVec2) -> Vec2 {
-        let Some(idx) = flat_index(cell_pos, self.resolution) else {
+        let Some(idx) = self.key(cell_pos) else {
             return Vec2::ZERO;
         };
         self.mixture_cells
@@ -93,7 +121,7 @@ impl Grid {
     /// `resolve_mixture_coupling()`. Same fallback convention as
     /// `resolved_solid_velocity_at`.
     pub fn resolved_fluid_velocity_at(&self, cell_pos: IVec2) -> Vec2 {
-        let Some(idx) = flat_index(cell_pos, self.resolution) else {
+        let Some(idx) = self.key(cell_pos) else {
             return Vec2::ZERO;
         };
         self.mixture_cells
@@ -196,23 +224,20 @@ impl Grid {
         }
     }
 
-    /// Flat index -> cell position. Inverse of `flat_index`.
+    /// Flat index -> cell position. Inverse of `GridDomain::flat_index`.
     fn idx_to_pos(&self, idx: u32) -> IVec2 {
-        let idx = idx as usize;
-        IVec2::new(
-            (idx / self.resolution) as i32,
-            (idx % self.resolution) as i32,
-        )
+        let (x, y) = self.domain.cell_of(idx as usize);
+        IVec2::new(x as i32, y as i32)
     }
 
     fn mixture_solid_v_or_zero(&self, pos: IVec2) -> Vec2 {
-        flat_index(pos, self.resolution)
+        self.key(pos)
             .and_then(|idx| self.mixture_cells.get(&idx))
             .map_or(Vec2::ZERO, |c| c.resolved_solid_v)
     }
 
     fn mixture_fluid_v_or_zero(&self, pos: IVec2) -> Vec2 {
-        flat_index(pos, self.resolution)
+        self.key(pos)
             .and_then(|idx| self.mixture_cells.get(&idx))
             .map_or(Vec2::ZERO, |c| c.resolved_fluid_v)
     }
@@ -346,15 +371,17 @@ impl Grid {
             pressure.insert(idx, 0.0);
         }
 
+        // Copies, not `self.key`: the closures must not borrow all of `self`,
+        // which the final loop below mutates.
+        let domain = self.domain;
+        let key = move |pos: IVec2| domain.flat_index(pos);
         let k_or_zero = |pos: IVec2| -> f32 {
-            flat_index(pos, self.resolution)
+            key(pos)
                 .and_then(|idx| mobility.get(&idx).copied())
                 .unwrap_or(0.0)
         };
         let p_or_zero = |p: &HashMap<u32, f32, FxU32BuildHasher>, pos: IVec2| -> f32 {
-            flat_index(pos, self.resolution)
-                .and_then(|idx| p.get(&idx).copied())
-                .unwrap_or(0.0)
+            key(pos).and_then(|idx| p.get(&idx).copied()).unwrap_or(0.0)
         };
         // Harmonic mean of two mobilities -- 0 if either side is ~0 (no material,
         // no flux through that face), never blows up even if one side is huge.
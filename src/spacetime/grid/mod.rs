@@ -19,6 +19,7 @@ pub mod kernel;
 mod contact;
 mod contact_normal;
 mod directional_grip;
+mod domain;
 mod mixture;
 
 use std::collections::HashMap;
@@ -28,6 +29,7 @@ use glam::{IVec2, Vec2};
 
 use contact::ContactCellMap;
 pub use directional_grip::DirectionalContactGrip;
+pub use domain::GridDomain;
 use mixture::MixtureCellMap;
 
 /// FxHash-style hasher for the grid's `u32` flat-index keys.
@@ -83,21 +85,6 @@ pub(crate) type CellMap = HashMap<u32, Cell, FxU32BuildHasher>;
 /// Flat-index → velocity snapshot, see `Grid::snapshot_velocities`.
 pub type VelocitySnapshot = HashMap<u32, Vec2, FxU32BuildHasher>;
 
-/// Converts a cell position to the flat HashMap key, or `None` if out of domain bounds.
-/// Shared by `Grid::add_mass_momentum` and the parallel P2G scatter in `transfer.rs` — both
-/// must agree on bounds-checking and indexing, so this is the single source of truth.
-pub(crate) fn flat_index(cell_pos: IVec2, resolution: usize) -> Option<u32> {
-    if cell_pos.x < 0 || cell_pos.y < 0 {
-        return None;
-    }
-    let x = cell_pos.x as usize;
-    let y = cell_pos.y as usize;
-    if x >= resolution || y >= resolution {
-        return None;
-    }
-    Some((x * resolution + y) as u32)
-}
-
 /// One grid cell — `repr(C)` for stable GPU buffer layout.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default)]
@@ -111,15 +98,20 @@ pub struct Cell {
 
 /// Sparse grid — HashMap-backed, only touched cells allocated.
 ///
-/// `resolution` defines the simulation domain (soft boundary enforcement).
-/// Memory cost is O(active particles × stencil) not O(resolution²).
+/// `domain` defines the simulation domain (soft boundary enforcement).
+/// Memory cost is O(active particles × stencil) not O(width × height).
 /// A 4096-cell domain with 50k particles uses ~4 MB instead of 192 MB.
 ///
 /// All P2G/G2P callers go through the public API. The HashMap key is the flat
-/// index `x * resolution + y`, matching the boundary condition convention.
+/// index `x * height + y` (`GridDomain::flat_index`), matching the boundary
+/// condition convention.
 #[derive(Debug)]
 pub struct Grid {
-    resolution: usize,
+    /// Per-axis resolution and periodicity. Every cell lookup goes through
+    /// `key`, which folds stencil nodes past a periodic seam back onto the far
+    /// side — so P2G, G2P and the contact/mixture fields all see a torus on
+    /// those axes without any per-caller wrapping.
+    domain: GridDomain,
     /// Sparse cell storage. Only contains cells touched this frame.
     cells: CellMap,
     /// Flat indices of cells touched this frame, in insertion order.
@@ -139,10 +131,18 @@ pub struct Grid {
 }
 
 impl Grid {
+    /// Square, non-periodic `resolution × resolution` grid.
     pub fn new(resolution: usize) -> Self {
-        assert!(resolution >= 4, "grid resolution must be >= 4");
+        Self::with_domain(GridDomain::square(resolution))
+    }
+
+    pub fn with_domain(domain: GridDomain) -> Self {
+        assert!(
+            domain.dims.min_element() >= 4,
+            "grid resolution must be >= 4 on both axes"
+        );
         Self {
-            resolution,
+            domain,
             cells: CellMap::default(),
             dirty: Vec::new(),
             contact_cells: ContactCellMap::default(),
@@ -152,8 +152,20 @@ impl Grid {
         }
     }
 
+    pub fn domain(&self) -> GridDomain {
+        self.domain
+    }
+
+    /// Cells along x. Equal to the height only on a square grid.
+    #[deprecated(note = "use `domain()`: grids can be non-square")]
     pub fn resolution(&self) -> usize {
-        self.resolution
+        self.domain.width()
+    }
+
+    /// Storage key for `cell_pos`: wrapped on periodic axes, then bounds-checked.
+    #[inline]
+    fn key(&self, cell_pos: IVec2) -> Option<u32> {
+        self.domain.flat_index(cell_pos)
     }
 
     /// True if any grip particle touched the grid this substep. Gates the extra
@@ -163,7 +175,7 @@ impl Grid {
         !self.contact_dirty.is_empty()
     }
 
-    /// Remove only touched cells. O(touched), not O(resolution²).
+    /// Remove only touched cells. O(touched), not O(width × height).
     pub fn clear(&mut self) {
         for &idx in &self.dirty {
             self.cells.remove(&idx);
@@ -188,7 +200,7 @@ impl Grid {
 
     /// Accumulate mass and momentum during P2G. OOB silently ignored.
     pub fn add_mass_momentum(&mut self, cell_pos: IVec2, mass: f32, momentum: Vec2) {
-        let Some(idx) = flat_index(cell_pos, self.resolution) else {
+        let Some(idx) = self.key(cell_pos) else {
             return;
         };
         self.accumulate(idx, mass, momentum);
@@ -213,44 +225,21 @@ impl Grid {
 
     /// Grid velocity at `cell_pos` — valid after `update_velocities()`. Zero for OOB/untouched.
     pub fn velocity_at(&self, cell_pos: IVec2) -> Vec2 {
-        if cell_pos.x < 0 || cell_pos.y < 0 {
-            return Vec2::ZERO;
-        }
-        let x = cell_pos.x as usize;
-        let y = cell_pos.y as usize;
-        if x >= self.resolution || y >= self.resolution {
-            return Vec2::ZERO;
-        }
-        self.cells
-            .get(&((x * self.resolution + y) as u32))
+        self.key(cell_pos)
+            .and_then(|idx| self.cells.get(&idx))
             .map_or(Vec2::ZERO, |c| c.momentum)
     }
 
     /// True if `cell_pos` was touched by P2G this frame.
     #[inline]
     pub fn cell_is_active(&self, cell_pos: IVec2) -> bool {
-        if cell_pos.x < 0 || cell_pos.y < 0 {
-            return false;
-        }
-        let x = cell_pos.x as usize;
-        let y = cell_pos.y as usize;
-        if x >= self.resolution || y >= self.resolution {
-            return false;
-        }
-        self.cells.contains_key(&((x * self.resolution + y) as u32))
+        self.key(cell_pos)
+            .is_some_and(|idx| self.cells.contains_key(&idx))
     }
 
     pub fn mass_at(&self, cell_pos: IVec2) -> f32 {
-        if cell_pos.x < 0 || cell_pos.y < 0 {
-            return 0.0;
-        }
-        let x = cell_pos.x as usize;
-        let y = cell_pos.y as usize;
-        if x >= self.resolution || y >= self.resolution {
-            return 0.0;
-        }
-        self.cells
-            .get(&((x * self.resolution + y) as u32))
+        self.key(cell_pos)
+            .and_then(|idx| self.cells.get(&idx))
             .map_or(0.0, |c| c.mass)
     }
 
@@ -310,7 +299,7 @@ impl Grid {
     /// Reads a pre-force velocity snapshot (see `snapshot_velocities`) at `cell_pos`,
     /// mirroring `velocity_at`'s own OOB/untouched-is-zero convention exactly.
     pub fn pre_force_velocity_at(&self, snapshot: &VelocitySnapshot, cell_pos: IVec2) -> Vec2 {
-        let Some(idx) = flat_index(cell_pos, self.resolution) else {
+        let Some(idx) = self.key(cell_pos) else {
             return Vec2::ZERO;
         };
         snapshot.get(&idx).copied().unwrap_or(Vec2::ZERO)
@@ -364,7 +353,7 @@ impl Grid {
     }
 
     /// Iterate active cells with flat index: `(flat_idx, &mut Cell)`.
-    /// `flat_idx = x * resolution + y` — same convention used by boundary conditions.
+    /// `flat_idx = x * height + y` — same convention used by boundary conditions.
     pub fn active_cells_with_index_mut(&mut self) -> impl Iterator<Item = (usize, &mut Cell)> {
         let (dirty, cells) = (&self.dirty, &mut self.cells);
         let ptr = cells as *mut CellMap;
@@ -378,6 +367,22 @@ impl Grid {
     pub fn active_cell_count(&self) -> usize {
         self.dirty.len()
     }
+
+    /// Add every cell `other` accumulated (ordinary, contact and mixture fields)
+    /// into `self`, walking `other`'s dirty lists in insertion order. Used by the
+    /// chunked parallel P2G (`scatter_particles_to_grid_parallel`): merging chunk
+    /// grids in chunk order reproduces the sequential scatter's first-touch cell
+    /// order exactly, so only the per-cell summation grouping differs.
+    pub(crate) fn merge_from(&mut self, other: &Grid) {
+        debug_assert_eq!(self.domain, other.domain);
+        for &idx in &other.dirty {
+            if let Some(cell) = other.cells.get(&idx) {
+                self.accumulate(idx, cell.mass, cell.momentum);
+            }
+        }
+        self.merge_contact_from(other);
+        self.merge_mixture_from(other);
+    }
 }
 
 #[cfg(test)]
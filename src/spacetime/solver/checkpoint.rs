@@ -0,0 +1,618 @@
+//! Versioned binary checkpoint/restore: `Simulation::save_checkpoint` /
+//! `load_checkpoint` and their `Write`/`Read` counterparts.
+//!
+//! Split out of `solver/lifecycle.rs` -- the on-disk format is its own
+//! contract (versioned, validated field by field) and has nothing to do with
+//! how a simulation is built up in the first place.
+//!
+//! # What a checkpoint holds
+//! Everything `step()` reads that is not rebuilt from scratch each substep:
+//! the full `SimConfig`, every particle record (active and sleeping, in
+//! physical order), the sleep partition (`active_count`), `next_tag`,
+//! `frame_index`, the thermal model's config and the scalar fields' configs,
+//! the flat `MaterialParams` of every registered material, each rigid body's
+//! pose and velocity, each emitter's fractional-particle accumulator and
+//! RNG state (so emission continues bit-exactly), the stable particle IDs
+//! with their counter when IDs are enabled, every passive tracer's
+//! position, velocity and lag, and the timeline's clock. The grid,
+//! spatial hash, and per-field scratch buffers are NOT stored -- every one of
+//! them is cleared/rebuilt before it is read, so restoring them would only
+//! make the file bigger. `tag_index` is likewise rebuilt from `user_tag`: it is
+//! a pure function of it (sleep/wake swaps keep the two in lockstep), and the
+//! particle ID index is rebuilt from the stored IDs the same way. Tracer
+//! history is a rendering aid, not state: a restore keeps the target's
+//! history length and restarts recording from the restored positions.
+//!
+//! # What it cannot hold
+//! Trait objects (`MaterialModel`, `BoundaryCondition`, `Field`, phase rules)
+//! and the scalar fields' `fn` pointers have no serializable form. Restore
+//! therefore goes INTO an already-built `Simulation`: register the same
+//! materials/boundaries/fields/scalar fields/rigid bodies/emitters/sinks the
+//! original run had (body shapes and emitter settings are setup, like
+//! materials), then call
+//! `load_checkpoint`. Material params are compared byte-for-byte against the
+//! registry, so restoring under a silently different material setup -- the one
+//! mistake that would make a restored run diverge without any visible error --
+//! fails loudly instead. Materials a timeline channel animates are the
+//! exception: the channel rebuilds them for the restored clock on the next
+//! substep, so their current params say nothing about the file.
+//!
+//! State that lives inside a boundary is out of reach for the same reason.
+//! The one built-in case is `SdfColliderBoundary`, whose `advance` moves the
+//! collider poses every substep: keep its `colliders()` snapshot next to the
+//! checkpoint and hand it to `set_colliders` after restoring, or the restored
+//! run sees the colliders where the target simulation left them.
+//!
+//! # Layout (version 1, little-endian)
+//! ```text
+//! magic "EMRGCKPT" | version u32 | particle stride u32 | material-params stride u32
+//! SimConfig fields (declaration order; usize as u64, bool as u8, grid_res_y 0 = square)
+//! frame_index u64 | next_tag u32 | last_step_dt f32
+//! particle count u64 | active_count u64 | particle records (raw `Particle` Pod bytes*)
+//! thermal present u8 [ThermalConfig fields | grid width u64 | grid height u64]
+//! scalar field count u32 { ScalarDiffusionConfig fields | grid width u64 | grid height u64 }
+//! material count u32 | material records (raw `MaterialParams` Pod bytes)
+//! rigid body count u32 { position xy | rotation | linear velocity xy | angular velocity }
+//! emitter count u32 { accumulator f32 | rng state u32 }
+//! particle ids present u8 [next id u64 | id u64 per particle, physical order]
+//! tracer count u64 { position xy | velocity xy | response time }
+//! timeline time f64
+//! ```
+//! *The Pod records are written in host byte order, which is little-endian on
+//! every target the engine runs on (the GPU upload path makes the same assumption).
+//!
+//! The two strides make the header self-describing: a file written by a
+//! build with a different `Particle`/`MaterialParams` layout is rejected with
+//! a clear error instead of being reinterpreted as garbage.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use glam::{UVec2, Vec2};
+
+use super::spatial_hash::SpatialHash;
+use super::{SimConfig, Simulation};
+use crate::grid::Grid;
+use crate::materials::MaterialParams;
+use crate::matter::tracer::Tracers;
+use crate::particle::{Particle, Particles};
+use crate::thermodynamics::{ThermalConfig, ThermalDiffusion};
+
+const MAGIC: &[u8; 8] = b"EMRGCKPT";
+
+/// Current checkpoint format version. Bump on any layout change and keep a
+/// reader for every older version that is still worth loading.
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+impl Simulation {
+    /// Write a checkpoint to `path` (created or truncated). See the
+    /// `solver::checkpoint` module doc for what is and is not stored.
+    pub fn save_checkpoint(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write_checkpoint(&mut w)?;
+        w.flush()
+    }
+
+    /// Restore a checkpoint written by `save_checkpoint` into this simulation.
+    ///
+    /// `self` must already have the same materials, scalar fields, boundaries
+    /// and force fields registered as the run that wrote the file -- those are
+    /// code, not data. On error `self` is left unchanged.
+    pub fn load_checkpoint(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.read_checkpoint(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Serialize a checkpoint into any writer (e.g. an in-memory `Vec<u8>`).
+    pub fn write_checkpoint<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        write_u32(w, CHECKPOINT_VERSION)?;
+        write_u32(w, std::mem::size_of::<Particle>() as u32)?;
+        write_u32(w, std::mem::size_of::<MaterialParams>() as u32)?;
+
+        write_config(w, &self.config)?;
+        write_u64(w, self.frame_index)?;
+        write_u32(w, self.next_tag)?;
+        write_f32(w, self.last_step_dt)?;
+
+        write_u64(w, self.particles.len() as u64)?;
+        write_u64(w, self.active_count as u64)?;
+        let records = self.particles.to_vec();
+        w.write_all(bytemuck::cast_slice(&records))?;
+
+        match &self.thermal {
+            Some(thermal) => {
+                write_u8(w, 1)?;
+                let c = &thermal.config;
+                for v in [
+                    c.conductivity,
+                    c.heat_capacity,
+                    c.ambient,
+                    c.grid_cell_size,
+                    c.cooling_rate,
+                ] {
+                    write_f32(w, v)?;
+                }
+                write_dims(w, thermal.grid_dims())?;
+            }
+            None => write_u8(w, 0)?,
+        }
+
+        write_u32(w, self.scalar_fields.len() as u32)?;
+        for field in &self.scalar_fields {
+            let c = &field.config;
+            for v in [c.diffusivity, c.decay_rate, c.ambient] {
+                write_f32(w, v)?;
+            }
+            write_dims(w, field.grid_dims())?;
+        }
+
+        let params = self.materials.all_params();
+        write_u32(w, params.len() as u32)?;
+        w.write_all(bytemuck::cast_slice(&params))?;
+
+        write_u32(w, self.rigid_bodies.len() as u32)?;
+        for body in &self.rigid_bodies {
+            for v in [
+                body.position.x,
+                body.position.y,
+                body.rotation,
+                body.linear_velocity.x,
+                body.linear_velocity.y,
+                body.angular_velocity,
+            ] {
+                write_f32(w, v)?;
+            }
+        }
+
+        write_u32(w, self.emitters.len() as u32)?;
+        for emitter in &self.emitters {
+            let (accumulator, rng_state) = emitter.emission_state();
+            write_f32(w, accumulator)?;
+            write_u32(w, rng_state)?;
+        }
+
+        match &self.id_index {
+            Some(_) => {
+                write_u8(w, 1)?;
+                write_u64(w, self.next_particle_id)?;
+                for &id in &self.particles.id {
+                    write_u64(w, id)?;
+                }
+            }
+            None => write_u8(w, 0)?,
+        }
+
+        let tracers = &self.tracers;
+        write_u64(w, tracers.len() as u64)?;
+        for i in 0..tracers.len() {
+            let (x, v) = (tracers.x()[i], tracers.v()[i]);
+            for value in [x.x, x.y, v.x, v.y, tracers.response_time()[i]] {
+                write_f32(w, value)?;
+            }
+        }
+
+        write_f64(w, self.timeline.time())?;
+        Ok(())
+    }
+
+    /// Restore a checkpoint from any reader. Same contract as `load_checkpoint`.
+    pub fn read_checkpoint<R: Read>(&mut self, r: &mut R) -> io::Result<()> {
+        let mut magic = [0u8; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not an emerge checkpoint (bad magic)".into()));
+        }
+        let version = read_u32(r)?;
+        if version != CHECKPOINT_VERSION {
+            return Err(invalid(format!(
+                "unsupported checkpoint version {version} (this build reads {CHECKPOINT_VERSION})"
+            )));
+        }
+        let particle_stride = read_u32(r)? as usize;
+        if particle_stride != std::mem::size_of::<Particle>() {
+            return Err(invalid(format!(
+                "particle record is {particle_stride} bytes, this build uses {}",
+                std::mem::size_of::<Particle>()
+            )));
+        }
+        let params_stride = read_u32(r)? as usize;
+        if params_stride != std::mem::size_of::<MaterialParams>() {
+            return Err(invalid(format!(
+                "material record is {params_stride} bytes, this build uses {}",
+                std::mem::size_of::<MaterialParams>()
+            )));
+        }
+
+        let config = read_config(r)?;
+        config.check().map_err(invalid)?;
+        let frame_index = read_u64(r)?;
+        let next_tag = read_u32(r)?;
+        let last_step_dt = read_f32(r)?;
+
+        let len = read_usize(r)?;
+        let active_count = read_usize(r)?;
+        if active_count > len {
+            return Err(invalid(format!(
+                "active_count {active_count} exceeds particle count {len}"
+            )));
+        }
+        // Read record-by-record rather than pre-allocating `len` records: a
+        // corrupt count then fails at EOF instead of attempting a huge allocation.
+        let mut particles = Particles::new();
+        let mut record = [0u8; std::mem::size_of::<Particle>()];
+        for i in 0..len {
+            r.read_exact(&mut record)?;
+            let particle = bytemuck::pod_read_unaligned::<Particle>(&record);
+            if !self.materials.is_registered(particle.material_id) {
+                return Err(invalid(format!(
+                    "particle {i} uses material {}, simulation has {} registered",
+                    particle.material_id,
+                    self.materials.len()
+                )));
+            }
+            particles.push(particle);
+        }
+
+        let thermal = match read_u8(r)? {
+            0 => None,
+            1 => {
+                let thermal_config = ThermalConfig {
+                    conductivity: read_f32(r)?,
+                    heat_capacity: read_f32(r)?,
+                    ambient: read_f32(r)?,
+                    grid_cell_size: read_f32(r)?,
+                    cooling_rate: read_f32(r)?,
+                };
+                let dims = read_dims(r)?;
+                check_dims("thermal model", dims, &config)?;
+                Some((thermal_config, dims))
+            }
+            other => return Err(invalid(format!("bad thermal flag {other}"))),
+        };
+
+        let scalar_count = read_u32(r)? as usize;
+        if scalar_count != self.scalar_fields.len() {
+            return Err(invalid(format!(
+                "checkpoint has {scalar_count} scalar fields, simulation has {} attached",
+                self.scalar_fields.len()
+            )));
+        }
+        let mut scalar_configs = Vec::with_capacity(scalar_count);
+        for (i, field) in self.scalar_fields.iter().enumerate() {
+            let diffusivity = read_f32(r)?;
+            let decay_rate = read_f32(r)?;
+            let ambient = read_f32(r)?;
+            let dims = read_dims(r)?;
+            check_dims(&format!("scalar field {i}"), dims, &config)?;
+            if dims != field.grid_dims() {
+                return Err(invalid(format!(
+                    "scalar field {i} was saved on a {dims} grid, attached field uses {}",
+                    field.grid_dims()
+                )));
+            }
+            scalar_configs.push((diffusivity, decay_rate, ambient));
+        }
+
+        let material_count = read_u32(r)? as usize;
+        let live = self.materials.all_params();
+        if material_count != live.len() {
+            return Err(invalid(format!(
+                "checkpoint has {material_count} materials, simulation has {} registered",
+                live.len()
+            )));
+        }
+        let mut record = [0u8; std::mem::size_of::<MaterialParams>()];
+        for (id, params) in live.iter().enumerate() {
+            r.read_exact(&mut record)?;
+            if record[..] != *bytemuck::bytes_of(params)
+                && !self.timeline.animates_material(id as u32)
+            {
+                return Err(invalid(format!(
+                    "material {id} params differ from the checkpoint -- register the same materials before restoring"
+                )));
+            }
+        }
+
+        let body_count = read_u32(r)? as usize;
+        if body_count != self.rigid_bodies.len() {
+            return Err(invalid(format!(
+                "checkpoint has {body_count} rigid bodies, simulation has {} attached",
+                self.rigid_bodies.len()
+            )));
+        }
+        let mut body_states = Vec::with_capacity(body_count);
+        for _ in 0..body_count {
+            let position = Vec2::new(read_f32(r)?, read_f32(r)?);
+            let rotation = read_f32(r)?;
+            let linear_velocity = Vec2::new(read_f32(r)?, read_f32(r)?);
+            let angular_velocity = read_f32(r)?;
+            body_states.push((position, rotation, linear_velocity, angular_velocity));
+        }
+
+        let emitter_count = read_u32(r)? as usize;
+        if emitter_count != self.emitters.len() {
+            return Err(invalid(format!(
+                "checkpoint has {emitter_count} emitters, simulation has {} attached",
+                self.emitters.len()
+            )));
+        }
+        let mut emitter_states = Vec::with_capacity(emitter_count);
+        for _ in 0..emitter_count {
+            emitter_states.push((read_f32(r)?, read_u32(r)?));
+        }
+
+        // A file without IDs restored into a simulation that has them enabled
+        // re-issues them below, in physical order.
+        let particle_ids = match read_u8(r)? {
+            0 => None,
+            1 => {
+                let next_id = read_u64(r)?;
+                let mut seen = HashSet::with_capacity(len);
+                for i in 0..len {
+                    let id = read_u64(r)?;
+                    if id >= next_id || !seen.insert(id) {
+                        return Err(invalid(format!(
+                            "particle {i} has id {id}, duplicated or not below the counter {next_id}"
+                        )));
+                    }
+                    particles.id[i] = id;
+                }
+                Some(next_id)
+            }
+            other => return Err(invalid(format!("bad particle id flag {other}"))),
+        };
+
+        let tracer_count = read_usize(r)?;
+        let mut tracers = Tracers::new();
+        for _ in 0..tracer_count {
+            let x = Vec2::new(read_f32(r)?, read_f32(r)?);
+            let v = Vec2::new(read_f32(r)?, read_f32(r)?);
+            tracers.push(x, read_f32(r)?);
+            *tracers.v_mut().last_mut().unwrap() = v;
+        }
+        tracers.set_history_len(self.tracers.history_len());
+
+        let timeline_time = read_f64(r)?;
+        if !timeline_time.is_finite() {
+            return Err(invalid(format!("bad timeline time {timeline_time}")));
+        }
+
+        // Everything validated -- only now touch `self`, so a failed load leaves
+        // the running simulation intact.
+        if config.grid_domain() != self.grid.domain() {
+            self.grid = Grid::with_domain(config.grid_domain());
+        }
+        self.spatial_hash = SpatialHash::for_config(&config);
+        self.config = config;
+        self.frame_index = frame_index;
+        self.next_tag = next_tag;
+        self.last_step_dt = last_step_dt;
+        self.particles = particles;
+        self.active_count = active_count;
+        self.tag_index.clear();
+        for (i, &tag) in self.particles.user_tag.iter().enumerate() {
+            self.tag_index.entry(tag).or_default().insert(i);
+        }
+        let ids_were_enabled = self.id_index.is_some();
+        self.id_index = None;
+        match particle_ids {
+            Some(next_id) => {
+                self.id_index = Some(HashMap::with_capacity(len));
+                self.next_particle_id = next_id;
+                self.rebuild_particle_id_index();
+            }
+            None if ids_were_enabled => self.enable_particle_ids(),
+            None => {}
+        }
+        self.thermal = thermal.map(|(config, dims)| ThermalDiffusion::with_dims(config, dims));
+        for (field, (diffusivity, decay_rate, ambient)) in
+            self.scalar_fields.iter_mut().zip(scalar_configs)
+        {
+            field.config.diffusivity = diffusivity;
+            field.config.decay_rate = decay_rate;
+            field.config.ambient = ambient;
+        }
+        for (body, (position, rotation, linear_velocity, angular_velocity)) in
+            self.rigid_bodies.iter_mut().zip(body_states)
+        {
+            body.position = position;
+            body.rotation = rotation;
+            body.linear_velocity = linear_velocity;
+            body.angular_velocity = angular_velocity;
+        }
+        for (emitter, (accumulator, rng_state)) in self.emitters.iter_mut().zip(emitter_states) {
+            emitter.set_emission_state(accumulator, rng_state);
+        }
+        self.tracers = tracers;
+        self.timeline.seek(timeline_time);
+        // Output recorded against the old particles and grid no longer applies,
+        // nor do targets aimed from the old positions.
+        self.reset_boundary_reactions();
+        self.kinematic_targets.clear();
+        self.record_stresses();
+        self.spatial_hash
+            .rebuild(&self.particles.x, self.active_count);
+        Ok(())
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Field grids are allocated from the stored dims, so anything but the
+/// restored config's own grid is rejected before it can size an allocation.
+fn check_dims(what: &str, dims: UVec2, config: &SimConfig) -> io::Result<()> {
+    if dims == config.grid_dims() {
+        Ok(())
+    } else {
+        Err(invalid(format!(
+            "{what} was saved on a {dims} grid, the checkpoint's config uses {}",
+            config.grid_dims()
+        )))
+    }
+}
+
+// ── SimConfig encoding ───────────────────────────────────────────────────────
+// Field-by-field, in declaration order. Adding a `SimConfig` field means
+// adding it here AND bumping `CHECKPOINT_VERSION` -- the struct literal in
+// `read_config` fails to compile until the new field is handled.
+
+fn write_config<W: Write>(w: &mut W, c: &SimConfig) -> io::Result<()> {
+    write_u64(w, c.grid_res as u64)?;
+    write_f32(w, c.grid_cell_size)?;
+    write_f32(w, c.dt)?;
+    write_bool(w, c.adaptive_timestep)?;
+    write_bool(w, c.cfl_include_affine_speed)?;
+    write_f32(w, c.cfl_coefficient)?;
+    write_f32(w, c.material_cfl_coefficient)?;
+    write_f32(w, c.viscous_timestep_coefficient)?;
+    write_f32(w, c.min_dt)?;
+    write_bool(w, c.project_invalid_state)?;
+    write_f32(w, c.projection_min_density)?;
+    write_f32(w, c.projection_min_volume)?;
+    write_f32(w, c.projection_min_deformation_j)?;
+    write_f32(w, c.gravity.x)?;
+    write_f32(w, c.gravity.y)?;
+    write_u64(w, c.boundary_thickness as u64)?;
+    write_f32(w, c.default_initial_volume)?;
+    write_bool(w, c.recompute_density_each_step)?;
+    write_f32(w, c.particle_mass)?;
+    write_u64(w, c.max_substeps_per_step as u64)?;
+    write_f32(w, c.apic_blend)?;
+    write_f32(w, c.j_max)?;
+    write_f32(w, c.sleep_threshold)?;
+    write_f32(w, c.contact_friction)?;
+    write_f32(w, c.asflip_blend)?;
+    write_f32(w, c.mixture_drag_coefficient)?;
+    write_u32(w, c.mixture_pressure_iterations)?;
+    write_f32(w, c.dx_meters)?;
+    write_f32(w, c.dt_seconds)?;
+    write_bool(w, c.parallel_p2g)?;
+    write_bool(w, c.periodic_x)?;
+    write_bool(w, c.periodic_y)?;
+    // 0 = square
+    write_u64(w, c.grid_res_y.unwrap_or(0) as u64)
+}
+
+fn read_config<R: Read>(r: &mut R) -> io::Result<SimConfig> {
+    Ok(SimConfig {
+        grid_res: read_usize(r)?,
+        grid_cell_size: read_f32(r)?,
+        dt: read_f32(r)?,
+        adaptive_timestep: read_bool(r)?,
+        cfl_include_affine_speed: read_bool(r)?,
+        cfl_coefficient: read_f32(r)?,
+        material_cfl_coefficient: read_f32(r)?,
+        viscous_timestep_coefficient: read_f32(r)?,
+        min_dt: read_f32(r)?,
+        project_invalid_state: read_bool(r)?,
+        projection_min_density: read_f32(r)?,
+        projection_min_volume: read_f32(r)?,
+        projection_min_deformation_j: read_f32(r)?,
+        gravity: Vec2::new(read_f32(r)?, read_f32(r)?),
+        boundary_thickness: read_usize(r)?,
+        default_initial_volume: read_f32(r)?,
+        recompute_density_each_step: read_bool(r)?,
+        particle_mass: read_f32(r)?,
+        max_substeps_per_step: read_usize(r)?,
+        apic_blend: read_f32(r)?,
+        j_max: read_f32(r)?,
+        sleep_threshold: read_f32(r)?,
+        contact_friction: read_f32(r)?,
+        asflip_blend: read_f32(r)?,
+        mixture_drag_coefficient: read_f32(r)?,
+        mixture_pressure_iterations: read_u32(r)?,
+        dx_meters: read_f32(r)?,
+        dt_seconds: read_f32(r)?,
+        parallel_p2g: read_bool(r)?,
+        periodic_x: read_bool(r)?,
+        periodic_y: read_bool(r)?,
+        grid_res_y: Some(read_usize(r)?).filter(|&h| h != 0),
+    })
+}
+
+/// Field-grid dimensions: width, then height.
+fn write_dims<W: Write>(w: &mut W, dims: UVec2) -> io::Result<()> {
+    write_u64(w, dims.x as u64)?;
+    write_u64(w, dims.y as u64)
+}
+
+fn read_dims<R: Read>(r: &mut R) -> io::Result<UVec2> {
+    let mut axis = || {
+        let v = read_u64(r)?;
+        u32::try_from(v).map_err(|_| invalid(format!("grid dimension {v} out of range")))
+    };
+    Ok(UVec2::new(axis()?, axis()?))
+}
+
+// ── Primitive little-endian codecs ───────────────────────────────────────────
+
+fn write_u8<W: Write>(w: &mut W, v: u8) -> io::Result<()> {
+    w.write_all(&[v])
+}
+
+fn write_bool<W: Write>(w: &mut W, v: bool) -> io::Result<()> {
+    write_u8(w, v as u8)
+}
+
+fn write_u32<W: Write>(w: &mut W, v: u32) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+fn write_u64<W: Write>(w: &mut W, v: u64) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+fn write_f32<W: Write>(w: &mut W, v: f32) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+fn write_f64<W: Write>(w: &mut W, v: f64) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+fn read_u8<R: Read>(r: &mut R) -> io::Result<u8> {
+    let mut b = [0u8; 1];
+    r.read_exact(&mut b)?;
+    Ok(b[0])
+}
+
+fn read_bool<R: Read>(r: &mut R) -> io::Result<bool> {
+    match read_u8(r)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        other => Err(invalid(format!("bad bool byte {other}"))),
+    }
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut b = [0u8; 4];
+    r.read_exact(&mut b)?;
+    Ok(u32::from_le_bytes(b))
+}
+
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut b = [0u8; 8];
+    r.read_exact(&mut b)?;
+    Ok(u64::from_le_bytes(b))
+}
+
+fn read_usize<R: Read>(r: &mut R) -> io::Result<usize> {
+    let v = read_u64(r)?;
+    usize::try_from(v).map_err(|_| invalid(format!("count {v} does not fit in usize")))
+}
+
+fn read_f32<R: Read>(r: &mut R) -> io::Result<f32> {
+    let mut b = [0u8; 4];
+    r.read_exact(&mut b)?;
+    Ok(f32::from_le_bytes(b))
+}
+
+fn read_f64<R: Read>(r: &mut R) -> io::Result<f64> {
+    let mut b = [0u8; 8];
+    r.read_exact(&mut b)?;
+    Ok(f64::from_le_bytes(b))
+}
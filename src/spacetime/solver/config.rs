@@ -1,10 +1,26 @@
-use glam::{IVec2, Mat2, Vec2};
+use glam::{BVec2, IVec2, Mat2, UVec2, Vec2};
+
+use crate::grid::GridDomain;
 
 /// Shape mask applied to the particle grid during spawning.
 ///
 /// The grid always iterates the bounding box defined by `SpawnRegion::box_size`.
-/// `SpawnShape::Disk` discards particles whose grid position falls outside the
-/// circle, producing a disk-shaped region with the same spacing and jitter.
+/// Every other shape discards particles whose grid position falls outside it,
+/// producing that shape with the same spacing and jitter. Coordinates are
+/// relative to `box_center` and, like the lattice, turn with
+/// `SpawnRegion::rotation`.
+///
+/// Shapes combine with `union`, `difference` and `intersection` — a hollow
+/// container is a rectangle minus a smaller one, a tyre an annulus:
+/// ```rust
+/// # extern crate emerge_engine as emerge;
+/// # use emerge::SpawnShape;
+/// # use glam::Vec2;
+/// let cup = SpawnShape::rect(Vec2::ZERO, Vec2::new(8.0, 6.0), 0.0)
+///     .difference(SpawnShape::rect(Vec2::new(0.0, 1.0), Vec2::new(6.0, 5.0), 0.0));
+/// assert!(cup.contains(Vec2::new(0.0, -5.5)));
+/// assert!(!cup.contains(Vec2::ZERO));
+/// ```
 #[non_exhaustive]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum SpawnShape {
@@ -15,16 +31,425 @@ pub enum SpawnShape {
     /// Set `box_size` large enough to contain the disk — a square of side
     /// `2 * radius` is exactly right, e.g. `IVec2::splat((2.0 * radius) as i32 + 1)`.
     Disk { radius: f32 },
+    /// Ring between `inner_radius` and `outer_radius`, centered on `box_center`.
+    Annulus {
+        inner_radius: f32,
+        outer_radius: f32,
+    },
+    /// Rectangle of half-extents `half` around `center`, turned `angle`
+    /// radians counter-clockwise.
+    Rect {
+        center: Vec2,
+        half: Vec2,
+        angle: f32,
+    },
+    /// Segment `a`–`b` inflated by `radius`; `a == b` is a disk anywhere.
+    Capsule { a: Vec2, b: Vec2, radius: f32 },
+    /// A polygon, or a union, difference or intersection of shapes. Built by
+    /// `polygon` and the combinators.
+    Composite(CompositeShape),
+}
+
+impl SpawnShape {
+    /// Ring between the two radii.
+    ///
+    /// Panics unless `0 <= inner_radius < outer_radius`.
+    pub fn annulus(inner_radius: f32, outer_radius: f32) -> Self {
+        assert!(
+            inner_radius >= 0.0 && outer_radius > inner_radius,
+            "annulus needs 0 <= inner_radius < outer_radius"
+        );
+        Self::Annulus {
+            inner_radius,
+            outer_radius,
+        }
+    }
+
+    /// Rectangle of half-extents `half` around `center`, turned `angle` radians.
+    ///
+    /// Panics unless both half-extents are positive.
+    pub fn rect(center: Vec2, half: Vec2, angle: f32) -> Self {
+        assert!(
+            half.x > 0.0 && half.y > 0.0,
+            "rect half-extents must be positive"
+        );
+        Self::Rect {
+            center,
+            half,
+            angle,
+        }
+    }
+
+    /// Segment `a`–`b` inflated by `radius`.
+    ///
+    /// Panics unless `radius` is positive.
+    pub fn capsule(a: Vec2, b: Vec2, radius: f32) -> Self {
+        assert!(radius > 0.0, "capsule radius must be positive");
+        Self::Capsule { a, b, radius }
+    }
+
+    /// Simple polygon, convex or concave, vertices in either winding order.
+    /// Inside is decided by an even-odd crossing test.
+    ///
+    /// Panics unless there are 3 to 22 vertices (`MAX_SHAPE_NUMBERS / 2`).
+    /// Combined with other shapes, all of their numbers share that budget.
+    pub fn polygon(vertices: &[Vec2]) -> Self {
+        Self::try_polygon(vertices).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Inside either shape.
+    ///
+    /// Panics if the combination needs more than `MAX_SHAPE_NODES` nodes or
+    /// `MAX_SHAPE_NUMBERS` numbers.
+    pub fn union(self, other: SpawnShape) -> Self {
+        self.combine(Node::Union, other)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Inside this shape but not `cut`. Panics like `union`.
+    pub fn difference(self, cut: SpawnShape) -> Self {
+        self.combine(Node::Difference, cut)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Inside both shapes. Panics like `union`.
+    pub fn intersection(self, other: SpawnShape) -> Self {
+        self.combine(Node::Intersection, other)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Non-panicking `polygon`, for shapes read from files.
+    pub(crate) fn try_polygon(vertices: &[Vec2]) -> Result<Self, String> {
+        if vertices.len() < 3 {
+            return Err("polygon needs at least 3 vertices".into());
+        }
+        if vertices.len() > MAX_SHAPE_NUMBERS / 2 {
+            return Err(format!(
+                "polygon has {} vertices, at most {} fit in a spawn shape",
+                vertices.len(),
+                MAX_SHAPE_NUMBERS / 2
+            ));
+        }
+        let mut shape = CompositeShape::EMPTY;
+        let flat: Vec<f32> = vertices.iter().flat_map(|v| v.to_array()).collect();
+        shape.push(Node::Polygon(vertices.len() as u8), &flat)?;
+        Ok(Self::Composite(shape))
+    }
+
+    /// Non-panicking combinators: `op` is `Union`, `Difference` or
+    /// `Intersection`.
+    pub(crate) fn combine(self, op: Node, other: SpawnShape) -> Result<Self, String> {
+        let mut shape = CompositeShape::from(self);
+        let other = CompositeShape::from(other);
+        shape.push_all(other.nodes(), other.numbers())?;
+        shape.push(op, &[])?;
+        Ok(Self::Composite(shape))
+    }
+
+    /// Whether `p`, relative to `box_center` in the region's own (unrotated)
+    /// frame, is inside. `Box` is inside everywhere: the lattice itself is
+    /// what clips it to `box_size`.
+    pub fn contains(&self, p: Vec2) -> bool {
+        match self {
+            Self::Box => true,
+            Self::Disk { radius } => p.length() <= *radius,
+            Self::Annulus {
+                inner_radius,
+                outer_radius,
+            } => (*inner_radius..=*outer_radius).contains(&p.length()),
+            Self::Rect {
+                center,
+                half,
+                angle,
+            } => {
+                let local = Vec2::from_angle(-angle).rotate(p - *center);
+                local.abs().cmple(*half).all()
+            }
+            Self::Capsule { a, b, radius } => {
+                let e = *b - *a;
+                let t = ((p - *a).dot(e) / e.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+                p.distance(*a + e * t) <= *radius
+            }
+            Self::Composite(shape) => shape.evaluate(
+                |shape| shape.contains(p),
+                |vertices| polygon_contains(vertices, p),
+                |op, a, b| match op {
+                    Node::Union => a || b,
+                    Node::Difference => a && !b,
+                    _ => a && b,
+                },
+            ),
+        }
+    }
+
+    /// Half-size of the `box_size` needed to cover the shape, or `None` when
+    /// its extent depends on `Box`, which fits whatever `box_size` is.
+    fn half_extent(&self) -> Option<Vec2> {
+        match self {
+            Self::Box => None,
+            Self::Disk { radius } => Some(Vec2::splat(*radius)),
+            Self::Annulus { outer_radius, .. } => Some(Vec2::splat(*outer_radius)),
+            Self::Rect {
+                center,
+                half,
+                angle,
+            } => Some(center.abs() + turned_half_extent(*half, *angle)),
+            Self::Capsule { a, b, radius } => Some(a.abs().max(b.abs()) + *radius),
+            Self::Composite(shape) => shape.evaluate(
+                SpawnShape::half_extent,
+                |vertices| {
+                    vertices
+                        .chunks_exact(2)
+                        .map(|v| Vec2::from_slice(v).abs())
+                        .reduce(Vec2::max)
+                },
+                |op, a, b| match op {
+                    Node::Union => Some(a?.max(b?)),
+                    Node::Difference => a,
+                    _ => Some(a?.min(b?)),
+                },
+            ),
+        }
+    }
+}
+
+/// Even-odd crossing test against the polygon of flat `x y` pairs.
+fn polygon_contains(vertices: &[f32], p: Vec2) -> bool {
+    let count = vertices.len() / 2;
+    let vertex = |i: usize| Vec2::from_slice(&vertices[2 * (i % count)..]);
+    let mut inside = false;
+    for i in 0..count {
+        let (a, b) = (vertex(i), vertex(i + 1));
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Most nodes (shapes plus combinators) one `CompositeShape` holds: eight
+/// shapes joined by seven combinators.
+pub const MAX_SHAPE_NODES: usize = 15;
+
+/// Most numbers one `CompositeShape` holds, counted as in a scene file's
+/// `shape`: 1 for a disk, 2 for an annulus, 5 for a rect or a capsule and 2
+/// per polygon vertex — a lone polygon can have up to 22 vertices.
+pub const MAX_SHAPE_NUMBERS: usize = 44;
+
+/// Polygons and CSG combinations, stored inline with a fixed capacity
+/// (`MAX_SHAPE_NODES`, `MAX_SHAPE_NUMBERS`) so that `SpawnShape` and
+/// `SpawnRegion` stay `Copy`. The nodes are in postfix order, each leaf
+/// taking its numbers from the pool in turn: a shape pushes its result, a
+/// combinator pops two and pushes their combination. Unused slots stay at
+/// their initial value, so the derived `PartialEq` compares shapes.
+#[derive(Clone, Copy, PartialEq)]
+pub struct CompositeShape {
+    nodes: [Node; MAX_SHAPE_NODES],
+    numbers: [f32; MAX_SHAPE_NUMBERS],
+    node_count: u8,
+    number_count: u8,
+}
+
+/// One postfix step of a `CompositeShape`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Node {
+    Box,
+    Disk,
+    Annulus,
+    Rect,
+    Capsule,
+    /// A polygon of this many vertices.
+    Polygon(u8),
+    Union,
+    Difference,
+    Intersection,
+}
+
+/// A `CompositeShape` node with its numbers decoded.
+pub(crate) enum Step<'a> {
+    Shape(SpawnShape),
+    /// Flat `x y` vertex pairs.
+    Polygon(&'a [f32]),
+    Combine(Node),
+}
+
+impl Node {
+    /// How many numbers the node takes from the pool.
+    fn arity(self) -> usize {
+        match self {
+            Self::Disk => 1,
+            Self::Annulus => 2,
+            Self::Rect | Self::Capsule => 5,
+            Self::Polygon(vertices) => 2 * vertices as usize,
+            Self::Box | Self::Union | Self::Difference | Self::Intersection => 0,
+        }
+    }
+}
+
+impl CompositeShape {
+    const EMPTY: Self = Self {
+        nodes: [Node::Box; MAX_SHAPE_NODES],
+        numbers: [0.0; MAX_SHAPE_NUMBERS],
+        node_count: 0,
+        number_count: 0,
+    };
+
+    fn nodes(&self) -> &[Node] {
+        &self.nodes[..self.node_count as usize]
+    }
+
+    fn numbers(&self) -> &[f32] {
+        &self.numbers[..self.number_count as usize]
+    }
+
+    fn push(&mut self, node: Node, numbers: &[f32]) -> Result<(), String> {
+        let (at, from) = (self.node_count as usize, self.number_count as usize);
+        if at == MAX_SHAPE_NODES {
+            return Err(format!(
+                "spawn shape has more than {MAX_SHAPE_NODES} shapes and combinators"
+            ));
+        }
+        debug_assert_eq!(numbers.len(), node.arity(), "{node:?} numbers");
+        if from + numbers.len() > MAX_SHAPE_NUMBERS {
+            return Err(format!(
+                "spawn shape needs more than {MAX_SHAPE_NUMBERS} numbers"
+            ));
+        }
+        self.nodes[at] = node;
+        self.numbers[from..][..numbers.len()].copy_from_slice(numbers);
+        self.node_count += 1;
+        self.number_count += numbers.len() as u8;
+        Ok(())
+    }
+
+    /// Push `nodes` with their `numbers`, in order.
+    fn push_all(&mut self, nodes: &[Node], mut numbers: &[f32]) -> Result<(), String> {
+        for &node in nodes {
+            let (own, rest) = numbers.split_at(node.arity());
+            self.push(node, own)?;
+            numbers = rest;
+        }
+        Ok(())
+    }
+
+    /// The nodes in postfix order, numbers decoded.
+    pub(crate) fn steps(&self) -> impl Iterator<Item = Step<'_>> {
+        let mut numbers = self.numbers();
+        self.nodes().iter().map(move |&node| {
+            let (v, rest) = numbers.split_at(node.arity());
+            numbers = rest;
+            let at = |i: usize| Vec2::new(v[i], v[i + 1]);
+            Step::Shape(match node {
+                Node::Box => SpawnShape::Box,
+                Node::Disk => SpawnShape::Disk { radius: v[0] },
+                Node::Annulus => SpawnShape::Annulus {
+                    inner_radius: v[0],
+                    outer_radius: v[1],
+                },
+                Node::Rect => SpawnShape::Rect {
+                    center: at(0),
+                    half: at(2),
+                    angle: v[4],
+                },
+                Node::Capsule => SpawnShape::Capsule {
+                    a: at(0),
+                    b: at(2),
+                    radius: v[4],
+                },
+                Node::Polygon(_) => return Step::Polygon(v),
+                op => return Step::Combine(op),
+            })
+        })
+    }
+
+    /// Fold the postfix steps: `shape` and `polygon` value the leaves,
+    /// `combine` joins the two values under a combinator.
+    fn evaluate<T: Copy + Default>(
+        &self,
+        shape: impl Fn(&SpawnShape) -> T,
+        polygon: impl Fn(&[f32]) -> T,
+        combine: impl Fn(Node, T, T) -> T,
+    ) -> T {
+        let mut stack = [T::default(); MAX_SHAPE_NODES];
+        let mut depth = 0;
+        for step in self.steps() {
+            let value = match step {
+                Step::Shape(leaf) => shape(&leaf),
+                Step::Polygon(vertices) => polygon(vertices),
+                Step::Combine(op) => {
+                    depth -= 2;
+                    combine(op, stack[depth], stack[depth + 1])
+                }
+            };
+            stack[depth] = value;
+            depth += 1;
+        }
+        stack[0]
+    }
+}
+
+impl From<SpawnShape> for CompositeShape {
+    fn from(shape: SpawnShape) -> Self {
+        let mut composite = Self::EMPTY;
+        // A single shape always fits.
+        let pushed = match shape {
+            SpawnShape::Composite(shape) => return shape,
+            SpawnShape::Box => composite.push(Node::Box, &[]),
+            SpawnShape::Disk { radius } => composite.push(Node::Disk, &[radius]),
+            SpawnShape::Annulus {
+                inner_radius,
+                outer_radius,
+            } => composite.push(Node::Annulus, &[inner_radius, outer_radius]),
+            SpawnShape::Rect {
+                center,
+                half,
+                angle,
+            } => composite.push(Node::Rect, &[center.x, center.y, half.x, half.y, angle]),
+            SpawnShape::Capsule { a, b, radius } => {
+                composite.push(Node::Capsule, &[a.x, a.y, b.x, b.y, radius])
+            }
+        };
+        debug_assert!(pushed.is_ok());
+        composite
+    }
+}
+
+impl std::fmt::Debug for CompositeShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompositeShape")
+            .field("nodes", &self.nodes())
+            .field("numbers", &self.numbers())
+            .finish()
+    }
+}
+
+/// Half-extents of the axis-aligned box around a box of half-extents `half`
+/// turned by `angle` radians.
+fn turned_half_extent(half: Vec2, angle: f32) -> Vec2 {
+    let (sin, cos) = angle.sin_cos();
+    Vec2::new(
+        cos.abs() * half.x + sin.abs() * half.y,
+        sin.abs() * half.x + cos.abs() * half.y,
+    )
 }
 
 /// D⁻¹ = 4.0 for the quadratic B-spline MLS-MPM kernel (always).
 /// Not a tunable parameter — hardcoded from Hu 2018 Table 1.
 pub(crate) const KERNEL_D_INVERSE: f32 = 4.0;
 
+/// Largest `grid_res` / `grid_res_y` that `SimConfig::check` accepts. Keeps
+/// every node index inside `u32` and the grid inside a sane allocation, even
+/// for configs read from untrusted files.
+pub const MAX_GRID_RES: usize = 8192;
+
 /// Parameters that control the physics solver and its runtime behavior.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct SimConfig {
+    /// Cells along x — and along y too unless `grid_res_y` is set.
     pub grid_res: usize,
+    /// Cells along y for a non-square domain (e.g. a 512×96 channel), or `None`
+    /// for a square `grid_res`² grid (default). CPU solver only.
+    pub grid_res_y: Option<usize>,
     pub grid_cell_size: f32,
     pub dt: f32,
     pub adaptive_timestep: bool,
@@ -105,6 +530,24 @@ pub struct SimConfig {
     /// liquid is the documented worst case for a low iteration count), not by
     /// assuming a small fixed count is free.
     pub mixture_pressure_iterations: u32,
+    /// Run P2G (and the density-recompute mass scatter) on rayon workers instead of
+    /// one thread — see `transfer::scatter_particles_to_grid_parallel`. Deterministic:
+    /// particles are scattered in fixed-size chunks merged in chunk order, so a scene
+    /// reproduces bit-for-bit across runs and across thread counts. NOT bit-identical
+    /// to the sequential path, though — cells shared between chunks sum in a different
+    /// grouping, which is enough to move a long chaotic run (the reason the first
+    /// parallel attempt was reverted, see `scatter_particles_to_grid` doc). false =
+    /// sequential (default), every existing scene keeps its exact trajectory.
+    pub parallel_p2g: bool,
+    /// Wrap the domain around on the x axis: material leaving through the right
+    /// edge re-enters on the left, and P2G/G2P stencils, spatial-hash queries and
+    /// the built-in wall boundaries all treat the two edges as one seam. Composes
+    /// with the walls on the other axis — `periodic_x` plus a `SlipBoundary` is
+    /// an endless channel with a floor and ceiling. false = walled (default).
+    /// CPU solver only.
+    pub periodic_x: bool,
+    /// Same as `periodic_x` for the y axis (e.g. an endless vertical fall).
+    pub periodic_y: bool,
 
     // ── Physical unit scaling ──────────────────────────────────────────────────
     // Default 1.0 = simulation units (no scaling). Set these to enable SI-calibrated materials.
@@ -127,6 +570,7 @@ impl Default for SimConfig {
     fn default() -> Self {
         Self {
             grid_res: 64,
+            grid_res_y: None,
             grid_cell_size: 1.0,
             dt: 1.0,
             adaptive_timestep: true,
@@ -152,6 +596,9 @@ impl Default for SimConfig {
             asflip_blend: 0.0,
             mixture_drag_coefficient: 0.0,
             mixture_pressure_iterations: 0,
+            parallel_p2g: false,
+            periodic_x: false,
+            periodic_y: false,
             dx_meters: 1.0,
             dt_seconds: 1.0,
         }
@@ -243,59 +690,106 @@ impl SimConfig {
             / (self.dt_seconds * self.dt_seconds * self.dt_seconds)
     }
 
-    /// Validate solver-side numerical and domain constraints.
+    /// Cells per axis: `(grid_res, grid_res_y.unwrap_or(grid_res))`.
+    pub fn grid_dims(&self) -> UVec2 {
+        UVec2::new(
+            self.grid_res as u32,
+            self.grid_res_y.unwrap_or(self.grid_res) as u32,
+        )
+    }
+
+    /// The grid's extent and wrap mask, as the grid and boundaries take it.
+    pub fn grid_domain(&self) -> GridDomain {
+        GridDomain::new(self.grid_dims()).with_periodic(self.periodic_axes())
+    }
+
+    /// `(periodic_x, periodic_y)` as a mask, the form the grid and boundaries take.
+    pub fn periodic_axes(&self) -> BVec2 {
+        BVec2::new(self.periodic_x, self.periodic_y)
+    }
+
+    /// Validate solver-side numerical and domain constraints; panics with
+    /// the first violation `check` finds.
     pub fn validate(&self) {
-        assert!(self.grid_res >= 4, "grid_res must be >= 4");
-        assert!(self.grid_cell_size > 0.0, "grid_cell_size must be positive");
-        assert!(self.dt > 0.0, "dt must be positive");
-        assert!(
-            self.cfl_coefficient > 0.0,
-            "cfl_coefficient must be positive"
-        );
-        assert!(
-            self.material_cfl_coefficient > 0.0,
-            "material_cfl_coefficient must be positive"
-        );
-        assert!(
-            self.viscous_timestep_coefficient > 0.0,
-            "viscous_timestep_coefficient must be positive"
-        );
-        assert!(self.min_dt > 0.0, "min_dt must be positive");
-        assert!(self.min_dt <= self.dt, "min_dt must be <= dt");
-        assert!(
-            self.projection_min_density > 0.0,
-            "projection_min_density must be positive"
-        );
-        assert!(
-            self.projection_min_volume > 0.0,
-            "projection_min_volume must be positive"
-        );
-        assert!(
-            self.projection_min_deformation_j > 0.0,
-            "projection_min_deformation_j must be positive"
-        );
-        assert!(self.particle_mass > 0.0, "particle_mass must be positive");
-        assert!(
-            self.contact_friction >= 0.0,
-            "contact_friction must be non-negative"
-        );
-        assert!(
-            self.max_substeps_per_step > 0,
-            "max_substeps_per_step must be > 0"
-        );
-        assert!(
-            self.default_initial_volume > 0.0,
-            "default_initial_volume must be positive"
-        );
-        assert!(self.j_max > 1.0, "j_max must be > 1.0");
-        assert!(
-            (0.0..=1.0).contains(&self.apic_blend),
-            "apic_blend must be in [0, 1]"
-        );
-        assert!(
-            self.boundary_thickness > 0 && self.boundary_thickness < self.grid_res - 1,
-            "boundary_thickness must be in [1, grid_res-2]"
-        );
+        if let Err(message) = self.check() {
+            panic!("{message}");
+        }
+    }
+
+    /// Non-panicking `validate`: the first violated constraint, for configs
+    /// read from files or the command line.
+    pub fn check(&self) -> Result<(), String> {
+        let checks = [
+            (self.grid_res >= 4, "grid_res must be >= 4"),
+            (
+                self.grid_res <= MAX_GRID_RES,
+                "grid_res must be <= MAX_GRID_RES (8192)",
+            ),
+            (
+                self.grid_res_y.is_none_or(|h| h >= 4),
+                "grid_res_y must be >= 4",
+            ),
+            (
+                self.grid_res_y.is_none_or(|h| h <= MAX_GRID_RES),
+                "grid_res_y must be <= MAX_GRID_RES (8192)",
+            ),
+            (self.grid_cell_size > 0.0, "grid_cell_size must be positive"),
+            (self.dt > 0.0, "dt must be positive"),
+            (
+                self.cfl_coefficient > 0.0,
+                "cfl_coefficient must be positive",
+            ),
+            (
+                self.material_cfl_coefficient > 0.0,
+                "material_cfl_coefficient must be positive",
+            ),
+            (
+                self.viscous_timestep_coefficient > 0.0,
+                "viscous_timestep_coefficient must be positive",
+            ),
+            (self.min_dt > 0.0, "min_dt must be positive"),
+            (self.min_dt <= self.dt, "min_dt must be <= dt"),
+            (
+                self.projection_min_density > 0.0,
+                "projection_min_density must be positive",
+            ),
+            (
+                self.projection_min_volume > 0.0,
+                "projection_min_volume must be positive",
+            ),
+            (
+                self.projection_min_deformation_j > 0.0,
+                "projection_min_deformation_j must be positive",
+            ),
+            (self.particle_mass > 0.0, "particle_mass must be positive"),
+            (
+                self.contact_friction >= 0.0,
+                "contact_friction must be non-negative",
+            ),
+            (
+                self.max_substeps_per_step > 0,
+                "max_substeps_per_step must be > 0",
+            ),
+            (
+                self.default_initial_volume > 0.0,
+                "default_initial_volume must be positive",
+            ),
+            (self.j_max > 1.0, "j_max must be > 1.0"),
+            (
+                (0.0..=1.0).contains(&self.apic_blend),
+                "apic_blend must be in [0, 1]",
+            ),
+            (
+                self.boundary_thickness > 0
+                    && self.boundary_thickness
+                        < (self.grid_dims().min_element() as usize).saturating_sub(1),
+                "boundary_thickness must be in [1, min(grid_res, grid_res_y)-2]",
+            ),
+        ];
+        match checks.into_iter().find(|&(ok, _)| !ok) {
+            Some((_, message)) => Err(message.into()),
+            None => Ok(()),
+        }
     }
 }
 
@@ -313,12 +807,20 @@ impl SimConfig {
 ///     .spacing(0.5)
 ///     .material(1);
 /// ```
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct SpawnRegion {
     pub spacing: f32,
     pub box_size: IVec2,
     pub box_center: Vec2,
     pub shape: SpawnShape,
+    /// Counter-clockwise turn (radians) of the box, lattice and shape about
+    /// `box_center`. 0.0 = axis-aligned (default).
+    pub rotation: f32,
+    /// Initial spin (radians per unit time, counter-clockwise) about
+    /// `box_center`: each particle starts with the rigid velocity
+    /// `angular_velocity × (x − box_center)` and the matching affine
+    /// velocity gradient, on top of any random velocity.
+    pub angular_velocity: f32,
     pub initial_deformation_gradient: Mat2,
     pub precompute_initial_volumes: bool,
     /// Randomized initial speed. Each particle gets a random velocity in [−scale/2, +scale/2]².
@@ -352,6 +854,8 @@ impl Default for SpawnRegion {
             box_size: IVec2::new(16, 16),
             box_center: Vec2::splat(32.0),
             shape: SpawnShape::Box,
+            rotation: 0.0,
+            angular_velocity: 0.0,
             initial_deformation_gradient: Mat2::IDENTITY,
             precompute_initial_volumes: false,
             initial_velocity_scale: 0.0,
@@ -366,10 +870,10 @@ impl Default for SpawnRegion {
 impl SpawnRegion {
     /// Starting point for fluent spawn configuration, centered in the solver domain.
     ///
-    /// The center tracks `grid_res` so examples remain correct when you change resolution.
+    /// The center tracks the grid dimensions so examples remain correct when you change resolution.
     pub fn for_sim(solver: &SimConfig) -> Self {
         Self {
-            box_center: Vec2::splat(solver.grid_res as f32 * 0.5),
+            box_center: solver.grid_dims().as_vec2() * 0.5,
             ..Self::default()
         }
     }
@@ -399,6 +903,34 @@ impl SpawnRegion {
         self
     }
 
+    /// Spawn `shape` (see `SpawnShape` for building polygons, capsules and
+    /// CSG combinations).
+    ///
+    /// Also sets `box_size` to the smallest centered box that covers the
+    /// shape, unless the shape involves `SpawnShape::Box` — then the current
+    /// `box_size` is kept, and is what `Box` fills.
+    pub fn shape(mut self, shape: SpawnShape) -> Self {
+        if let Some(half) = shape.half_extent() {
+            self.box_size = (2.0 * half).ceil().as_ivec2() + 1;
+        }
+        self.shape = shape;
+        self
+    }
+
+    /// Turn the whole region (box, lattice and shape) `radians`
+    /// counter-clockwise about `box_center` — a tilted plank is
+    /// `.box_of(IVec2::new(30, 4)).rotation(0.3)`.
+    pub fn rotation(mut self, radians: f32) -> Self {
+        self.rotation = radians;
+        self
+    }
+
+    /// Start the body spinning at `radians_per_time` about `box_center`.
+    pub fn angular_velocity(mut self, radians_per_time: f32) -> Self {
+        self.angular_velocity = radians_per_time;
+        self
+    }
+
     /// Particle lattice spacing in grid cells.
     pub fn spacing(mut self, s: f32) -> Self {
         self.spacing = s;
@@ -473,12 +1005,17 @@ impl SpawnRegion {
         if self.spacing <= 0.0 || self.box_size.x <= 0 || self.box_size.y <= 0 {
             return false;
         }
-        let half = self.box_size.as_vec2() * 0.5;
+        let half = self.world_half_extent();
         let min = self.box_center - half;
         let max = self.box_center + half;
-        let domain_min = solver.boundary_thickness as f32;
-        let domain_max = solver.grid_res.saturating_sub(solver.boundary_thickness) as f32;
-        min.x >= domain_min && min.y >= domain_min && max.x <= domain_max && max.y <= domain_max
+        let domain_min = Vec2::splat(solver.boundary_thickness as f32);
+        let domain_max = solver.grid_dims().as_vec2() - domain_min;
+        min.cmpge(domain_min).all() && max.cmple(domain_max).all()
+    }
+
+    /// Half-size of the axis-aligned box around the (possibly rotated) region.
+    fn world_half_extent(&self) -> Vec2 {
+        turned_half_extent(self.box_size.as_vec2() * 0.5, self.rotation)
     }
 
     /// Validate spawn-side constraints relative to the solver domain.
@@ -487,16 +1024,16 @@ impl SpawnRegion {
         assert!(self.box_size.x > 0, "box_size.x must be positive");
         assert!(self.box_size.y > 0, "box_size.y must be positive");
 
-        let half = self.box_size.as_vec2() * 0.5;
+        let half = self.world_half_extent();
         let min = self.box_center - half;
         let max = self.box_center + half;
 
         assert!(
             self.fits_in_sim(solver),
             "spawn region must stay inside the simulation domain \
-             (boundary_thickness={}, grid_res={}): box [{:.1},{:.1}]–[{:.1},{:.1}]",
+             (boundary_thickness={}, grid dims={}): box [{:.1},{:.1}]–[{:.1},{:.1}]",
             solver.boundary_thickness,
-            solver.grid_res,
+            solver.grid_dims(),
             min.x,
             min.y,
             max.x,
@@ -507,6 +1044,8 @@ impl SpawnRegion {
 
 #[cfg(test)]
 mod fits_in_sim_tests {
+    use std::f32::consts::FRAC_PI_4;
+
     use super::*;
 
     fn config() -> SimConfig {
@@ -554,6 +1093,28 @@ mod fits_in_sim_tests {
         assert!(region.fits_in_sim(&c));
     }
 
+    #[test]
+    fn rectangular_domain_bounds_each_axis_separately() {
+        // 64 wide, 16 tall: a region at y=20 is inside grid_res but outside
+        // the domain's height.
+        let c = SimConfig {
+            grid_res_y: Some(16),
+            ..config()
+        };
+        let region = |center: glam::Vec2| SpawnRegion {
+            spacing: 0.5,
+            box_size: glam::IVec2::new(6, 6),
+            box_center: center,
+            ..SpawnRegion::for_sim(&c)
+        };
+        assert_eq!(
+            SpawnRegion::for_sim(&c).box_center,
+            glam::Vec2::new(32.0, 8.0)
+        );
+        assert!(region(glam::Vec2::new(50.0, 8.0)).fits_in_sim(&c));
+        assert!(!region(glam::Vec2::new(32.0, 20.0)).fits_in_sim(&c));
+    }
+
     #[test]
     fn fits_in_sim_and_validate_for_sim_agree() {
         // The two must never disagree -- validate_for_sim delegates to
@@ -569,4 +1130,14 @@ mod fits_in_sim_tests {
         let result = std::panic::catch_unwind(|| bad.validate_for_sim(&c));
         assert!(result.is_err(), "validate_for_sim should have panicked");
     }
+
+    #[test]
+    fn rotated_region_is_bounded_by_its_turned_box() {
+        // A 50x50 block fits level in the 60-wide interior, but its corners
+        // poke through the walls once turned 45°.
+        let block = SpawnRegion::for_sim(&config()).box_of(glam::IVec2::splat(50));
+        assert!(block.fits_in_sim(&config()));
+        assert!(!block.rotation(FRAC_PI_4).fits_in_sim(&config()));
+        assert!(block.rotation(0.1).fits_in_sim(&config()));
+    }
 }
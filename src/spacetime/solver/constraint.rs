@@ -0,0 +1,376 @@
+//! Soft positional constraints: springs to points, springs between particles,
+//! and distance limits.
+//!
+//! A [`ConstraintSet`] holds constraints by [`ConstraintHandle`]; the
+//! simulation owns one (`Simulation::add_constraint`, `constraints_mut`,
+//! `with_constraints`) and applies it every substep as particle forces, right
+//! after the force fields and before the post-field velocity clamp, so a
+//! stiff spring cannot push a particle past the CFL limit. Use it for mouse
+//! dragging (a point spring per grabbed particle, target moved each frame) or
+//! for tendons and ropes inside a creature (particle springs, distance
+//! limits).
+//!
+//! CPU `Simulation` only: `GpuSimulation` has no constraint pass, so GPU
+//! front-ends keep pushing particles directly (`apply_radial_impulse`).
+//!
+//! Constraints name particles by [`ParticleId`], so `add_constraint` and
+//! `set_constraints` turn `enable_particle_ids` on; `constraints_mut` is plain
+//! access and leaves them as they are. A constraint whose particle no longer
+//! resolves (removed by a sink or `remove_particles`) is skipped; remove it to
+//! drop it for good. Sleeping
+//! constrained particles are woken at the start of each `step()`, and pinned
+//! particles are never moved, like the force fields.
+//!
+//! [`Spring`] parameters are per unit mass, so one setting behaves the same
+//! on light and heavy material: `stiffness` is the spring's ω² (s⁻²) and
+//! `damping` a rate (s⁻¹). Between two particles the force acts through their
+//! reduced mass and is equal and opposite, so pairs conserve momentum. The
+//! integration is explicit: keep `stiffness · dt²` well below 1 for the
+//! smallest substep you expect. Like emitters and force fields, constraints
+//! are configuration rather than state and are not checkpointed.
+
+use glam::Vec2;
+
+use super::Simulation;
+use super::handle::ParticleId;
+use crate::grid::GridDomain;
+use crate::particle::Particles;
+
+/// Typed index of a constraint added with `ConstraintSet::add`. Never reused,
+/// so a removed constraint's handle stays dead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConstraintHandle(pub usize);
+
+/// Stiffness and damping of one constraint. See the module doc for units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spring {
+    pub stiffness: f32,
+    pub damping: f32,
+}
+
+impl Spring {
+    pub fn new(stiffness: f32, damping: f32) -> Self {
+        Self { stiffness, damping }
+    }
+}
+
+/// One soft constraint.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Constraint {
+    /// Pulls `particle` toward `target` (a grab handle). Move `target` each
+    /// frame to drag.
+    PointSpring {
+        particle: ParticleId,
+        target: Vec2,
+        spring: Spring,
+    },
+    /// Holds `a` and `b` at `rest_length`, pushing and pulling.
+    ParticleSpring {
+        a: ParticleId,
+        b: ParticleId,
+        rest_length: f32,
+        spring: Spring,
+    },
+    /// Pulls `a` and `b` together only while they are more than
+    /// `max_length` apart: a rope or tendon that goes slack.
+    DistanceLimit {
+        a: ParticleId,
+        b: ParticleId,
+        max_length: f32,
+        spring: Spring,
+    },
+}
+
+/// Handle-addressed collection of constraints. See the module doc.
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintSet {
+    slots: Vec<Option<Constraint>>,
+    len: usize,
+}
+
+impl ConstraintSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, constraint: Constraint) -> ConstraintHandle {
+        self.slots.push(Some(constraint));
+        self.len += 1;
+        ConstraintHandle(self.slots.len() - 1)
+    }
+
+    /// Remove and return the constraint, or `None` if it was already removed.
+    pub fn remove(&mut self, handle: ConstraintHandle) -> Option<Constraint> {
+        let removed = self.slots.get_mut(handle.0)?.take();
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    pub fn get(&self, handle: ConstraintHandle) -> Option<&Constraint> {
+        self.slots.get(handle.0)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle: ConstraintHandle) -> Option<&mut Constraint> {
+        self.slots.get_mut(handle.0)?.as_mut()
+    }
+
+    /// Number of live constraints.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Live constraints in `add` order.
+    pub fn iter(&self) -> impl Iterator<Item = (ConstraintHandle, &Constraint)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| Some((ConstraintHandle(i), slot.as_ref()?)))
+    }
+
+    /// Remove every constraint. Handles issued so far stay dead.
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.len = 0;
+    }
+
+    /// Every particle the constraints refer to (with repeats).
+    fn particles(&self) -> impl Iterator<Item = ParticleId> + '_ {
+        self.iter()
+            .flat_map(|(_, constraint)| match *constraint {
+                Constraint::PointSpring { particle, .. } => [Some(particle), None],
+                Constraint::ParticleSpring { a, b, .. }
+                | Constraint::DistanceLimit { a, b, .. } => [Some(a), Some(b)],
+            })
+            .flatten()
+    }
+
+    /// One substep of constraint forces on the active particles.
+    /// `index` resolves an ID to a physical index.
+    fn apply(
+        &self,
+        particles: &mut Particles,
+        active_count: usize,
+        domain: GridDomain,
+        dt: f32,
+        index: impl Fn(ParticleId) -> Option<usize>,
+    ) {
+        let movable =
+            |particles: &Particles, i: usize| i < active_count && particles.pinned[i] == 0;
+        for (_, constraint) in self.iter() {
+            match *constraint {
+                Constraint::PointSpring {
+                    particle,
+                    target,
+                    spring,
+                } => {
+                    let Some(i) = index(particle).filter(|&i| movable(particles, i)) else {
+                        continue;
+                    };
+                    let offset = domain.nearest_image(particles.x[i] - target);
+                    let damping = (spring.damping * dt).min(1.0);
+                    let v = particles.v[i];
+                    particles.v[i] = v - spring.stiffness * dt * offset - damping * v;
+                }
+                Constraint::ParticleSpring {
+                    a,
+                    b,
+                    rest_length,
+                    spring,
+                } => {
+                    let (Some(a), Some(b)) = (index(a), index(b)) else {
+                        continue;
+                    };
+                    let moves = [movable(particles, a), movable(particles, b)];
+                    pull_pair(particles, domain, dt, [a, b], moves, spring, |length| {
+                        Some(length - rest_length)
+                    });
+                }
+                Constraint::DistanceLimit {
+                    a,
+                    b,
+                    max_length,
+                    spring,
+                } => {
+                    let (Some(a), Some(b)) = (index(a), index(b)) else {
+                        continue;
+                    };
+                    let moves = [movable(particles, a), movable(particles, b)];
+                    pull_pair(particles, domain, dt, [a, b], moves, spring, |length| {
+                        (length > max_length).then_some(length - max_length)
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Spring between particles `a` and `b` along their separation.
+/// `stretch(length)` is the extension it acts on, `None` while slack (no
+/// force, no damping). An end that cannot move (`moves`) holds still and the
+/// other takes the whole pull, as against a wall.
+fn pull_pair(
+    particles: &mut Particles,
+    domain: GridDomain,
+    dt: f32,
+    [a, b]: [usize; 2],
+    moves: [bool; 2],
+    spring: Spring,
+    stretch: impl Fn(f32) -> Option<f32>,
+) {
+    let d = domain.nearest_image(particles.x[b] - particles.x[a]);
+    let length = d.length();
+    if moves == [false; 2] || length <= f32::EPSILON {
+        return;
+    }
+    let Some(stretch) = stretch(length) else {
+        return;
+    };
+    let n = d / length;
+    let (ma, mb) = (particles.mass[a], particles.mass[b]);
+    let reduced_mass = match moves {
+        [true, true] => ma * mb / (ma + mb),
+        [true, false] => ma,
+        _ => mb,
+    };
+    let separating = (particles.v[b] - particles.v[a]).dot(n);
+    let damping = (spring.damping * dt).min(1.0);
+    // Impulse pulling `a` toward `b` (and `b` toward `a`).
+    let impulse = reduced_mass * (spring.stiffness * stretch * dt + damping * separating);
+    if moves[0] {
+        particles.v[a] += n * (impulse / ma);
+    }
+    if moves[1] {
+        particles.v[b] -= n * (impulse / mb);
+    }
+}
+
+impl Simulation {
+    /// The simulation's constraints. Empty by default.
+    pub fn constraints(&self) -> &ConstraintSet {
+        &self.constraints
+    }
+
+    /// Remove or retarget constraints (e.g. move a drag handle's `target`
+    /// each frame). Adding through it needs particle IDs already on, which
+    /// any `ParticleId` from `id_of` implies.
+    pub fn constraints_mut(&mut self) -> &mut ConstraintSet {
+        &mut self.constraints
+    }
+
+    /// Add a constraint. Enables particle IDs.
+    pub fn add_constraint(&mut self, constraint: Constraint) -> ConstraintHandle {
+        self.enable_particle_ids();
+        self.constraints.add(constraint)
+    }
+
+    /// Replace the constraint set. Enables particle IDs.
+    pub fn set_constraints(&mut self, constraints: ConstraintSet) {
+        self.enable_particle_ids();
+        self.constraints = constraints;
+    }
+
+    /// Builder-style [`Self::set_constraints`].
+    pub fn with_constraints(mut self, constraints: ConstraintSet) -> Self {
+        self.set_constraints(constraints);
+        self
+    }
+
+    /// Wake every sleeping particle a constraint refers to.
+    pub(super) fn wake_constrained_particles(&mut self) {
+        if self.constraints.is_empty() || self.active_count == self.particles.len() {
+            return;
+        }
+        let ids: Vec<ParticleId> = self.constraints.particles().collect();
+        for id in ids {
+            if let Some(i) = self.index_of(id) {
+                self.wake_particle(i);
+            }
+        }
+    }
+
+    /// One substep of constraint forces (see `ConstraintSet::apply`).
+    pub(super) fn apply_constraints(&mut self, dt: f32) {
+        // Only empty before any constraint was set: `add_constraint` and
+        // `set_constraints` enable IDs, a `ParticleId` added through
+        // `constraints_mut` came from them, and a checkpoint restore keeps
+        // them on.
+        let Some(ids) = &self.id_index else {
+            debug_assert!(
+                self.constraints.is_empty(),
+                "constraints without particle IDs (use `add_constraint`)"
+            );
+            return;
+        };
+        self.constraints.apply(
+            &mut self.particles,
+            self.active_count,
+            self.grid.domain(),
+            dt,
+            |id| ids.get(&id.0).copied(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::particle::Particle;
+    use glam::UVec2;
+
+    #[test]
+    fn handles_stay_dead_and_pairs_conserve_momentum() {
+        let mut set = ConstraintSet::new();
+        let spring = Spring::new(4.0, 0.5);
+        let point = set.add(Constraint::PointSpring {
+            particle: ParticleId(9),
+            target: Vec2::ZERO,
+            spring,
+        });
+        let pair = set.add(Constraint::ParticleSpring {
+            a: ParticleId(0),
+            b: ParticleId(1),
+            rest_length: 1.0,
+            spring,
+        });
+        let rope = set.add(Constraint::DistanceLimit {
+            a: ParticleId(1),
+            b: ParticleId(2),
+            max_length: 5.0,
+            spring,
+        });
+        assert!(set.remove(point).is_some());
+        assert!(set.remove(point).is_none());
+        assert_eq!(set.len(), 2);
+        assert_eq!(
+            set.add(set.get(pair).copied().unwrap()),
+            ConstraintHandle(3)
+        );
+        set.remove(ConstraintHandle(3));
+
+        let mut particles = Particles::from(
+            [(0.0, 1.0), (3.0, 3.0), (6.0, 1.0)]
+                .map(|(x, mass)| Particle {
+                    x: Vec2::new(x, 8.0),
+                    mass,
+                    ..Particle::zeroed()
+                })
+                .to_vec(),
+        );
+        let domain = GridDomain::new(UVec2::new(16, 16));
+        set.apply(&mut particles, 3, domain, 0.1, |id| Some(id.0 as usize));
+
+        // Stretched spring pulls 0 and 1 together; momentum stays zero. The
+        // rope between 1 and 2 is slack and does nothing.
+        assert!(particles.v[0].x > 0.0 && particles.v[1].x < 0.0);
+        let momentum: Vec2 = (0..3).map(|i| particles.mass[i] * particles.v[i]).sum();
+        assert!(momentum.length() < 1e-6, "{momentum}");
+        assert_eq!(particles.v[2], Vec2::ZERO);
+        assert!(set.get(rope).is_some());
+    }
+}
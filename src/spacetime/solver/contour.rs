@@ -0,0 +1,433 @@
+//! Surface outlines from the density grid: marching squares over
+//! `compute_density_grid`'s node masses.
+//!
+//! Grid values sit at node positions (`cell + 0.5`). Each square between four
+//! nodes contributes up to two segments, with the crossing point linearly
+//! interpolated along each edge; saddles are resolved by the square's mean.
+//! Segments are oriented with the material on their left, so chained
+//! polylines run counter-clockwise around material (clockwise around holes)
+//! and each vertex's normal points out of the material.
+//!
+//! Nodes outside a non-periodic domain count as empty, so material touching a
+//! wall still gets a closed outline. Across a periodic axis there is no
+//! outside: an outline reaching the seam ends there as an open polyline.
+
+use std::collections::HashMap;
+
+use glam::{IVec2, Vec2};
+
+use super::density::density_grid_where;
+use crate::grid::GridDomain;
+use crate::particle::Particles;
+
+/// Which particles contribute to the contoured density field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ContourFilter {
+    #[default]
+    All,
+    Material(u32),
+    Tag(u32),
+}
+
+/// What to outline and how to post-process it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContourOptions {
+    /// Iso level on the node mass `Σ w·m` (the `compute_density_grid` value).
+    pub threshold: f32,
+    pub filter: ContourFilter,
+    /// Laplacian smoothing passes (0 = raw marching-squares vertices).
+    pub smoothing_iterations: usize,
+    /// Douglas–Peucker tolerance in grid cells (0 = keep every vertex).
+    pub simplify_tolerance: f32,
+}
+
+impl ContourOptions {
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            filter: ContourFilter::All,
+            smoothing_iterations: 0,
+            simplify_tolerance: 0.0,
+        }
+    }
+
+    pub fn material(mut self, material_id: u32) -> Self {
+        self.filter = ContourFilter::Material(material_id);
+        self
+    }
+
+    pub fn tag(mut self, tag: u32) -> Self {
+        self.filter = ContourFilter::Tag(tag);
+        self
+    }
+
+    pub fn smoothing(mut self, iterations: usize) -> Self {
+        self.smoothing_iterations = iterations;
+        self
+    }
+
+    pub fn simplify(mut self, tolerance: f32) -> Self {
+        self.simplify_tolerance = tolerance;
+        self
+    }
+}
+
+/// One outline. For a closed contour the last point connects back to the first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Contour {
+    pub points: Vec<Vec2>,
+    /// Unit outward normal per point (out of the material).
+    pub normals: Vec<Vec2>,
+    pub closed: bool,
+}
+
+impl Contour {
+    fn new(points: Vec<Vec2>, closed: bool) -> Self {
+        let mut contour = Self {
+            points,
+            normals: Vec::new(),
+            closed,
+        };
+        contour.compute_normals();
+        contour
+    }
+
+    /// `iterations` passes of `p ← ½p + ¼(prev + next)`. Endpoints of an
+    /// open contour stay put.
+    pub fn smoothed(mut self, iterations: usize) -> Self {
+        let n = self.points.len();
+        if n < 3 {
+            return self;
+        }
+        for _ in 0..iterations {
+            let old = self.points.clone();
+            for i in 0..n {
+                let (prev, next) = if self.closed {
+                    ((i + n - 1) % n, (i + 1) % n)
+                } else if i == 0 || i == n - 1 {
+                    continue;
+                } else {
+                    (i - 1, i + 1)
+                };
+                self.points[i] = 0.5 * old[i] + 0.25 * (old[prev] + old[next]);
+            }
+        }
+        self.compute_normals();
+        self
+    }
+
+    /// Douglas–Peucker: drop vertices closer than `tolerance` to the
+    /// simplified line. A closed contour is split at its first vertex and the
+    /// vertex farthest from it, so both halves keep their extremes.
+    pub fn simplified(mut self, tolerance: f32) -> Self {
+        if tolerance <= 0.0 || self.points.len() < 3 {
+            return self;
+        }
+        let keep = if self.closed {
+            let far = (1..self.points.len())
+                .max_by(|&a, &b| {
+                    let da = self.points[a].distance_squared(self.points[0]);
+                    let db = self.points[b].distance_squared(self.points[0]);
+                    da.total_cmp(&db)
+                })
+                .unwrap();
+            let mut ring = self.points.clone();
+            ring.push(self.points[0]);
+            let mut keep = vec![false; ring.len()];
+            douglas_peucker(&ring, 0, far, tolerance, &mut keep);
+            douglas_peucker(&ring, far, ring.len() - 1, tolerance, &mut keep);
+            keep.pop();
+            keep
+        } else {
+            let mut keep = vec![false; self.points.len()];
+            douglas_peucker(&self.points, 0, self.points.len() - 1, tolerance, &mut keep);
+            keep
+        };
+        self.points = self
+            .points
+            .iter()
+            .zip(&keep)
+            .filter_map(|(&p, &k)| k.then_some(p))
+            .collect();
+        self.compute_normals();
+        self
+    }
+
+    /// Enclosed area, positive for counter-clockwise (material) loops and
+    /// negative for holes. Zero for open contours.
+    pub fn signed_area(&self) -> f32 {
+        if !self.closed {
+            return 0.0;
+        }
+        let n = self.points.len();
+        0.5 * (0..n)
+            .map(|i| self.points[i].perp_dot(self.points[(i + 1) % n]))
+            .sum::<f32>()
+    }
+
+    /// Outward normal at each vertex: the mean of the adjacent segments'
+    /// right-hand perpendiculars (material is on the left).
+    fn compute_normals(&mut self) {
+        let n = self.points.len();
+        let outward = |a: Vec2, b: Vec2| {
+            let d = b - a;
+            Vec2::new(d.y, -d.x).normalize_or_zero()
+        };
+        self.normals = (0..n)
+            .map(|i| {
+                let prev = (i > 0 || self.closed).then(|| (i + n - 1) % n);
+                let next = (i + 1 < n || self.closed).then(|| (i + 1) % n);
+                let mut sum = Vec2::ZERO;
+                if let Some(j) = prev {
+                    sum += outward(self.points[j], self.points[i]);
+                }
+                if let Some(j) = next {
+                    sum += outward(self.points[i], self.points[j]);
+                }
+                sum.normalize_or_zero()
+            })
+            .collect();
+    }
+}
+
+fn douglas_peucker(points: &[Vec2], first: usize, last: usize, tolerance: f32, keep: &mut [bool]) {
+    keep[first] = true;
+    keep[last] = true;
+    if last <= first + 1 {
+        return;
+    }
+    let (a, b) = (points[first], points[last]);
+    let ab = b - a;
+    let distance = |p: Vec2| {
+        if ab.length_squared() > 0.0 {
+            ab.perp_dot(p - a).abs() / ab.length()
+        } else {
+            p.distance(a)
+        }
+    };
+    let (far, dist) = (first + 1..last)
+        .map(|i| (i, distance(points[i])))
+        .max_by(|x, y| x.1.total_cmp(&y.1))
+        .unwrap();
+    if dist > tolerance {
+        douglas_peucker(points, first, far, tolerance, keep);
+        douglas_peucker(points, far, last, tolerance, keep);
+    }
+}
+
+/// Outline the particles `options.filter` selects at `options.threshold`,
+/// then smooth and simplify as configured.
+pub fn extract_contours(
+    particles: &Particles,
+    domain: GridDomain,
+    options: &ContourOptions,
+) -> Vec<Contour> {
+    let field = match options.filter {
+        ContourFilter::All => density_grid_where(particles, domain, |_| true),
+        ContourFilter::Material(id) => {
+            density_grid_where(particles, domain, |i| particles.material_id[i] == id)
+        }
+        ContourFilter::Tag(tag) => {
+            density_grid_where(particles, domain, |i| particles.user_tag[i] == tag)
+        }
+    };
+    marching_squares(&field, domain, options.threshold)
+        .into_iter()
+        .map(|c| {
+            c.smoothed(options.smoothing_iterations)
+                .simplified(options.simplify_tolerance)
+        })
+        .collect()
+}
+
+/// Raw iso-contours of any node buffer laid out like `compute_density_grid_in`
+/// (`x * height + y`). A node is inside when its value is `>= threshold`;
+/// off-grid nodes read as 0, so `threshold` must be positive for outlines to
+/// close at walls.
+pub fn marching_squares(field: &[f32], domain: GridDomain, threshold: f32) -> Vec<Contour> {
+    assert_eq!(
+        field.len(),
+        domain.cell_count(),
+        "field does not match domain"
+    );
+    let dims = domain.dims.as_ivec2();
+    let value = |node: IVec2| {
+        let in_grid = node.cmpge(IVec2::ZERO).all() && node.cmplt(dims).all();
+        if in_grid {
+            field[(node.x * dims.y + node.y) as usize]
+        } else {
+            0.0
+        }
+    };
+    // Non-periodic axes get a ring of empty nodes so outlines close at walls.
+    let range = |axis: usize| {
+        if domain.periodic.test(axis) {
+            0..dims[axis] - 1
+        } else {
+            -1..dims[axis]
+        }
+    };
+
+    // Segment start edge → end edge; edges are keyed by their lower node and
+    // direction (0 = +x, 1 = +y). Scan order is kept for deterministic output.
+    let mut next: HashMap<(IVec2, u8), (IVec2, u8)> = HashMap::new();
+    let mut starts = Vec::new();
+    let mut crossing: HashMap<(IVec2, u8), Vec2> = HashMap::new();
+    let corners = [IVec2::ZERO, IVec2::X, IVec2::ONE, IVec2::Y];
+    // Edge k runs corner k → corner k+1 (counter-clockwise).
+    let edge_keys = |base: IVec2| {
+        [
+            (base, 0),
+            (base + IVec2::X, 1),
+            (base + IVec2::Y, 0),
+            (base, 1),
+        ]
+    };
+    for x in range(0) {
+        for y in range(1) {
+            let base = IVec2::new(x, y);
+            let v = corners.map(|c| value(base + c));
+            let inside = v.map(|v| v >= threshold);
+            if inside.iter().all(|&i| i) || inside.iter().all(|&i| !i) {
+                continue;
+            }
+            let keys = edge_keys(base);
+            for (a, &key) in keys.iter().enumerate() {
+                let b = (a + 1) % 4;
+                if inside[a] != inside[b] {
+                    let t = (threshold - v[a]) / (v[b] - v[a]);
+                    let pa = (base + corners[a]).as_vec2() + 0.5;
+                    let pb = (base + corners[b]).as_vec2() + 0.5;
+                    crossing.insert(key, pa.lerp(pb, t));
+                }
+            }
+            // Each in→out edge (CCW) pairs with the next out→in edge, except
+            // at a saddle whose centre is empty: there the two inside corners
+            // are separate and each pairs with the previous one.
+            let is_saddle = inside[0] == inside[2] && inside[1] == inside[3];
+            let centre_inside = v.iter().sum::<f32>() * 0.25 >= threshold;
+            let step = if is_saddle && !centre_inside { 3 } else { 1 };
+            let leaves = |k: usize| inside[k] && !inside[(k + 1) % 4];
+            let enters = |k: usize| !inside[k] && inside[(k + 1) % 4];
+            for k in (0..4).filter(|&k| leaves(k)) {
+                let mut j = (k + step) % 4;
+                while !enters(j) {
+                    j = (j + step) % 4;
+                }
+                next.insert(keys[k], keys[j]);
+                starts.push(keys[k]);
+            }
+        }
+    }
+
+    let ends: std::collections::HashSet<_> = next.values().copied().collect();
+    let mut visited = std::collections::HashSet::new();
+    let mut contours = Vec::new();
+    // Open chains first (they start at an edge nothing leads into), then loops.
+    let open_starts = starts.iter().filter(|s| !ends.contains(*s));
+    for &start in open_starts.chain(starts.iter()) {
+        if visited.contains(&start) {
+            continue;
+        }
+        let mut points = Vec::new();
+        let mut edge = start;
+        let mut closed = false;
+        loop {
+            visited.insert(edge);
+            points.push(crossing[&edge]);
+            match next.get(&edge) {
+                Some(&e) if e == start => {
+                    closed = true;
+                    break;
+                }
+                Some(&e) => edge = e,
+                None => break,
+            }
+        }
+        contours.push(Contour::new(points, closed));
+    }
+    contours
+}
+
+/// Highest point at which any contour crosses the vertical line at `x` — the
+/// free-surface height there — or `None` if none does.
+pub fn surface_height(contours: &[Contour], x: f32) -> Option<f32> {
+    contours
+        .iter()
+        .flat_map(|c| {
+            let n = c.points.len();
+            let segments = if c.closed { n } else { n.saturating_sub(1) };
+            (0..segments).map(move |i| (c.points[i], c.points[(i + 1) % n]))
+        })
+        .filter(|(a, b)| (a.x <= x) != (b.x <= x))
+        .map(|(a, b)| a.y + (b.y - a.y) * (x - a.x) / (b.x - a.x))
+        .max_by(f32::total_cmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::UVec2;
+
+    fn disk_field(domain: GridDomain, center: Vec2, radius: f32) -> Vec<f32> {
+        let mut field = vec![0.0; domain.cell_count()];
+        for x in 0..domain.width() {
+            for y in 0..domain.height() {
+                let p = Vec2::new(x as f32, y as f32) + 0.5;
+                // Offset by 1 so the iso level (1) is positive, as for density.
+                field[x * domain.height() + y] = (radius + 1.0 - p.distance(center)).max(0.0);
+            }
+        }
+        field
+    }
+
+    #[test]
+    fn disk_gives_one_closed_ccw_loop_with_outward_normals() {
+        let domain = GridDomain::new(UVec2::new(32, 24));
+        let center = Vec2::new(12.0, 11.0);
+        let field = disk_field(domain, center, 6.0);
+        let contours = marching_squares(&field, domain, 1.0);
+        assert_eq!(contours.len(), 1);
+        let disk = &contours[0];
+        assert!(disk.closed);
+        for (p, n) in disk.points.iter().zip(&disk.normals) {
+            assert!((p.distance(center) - 6.0).abs() < 0.1, "{p}");
+            assert!(n.dot((*p - center).normalize()) > 0.95);
+        }
+        let area = disk.signed_area();
+        assert!(
+            (area - std::f32::consts::PI * 36.0).abs() < 2.0,
+            "area {area}"
+        );
+
+        let simple = disk.clone().smoothed(2).simplified(0.2);
+        assert!(simple.closed && simple.points.len() < disk.points.len() / 2);
+        assert!((simple.signed_area() - area).abs() < 0.05 * area);
+
+        let top = surface_height(&contours, center.x).unwrap();
+        assert!((top - (center.y + 6.0)).abs() < 0.1);
+    }
+
+    #[test]
+    fn walls_close_outlines_and_periodic_seams_leave_them_open() {
+        // A pool filling the bottom of the domain up to y = 6.
+        let pool = |domain: GridDomain| {
+            let mut field = vec![0.0; domain.cell_count()];
+            for x in 0..domain.width() {
+                for y in 0..6 {
+                    field[x * domain.height() + y] = 1.0;
+                }
+            }
+            field
+        };
+        let walled = GridDomain::new(UVec2::new(16, 16));
+        let contours = marching_squares(&pool(walled), walled, 0.5);
+        assert_eq!(contours.len(), 1);
+        assert!(contours[0].closed && contours[0].signed_area() > 0.0);
+
+        let periodic = walled.with_periodic(glam::BVec2::new(true, false));
+        let contours = marching_squares(&pool(periodic), periodic, 0.5);
+        assert!(contours.iter().all(|c| !c.closed));
+        assert_eq!(surface_height(&contours, 8.0), Some(6.0));
+    }
+}
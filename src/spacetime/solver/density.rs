@@ -1,7 +1,9 @@
 use glam::{IVec2, Vec2};
+use rayon::prelude::*;
 
-use crate::transfer::scatter_particle_mass;
-use crate::{grid::Grid, grid::kernel::quadratic_weights, particle::Particles};
+use crate::grid::{Grid, GridDomain};
+use crate::transfer::{P2GScratch, scatter_particle_mass, scatter_particle_mass_parallel};
+use crate::{grid::kernel::quadratic_weights, particle::Particles};
 
 /// Export the mass-density field as a flat `grid_res × grid_res` buffer.
 ///
@@ -16,20 +18,35 @@ use crate::{grid::Grid, grid::kernel::quadratic_weights, particle::Particles};
 ///
 /// Layout: column-major, index = x * grid_res + y — matches mechanics grid.
 pub fn compute_density_grid(particles: &Particles, grid_res: usize) -> Vec<f32> {
-    let mut buf = vec![0.0f32; grid_res * grid_res];
-    let res = grid_res as i32;
-    for i in 0..particles.len() {
+    compute_density_grid_in(particles, GridDomain::square(grid_res))
+}
+
+/// `compute_density_grid` on any domain (`SimConfig::grid_domain`): a
+/// `width × height` buffer, index = x * height + y. Stencils crossing a
+/// periodic edge wrap like P2G's, so the buffer holds every particle's mass.
+pub fn compute_density_grid_in(particles: &Particles, domain: GridDomain) -> Vec<f32> {
+    density_grid_where(particles, domain, |_| true)
+}
+
+/// `compute_density_grid_in` over only the particles `keep` accepts (by index).
+pub(crate) fn density_grid_where(
+    particles: &Particles,
+    domain: GridDomain,
+    keep: impl Fn(usize) -> bool,
+) -> Vec<f32> {
+    let mut buf = vec![0.0f32; domain.cell_count()];
+    for i in (0..particles.len()).filter(|&i| keep(i)) {
         let x = particles.x[i];
         let mass = particles.mass[i];
         let w = quadratic_weights(x);
         for gx in 0i32..3 {
             for gy in 0i32..3 {
                 let cell = w.base_cell + IVec2::new(gx - 1, gy - 1);
-                if cell.x < 0 || cell.y < 0 || cell.x >= res || cell.y >= res {
+                let Some(idx) = domain.flat_index(cell) else {
                     continue;
-                }
+                };
                 let weight = w.wx[gx as usize] * w.wy[gy as usize];
-                buf[(cell.x * res + cell.y) as usize] += weight * mass;
+                buf[idx as usize] += weight * mass;
             }
         }
     }
@@ -71,6 +88,47 @@ pub fn estimate_particle_volumes(
     }
 }
 
+/// Parallel `estimate_particle_volumes`: chunked mass scatter
+/// (`scatter_particle_mass_parallel`, deterministic for any thread count) followed by a
+/// per-particle gather that is race-free by construction — each particle writes only its
+/// own density/volume. Used by `Simulation` when `SimConfig::parallel_p2g` is set.
+pub fn estimate_particle_volumes_parallel(
+    particles: &mut Particles,
+    grid: &mut Grid,
+    scratch: &mut P2GScratch,
+    count: usize,
+    write_initial: bool,
+) {
+    grid.clear();
+    scatter_particle_mass_parallel(particles, grid, scratch, count);
+
+    let grid: &Grid = grid;
+    particles.density[..count]
+        .par_iter_mut()
+        .zip(particles.volume[..count].par_iter_mut())
+        .zip(particles.initial_volume[..count].par_iter_mut())
+        .zip(particles.x[..count].par_iter())
+        .zip(particles.mass[..count].par_iter())
+        .for_each(|((((density_out, volume), initial_volume), &x), &mass)| {
+            let weights = quadratic_weights(x);
+            let mut density = 0.0;
+            for gx in 0..3 {
+                for gy in 0..3 {
+                    let weight = weights.wx[gx] * weights.wy[gy];
+                    let cell_pos = weights.base_cell + IVec2::new(gx as i32 - 1, gy as i32 - 1);
+                    density += grid.mass_at(cell_pos) * weight;
+                }
+            }
+            if density > f32::EPSILON {
+                *density_out = density;
+                *volume = mass / density;
+                if write_initial {
+                    *initial_volume = *volume;
+                }
+            }
+        });
+}
+
 /// Compute density and volume only for particles in `[new_start..active_count]`.
 ///
 /// Scatters only particles whose positions fall within the AABB of the new group
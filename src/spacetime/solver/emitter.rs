@@ -0,0 +1,390 @@
+//! Continuous particle sources and drains: `Emitter` (taps, volcanoes, sand
+//! pourers) and `Sink` (drains that delete or recycle particles), registered
+//! on `Simulation` and processed once per `step()`.
+//!
+//! Emitters run at the start of `step()`, so fresh particles take part in that
+//! step's physics; sinks run at the end, after the last substep, so queries
+//! between steps never see a particle that is already inside a drain. Both go
+//! through the same partition/tag bookkeeping as `add_body` and
+//! `remove_particles`: emitted particles land at the end of the active zone
+//! under the emitter's tag, recycled sleeping particles are woken first.
+
+use glam::Vec2;
+
+use super::events::ParticleEventKind;
+use super::{LcgRng, Simulation, fresh_particle};
+use crate::particle::Particle;
+
+/// Region an emitter fills or a sink drains, relative to its `center`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RegionShape {
+    Box { half_extents: Vec2 },
+    Disk { radius: f32 },
+}
+
+impl RegionShape {
+    pub fn contains(&self, center: Vec2, p: Vec2) -> bool {
+        let d = p - center;
+        match *self {
+            Self::Box { half_extents } => d.abs().cmple(half_extents).all(),
+            Self::Disk { radius } => d.length_squared() <= radius * radius,
+        }
+    }
+
+    /// Half-size of the axis-aligned box around the region.
+    fn half_extent(&self) -> Vec2 {
+        match *self {
+            Self::Box { half_extents } => half_extents,
+            Self::Disk { radius } => Vec2::splat(radius),
+        }
+    }
+
+    /// Uniform random point inside the region (rejection-sampled for disks).
+    fn sample(&self, center: Vec2, rng: &mut LcgRng) -> Vec2 {
+        let unit = |rng: &mut LcgRng| Vec2::new(rng.next_f32(), rng.next_f32()) * 2.0 - Vec2::ONE;
+        match *self {
+            Self::Box { half_extents } => center + unit(rng) * half_extents,
+            Self::Disk { radius } => loop {
+                let u = unit(rng);
+                if u.length_squared() <= 1.0 {
+                    break center + u * radius;
+                }
+            },
+        }
+    }
+}
+
+/// How fast an emitter produces material, per unit of simulation time (the
+/// `SimConfig::dt` clock). Fractional particles carry over between steps, so
+/// a rate of 2.5/s at dt = 0.1 emits one particle every fourth step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EmissionRate {
+    ParticlesPerSecond(f32),
+    /// Divided by the emitter's particle mass (`mass_override` or
+    /// `SimConfig::particle_mass`) to get a particle rate.
+    MassPerSecond(f32),
+}
+
+/// Typed index of an emitter registered with `Simulation::add_emitter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EmitterHandle(pub usize);
+
+/// Typed index of a sink registered with `Simulation::add_sink`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SinkHandle(pub usize);
+
+/// Continuous particle source. Particles appear at uniformly random points in
+/// the region, at rest in their reference state (F = I, default volume) with
+/// `velocity`, like a `SpawnRegion` without `precompute_volumes`. The region
+/// must lie inside the domain walls when registered; if `emitter_mut` later
+/// moves it past them, points beyond the walls are clamped onto their edge.
+#[derive(Debug)]
+pub struct Emitter {
+    pub shape: RegionShape,
+    pub center: Vec2,
+    pub rate: EmissionRate,
+    pub velocity: Vec2,
+    pub material_id: u32,
+    /// Group every emitted particle joins. `None` → `add_emitter` issues a
+    /// fresh tag, exactly like `add_body`; read it back via `emitter(h).tag`.
+    pub tag: Option<u32>,
+    pub temperature: f32,
+    pub scalar_field: f32,
+    /// Per-particle mass; `None` falls back to `SimConfig::particle_mass`
+    /// (same convention as `SpawnRegion::mass_override`).
+    pub mass_override: Option<f32>,
+    /// Paused emitters emit nothing and accumulate nothing.
+    pub enabled: bool,
+    /// Fractional particles owed from previous steps, in [0, 1).
+    accumulator: f32,
+    rng: LcgRng,
+}
+
+impl Emitter {
+    pub fn new(shape: RegionShape, center: Vec2, rate: EmissionRate) -> Self {
+        Self {
+            shape,
+            center,
+            rate,
+            velocity: Vec2::ZERO,
+            material_id: 0,
+            tag: None,
+            temperature: 0.0,
+            scalar_field: 0.0,
+            mass_override: None,
+            enabled: true,
+            accumulator: 0.0,
+            rng: LcgRng::new(1),
+        }
+    }
+
+    pub fn velocity(mut self, velocity: Vec2) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn material(mut self, material_id: u32) -> Self {
+        self.material_id = material_id;
+        self
+    }
+
+    pub fn tag(mut self, tag: u32) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    pub fn scalar_field(mut self, value: f32) -> Self {
+        self.scalar_field = value;
+        self
+    }
+
+    pub fn mass(mut self, mass: f32) -> Self {
+        assert!(mass > 0.0, "emitter particle mass must be positive");
+        self.mass_override = Some(mass);
+        self
+    }
+
+    /// Seed for emission positions (default 1). Emission is deterministic for
+    /// a given seed and step sequence.
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.rng = LcgRng::new(seed);
+        self
+    }
+
+    /// Whether the region lies inside the domain minus `boundary_thickness`,
+    /// the same test as `SpawnRegion::fits_in_sim`.
+    pub fn fits_in_sim(&self, config: &super::SimConfig) -> bool {
+        let (min, max) = interior(config);
+        let half = self.shape.half_extent();
+        (self.center - half).cmpge(min).all() && (self.center + half).cmple(max).all()
+    }
+
+    fn particles_per_second(&self, particle_mass: f32) -> f32 {
+        match self.rate {
+            EmissionRate::ParticlesPerSecond(rate) => rate,
+            EmissionRate::MassPerSecond(rate) => rate / particle_mass,
+        }
+    }
+
+    /// One emitted particle, tagged but not yet material-initialized.
+    fn make_particle(&mut self, config: &super::SimConfig) -> Particle {
+        let mass = self.mass_override.unwrap_or(config.particle_mass);
+        let (min, max) = interior(config);
+        let x = self
+            .shape
+            .sample(self.center, &mut self.rng)
+            .clamp(min, max);
+        let mut p = fresh_particle(config, x, self.velocity, mass, self.material_id);
+        p.temperature = self.temperature;
+        p.scalar_field = self.scalar_field;
+        p.user_tag = self.tag.unwrap_or(0);
+        p
+    }
+
+    pub(super) fn emission_state(&self) -> (f32, u32) {
+        (self.accumulator, self.rng.state)
+    }
+
+    pub(super) fn set_emission_state(&mut self, accumulator: f32, rng_state: u32) {
+        self.accumulator = accumulator;
+        self.rng = LcgRng::new(rng_state);
+    }
+}
+
+/// Corners of the domain inside the boundary walls.
+fn interior(config: &super::SimConfig) -> (Vec2, Vec2) {
+    let min = Vec2::splat(config.boundary_thickness as f32);
+    (min, config.grid_dims().as_vec2() - min)
+}
+
+/// What a sink does with a particle inside its region.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SinkAction {
+    Delete,
+    /// Re-emit the particle from this emitter (position, velocity, material,
+    /// tag, temperature and scalar field all reset to the emitter's), keeping
+    /// the particle count constant — a closed-loop waterfall or sand timer.
+    /// Recycled particles don't count against the emitter's own rate.
+    Recycle(EmitterHandle),
+}
+
+/// Region that drains every particle (active or sleeping) inside it each step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sink {
+    pub shape: RegionShape,
+    pub center: Vec2,
+    pub action: SinkAction,
+    pub enabled: bool,
+}
+
+impl Sink {
+    pub fn delete(shape: RegionShape, center: Vec2) -> Self {
+        Self {
+            shape,
+            center,
+            action: SinkAction::Delete,
+            enabled: true,
+        }
+    }
+
+    pub fn recycle(shape: RegionShape, center: Vec2, to: EmitterHandle) -> Self {
+        Self {
+            shape,
+            center,
+            action: SinkAction::Recycle(to),
+            enabled: true,
+        }
+    }
+
+    fn contains(&self, p: Vec2) -> bool {
+        self.enabled && self.shape.contains(self.center, p)
+    }
+}
+
+impl Simulation {
+    /// Register an emitter. Assigns a fresh tag when `emitter.tag` is `None`.
+    /// Panics if the region reaches into the boundary walls (see
+    /// `Emitter::fits_in_sim`).
+    pub fn add_emitter(&mut self, mut emitter: Emitter) -> EmitterHandle {
+        let rate = match emitter.rate {
+            EmissionRate::ParticlesPerSecond(r) | EmissionRate::MassPerSecond(r) => r,
+        };
+        assert!(
+            rate >= 0.0 && rate.is_finite(),
+            "emission rate must be finite and >= 0"
+        );
+        assert!(
+            emitter.fits_in_sim(&self.config),
+            "emitter region must stay inside the simulation domain \
+             (boundary_thickness={}, grid dims={}): center {}",
+            self.config.boundary_thickness,
+            self.config.grid_dims(),
+            emitter.center,
+        );
+        debug_assert!(
+            self.materials.is_registered(emitter.material_id),
+            "add_emitter: material_id {} is not registered",
+            emitter.material_id,
+        );
+        if emitter.tag.is_none() {
+            emitter.tag = Some(self.next_tag);
+            self.next_tag += 1;
+        }
+        self.emitters.push(emitter);
+        EmitterHandle(self.emitters.len() - 1)
+    }
+
+    pub fn emitter(&self, handle: EmitterHandle) -> Option<&Emitter> {
+        self.emitters.get(handle.0)
+    }
+
+    /// Mutable access for game control (move a tap, change its rate, pause it).
+    /// A tap moved into the walls emits onto their edge instead.
+    pub fn emitter_mut(&mut self, handle: EmitterHandle) -> Option<&mut Emitter> {
+        self.emitters.get_mut(handle.0)
+    }
+
+    /// Register a sink. Panics if a recycling sink names an unknown emitter.
+    pub fn add_sink(&mut self, sink: Sink) -> SinkHandle {
+        if let SinkAction::Recycle(to) = sink.action {
+            assert!(
+                to.0 < self.emitters.len(),
+                "add_sink: recycle target {to:?} is not a registered emitter"
+            );
+        }
+        self.sinks.push(sink);
+        SinkHandle(self.sinks.len() - 1)
+    }
+
+    pub fn sink(&self, handle: SinkHandle) -> Option<&Sink> {
+        self.sinks.get(handle.0)
+    }
+
+    pub fn sink_mut(&mut self, handle: SinkHandle) -> Option<&mut Sink> {
+        self.sinks.get_mut(handle.0)
+    }
+
+    /// Emit this step's particles from every enabled emitter.
+    pub(super) fn run_emitters(&mut self) {
+        self.last_emitted_count = 0;
+        if self.emitters.is_empty() {
+            return;
+        }
+        let mut emitters = std::mem::take(&mut self.emitters);
+        let mut batch = Vec::new();
+        for emitter in emitters.iter_mut().filter(|e| e.enabled) {
+            let mass = emitter.mass_override.unwrap_or(self.config.particle_mass);
+            emitter.accumulator += emitter.particles_per_second(mass) * self.config.dt;
+            let count = emitter.accumulator.floor();
+            emitter.accumulator -= count;
+            for _ in 0..count as usize {
+                let mut p = emitter.make_particle(&self.config);
+                if self.materials.is_registered(p.material_id) {
+                    self.materials.get(p.material_id).init_particle(&mut p);
+                }
+                batch.push(p);
+            }
+        }
+        self.emitters = emitters;
+        self.last_emitted_count = batch.len();
+        if !batch.is_empty() {
+            self.insert_active_particles(batch);
+        }
+    }
+
+    /// Recycle, then delete, every particle inside an enabled sink. The first
+    /// sink (in `add_sink` order) containing a particle decides its fate.
+    pub(super) fn run_sinks(&mut self) {
+        self.last_sink_removed_count = 0;
+        self.last_sink_recycled_count = 0;
+        if self.sinks.is_empty() {
+            return;
+        }
+        let sinks = std::mem::take(&mut self.sinks);
+        let first_sink = |x: Vec2| sinks.iter().find(|s| s.contains(x)).map(|s| s.action);
+
+        // Recycling: wake sleeping candidates first (ascending, see `wake_tag`),
+        // each lands at the new end of the active zone.
+        let mut recycle: Vec<(usize, EmitterHandle)> = Vec::new();
+        for i in 0..self.particles.len() {
+            if let Some(SinkAction::Recycle(to)) = first_sink(self.particles.x[i]) {
+                recycle.push((i, to));
+            }
+        }
+        for entry in &mut recycle {
+            if self.particles.sleeping[entry.0] {
+                self.wake_particle(entry.0);
+                entry.0 = self.active_count - 1;
+            }
+        }
+        for &(i, to) in &recycle {
+            self.record_event(i, ParticleEventKind::Removed);
+            let emitter = &mut self.emitters[to.0];
+            let tag = emitter.tag.unwrap_or(0);
+            let mut p = emitter.make_particle(&self.config);
+            if self.materials.is_registered(p.material_id) {
+                self.materials.get(p.material_id).init_particle(&mut p);
+            }
+            p.user_tag = self.particles.user_tag[i];
+            self.particles.set(i, p);
+            self.retag_particle(i, tag);
+            self.record_event(i, ParticleEventKind::Added);
+        }
+        self.last_sink_recycled_count = recycle.len();
+
+        if sinks
+            .iter()
+            .any(|s| s.enabled && s.action == SinkAction::Delete)
+        {
+            self.last_sink_removed_count = self.remove_particles(|p| {
+                sinks.iter().find(|s| s.contains(p.x)).map(|s| s.action) == Some(SinkAction::Delete)
+            });
+        }
+        self.sinks = sinks;
+    }
+}
@@ -0,0 +1,100 @@
+//! Particle lifecycle and phase-change events.
+//!
+//! Consumers that react to *changes* — sound, VFX, scoring — would otherwise
+//! diff the particle arrays every frame. With `Simulation::enable_particle_events`
+//! the solver appends a [`ParticleEvent`] to a queue at each site where the
+//! change happens, and the caller drains it between `step()` calls.
+//!
+//! Off by default: a disabled queue costs one `Option` check per site. Nothing
+//! drains the queue automatically, so a caller that enables it must drain it
+//! (it grows without bound otherwise). Events are output, not state —
+//! checkpoints neither save nor restore them.
+
+use glam::Vec2;
+
+use super::Simulation;
+use super::handle::ParticleId;
+
+/// What happened to the particle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleEventKind {
+    /// Added by `add_body` or an emitter (also the re-emission half of a sink recycle).
+    Added,
+    /// Removed by `remove_particles`, `retain_particles` or a sink (also the
+    /// drain half of a sink recycle). Position is where it was last seen.
+    Removed,
+    /// Split in two by `split_particles`; one event per parent, at the parent's position.
+    Split,
+    /// Material switched by `phase_transition` or a phase rule. `from` equals `to`
+    /// when a transition re-applies a particle's own material.
+    PhaseChanged { from: u32, to: u32 },
+    /// Moved into the sleeping partition.
+    Slept,
+    /// Moved back into the active partition.
+    Woke,
+    /// Repaired by `SimConfig::project_invalid_state` — non-finite state, or J
+    /// outside `projection_min_deformation_j..=j_max`. Position is after the repair.
+    Projected,
+}
+
+/// One recorded change to one particle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParticleEvent {
+    pub kind: ParticleEventKind,
+    /// The particle's `user_tag` at the time of the event.
+    pub tag: u32,
+    pub position: Vec2,
+    /// Stable ID, when `Simulation::enable_particle_ids` is on.
+    pub id: Option<ParticleId>,
+    /// `Simulation::frame_index` at the time of the event: the step that is
+    /// running, or the last completed one for calls between steps.
+    pub frame: u64,
+}
+
+impl Simulation {
+    /// Start recording [`ParticleEvent`]s. No-op when already enabled.
+    pub fn enable_particle_events(&mut self) {
+        self.events.get_or_insert_with(Vec::new);
+    }
+
+    /// Builder-style [`Self::enable_particle_events`].
+    pub fn with_particle_events(mut self) -> Self {
+        self.enable_particle_events();
+        self
+    }
+
+    /// Stop recording and drop any undrained events.
+    pub fn disable_particle_events(&mut self) {
+        self.events = None;
+    }
+
+    pub fn particle_events_enabled(&self) -> bool {
+        self.events.is_some()
+    }
+
+    /// Events recorded since the last drain, oldest first.
+    pub fn particle_events(&self) -> &[ParticleEvent] {
+        self.events.as_deref().unwrap_or_default()
+    }
+
+    /// Take every recorded event, oldest first, leaving the queue empty.
+    pub fn drain_particle_events(&mut self) -> Vec<ParticleEvent> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Record `kind` for the particle currently at index `i` (no-op while disabled).
+    pub(super) fn record_event(&mut self, i: usize, kind: ParticleEventKind) {
+        if let Some(events) = &mut self.events {
+            events.push(ParticleEvent {
+                kind,
+                tag: self.particles.user_tag[i],
+                position: self.particles.x[i],
+                id: self
+                    .id_index
+                    .is_some()
+                    .then(|| ParticleId(self.particles.id[i])),
+                frame: self.frame_index,
+            });
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! Connected-component labeling of bodies, and body-split (fracture)
+//! detection.
+//!
+//! A `RankineMaterial` slab that cracks or a jelly that tears in two is still
+//! one `user_tag` to the solver. [`label_fragments`] finds the pieces: two
+//! particles with the same tag are linked when they lie within
+//! `link_distance` of each other (minimum-image across periodic seams), and a
+//! fragment is a connected set of links. The default link distance of one
+//! grid cell is the material's own coupling scale — particles that far apart
+//! share most of their kernel nodes — while a crack a cell wide separates the
+//! pieces. Sleeping particles are included.
+//!
+//! `Simulation::split_fragments` gives every fragment but the largest of each
+//! nonzero tag a fresh tag and reports a [`BodySplit`]; with
+//! `Simulation::enable_fracture_detection` it runs at the end of every
+//! `step()` and queues the splits for `drain_body_splits`. Like events, the
+//! queue is output rather than state and is not checkpointed (the new tags
+//! themselves are).
+
+use std::collections::BTreeMap;
+
+use glam::Vec2;
+
+use super::Simulation;
+use super::spatial_hash::SpatialHash;
+use crate::grid::GridDomain;
+use crate::particle::Particles;
+
+/// How fragments are found and what happens to them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FragmentOptions {
+    /// Same-tag particles closer than this (grid cells) are connected.
+    pub link_distance: f32,
+    /// Fragments with fewer particles are debris: never reported as pieces of
+    /// a split and never retagged.
+    pub min_fragment_size: usize,
+    /// Give split-off fragments fresh tags. Without it a split body is
+    /// reported again on every pass.
+    pub retag: bool,
+}
+
+impl FragmentOptions {
+    pub fn new(link_distance: f32) -> Self {
+        Self {
+            link_distance,
+            min_fragment_size: 1,
+            retag: true,
+        }
+    }
+
+    pub fn min_fragment_size(mut self, particles: usize) -> Self {
+        self.min_fragment_size = particles;
+        self
+    }
+
+    pub fn retag(mut self, retag: bool) -> Self {
+        self.retag = retag;
+        self
+    }
+}
+
+impl Default for FragmentOptions {
+    /// One-cell links, every fragment counts, split-offs are retagged.
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// One connected piece of a tag group.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fragment {
+    pub tag: u32,
+    /// Number of particles.
+    pub size: usize,
+    pub mass: f32,
+    /// Mass-weighted centre, measured across periodic seams and wrapped back
+    /// into the domain.
+    pub centroid: Vec2,
+}
+
+/// Result of [`label_fragments`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FragmentLabels {
+    /// Fragment index (into `fragments`) per particle, indexed like the
+    /// particles that were labeled.
+    pub labels: Vec<usize>,
+    /// Fragments in order of their lowest particle index.
+    pub fragments: Vec<Fragment>,
+}
+
+impl FragmentLabels {
+    /// Indices of the particles in fragment `fragment`.
+    pub fn indices(&self, fragment: usize) -> impl Iterator<Item = usize> + '_ {
+        self.labels
+            .iter()
+            .enumerate()
+            .filter(move |&(_, &label)| label == fragment)
+            .map(|(i, _)| i)
+    }
+}
+
+/// A tag group that came apart, reported by `Simulation::split_fragments`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BodySplit {
+    /// The tag the body had before the split.
+    pub tag: u32,
+    /// Pieces of at least `min_fragment_size` particles, largest first. The
+    /// first keeps `tag`; the others carry their new tags when retagging.
+    pub fragments: Vec<Fragment>,
+    /// `Simulation::frame_index` when the split was found: the running step
+    /// for per-step detection, the last completed one otherwise.
+    pub frame: u64,
+}
+
+/// Label the connected fragments of every tag group. See the module doc.
+/// Tag 0 is labeled like any other, but it is the "no body" tag rather than a
+/// body, so `Simulation::split_fragments` never reports or retags it.
+/// O(N · neighbours within `link_distance`).
+pub fn label_fragments(
+    particles: &Particles,
+    domain: GridDomain,
+    link_distance: f32,
+) -> FragmentLabels {
+    let n = particles.len();
+    let mut hash = SpatialHash::for_domain(link_distance.max(f32::EPSILON), domain);
+    hash.rebuild(&particles.x, n);
+
+    let mut parent: Vec<usize> = (0..n).collect();
+    for i in 0..n {
+        let (x, tag) = (particles.x[i], particles.user_tag[i]);
+        for j in hash.query(x, link_distance) {
+            if j > i
+                && particles.user_tag[j] == tag
+                && hash.offset(x, particles.x[j]).length() <= link_distance
+            {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                // Lower root wins, so a fragment's root is its lowest index.
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+
+    // Roots are visited before their members, so fragment order follows the
+    // lowest particle index.
+    let mut labels = vec![0; n];
+    let mut fragments: Vec<Fragment> = Vec::new();
+    let mut anchors: Vec<Vec2> = Vec::new();
+    let mut moments: Vec<Vec2> = Vec::new();
+    let mut slot = vec![usize::MAX; n];
+    for (i, label) in labels.iter_mut().enumerate() {
+        let root = find(&mut parent, i);
+        if slot[root] == usize::MAX {
+            slot[root] = fragments.len();
+            fragments.push(Fragment {
+                tag: particles.user_tag[i],
+                size: 0,
+                mass: 0.0,
+                centroid: Vec2::ZERO,
+            });
+            anchors.push(particles.x[i]);
+            moments.push(Vec2::ZERO);
+        }
+        let k = slot[root];
+        *label = k;
+        let m = particles.mass[i];
+        fragments[k].size += 1;
+        fragments[k].mass += m;
+        moments[k] += m * hash.offset(anchors[k], particles.x[i]);
+    }
+    for ((fragment, anchor), moment) in fragments.iter_mut().zip(anchors).zip(moments) {
+        let shift = if fragment.mass > 0.0 {
+            moment / fragment.mass
+        } else {
+            Vec2::ZERO
+        };
+        fragment.centroid = domain.wrap_position(anchor + shift);
+    }
+    FragmentLabels { labels, fragments }
+}
+
+/// Union-find root of `i`, halving the path on the way.
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Per-step detection state behind `Simulation::enable_fracture_detection`.
+#[derive(Debug)]
+pub(super) struct FractureDetector {
+    options: FragmentOptions,
+    splits: Vec<BodySplit>,
+}
+
+impl Simulation {
+    /// Connected fragments of every tag group as the particles stand now
+    /// (see [`label_fragments`]).
+    pub fn label_fragments(&self, link_distance: f32) -> FragmentLabels {
+        label_fragments(&self.particles, self.grid.domain(), link_distance)
+    }
+
+    /// Find tag groups that have come apart and, with `options.retag`, move
+    /// every piece but the largest to a fresh tag (as `add_body` would issue).
+    /// Untagged particles (tag 0) are not a body and are skipped. Returns one
+    /// [`BodySplit`] per broken group, in tag order.
+    pub fn split_fragments(&mut self, options: &FragmentOptions) -> Vec<BodySplit> {
+        let labels = self.label_fragments(options.link_distance);
+        let mut by_tag: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+        for (k, fragment) in labels.fragments.iter().enumerate() {
+            if fragment.tag != 0 && fragment.size >= options.min_fragment_size {
+                by_tag.entry(fragment.tag).or_default().push(k);
+            }
+        }
+
+        let mut new_tags = vec![None; labels.fragments.len()];
+        let mut splits = Vec::new();
+        for (tag, mut pieces) in by_tag {
+            if pieces.len() < 2 {
+                continue;
+            }
+            // Stable: equal sizes keep lowest-index-first order.
+            pieces.sort_by_key(|&k| std::cmp::Reverse(labels.fragments[k].size));
+            let mut fragments = Vec::with_capacity(pieces.len());
+            for (rank, &k) in pieces.iter().enumerate() {
+                let mut fragment = labels.fragments[k];
+                if rank > 0 && options.retag {
+                    fragment.tag = self.next_tag;
+                    self.next_tag += 1;
+                    new_tags[k] = Some(fragment.tag);
+                }
+                fragments.push(fragment);
+            }
+            splits.push(BodySplit {
+                tag,
+                fragments,
+                frame: self.frame_index,
+            });
+        }
+
+        for (i, &label) in labels.labels.iter().enumerate() {
+            if let Some(tag) = new_tags[label] {
+                self.retag_particle(i, tag);
+            }
+        }
+        splits
+    }
+
+    /// Run [`Self::split_fragments`] with `options` at the end of every
+    /// `step()`, queuing the splits. Replaces the options when already enabled
+    /// and keeps undrained splits.
+    pub fn enable_fracture_detection(&mut self, options: FragmentOptions) {
+        match &mut self.fracture {
+            Some(detector) => detector.options = options,
+            None => {
+                self.fracture = Some(FractureDetector {
+                    options,
+                    splits: Vec::new(),
+                })
+            }
+        }
+    }
+
+    /// Builder-style [`Self::enable_fracture_detection`].
+    pub fn with_fracture_detection(mut self, options: FragmentOptions) -> Self {
+        self.enable_fracture_detection(options);
+        self
+    }
+
+    /// Stop detecting and drop any undrained splits.
+    pub fn disable_fracture_detection(&mut self) {
+        self.fracture = None;
+    }
+
+    pub fn fracture_detection_enabled(&self) -> bool {
+        self.fracture.is_some()
+    }
+
+    /// Splits queued since the last drain, oldest first.
+    pub fn body_splits(&self) -> &[BodySplit] {
+        self.fracture
+            .as_ref()
+            .map_or(&[], |detector| &detector.splits)
+    }
+
+    /// Take every queued split, oldest first, leaving the queue empty.
+    pub fn drain_body_splits(&mut self) -> Vec<BodySplit> {
+        self.fracture
+            .as_mut()
+            .map(|detector| std::mem::take(&mut detector.splits))
+            .unwrap_or_default()
+    }
+
+    /// The per-step detection pass (no-op while disabled).
+    pub(super) fn detect_fractures(&mut self) {
+        let Some(options) = self.fracture.as_ref().map(|detector| detector.options) else {
+            return;
+        };
+        let splits = self.split_fragments(&options);
+        if let Some(detector) = &mut self.fracture {
+            detector.splits.extend(splits);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::particle::Particle;
+    use glam::{BVec2, UVec2};
+
+    fn particles(points: &[(f32, f32, u32)]) -> Particles {
+        let particles: Vec<Particle> = points
+            .iter()
+            .map(|&(x, y, tag)| {
+                let mut p = Particle::zeroed();
+                p.x = Vec2::new(x, y);
+                p.mass = 1.0;
+                p.user_tag = tag;
+                p
+            })
+            .collect();
+        Particles::from(particles)
+    }
+
+    #[test]
+    fn links_stay_within_a_tag_and_wrap_across_periodic_seams() {
+        let domain = GridDomain::new(UVec2::new(16, 16)).with_periodic(BVec2::new(true, false));
+        let particles = particles(&[
+            // Tag 0: a chain straddling the x seam, and a separate pair.
+            (15.6, 4.0, 0),
+            (0.4, 4.0, 0),
+            (1.2, 4.0, 0),
+            (8.0, 4.0, 0),
+            (8.8, 4.0, 0),
+            // Tag 1: touches the pair but is its own body.
+            (8.8, 4.8, 1),
+        ]);
+        let labels = label_fragments(&particles, domain, 1.0);
+
+        assert_eq!(labels.labels, [0, 0, 0, 1, 1, 2]);
+        let seam = labels.fragments[0];
+        assert_eq!((seam.tag, seam.size), (0, 3));
+        // Mean of 15.6, 16.4 and 17.2, wrapped: 16.4 → 0.4.
+        assert!(
+            (seam.centroid - Vec2::new(0.4, 4.0)).length() < 1e-4,
+            "{}",
+            seam.centroid
+        );
+        assert_eq!(labels.fragments[2].tag, 1);
+        assert_eq!(labels.indices(1).collect::<Vec<_>>(), [3, 4]);
+    }
+}
@@ -1,7 +1,15 @@
-//! Typed handles for materials and particle groups.
+//! Typed handles for materials, particle groups and individual particles.
 
 use crate::solver::query::BodyState;
 
+/// Stable identity of one particle (see `Simulation::enable_particle_ids`).
+///
+/// Wraps the `u64` stored in `Particles::id`. Unlike a physical index it
+/// survives sleep/wake swaps, removals and insertions; resolve it with
+/// `Simulation::index_of`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ParticleId(pub u64);
+
 /// Typed handle for a registered material.
 ///
 /// Wraps a `u32` material ID. Use instead of raw integers to prevent
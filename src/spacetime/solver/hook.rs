@@ -0,0 +1,55 @@
+//! User code inside the substep loop.
+//!
+//! `Simulation::step` advances `SimConfig::dt` in several adaptive substeps,
+//! and anything a caller does between `step()` calls only ever sees the last
+//! of them. A [`SubstepHook`] registered with `Simulation::add_substep_hook`
+//! is called at fixed stages of every substep instead — the place for
+//! controllers that integrate at the physics rate (an `Lnn` writing
+//! `Particle::activation`) and for forces that act on grid nodes rather than
+//! on particles.
+//!
+//! Stages, in `do_substep` order:
+//!
+//! 1. `pre_p2g` — after state projection and density recompute, right before
+//!    the scatter. Changes to velocity, activation, etc. enter this substep's grid.
+//! 2. `post_grid_update` — grid velocities are final: gravity, boundaries,
+//!    contact, mixture and rigid-body coupling are applied, and G2P reads them next.
+//! 3. `post_g2p` — particles hold this substep's velocity and position, before
+//!    force fields.
+//! 4. `end_of_substep` — after thermal diffusion, phase rules and sleep.
+//!
+//! At each stage hooks run in registration order. Hooks are code, not state:
+//! checkpoints neither save nor restore them.
+
+use crate::grid::Grid;
+use crate::particle::Particles;
+
+/// Where in the current `step()` a hook call happens.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SubstepInfo {
+    /// Duration of this substep — the adaptive loop's choice, at most `SimConfig::dt`.
+    pub sub_dt: f32,
+    /// 0-based index of this substep within the current `step()`.
+    pub substep: usize,
+    /// `particles[0..active_count]` is the active partition at the time of the
+    /// call; P2G and G2P only visit those.
+    pub active_count: usize,
+}
+
+/// Callbacks at named stages of every substep (see the module doc). Every
+/// method defaults to a no-op, so implement only the stages you need.
+///
+/// Particle callbacks may change per-particle state (velocity, activation,
+/// temperature, ...) but must not add, remove or reorder particles, or change
+/// `user_tag` — the solver's active partition and tag index depend on both.
+pub trait SubstepHook: Send + Sync {
+    fn pre_p2g(&mut self, _particles: &mut Particles, _info: SubstepInfo) {}
+
+    /// `grid.active_cells_with_index_mut()` yields every node with mass; at
+    /// this stage `Cell::momentum` holds the node *velocity* (see `Cell`).
+    fn post_grid_update(&mut self, _grid: &mut Grid, _info: SubstepInfo) {}
+
+    fn post_g2p(&mut self, _particles: &mut Particles, _info: SubstepInfo) {}
+
+    fn end_of_substep(&mut self, _particles: &mut Particles, _info: SubstepInfo) {}
+}
@@ -0,0 +1,120 @@
+//! Optional stable per-particle IDs.
+//!
+//! Physical indices are not identities: sleep/wake swaps, `remove_particles`,
+//! `split_particles` and emitter insertion all move particles around. Tags name
+//! whole groups; an ID names one particle for its whole lifetime. IDs are off
+//! by default — `Simulation::enable_particle_ids` turns them on, after which
+//! every particle (existing and future) carries a unique `Particles::id` and
+//! `index_of` resolves it in O(1).
+//!
+//! IDs are issued from a monotonic counter and never reused: a removed
+//! particle's ID simply stops resolving. A split keeps the parent's ID on the
+//! first child and gives the second a fresh one. A particle recycled by a sink
+//! keeps its ID (it is the same slot, reset in place). Checkpoints store the
+//! IDs and the counter.
+
+use std::collections::HashMap;
+
+use super::Simulation;
+use super::handle::ParticleId;
+use crate::particle::Particle;
+
+impl Simulation {
+    /// Start tracking stable particle IDs. Every current particle gets a fresh
+    /// ID in physical order; particles added later get one on insertion.
+    /// No-op when IDs are already enabled.
+    pub fn enable_particle_ids(&mut self) {
+        if self.id_index.is_some() {
+            return;
+        }
+        self.id_index = Some(HashMap::with_capacity(self.particles.len()));
+        self.assign_particle_ids(0..self.particles.len());
+        self.reindex_particle_ids(0..self.particles.len());
+    }
+
+    /// Builder-style [`Self::enable_particle_ids`].
+    pub fn with_particle_ids(mut self) -> Self {
+        self.enable_particle_ids();
+        self
+    }
+
+    pub fn particle_ids_enabled(&self) -> bool {
+        self.id_index.is_some()
+    }
+
+    /// Current physical index of `id`, or `None` if IDs are disabled or the
+    /// particle no longer exists. O(1). Only valid until the next call that
+    /// moves particles (`step`, sleep/wake, removal, insertion).
+    pub fn index_of(&self, id: ParticleId) -> Option<usize> {
+        self.id_index.as_ref()?.get(&id.0).copied()
+    }
+
+    /// Stable ID of the particle at physical index `i`, or `None` if IDs are
+    /// disabled or `i` is out of range.
+    pub fn id_of(&self, i: usize) -> Option<ParticleId> {
+        self.id_index.as_ref()?;
+        self.particles.id.get(i).map(|&id| ParticleId(id))
+    }
+
+    /// Copy of the particle with `id`, wherever it currently lives.
+    pub fn particle_by_id(&self, id: ParticleId) -> Option<Particle> {
+        self.index_of(id).map(|i| self.particles.get(i))
+    }
+
+    /// Edit the particle with `id` through a [`Particle`] view and write it
+    /// back; returns `f`'s result, or `None` if `id` does not resolve.
+    ///
+    /// Same CFL caveat as `particles_mut` for velocity writes. A changed
+    /// `user_tag` moves the particle to that tag group; `sleeping` is ignored
+    /// (use the tag sleep/wake API).
+    pub fn update_particle_by_id<R>(
+        &mut self,
+        id: ParticleId,
+        f: impl FnOnce(&mut Particle) -> R,
+    ) -> Option<R> {
+        let i = self.index_of(id)?;
+        let mut p = self.particles.get(i);
+        let old_tag = p.user_tag;
+        let result = f(&mut p);
+        let new_tag = p.user_tag;
+        p.user_tag = old_tag;
+        self.particles.set(i, p);
+        self.retag_particle(i, new_tag);
+        Some(result)
+    }
+
+    /// Hand out the next ID. Only meaningful while IDs are enabled.
+    pub(super) fn next_particle_id(&mut self) -> u64 {
+        let id = self.next_particle_id;
+        self.next_particle_id += 1;
+        id
+    }
+
+    /// Give every particle in `indices` a fresh ID (no-op while disabled).
+    /// Does not touch the index — follow with `reindex_particle_ids`.
+    pub(super) fn assign_particle_ids(&mut self, indices: impl IntoIterator<Item = usize>) {
+        if self.id_index.is_none() {
+            return;
+        }
+        for i in indices {
+            self.particles.id[i] = self.next_particle_id();
+        }
+    }
+
+    /// Point the index at the current position of each particle in `indices`.
+    pub(super) fn reindex_particle_ids(&mut self, indices: impl IntoIterator<Item = usize>) {
+        if let Some(index) = &mut self.id_index {
+            for i in indices {
+                index.insert(self.particles.id[i], i);
+            }
+        }
+    }
+
+    /// Rebuild the index from `Particles::id` after a compaction.
+    pub(super) fn rebuild_particle_id_index(&mut self) {
+        if let Some(index) = &mut self.id_index {
+            index.clear();
+        }
+        self.reindex_particle_ids(0..self.particles.len());
+    }
+}
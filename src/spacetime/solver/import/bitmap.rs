@@ -0,0 +1,275 @@
+//! Painted material masks: `Bitmap` (PNG or PGM) plus a `Palette` mapping
+//! pixel colours to material IDs.
+
+use std::io::{self, Read};
+use std::path::Path;
+
+use glam::Vec2;
+
+use super::{ImportedPoint, PointSet, invalid, png, with_path};
+
+/// An RGBA image, top row first (as painted — `PointSet::from_bitmap` flips
+/// it so the top row lands at the highest `y`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bitmap {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 4]>,
+}
+
+impl Bitmap {
+    /// Panics unless `pixels.len() == width * height`.
+    pub fn new(width: usize, height: usize, pixels: Vec<[u8; 4]>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "Bitmap::new: {width}x{height} needs {} pixels",
+            width * height
+        );
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// RGBA of column `x`, row `y` counted from the top.
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Decode a PNG or a binary (`P5`) / plain (`P2`) PGM, told apart by
+    /// their leading bytes. PNG transparency is kept; PGM grey `g` becomes
+    /// opaque `(g, g, g)`, scaled to 8 bits. PNG needs the `import` feature;
+    /// without it a PNG is an error.
+    pub fn read(mut reader: impl Read) -> io::Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        if png::is_png(&data) {
+            let (width, height, pixels) = png::decode(&data)?;
+            Ok(Self::new(width, height, pixels))
+        } else if data.starts_with(b"P5") || data.starts_with(b"P2") {
+            read_pgm(&data)
+        } else {
+            Err(invalid("not a PNG or PGM image".into()))
+        }
+    }
+
+    /// `read` a file; errors are prefixed with the path.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        std::fs::File::open(path)
+            .and_then(Self::read)
+            .map_err(|err| with_path(path, err))
+    }
+}
+
+/// Pixel colour → material ID. Colours not in the palette, and pixels less
+/// than half opaque, are empty space.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Palette {
+    entries: Vec<([u8; 3], u32)>,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map `0xRRGGBB` to `material_id`; a repeated colour keeps its first mapping.
+    pub fn with(mut self, rgb: u32, material_id: u32) -> Self {
+        let [_, r, g, b] = rgb.to_be_bytes();
+        self.entries.push(([r, g, b], material_id));
+        self
+    }
+
+    pub fn material_for(&self, [r, g, b, a]: [u8; 4]) -> Option<u32> {
+        if a < 128 {
+            return None;
+        }
+        self.entries
+            .iter()
+            .find(|(rgb, _)| *rgb == [r, g, b])
+            .map(|&(_, id)| id)
+    }
+}
+
+impl PointSet {
+    /// Lattice points over `bitmap`, each taking the material its pixel's
+    /// colour maps to in `palette`; unmapped pixels stay empty.
+    ///
+    /// The bitmap's bottom-left corner sits at `origin` and each pixel covers
+    /// `pixel_size` grid cells; points are `spacing` apart, offset half a
+    /// spacing from the edges so they fall inside pixels rather than on their
+    /// borders. Pass the same `spacing` to the `SpawnRegion` used with
+    /// `build_particles_from_points`, so mass and jitter match the lattice.
+    pub fn from_bitmap(
+        bitmap: &Bitmap,
+        palette: &Palette,
+        origin: Vec2,
+        pixel_size: f32,
+        spacing: f32,
+    ) -> Self {
+        assert!(
+            pixel_size > 0.0 && spacing > 0.0,
+            "pixel_size and spacing must be positive"
+        );
+        let extent = Vec2::new(bitmap.width as f32, bitmap.height as f32) * pixel_size;
+        let steps = (extent / spacing).floor();
+        let mut set = Self::new();
+        for i in 0..steps.x as usize {
+            for j in 0..steps.y as usize {
+                let local = (Vec2::new(i as f32, j as f32) + 0.5) * spacing;
+                let column = ((local.x / pixel_size) as usize).min(bitmap.width - 1);
+                let row_from_bottom = ((local.y / pixel_size) as usize).min(bitmap.height - 1);
+                let pixel = bitmap.pixel(column, bitmap.height - 1 - row_from_bottom);
+                if let Some(material_id) = palette.material_for(pixel) {
+                    set.points.push(ImportedPoint {
+                        material_id: Some(material_id),
+                        ..ImportedPoint::at(origin + local)
+                    });
+                }
+            }
+        }
+        set
+    }
+}
+
+/// Parse a PGM: magic, width, height and maxval separated by whitespace and
+/// `#` comments, then one sample per pixel — one or two big-endian bytes
+/// (`P5`) or decimal text (`P2`).
+fn read_pgm(data: &[u8]) -> io::Result<Bitmap> {
+    let mut pos = 2;
+    let mut field = |what: &str| -> io::Result<usize> {
+        loop {
+            match data.get(pos) {
+                Some(b'#') => {
+                    while data.get(pos).is_some_and(|&c| c != b'\n') {
+                        pos += 1;
+                    }
+                }
+                Some(c) if c.is_ascii_whitespace() => pos += 1,
+                _ => break,
+            }
+        }
+        let start = pos;
+        while data.get(pos).is_some_and(u8::is_ascii_digit) {
+            pos += 1;
+        }
+        std::str::from_utf8(&data[start..pos])
+            .ok()
+            .and_then(|digits| digits.parse().ok())
+            .ok_or_else(|| invalid(format!("PGM header has no valid {what}")))
+    };
+    let width = field("width")?;
+    let height = field("height")?;
+    let max = field("maxval")?;
+    if !(1..=65535).contains(&max) {
+        return Err(invalid(format!("PGM maxval {max} is out of range")));
+    }
+    let count = width
+        .checked_mul(height)
+        .ok_or_else(|| invalid(format!("PGM size {width}x{height} is too large")))?;
+    let samples: Vec<usize> = if data.starts_with(b"P2") {
+        (0..count)
+            .map(|_| field("sample"))
+            .collect::<io::Result<_>>()?
+    } else {
+        // Exactly one whitespace byte separates the header from the samples.
+        let body = data.get(pos + 1..).unwrap_or_default();
+        let size = if max > 255 { 2 } else { 1 };
+        if count
+            .checked_mul(size)
+            .is_none_or(|bytes| body.len() < bytes)
+        {
+            return Err(invalid("PGM pixel data is truncated".into()));
+        }
+        body.chunks_exact(size)
+            .take(count)
+            .map(|s| s.iter().fold(0, |v, &b| v << 8 | b as usize))
+            .collect()
+    };
+    let pixels = samples
+        .into_iter()
+        .map(|s| {
+            let g = (s.min(max) * 255 / max) as u8;
+            [g, g, g, 255]
+        })
+        .collect();
+    Ok(Bitmap::new(width, height, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 3x2 RGBA PNG, rows filtered None and Sub:
+    // red, green, transparent / blue, red, green.
+    const RGBA_PNG: &[u8] = &[
+        137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 3, 0, 0, 0, 2, 8, 6,
+        0, 0, 0, 157, 116, 102, 26, 0, 0, 0, 25, 73, 68, 65, 84, 120, 218, 99, 248, 207, 192, 240,
+        31, 12, 129, 128, 17, 200, 254, 255, 31, 72, 49, 2, 185, 0, 114, 190, 7, 252, 54, 55, 57,
+        102, 0, 0, 0, 0, 73, 69, 78, 68, 174, 66, 96, 130,
+    ];
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    #[test]
+    #[cfg(feature = "import")]
+    fn png_decodes_to_rgba() {
+        let png = Bitmap::read(RGBA_PNG).unwrap();
+        assert_eq!((png.width(), png.height()), (3, 2));
+        assert_eq!(png.pixels, vec![RED, GREEN, [0, 0, 0, 0], BLUE, RED, GREEN]);
+        let truncated = Bitmap::read(&RGBA_PNG[..RGBA_PNG.len() - 20]).unwrap_err();
+        assert!(truncated.to_string().starts_with("bad PNG"), "{truncated}");
+    }
+
+    #[test]
+    fn pgm_decodes_to_rgba() {
+        let plain = Bitmap::read(&b"P2\n# painted\n2 1\n4\n0 4\n"[..]).unwrap();
+        assert_eq!(plain.pixels, vec![[0, 0, 0, 255], [255; 4]]);
+        let binary = Bitmap::read(&b"P5 2 1 65535\n\x80\x00\xff\xff"[..]).unwrap();
+        assert_eq!(binary.pixels, vec![[127, 127, 127, 255], [255; 4]]);
+        let huge = format!("P5 {} 2 255\n\0", usize::MAX / 2 + 1);
+        assert!(Bitmap::read(huge.as_bytes()).is_err());
+        let wide = format!("P5 {} 1 65535\n\0", usize::MAX / 2 + 1);
+        assert!(Bitmap::read(wide.as_bytes()).is_err());
+
+        let error = |data: &[u8]| Bitmap::read(data).unwrap_err().to_string();
+        assert_eq!(error(b"GIF89a"), "not a PNG or PGM image");
+        assert_eq!(error(b"P5 2 2 255\n\x00"), "PGM pixel data is truncated");
+        if cfg!(not(feature = "import")) {
+            assert!(error(RGBA_PNG).contains("`import` feature"));
+        }
+    }
+
+    #[test]
+    fn bitmap_pixels_become_material_points_bottom_row_first() {
+        let bitmap = Bitmap::new(2, 2, vec![RED, [0, 0, 255, 0], GREEN, BLUE]);
+        let palette = Palette::new().with(0xff0000, 1).with(0x00ff00, 2);
+        let points = PointSet::from_bitmap(&bitmap, &palette, Vec2::new(10.0, 20.0), 2.0, 1.0);
+        // Blue is unmapped (and transparent blue would be skipped anyway).
+        assert_eq!(points.len(), 8);
+        let material_at = |x: f32, y: f32| {
+            points
+                .points
+                .iter()
+                .find(|p| p.position == Vec2::new(x, y))
+                .and_then(|p| p.material_id)
+        };
+        assert_eq!(material_at(10.5, 20.5), Some(2));
+        assert_eq!(material_at(11.5, 23.5), Some(1));
+        assert_eq!(material_at(12.5, 23.5), None);
+        assert_eq!(material_at(13.5, 20.5), None);
+    }
+}
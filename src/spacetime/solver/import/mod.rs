@@ -0,0 +1,602 @@
+//! Particle import: point sets from CSV and PLY files, and material masks
+//! painted as PNG/PGM bitmaps (PNG decoding behind the `import` feature).
+//!
+//! A [`PointSet`] is an intermediate list of positions in grid units plus
+//! whichever per-point attributes the source carried. Turn it into particles
+//! with `build_particles_from_points`, which takes a `SpawnRegion` for
+//! everything the points leave unset (material, mass, jitter, initial `F`,
+//! volume precompute), then hand them to `Simulation::add_particles` or
+//! `GpuSimulation::new`. The readers reject non-finite values; points that
+//! fall outside the domain or name an unregistered material are caught by
+//! `Simulation::check_particles`, which `add_particles` panics on:
+//! ```rust,no_run
+//! # extern crate emerge_engine as emerge;
+//! # use emerge::solver::Simulation;
+//! # use emerge::solver::import::{Bitmap, Palette, PointSet};
+//! # use emerge::{SimConfig, SpawnRegion, build_particles_from_points};
+//! # use glam::Vec2;
+//! # fn main() -> std::io::Result<()> {
+//! # let config = SimConfig::standard(128, 0.05, Vec2::NEG_Y * 0.3);
+//! # let mut sim = Simulation::empty(config);
+//! // Dirt (brown) is material 0, water (blue) 1; every other colour is empty.
+//! let palette = Palette::new().with(0x8b5a2b, 0).with(0x2060ff, 1);
+//! let level = Bitmap::load("level.png")?;
+//! // One pixel per grid cell, two particles per cell along each axis.
+//! let points = PointSet::from_bitmap(&level, &palette, Vec2::splat(4.0), 1.0, 0.5);
+//! let spawn = SpawnRegion::for_sim(&config).spacing(0.5).precompute_volumes();
+//! let terrain = build_particles_from_points(&config, spawn, &points);
+//! sim.check_particles(&terrain).map_err(std::io::Error::other)?;
+//! let terrain = sim.add_particles(terrain);
+//!
+//! let rock = PointSet::load_csv("rock.csv")?.centered_at(Vec2::new(64.0, 90.0));
+//! let rock = sim.add_particles(build_particles_from_points(&config, spawn, &rock));
+//! # Ok(()) }
+//! ```
+//!
+//! # Point attributes
+//! CSV headers and PLY `vertex` properties are matched by name: `x`, `y`
+//! (required), `vx`, `vy`, `mass`, `material` and `temperature`. Other
+//! columns — `z`, normals, colours, the `tag` and `j` of `emerge run`'s
+//! particle dumps — are ignored, so those dumps read straight back in.
+
+mod bitmap;
+mod png;
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use glam::Vec2;
+
+use crate::particle::Particle;
+use crate::solver::config::{SimConfig, SpawnRegion};
+use crate::solver::{LcgRng, fresh_particle};
+
+pub use bitmap::{Bitmap, Palette};
+
+/// One imported sample. `None` attributes fall back to the `SpawnRegion`
+/// passed to `build_particles_from_points`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImportedPoint {
+    /// Position in grid units.
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub mass: Option<f32>,
+    pub material_id: Option<u32>,
+    pub temperature: Option<f32>,
+}
+
+impl ImportedPoint {
+    /// A point at rest with no attributes of its own.
+    pub fn at(position: Vec2) -> Self {
+        Self {
+            position,
+            velocity: Vec2::ZERO,
+            mass: None,
+            material_id: None,
+            temperature: None,
+        }
+    }
+}
+
+/// Imported positions and attributes, ready for `build_particles_from_points`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PointSet {
+    pub points: Vec<ImportedPoint>,
+}
+
+impl PointSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Corners of the axis-aligned box around every position, or `None` when
+    /// the set is empty.
+    pub fn bounds(&self) -> Option<(Vec2, Vec2)> {
+        let first = self.points.first()?.position;
+        Some(self.points.iter().fold((first, first), |(min, max), p| {
+            (min.min(p.position), max.max(p.position))
+        }))
+    }
+
+    /// Scale every position about the origin, then shift it by `offset` —
+    /// e.g. metres to grid cells with `scale = 1.0 / config.dx_meters`.
+    pub fn transformed(mut self, scale: f32, offset: Vec2) -> Self {
+        for p in &mut self.points {
+            p.position = p.position * scale + offset;
+        }
+        self
+    }
+
+    /// Move the set so the centre of its `bounds` lands on `center`.
+    pub fn centered_at(self, center: Vec2) -> Self {
+        match self.bounds() {
+            Some((min, max)) => self.transformed(1.0, center - (min + max) * 0.5),
+            None => self,
+        }
+    }
+
+    /// Parse CSV text: a header row naming the columns (see the module docs),
+    /// then one point per row. Blank lines and `#` comments are skipped.
+    pub fn read_csv(reader: impl BufRead) -> io::Result<Self> {
+        let mut lines = reader.lines().enumerate().filter(|(_, line)| {
+            line.as_ref().map_or(true, |l| {
+                !l.trim().is_empty() && !l.trim_start().starts_with('#')
+            })
+        });
+        let Some((_, header)) = lines.next() else {
+            return Err(invalid("CSV has no header row".into()));
+        };
+        let names: Vec<String> = header?.split(',').map(|n| n.trim().to_string()).collect();
+        let columns = Columns::find(names.iter().map(String::as_str))
+            .map_err(|err| invalid(format!("CSV header: {err}")))?;
+
+        let mut set = Self::new();
+        for (index, line) in lines {
+            let line = line?;
+            let at = |message: String| invalid(format!("line {}: {message}", index + 1));
+            let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+            if cells.len() != names.len() {
+                return Err(at(format!(
+                    "expected {} values, found {}",
+                    names.len(),
+                    cells.len()
+                )));
+            }
+            let point = columns.point(|column| {
+                cells[column].parse::<f64>().map_err(|_| {
+                    at(format!(
+                        "`{}` in column `{}` is not a number",
+                        cells[column], names[column]
+                    ))
+                })
+            })?;
+            set.points.push(point);
+        }
+        Ok(set)
+    }
+
+    /// Parse a PLY file's `vertex` element — ASCII or binary, either byte
+    /// order. Other elements (faces, edges) are skipped.
+    pub fn read_ply(mut reader: impl BufRead) -> io::Result<Self> {
+        let header = PlyHeader::read(&mut reader)?;
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let text;
+        let mut source = match header.format {
+            PlyFormat::Ascii => {
+                text = String::from_utf8(data)
+                    .map_err(|_| invalid("PLY body is not ASCII text".into()))?;
+                PlySource::Ascii(text.split_whitespace())
+            }
+            PlyFormat::Binary { big_endian } => PlySource::Binary {
+                data,
+                pos: 0,
+                big_endian,
+            },
+        };
+
+        let mut set = Self::new();
+        for element in &header.elements {
+            let vertices = element.name == "vertex";
+            let columns = if vertices {
+                Some(
+                    Columns::find(element.properties.iter().map(|p| p.name.as_str()))
+                        .map_err(|err| invalid(format!("PLY vertex: {err}")))?,
+                )
+            } else {
+                None
+            };
+            let mut values = vec![0.0; element.properties.len()];
+            for _ in 0..element.count {
+                for (value, property) in values.iter_mut().zip(&element.properties) {
+                    *value = match property.list {
+                        // List properties (face indices) carry nothing we read.
+                        Some(count) => {
+                            let len = source.scalar(count)?;
+                            for _ in 0..len as usize {
+                                source.scalar(property.kind)?;
+                            }
+                            0.0
+                        }
+                        None => source.scalar(property.kind)?,
+                    };
+                }
+                if let Some(columns) = &columns {
+                    set.points.push(columns.point(|i| Ok(values[i]))?);
+                }
+            }
+        }
+        if !header.elements.iter().any(|e| e.name == "vertex") {
+            return Err(invalid("PLY has no `vertex` element".into()));
+        }
+        Ok(set)
+    }
+
+    /// `read_csv` a file; errors are prefixed with the path.
+    pub fn load_csv(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        File::open(path)
+            .and_then(|file| Self::read_csv(BufReader::new(file)))
+            .map_err(|err| with_path(path, err))
+    }
+
+    /// `read_ply` a file; errors are prefixed with the path.
+    pub fn load_ply(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        File::open(path)
+            .and_then(|file| Self::read_ply(BufReader::new(file)))
+            .map_err(|err| with_path(path, err))
+    }
+}
+
+/// Spawn particles at `points`, taking whatever they leave unset from
+/// `spawn`. Counterpart of `initialize_particles`; `box_size`, `box_center`,
+/// `shape`, `rotation` and `angular_velocity` do not apply.
+pub(crate) fn initialize_points(
+    config: &SimConfig,
+    spawn: &SpawnRegion,
+    points: &PointSet,
+    rng: &mut LcgRng,
+) -> Vec<Particle> {
+    let jitter_mag = spawn.position_jitter * spawn.spacing;
+    points
+        .points
+        .iter()
+        .map(|point| {
+            let jitter = Vec2::new(rng.next_f32() - 0.5, rng.next_f32() - 0.5) * 2.0 * jitter_mag;
+            let random = Vec2::new(rng.next_f32(), rng.next_f32());
+            let velocity =
+                point.velocity + (random - Vec2::splat(0.5)) * spawn.initial_velocity_scale;
+            let mass = point
+                .mass
+                .or(spawn.mass_override)
+                .unwrap_or(config.particle_mass);
+            let material_id = point.material_id.unwrap_or(spawn.material_id);
+            let mut p =
+                fresh_particle(config, point.position + jitter, velocity, mass, material_id);
+            p.deformation_gradient = spawn.initial_deformation_gradient;
+            if let Some(temperature) = point.temperature {
+                p.temperature = temperature;
+            }
+            p
+        })
+        .collect()
+}
+
+/// Where each recognised attribute sits in a CSV row or PLY vertex.
+struct Columns {
+    x: usize,
+    y: usize,
+    vx: Option<usize>,
+    vy: Option<usize>,
+    mass: Option<usize>,
+    material: Option<usize>,
+    temperature: Option<usize>,
+}
+
+impl Columns {
+    fn find<'a>(names: impl Iterator<Item = &'a str> + Clone) -> Result<Self, String> {
+        let column = |name: &str| names.clone().position(|n| n == name);
+        let required = |name: &str| column(name).ok_or(format!("no `{name}` column"));
+        Ok(Self {
+            x: required("x")?,
+            y: required("y")?,
+            vx: column("vx"),
+            vy: column("vy"),
+            mass: column("mass"),
+            material: column("material"),
+            temperature: column("temperature"),
+        })
+    }
+
+    /// Build a point, reading each present column through `value`.
+    fn point(&self, mut value: impl FnMut(usize) -> io::Result<f64>) -> io::Result<ImportedPoint> {
+        // NaN, infinities and values past `f32` range would reach the solver
+        // as non-finite particle state.
+        let mut value = |column| {
+            let v = value(column)?;
+            if (v as f32).is_finite() {
+                Ok(v)
+            } else {
+                Err(invalid(format!("`{v:e}` is not a finite number")))
+            }
+        };
+        let mut optional = |column: Option<usize>| column.map(&mut value).transpose();
+        let vx = optional(self.vx)?.unwrap_or(0.0);
+        let vy = optional(self.vy)?.unwrap_or(0.0);
+        let mass = optional(self.mass)?;
+        let material = optional(self.material)?;
+        let temperature = optional(self.temperature)?;
+        let material_id = match material {
+            Some(m) if m < 0.0 || m.fract() != 0.0 || m > u32::MAX as f64 => {
+                return Err(invalid(format!("material `{m}` is not a material ID")));
+            }
+            m => m.map(|m| m as u32),
+        };
+        Ok(ImportedPoint {
+            position: Vec2::new(value(self.x)? as f32, value(self.y)? as f32),
+            velocity: Vec2::new(vx as f32, vy as f32),
+            mass: mass.map(|m| m as f32),
+            material_id,
+            temperature: temperature.map(|t| t as f32),
+        })
+    }
+}
+
+enum PlyFormat {
+    Ascii,
+    Binary { big_endian: bool },
+}
+
+#[derive(Clone, Copy)]
+enum PlyType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl PlyType {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => Self::I8,
+            "uchar" | "uint8" => Self::U8,
+            "short" | "int16" => Self::I16,
+            "ushort" | "uint16" => Self::U16,
+            "int" | "int32" => Self::I32,
+            "uint" | "uint32" => Self::U32,
+            "float" | "float32" => Self::F32,
+            "double" | "float64" => Self::F64,
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+}
+
+struct PlyProperty {
+    name: String,
+    kind: PlyType,
+    /// Count type of a `property list`.
+    list: Option<PlyType>,
+}
+
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+struct PlyHeader {
+    format: PlyFormat,
+    elements: Vec<PlyElement>,
+}
+
+impl PlyHeader {
+    /// Read up to and including `end_header`, leaving `reader` at the body.
+    fn read(reader: &mut impl BufRead) -> io::Result<Self> {
+        let mut format = None;
+        let mut elements: Vec<PlyElement> = Vec::new();
+        let mut line = String::new();
+        for number in 1.. {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(invalid("PLY header has no `end_header`".into()));
+            }
+            let at = |message: &str| invalid(format!("PLY header line {number}: {message}"));
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words[..] {
+                ["ply"] if number == 1 => {}
+                _ if number == 1 => return Err(at("not a PLY file")),
+                ["end_header"] => break,
+                ["format", kind, _] => {
+                    format = Some(match kind {
+                        "ascii" => PlyFormat::Ascii,
+                        "binary_little_endian" => PlyFormat::Binary { big_endian: false },
+                        "binary_big_endian" => PlyFormat::Binary { big_endian: true },
+                        _ => return Err(at(&format!("unknown format `{kind}`"))),
+                    });
+                }
+                ["comment" | "obj_info", ..] | [] => {}
+                ["element", name, count] => elements.push(PlyElement {
+                    name: name.to_string(),
+                    count: count.parse().map_err(|_| at("bad element count"))?,
+                    properties: Vec::new(),
+                }),
+                ["property", ..] => {
+                    let element = elements
+                        .last_mut()
+                        .ok_or_else(|| at("property before any element"))?;
+                    let kind = |name: &str| {
+                        PlyType::parse(name).ok_or_else(|| at(&format!("unknown type `{name}`")))
+                    };
+                    element.properties.push(match words[1..] {
+                        ["list", count, item, name] => PlyProperty {
+                            name: name.to_string(),
+                            kind: kind(item)?,
+                            list: Some(kind(count)?),
+                        },
+                        [ty, name] => PlyProperty {
+                            name: name.to_string(),
+                            kind: kind(ty)?,
+                            list: None,
+                        },
+                        _ => return Err(at("malformed property")),
+                    });
+                }
+                _ => return Err(at(&format!("unexpected `{}`", line.trim()))),
+            }
+        }
+        Ok(Self {
+            format: format.ok_or_else(|| invalid("PLY header has no `format` line".into()))?,
+            elements,
+        })
+    }
+}
+
+/// A PLY body, read one scalar at a time.
+enum PlySource<'a> {
+    Ascii(std::str::SplitWhitespace<'a>),
+    Binary {
+        data: Vec<u8>,
+        pos: usize,
+        big_endian: bool,
+    },
+}
+
+impl PlySource<'_> {
+    fn scalar(&mut self, kind: PlyType) -> io::Result<f64> {
+        match self {
+            Self::Ascii(words) => {
+                let word = words
+                    .next()
+                    .ok_or_else(|| invalid("PLY body ends early".into()))?;
+                word.parse()
+                    .map_err(|_| invalid(format!("PLY value `{word}` is not a number")))
+            }
+            Self::Binary {
+                data,
+                pos,
+                big_endian,
+            } => {
+                let bytes = data
+                    .get(*pos..*pos + kind.size())
+                    .ok_or_else(|| invalid("PLY body ends early".into()))?;
+                *pos += kind.size();
+                let mut raw = [0u8; 8];
+                raw[..bytes.len()].copy_from_slice(bytes);
+                if *big_endian {
+                    raw[..bytes.len()].reverse();
+                }
+                Ok(match kind {
+                    PlyType::I8 => raw[0] as i8 as f64,
+                    PlyType::U8 => raw[0] as f64,
+                    PlyType::I16 => i16::from_le_bytes([raw[0], raw[1]]) as f64,
+                    PlyType::U16 => u16::from_le_bytes([raw[0], raw[1]]) as f64,
+                    PlyType::I32 => i32::from_le_bytes(raw[..4].try_into().unwrap()) as f64,
+                    PlyType::U32 => u32::from_le_bytes(raw[..4].try_into().unwrap()) as f64,
+                    PlyType::F32 => f32::from_le_bytes(raw[..4].try_into().unwrap()) as f64,
+                    PlyType::F64 => f64::from_le_bytes(raw),
+                })
+            }
+        }
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Prefix an error with the file it came from, keeping its kind.
+fn with_path(path: &Path, err: io::Error) -> io::Error {
+    io::Error::new(err.kind(), format!("{}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_reads_named_columns_and_reports_bad_rows() {
+        let csv = "# exported\nx,y,vx,vy,mass,material,tag,temperature,j\n\
+                   1.5,2,0.5,-1,2,3,9,300,1\n\n4,5,0,0,1,0,9,0,1\n";
+        let set = PointSet::read_csv(csv.as_bytes()).unwrap();
+        assert_eq!(set.len(), 2);
+        let first = set.points[0];
+        assert_eq!(first.position, Vec2::new(1.5, 2.0));
+        assert_eq!(first.velocity, Vec2::new(0.5, -1.0));
+        assert_eq!(first.mass, Some(2.0));
+        assert_eq!(first.material_id, Some(3));
+        assert_eq!(first.temperature, Some(300.0));
+
+        let bare = PointSet::read_csv("y, x\n1, 2\n".as_bytes()).unwrap();
+        assert_eq!(bare.points, vec![ImportedPoint::at(Vec2::new(2.0, 1.0))]);
+        assert_eq!(
+            bare.centered_at(Vec2::new(10.0, 10.0)).bounds(),
+            Some((Vec2::splat(10.0), Vec2::splat(10.0)))
+        );
+
+        let error = |csv: &str| PointSet::read_csv(csv.as_bytes()).unwrap_err().to_string();
+        assert_eq!(
+            error("x,y\n1,2\n1,oops\n"),
+            "line 3: `oops` in column `y` is not a number"
+        );
+        assert_eq!(error("x,y\n1\n"), "line 2: expected 2 values, found 1");
+        assert_eq!(error("x,z\n"), "CSV header: no `y` column");
+        assert_eq!(
+            error("x,y,material\n1,2,0.5\n"),
+            "material `0.5` is not a material ID"
+        );
+        assert_eq!(error("x,y\n1,NaN\n"), "`NaN` is not a finite number");
+        assert_eq!(
+            error("x,y,vx\n1,2,1e300\n"),
+            "`1e300` is not a finite number"
+        );
+    }
+
+    #[test]
+    fn ply_reads_vertices_in_every_encoding() {
+        let ascii = "ply\nformat ascii 1.0\ncomment two points\nelement vertex 2\n\
+                     property float x\nproperty float y\nproperty float z\n\
+                     property uchar material\nelement face 1\n\
+                     property list uchar int vertex_indices\nend_header\n\
+                     1 2 0 4\n3 4 0 5\n3 0 1 0\n";
+        let set = PointSet::read_ply(ascii.as_bytes()).unwrap();
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.points[1].position, Vec2::new(3.0, 4.0));
+        assert_eq!(set.points[1].material_id, Some(5));
+
+        for big_endian in [false, true] {
+            let format = if big_endian { "big" } else { "little" };
+            let mut ply = format!(
+                "ply\nformat binary_{format}_endian 1.0\nelement face 1\n\
+                 property list uchar int vertex_indices\nelement vertex 1\n\
+                 property double x\nproperty float y\nproperty short vx\nend_header\n"
+            )
+            .into_bytes();
+            let order = |mut bytes: Vec<u8>| {
+                if big_endian {
+                    bytes.reverse();
+                }
+                bytes
+            };
+            ply.push(1);
+            ply.extend(order(7i32.to_le_bytes().to_vec()));
+            ply.extend(order(6.5f64.to_le_bytes().to_vec()));
+            ply.extend(order(8.25f32.to_le_bytes().to_vec()));
+            ply.extend(order((-2i16).to_le_bytes().to_vec()));
+            let set = PointSet::read_ply(&ply[..]).unwrap();
+            assert_eq!(set.points[0].position, Vec2::new(6.5, 8.25));
+            assert_eq!(set.points[0].velocity, Vec2::new(-2.0, 0.0));
+        }
+
+        let error = |ply: &str| PointSet::read_ply(ply.as_bytes()).unwrap_err().to_string();
+        assert_eq!(error("obj\n"), "PLY header line 1: not a PLY file");
+        assert_eq!(
+            error(
+                "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\nproperty float y\nend_header\n1 2 3\n"
+            ),
+            "PLY body ends early"
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! PNG decoding for `Bitmap::read`, through the `png` crate (feature
+//! `import`). Every colour type and bit depth decodes to 8-bit RGBA, with
+//! palette and colour-key transparency kept as alpha. The decoder's default
+//! allocation limit (64 MiB) bounds what a small, highly compressed file can
+//! expand to.
+
+use super::invalid;
+use std::io;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+pub(super) fn is_png(data: &[u8]) -> bool {
+    data.starts_with(&SIGNATURE)
+}
+
+/// Decode to `(width, height, RGBA pixels, top row first)`.
+#[cfg(feature = "import")]
+pub(super) fn decode(data: &[u8]) -> io::Result<(usize, usize, Vec<[u8; 4]>)> {
+    use ::png::{ColorType, Decoder, Transformations};
+    use std::io::Cursor;
+
+    let error = |err: ::png::DecodingError| invalid(format!("bad PNG: {err}"));
+    let mut decoder = Decoder::new(Cursor::new(data));
+    // Palette → RGB, sub-byte grey → 8 bits, tRNS → alpha, 16 bits → 8.
+    decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(error)?;
+    let size = reader
+        .output_buffer_size()
+        .ok_or_else(|| invalid("PNG is too large to decode".into()))?;
+    let mut buf = vec![0; size];
+    let info = reader.next_frame(&mut buf).map_err(error)?;
+    let (width, height) = (info.width as usize, info.height as usize);
+    let channels = info.color_type.samples();
+    let pixels = buf[..info.buffer_size()]
+        .chunks_exact(info.line_size)
+        .flat_map(|row| row[..width * channels].chunks_exact(channels))
+        .map(|s| match info.color_type {
+            ColorType::Grayscale => [s[0], s[0], s[0], 255],
+            ColorType::GrayscaleAlpha => [s[0], s[0], s[0], s[1]],
+            ColorType::Rgb => [s[0], s[1], s[2], 255],
+            _ => [s[0], s[1], s[2], s[3]],
+        })
+        .collect();
+    Ok((width, height, pixels))
+}
+
+/// Without the `import` feature PNGs are recognised but not decoded.
+#[cfg(not(feature = "import"))]
+pub(super) fn decode(_data: &[u8]) -> io::Result<(usize, usize, Vec<[u8; 4]>)> {
+    Err(invalid(
+        "PNG decoding needs the `import` feature (PGM works without it)".into(),
+    ))
+}
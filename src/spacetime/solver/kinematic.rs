@@ -0,0 +1,171 @@
+//! Prescribed-motion (kinematic) particles.
+//!
+//! A kinematic particle (`Particle::pinned == PIN_KINEMATIC`) is a moving
+//! Dirichlet anchor: G2P and the force-field pass leave its velocity alone, it
+//! advances with that velocity, and P2G still scatters its mass and momentum,
+//! so the surrounding material is pushed, dragged and supported by it. Use it
+//! for a grabbed handle, a piston, a shaking base plate, or a skeleton driving
+//! flesh. The grid velocity it imposes is the mass-weighted mix of its own and
+//! its neighbours' momentum, so a heavier driver (`SpawnRegion::mass_override`)
+//! grips the material more firmly.
+//!
+//! The prescribed velocity persists until it is set again, so a piston at
+//! constant speed needs one call. A position target is kept until the end of
+//! the next `step()`: every substep re-aims the velocity at it over the time
+//! left in the step, and once the step is complete the particles stop there
+//! (velocity zero, still kinematic) and the target is dropped. A step cut
+//! short by `max_substeps_per_step` keeps the target for the next one.
+//! Setting a velocity or releasing replaces a pending target; restoring a
+//! checkpoint drops them all. Setting motion wakes the particles; kinematic
+//! particles never fall asleep. `release_kinematic` hands the group back to
+//! the solver with its current velocity, so a thrown handle keeps its
+//! momentum.
+
+use glam::Vec2;
+
+use super::Simulation;
+use super::handle::ParticleId;
+use crate::particle::PIN_KINEMATIC;
+
+/// A position target waiting for the end of the step.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum KinematicTarget {
+    Group { tag: u32, centroid: Vec2 },
+    Particle { id: ParticleId, position: Vec2 },
+}
+
+impl Simulation {
+    /// Make every particle with `tag` kinematic, moving at `velocity`.
+    pub fn set_kinematic_velocity(&mut self, tag: u32, velocity: Vec2) {
+        self.set_kinematic_velocity_fn(tag, |_| velocity);
+    }
+
+    /// Make every particle with `tag` kinematic, with the velocity `f` gives
+    /// for its current position (rotation, a skeleton's velocity field).
+    pub fn set_kinematic_velocity_fn(&mut self, tag: u32, f: impl Fn(Vec2) -> Vec2) {
+        self.drop_group_target(tag);
+        self.drive_group(tag, f);
+    }
+
+    fn drive_group(&mut self, tag: u32, f: impl Fn(Vec2) -> Vec2) {
+        self.wake_tag(tag);
+        if let Some(indices) = self.tag_index.get(&tag) {
+            for &i in indices {
+                self.particles.pinned[i] = PIN_KINEMATIC;
+                self.particles.v[i] = f(self.particles.x[i]);
+            }
+        }
+    }
+
+    /// Move the group with `tag` rigidly so that its `group_centroid` reaches
+    /// `centroid` at the end of the next `step()`, and stop it there.
+    pub fn set_kinematic_target(&mut self, tag: u32, centroid: Vec2) {
+        self.drop_group_target(tag);
+        self.kinematic_targets
+            .push(KinematicTarget::Group { tag, centroid });
+        self.steer_kinematic_targets(self.config.dt);
+    }
+
+    /// Make the particle with `id` kinematic, moving at `velocity`. Returns
+    /// `false` when `id` does not resolve (or IDs are disabled).
+    pub fn set_particle_kinematic_velocity(&mut self, id: ParticleId, velocity: Vec2) -> bool {
+        self.drop_particle_target(id);
+        self.drive_particle(id, velocity)
+    }
+
+    fn drive_particle(&mut self, id: ParticleId, velocity: Vec2) -> bool {
+        let Some(i) = self.index_of(id) else {
+            return false;
+        };
+        self.wake_particle(i);
+        // Waking can move the particle; look it up again.
+        let Some(i) = self.index_of(id) else {
+            return false;
+        };
+        self.particles.pinned[i] = PIN_KINEMATIC;
+        self.particles.v[i] = velocity;
+        true
+    }
+
+    /// Make the particle with `id` kinematic, arriving at `position` at the
+    /// end of the next `step()` and stopping there. Returns `false` when `id`
+    /// does not resolve.
+    pub fn set_particle_kinematic_target(&mut self, id: ParticleId, position: Vec2) -> bool {
+        if self.index_of(id).is_none() {
+            return false;
+        }
+        self.drop_particle_target(id);
+        self.kinematic_targets
+            .push(KinematicTarget::Particle { id, position });
+        self.steer_kinematic_targets(self.config.dt);
+        true
+    }
+
+    /// Return the kinematic particles with `tag` to free motion. Anchored
+    /// (`PIN_ANCHOR`) particles stay anchored.
+    pub fn release_kinematic(&mut self, tag: u32) {
+        self.drop_group_target(tag);
+        let particles = &self.particles;
+        let ids = &self.id_index;
+        self.kinematic_targets.retain(|target| match *target {
+            KinematicTarget::Particle { id, .. } => ids
+                .as_ref()
+                .and_then(|ids| ids.get(&id.0))
+                .is_none_or(|&i| particles.user_tag[i] != tag),
+            KinematicTarget::Group { .. } => true,
+        });
+        if let Some(indices) = self.tag_index.get(&tag) {
+            for &i in indices {
+                if self.particles.pinned[i] == PIN_KINEMATIC {
+                    self.particles.pinned[i] = 0;
+                }
+            }
+        }
+    }
+
+    fn drop_group_target(&mut self, tag: u32) {
+        self.kinematic_targets
+            .retain(|target| !matches!(*target, KinematicTarget::Group { tag: t, .. } if t == tag));
+    }
+
+    fn drop_particle_target(&mut self, id: ParticleId) {
+        self.kinematic_targets.retain(
+            |target| !matches!(*target, KinematicTarget::Particle { id: t, .. } if t == id),
+        );
+    }
+
+    /// Aim every pending target's velocity so it arrives in `remaining`, the
+    /// simulated time left in the step. Run before each substep.
+    pub(super) fn steer_kinematic_targets(&mut self, remaining: f32) {
+        let domain = self.grid.domain();
+        for target in self.kinematic_targets.clone() {
+            match target {
+                KinematicTarget::Group { tag, centroid } => {
+                    let shift = domain.nearest_image(centroid - self.group_centroid(tag));
+                    self.drive_group(tag, |_| shift / remaining);
+                }
+                KinematicTarget::Particle { id, position } => {
+                    if let Some(i) = self.index_of(id) {
+                        let shift = domain.nearest_image(position - self.particles.x[i]);
+                        self.drive_particle(id, shift / remaining);
+                    }
+                }
+            }
+        }
+    }
+
+    /// After a complete step the targets are reached: stop there and drop them.
+    pub(super) fn finish_kinematic_targets(&mut self) {
+        if self.last_sim_time_dropped > f32::EPSILON {
+            return;
+        }
+        for target in std::mem::take(&mut self.kinematic_targets) {
+            match target {
+                KinematicTarget::Group { tag, .. } => self.drive_group(tag, |_| Vec2::ZERO),
+                KinematicTarget::Particle { id, .. } => {
+                    self.drive_particle(id, Vec2::ZERO);
+                }
+            }
+        }
+    }
+}
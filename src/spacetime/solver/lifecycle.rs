@@ -10,13 +10,20 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 
 use glam::Vec2;
 
+use super::constraint::ConstraintSet;
+use super::events::ParticleEventKind;
 use super::spatial_hash::SpatialHash;
-use super::{LcgRng, MaterialHandle, SimConfig, Simulation, SpawnRegion, initialize_particles};
+use super::timeline::Timeline;
+use super::{
+    LcgRng, MaterialHandle, SimConfig, Simulation, SpawnRegion, SubstepHook, initialize_particles,
+};
 use crate::boundary::{BoundaryCondition, SlipBoundary};
 use crate::fields::Field;
 use crate::grid::Grid;
 use crate::materials::registry::MaterialRegistry;
 use crate::materials::{FallbackMaterial, MaterialModel};
+use crate::matter::rigid::{RigidBody, RigidBodyHandle};
+use crate::matter::tracer::Tracers;
 use crate::particle::{Particle, Particles};
 use crate::solver::density::estimate_particle_volumes;
 use crate::thermodynamics::{ThermalConfig, ThermalDiffusion};
@@ -28,13 +35,16 @@ impl Simulation {
         let materials = MaterialRegistry::with_default(Box::new(FallbackMaterial));
         let default_boundary: Box<dyn BoundaryCondition> =
             Box::new(SlipBoundary::new(config.boundary_thickness));
+        let grid = Grid::with_domain(config.grid_domain());
         Self {
             config,
             particles: Particles::default(),
             active_count: 0,
             tag_index: HashMap::new(),
             next_tag: 1,
-            grid: Grid::new(config.grid_res),
+            id_index: None,
+            next_particle_id: 1,
+            grid,
             materials,
             boundaries: vec![default_boundary],
             contact_grip: None,
@@ -49,8 +59,24 @@ impl Simulation {
             last_sim_time_dropped: 0.0,
             last_timing: crate::diagnostics::StepTiming::default(),
             phase_rules: Vec::new(),
-            spatial_hash: SpatialHash::new(config.grid_cell_size),
+            spatial_hash: SpatialHash::for_config(&config),
             scratch_indices: Vec::new(),
+            p2g_scratch: crate::transfer::P2GScratch::new(),
+            rigid_bodies: Vec::new(),
+            emitters: Vec::new(),
+            sinks: Vec::new(),
+            last_emitted_count: 0,
+            last_sink_removed_count: 0,
+            last_sink_recycled_count: 0,
+            substep_hooks: Vec::new(),
+            constraints: ConstraintSet::new(),
+            kinematic_targets: Vec::new(),
+            tracers: Tracers::new(),
+            events: None,
+            stresses: None,
+            reactions: None,
+            fracture: None,
+            timeline: Timeline::new(),
         }
     }
 
@@ -59,8 +85,8 @@ impl Simulation {
         spawn.validate_for_sim(&config);
 
         let mut rng = LcgRng::new(spawn.rng_seed);
-        let mut particles = Particles::from(initialize_particles(&config, spawn, &mut rng));
-        let mut grid = Grid::new(config.grid_res);
+        let mut particles = Particles::from(initialize_particles(&config, &spawn, &mut rng));
+        let mut grid = Grid::with_domain(config.grid_domain());
         if spawn.precompute_initial_volumes {
             let n = particles.len();
             estimate_particle_volumes(&mut particles, &mut grid, n, true);
@@ -80,6 +106,8 @@ impl Simulation {
             active_count,
             tag_index,
             next_tag: 1,
+            id_index: None,
+            next_particle_id: 1,
             grid,
             materials,
             boundaries: vec![default_boundary],
@@ -95,8 +123,24 @@ impl Simulation {
             last_sim_time_dropped: 0.0,
             last_timing: crate::diagnostics::StepTiming::default(),
             phase_rules: Vec::new(),
-            spatial_hash: SpatialHash::new(config.grid_cell_size),
+            spatial_hash: SpatialHash::for_config(&config),
             scratch_indices: Vec::new(),
+            p2g_scratch: crate::transfer::P2GScratch::new(),
+            rigid_bodies: Vec::new(),
+            emitters: Vec::new(),
+            sinks: Vec::new(),
+            last_emitted_count: 0,
+            last_sink_removed_count: 0,
+            last_sink_recycled_count: 0,
+            substep_hooks: Vec::new(),
+            constraints: ConstraintSet::new(),
+            kinematic_targets: Vec::new(),
+            tracers: Tracers::new(),
+            events: None,
+            stresses: None,
+            reactions: None,
+            fracture: None,
+            timeline: Timeline::new(),
         };
         solver
             .spatial_hash
@@ -116,6 +160,12 @@ impl Simulation {
         self
     }
 
+    /// Register a substep hook (builder), see `add_substep_hook`.
+    pub fn with_substep_hook(mut self, hook: Box<dyn SubstepHook>) -> Self {
+        self.add_substep_hook(hook);
+        self
+    }
+
     /// Set directional (setae-style) friction for the multi-field contact "grip"
     /// field — see `DirectionalContactGrip`'s doc. Takes an `Arc` so the same
     /// instance can be shared with external code (player/AI input) for live
@@ -237,7 +287,7 @@ impl Simulation {
     /// Remove particles where `pred` returns `false`, keeping `active_count` and
     /// tag index in sync. Use instead of `particles_mut().retain()` directly.
     pub fn retain_particles<F: Fn(&Particle) -> bool>(&mut self, pred: F) {
-        self.particles.retain(pred);
+        self.retain_recording_removals(pred);
         let new_len = self.particles.len();
         self.active_count = new_len;
         // Rebuild tag index from scratch — indices shift after retain.
@@ -248,6 +298,7 @@ impl Simulation {
                 .or_default()
                 .insert(i);
         }
+        self.rebuild_particle_id_index();
         self.spatial_hash
             .rebuild(&self.particles.x, self.active_count);
     }
@@ -260,7 +311,8 @@ impl Simulation {
     /// gradient, material_id, temperature, etc.) is inherited unchanged from the parent;
     /// only mass/volume/position differ, and children always wake up (a freshly-fractured
     /// piece has no reason to start asleep). Sleeping particles are left untouched, never
-    /// split. CPU-only (`Simulation`, not `GpuSimulation`) — splitting requires growing the
+    /// split. With particle IDs enabled the first child keeps the parent's ID and the
+    /// second gets a fresh one. CPU-only (`Simulation`, not `GpuSimulation`) — splitting requires growing the
     /// particle buffer, which the GPU path's fixed-size buffers don't support; not attempted
     /// here, real future work if needed.
     ///
@@ -272,11 +324,14 @@ impl Simulation {
         let mut rng = LcgRng::new(0xC0FF_EE11);
         let n = self.particles.len();
         let mut new_particles = Particles::from(Vec::with_capacity(n));
+        // `Particles::push` starts every ID at 0 — carry them over alongside.
+        let mut ids = Vec::with_capacity(n);
         let mut new_active_count = 0usize;
         for i in 0..self.active_count {
             let p = self.particles.get(i);
             if should_split(&p) {
-                for _ in 0..2 {
+                self.record_event(i, ParticleEventKind::Split);
+                for k in 0..2 {
                     let mut child = p;
                     child.mass *= 0.5;
                     child.initial_volume *= 0.5;
@@ -286,16 +341,24 @@ impl Simulation {
                     child.x += Vec2::new(jx, jy);
                     child.sleeping = 0;
                     new_particles.push(child);
+                    ids.push(if k == 1 && self.id_index.is_some() {
+                        self.next_particle_id()
+                    } else {
+                        self.particles.id[i]
+                    });
                     new_active_count += 1;
                 }
             } else {
                 new_particles.push(p);
+                ids.push(self.particles.id[i]);
                 new_active_count += 1;
             }
         }
         for i in self.active_count..n {
             new_particles.push(self.particles.get(i));
+            ids.push(self.particles.id[i]);
         }
+        new_particles.id = ids;
         self.particles = new_particles;
         self.active_count = new_active_count;
         self.tag_index.clear();
@@ -305,6 +368,7 @@ impl Simulation {
                 .or_default()
                 .insert(i);
         }
+        self.rebuild_particle_id_index();
         self.spatial_hash
             .rebuild(&self.particles.x, self.active_count);
     }
@@ -396,6 +460,36 @@ impl Simulation {
         self.force_fields.iter().map(|(n, _)| n.as_str()).collect()
     }
 
+    /// Register a callback run at fixed stages of every substep (see
+    /// `solver::hook`). Hooks run in registration order.
+    pub fn add_substep_hook(&mut self, hook: Box<dyn SubstepHook>) {
+        self.substep_hooks.push(hook);
+    }
+
+    /// Remove all substep hooks.
+    pub fn clear_substep_hooks(&mut self) {
+        self.substep_hooks.clear();
+    }
+
+    /// Add a two-way coupled rigid body (see `matter::rigid`). Bodies are never
+    /// removed, so the handle stays valid for the simulation's lifetime.
+    pub fn add_rigid_body(&mut self, body: RigidBody) -> RigidBodyHandle {
+        self.rigid_bodies.push(body);
+        RigidBodyHandle(self.rigid_bodies.len() - 1)
+    }
+
+    /// Builder form of `add_rigid_body` (handle is `RigidBodyHandle(n)` for the
+    /// n-th body added).
+    pub fn with_rigid_body(mut self, body: RigidBody) -> Self {
+        self.add_rigid_body(body);
+        self
+    }
+
+    /// Mutable access for game control (teleport, kick, change friction).
+    pub fn rigid_body_mut(&mut self, handle: RigidBodyHandle) -> Option<&mut RigidBody> {
+        self.rigid_bodies.get_mut(handle.0)
+    }
+
     pub fn gravity(&self) -> Vec2 {
         self.config.gravity
     }
@@ -1,19 +1,54 @@
+pub mod checkpoint;
 pub mod config;
+pub mod constraint;
+pub mod contour;
 pub mod cutoff;
 pub mod density;
+pub mod emitter;
+pub mod events;
+pub mod fragments;
 pub mod handle;
+pub mod hook;
+mod ids;
+pub mod import;
+mod kinematic;
 mod lifecycle;
 mod particles;
 mod queries;
 pub mod query;
+pub mod raycast;
+pub mod reaction;
+pub mod sampler;
+pub mod scene;
 pub mod spatial_hash;
 mod step;
+pub mod stress;
+pub mod timeline;
+mod tracers;
 
-pub use config::{SimConfig, SpawnRegion};
+pub use checkpoint::CHECKPOINT_VERSION;
+pub use config::{MAX_GRID_RES, MAX_SHAPE_NODES, MAX_SHAPE_NUMBERS, SimConfig, SpawnRegion};
+pub use constraint::{Constraint, ConstraintHandle, ConstraintSet, Spring};
+pub use contour::{Contour, ContourFilter, ContourOptions, extract_contours, surface_height};
 pub use cutoff::smooth_cutoff;
-pub use density::compute_density_grid;
-pub use handle::{MaterialHandle, ParticleGroup};
+pub use density::{compute_density_grid, compute_density_grid_in};
+pub use emitter::{
+    EmissionRate, Emitter, EmitterHandle, RegionShape, Sink, SinkAction, SinkHandle,
+};
+pub use events::{ParticleEvent, ParticleEventKind};
+pub use fragments::{BodySplit, Fragment, FragmentLabels, FragmentOptions, label_fragments};
+pub use handle::{MaterialHandle, ParticleGroup, ParticleId};
+pub use import::{Bitmap, ImportedPoint, Palette, PointSet};
 pub use query::{BodyState, body_state_of, region_body_state_of};
+pub use raycast::{RayHit, RaySurface, RaycastOptions};
+pub use reaction::{BoundaryReactions, WallReaction};
+pub use sampler::{FieldSample, FieldSampler};
+pub use scene::{
+    PhaseQuantity, ScalarChannel, Scene, SceneBody, SceneBoundary, SceneError, SceneField,
+    SceneMaterial, ScenePhaseRule, SceneScalarField, Threshold,
+};
+pub use stress::{StressState, rasterize_stress};
+pub use timeline::{Interpolation, Timeline, Track};
 // Only consumed by systems::gpu's own CFL scan -- unused (and correctly
 // warned about) in a build without that feature.
 #[cfg(feature = "gpu")]
@@ -21,6 +56,7 @@ pub(crate) use step::{affine_cfl_speed_contribution, cfl_bound};
 
 use std::collections::{HashMap, HashSet};
 
+use hook::SubstepHook;
 use spatial_hash::SpatialHash;
 
 use glam::{Mat2, Vec2};
@@ -45,6 +81,11 @@ pub struct Simulation {
     tag_index: HashMap<u32, HashSet<usize>>,
     /// Monotonically increasing counter — next tag issued by add_body.
     next_tag: u32,
+    /// Maps `Particles::id` → physical index; `None` until
+    /// `enable_particle_ids` (see `solver::ids`).
+    id_index: Option<HashMap<u64, usize>>,
+    /// Next particle ID to issue. IDs start at 1; 0 means "unassigned".
+    next_particle_id: u64,
     grid: Grid,
     materials: MaterialRegistry,
     boundaries: Vec<Box<dyn BoundaryCondition>>,
@@ -72,12 +113,49 @@ pub struct Simulation {
     /// Scratch buffer for wake/sleep candidates — pre-allocated once, cleared per substep.
     /// Pattern from ziran2020 MpmSimulationBase: scratch_xp/scratch_vp member fields.
     scratch_indices: Vec<usize>,
+    /// Per-chunk scratch grids for `SimConfig::parallel_p2g` — same reuse pattern as
+    /// `scratch_indices`; stays empty when the sequential path is used.
+    p2g_scratch: crate::transfer::P2GScratch,
+    /// Two-way coupled rigid bodies (see `matter::rigid`), stepped inside
+    /// `do_substep`. Empty by default: no extra work for scenes without bodies.
+    rigid_bodies: Vec<crate::matter::rigid::RigidBody>,
+    /// Continuous sources/drains (see `solver::emitter`), run once per `step()`.
+    emitters: Vec<emitter::Emitter>,
+    sinks: Vec<emitter::Sink>,
+    last_emitted_count: usize,
+    last_sink_removed_count: usize,
+    last_sink_recycled_count: usize,
+    /// User callbacks at named substep stages (see `solver::hook`).
+    substep_hooks: Vec<Box<dyn SubstepHook>>,
+    /// Soft springs and distance limits (see `solver::constraint`), applied
+    /// with the force fields. Empty by default.
+    constraints: constraint::ConstraintSet,
+    /// Pending `set_kinematic_target` positions (see `solver::kinematic`).
+    kinematic_targets: Vec<kinematic::KinematicTarget>,
+    /// Massless flow markers (see `matter::tracer`), advected after each G2P.
+    /// Empty by default: no extra work for scenes without tracers.
+    tracers: crate::matter::tracer::Tracers,
+    /// Recorded lifecycle events; `None` until `enable_particle_events`
+    /// (see `solver::events`).
+    events: Option<Vec<events::ParticleEvent>>,
+    /// Per-particle Cauchy stress as of the last step; `None` until
+    /// `enable_stress_export` (see `solver::stress`).
+    stresses: Option<Vec<Mat2>>,
+    /// Boundary impulse accounting; `None` until `enable_boundary_reactions`
+    /// (see `solver::reaction`).
+    reactions: Option<Box<reaction::ReactionRecorder>>,
+    /// Per-step body-split detection; `None` until `enable_fracture_detection`
+    /// (see `solver::fragments`).
+    fracture: Option<fragments::FractureDetector>,
+    /// Keyframed parameter animation (see `solver::timeline`), evaluated at
+    /// the start of every substep. Empty by default.
+    timeline: timeline::Timeline,
 }
 
 impl std::fmt::Debug for Simulation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Simulation")
-            .field("grid_res", &self.config.grid_res)
+            .field("grid_dims", &self.config.grid_dims())
             .field("particles_total", &self.particles.len())
             .field("active", &self.active_count)
             .field("frame", &self.frame_index)
@@ -87,60 +165,45 @@ impl std::fmt::Debug for Simulation {
 
 pub(crate) fn initialize_particles(
     config: &SimConfig,
-    spawn: SpawnRegion,
+    spawn: &SpawnRegion,
     rng: &mut LcgRng,
 ) -> Vec<Particle> {
-    use crate::solver::config::SpawnShape;
     let mass = spawn.mass_override.unwrap_or(config.particle_mass);
     let mut particles = Vec::new();
     let half = spawn.box_size.as_vec2() * 0.5;
     let min = spawn.box_center - half;
     let max = spawn.box_center + half;
+    let turn = Vec2::from_angle(spawn.rotation);
+    // Rigid spin: v = ω × r, whose gradient is the skew matrix [[0, −ω], [ω, 0]].
+    let omega = spawn.angular_velocity;
+    let spin_gradient = Mat2::from_cols(Vec2::new(0.0, omega), Vec2::new(-omega, 0.0));
 
     let mut i = min.x;
     while i < max.x {
         let mut j = min.y;
         while j < max.y {
-            let pos = Vec2::new(i, j);
+            let lattice = Vec2::new(i, j);
+            let offset = lattice - spawn.box_center;
 
-            // Apply shape mask — skip particles outside the disk if disk shape is active.
-            let inside = match spawn.shape {
-                SpawnShape::Box => true,
-                SpawnShape::Disk { radius } => (pos - spawn.box_center).length() <= radius,
-            };
-
-            if inside {
+            // Apply shape mask in the region's own frame, then turn into place.
+            if spawn.shape.contains(offset) {
+                // Unrotated regions keep their exact lattice positions.
+                let pos = if spawn.rotation == 0.0 {
+                    lattice
+                } else {
+                    spawn.box_center + turn.rotate(offset)
+                };
                 let jitter_mag = spawn.position_jitter * spawn.spacing;
                 let jx = (rng.next_f32() - 0.5) * 2.0 * jitter_mag;
                 let jy = (rng.next_f32() - 0.5) * 2.0 * jitter_mag;
                 let jittered_pos = pos + Vec2::new(jx, jy);
                 let random = Vec2::new(rng.next_f32(), rng.next_f32());
-                let velocity = (random - Vec2::splat(0.5)) * spawn.initial_velocity_scale;
-                particles.push(Particle {
-                    x: jittered_pos,
-                    v: velocity,
-                    velocity_gradient: Mat2::ZERO,
-                    deformation_gradient: spawn.initial_deformation_gradient,
-                    mass,
-                    initial_volume: config.default_initial_volume,
-                    volume: config.default_initial_volume,
-                    density: mass / config.default_initial_volume,
-                    material_id: spawn.material_id,
-                    plastic_volume_ratio: 1.0,
-                    hardening_scale: 1.0,
-                    friction_hardening: 0.0,
-                    log_volume_strain: 0.0,
-                    temperature: 0.0,
-                    user_tag: 0,
-                    activation: 0.0,
-                    activation_dir: Vec2::ZERO,
-                    muscle_group_id: 0,
-                    contact_group: 0,
-                    sleeping: 0,
-                    pinned: 0,
-                    scalar_field: 0.0,
-                    _pad: 0,
-                });
+                let velocity = (random - Vec2::splat(0.5)) * spawn.initial_velocity_scale
+                    + omega * (jittered_pos - spawn.box_center).perp();
+                let mut p = fresh_particle(config, jittered_pos, velocity, mass, spawn.material_id);
+                p.deformation_gradient = spawn.initial_deformation_gradient;
+                p.velocity_gradient = spin_gradient;
+                particles.push(p);
             }
 
             j += spawn.spacing;
@@ -151,6 +214,42 @@ pub(crate) fn initialize_particles(
     particles
 }
 
+/// A particle at rest in its reference state: identity F, zero C, default volume.
+/// Shared by region spawning and emitters so both start particles identically.
+pub(crate) fn fresh_particle(
+    config: &SimConfig,
+    x: Vec2,
+    v: Vec2,
+    mass: f32,
+    material_id: u32,
+) -> Particle {
+    Particle {
+        x,
+        v,
+        velocity_gradient: Mat2::ZERO,
+        deformation_gradient: Mat2::IDENTITY,
+        mass,
+        initial_volume: config.default_initial_volume,
+        volume: config.default_initial_volume,
+        density: mass / config.default_initial_volume,
+        material_id,
+        plastic_volume_ratio: 1.0,
+        hardening_scale: 1.0,
+        friction_hardening: 0.0,
+        log_volume_strain: 0.0,
+        temperature: 0.0,
+        user_tag: 0,
+        activation: 0.0,
+        activation_dir: Vec2::ZERO,
+        muscle_group_id: 0,
+        contact_group: 0,
+        sleeping: 0,
+        pinned: 0,
+        scalar_field: 0.0,
+        _pad: 0,
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct LcgRng {
     state: u32,
@@ -10,6 +10,7 @@ use std::collections::{HashMap, HashSet};
 
 use glam::Vec2;
 
+use super::events::ParticleEventKind;
 use super::{LcgRng, Simulation, SpawnRegion, density, initialize_particles};
 use crate::particle::Particle;
 use crate::solver::density::estimate_particle_volumes;
@@ -49,6 +50,13 @@ impl Simulation {
             let p = self.particles.get(i);
             if predicate(&p) {
                 self.particles.material_id[i] = new_material_id;
+                self.record_event(
+                    i,
+                    ParticleEventKind::PhaseChanged {
+                        from: p.material_id,
+                        to: new_material_id,
+                    },
+                );
                 if let (true, Some(cp)) = (latent_heat != 0.0, heat_capacity) {
                     self.particles.temperature[i] -= latent_heat / cp;
                 }
@@ -173,11 +181,10 @@ impl Simulation {
     ///
     /// LP pattern: tag particles with a sentinel before the step, then call
     /// `solver.remove_particles(|p| p.user_tag == DEAD)`. The tag-based group API
-    /// remains valid after removal — tag_index is rebuilt internally.
+    /// remains valid after removal — tag_index is rebuilt internally, and so is
+    /// the particle ID index (removed IDs stop resolving).
     pub fn remove_particles<F: Fn(&Particle) -> bool>(&mut self, predicate: F) -> usize {
-        let before = self.particles.len();
-        self.particles.retain(|p| !predicate(p));
-        let removed = before - self.particles.len();
+        let removed = self.retain_recording_removals(|p| !predicate(p));
         if removed > 0 {
             // retain() compacted the array — all physical indices in tag_index are stale.
             // Rebuild from scratch and re-establish the sleep partition.
@@ -201,10 +208,30 @@ impl Simulation {
                     .or_default()
                     .insert(i);
             }
+            self.rebuild_particle_id_index();
         }
         removed
     }
 
+    /// `Particles::retain`, recording a `Removed` event for every dropped particle
+    /// first (while its index is still valid). Returns the number removed.
+    pub(super) fn retain_recording_removals(&mut self, keep: impl Fn(&Particle) -> bool) -> usize {
+        if self.events.is_none() {
+            let before = self.particles.len();
+            self.particles.retain(keep);
+            return before - self.particles.len();
+        }
+        let kept: Vec<bool> = (0..self.particles.len())
+            .map(|i| keep(&self.particles.get(i)))
+            .collect();
+        for i in (0..kept.len()).filter(|&i| !kept[i]) {
+            self.record_event(i, ParticleEventKind::Removed);
+        }
+        let mut read = kept.iter();
+        self.particles.retain(|_| *read.next().unwrap());
+        kept.len() - self.particles.len()
+    }
+
     /// Iterate physical indices of all particles with `tag`. O(group_size) via tag_index.
     ///
     /// Returns indices only — read particle data via `solver.particles().x[i]` etc.
@@ -222,13 +249,14 @@ impl Simulation {
     /// Put particle at physical index `i` to sleep.
     ///
     /// Swaps it with the last active particle, decrementing `active_count`.
-    /// Updates `tag_index` for both affected particles.
+    /// Updates `tag_index` (and the particle ID index) for both affected particles.
     /// No-op if already sleeping.
     pub(super) fn sleep_particle(&mut self, i: usize) {
         if self.particles.sleeping[i] || self.active_count == 0 {
             return;
         }
         self.particles.sleeping[i] = true;
+        self.record_event(i, ParticleEventKind::Slept);
         let last_active = self.active_count - 1;
         if i != last_active {
             let tag_i = self.particles.user_tag[i];
@@ -240,6 +268,7 @@ impl Simulation {
                 Self::tag_index_replace(&mut self.tag_index, tag_j, last_active, i);
             }
             self.particles.swap(i, last_active);
+            self.reindex_particle_ids([i, last_active]);
         }
         self.active_count -= 1;
     }
@@ -247,13 +276,14 @@ impl Simulation {
     /// Wake particle at physical index `i`.
     ///
     /// Swaps it with the first sleeping particle, incrementing `active_count`.
-    /// Updates `tag_index` for both affected particles.
+    /// Updates `tag_index` (and the particle ID index) for both affected particles.
     /// No-op if already awake.
     pub(super) fn wake_particle(&mut self, i: usize) {
         if !self.particles.sleeping[i] {
             return;
         }
         self.particles.sleeping[i] = false;
+        self.record_event(i, ParticleEventKind::Woke);
         let first_sleeping = self.active_count;
         if i != first_sleeping {
             let tag_i = self.particles.user_tag[i];
@@ -263,6 +293,7 @@ impl Simulation {
                 Self::tag_index_replace(&mut self.tag_index, tag_j, first_sleeping, i);
             }
             self.particles.swap(i, first_sleeping);
+            self.reindex_particle_ids([i, first_sleeping]);
         }
         self.active_count += 1;
     }
@@ -353,13 +384,6 @@ impl Simulation {
     /// ```
     #[must_use = "store the tag — it is the only stable identity for this group"]
     pub fn add_body(&mut self, spawn: SpawnRegion) -> u32 {
-        let tag = self.next_tag;
-        self.next_tag += 1;
-
-        let old_active = self.active_count;
-        let old_len = self.particles.len();
-        // sleeping zone is [old_active..old_len] — new particles must land before it.
-
         spawn.validate_for_sim(&self.config);
         debug_assert!(
             self.materials.is_registered(spawn.material_id),
@@ -367,24 +391,109 @@ impl Simulation {
             spawn.material_id,
         );
         let mut rng = LcgRng::new(spawn.rng_seed);
-        let new_particles = initialize_particles(&self.config, spawn, &mut rng);
+        let new_particles = initialize_particles(&self.config, &spawn, &mut rng);
+        // Not `add_particles`: the region was validated above, and its jitter may
+        // legitimately put particles inside the wall band, where the boundary
+        // clamp pulls them out on the first step.
+        self.add_group(new_particles)
+    }
+
+    /// Non-panicking `add_particles` check, for particles read from files:
+    /// every position is finite and inside the domain walls, and every
+    /// `material_id` is registered.
+    pub fn check_particles(&self, particles: &[Particle]) -> Result<(), String> {
+        let domain_min = Vec2::splat(self.config.boundary_thickness as f32);
+        let domain_max = self.config.grid_dims().as_vec2() - domain_min;
+        for p in particles {
+            // Written so a NaN coordinate fails too.
+            if !(p.x.cmpge(domain_min).all() && p.x.cmple(domain_max).all()) {
+                return Err(format!(
+                    "particles must stay inside the simulation domain \
+                     (boundary_thickness={}, grid dims={}): particle at [{:.1},{:.1}]",
+                    self.config.boundary_thickness,
+                    self.config.grid_dims(),
+                    p.x.x,
+                    p.x.y
+                ));
+            }
+            if !self.materials.is_registered(p.material_id) {
+                return Err(format!(
+                    "particle at [{:.1},{:.1}] uses material {}, only {} are registered",
+                    p.x.x,
+                    p.x.y,
+                    p.material_id,
+                    self.materials.len()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Add prebuilt particles as one tagged group and return its tag — the
+    /// `add_body` path for particles from `build_particles_from_points` (or
+    /// anywhere else). Each particle keeps its own `material_id`, gets its
+    /// material's initial plastic state, and has its volume re-estimated from
+    /// the local density exactly as `add_body` does.
+    ///
+    /// Panics where `check_particles` fails: a particle outside the domain
+    /// minus `boundary_thickness` (the margin `SpawnRegion::validate_for_sim`
+    /// enforces) or made of an unregistered material.
+    #[must_use = "store the tag — it is the only stable identity for this group"]
+    pub fn add_particles(&mut self, new_particles: Vec<Particle>) -> u32 {
+        if let Err(message) = self.check_particles(&new_particles) {
+            panic!("{message}");
+        }
+        self.add_group(new_particles)
+    }
+
+    /// Shared tail of `add_body` and `add_particles`, after their own checks.
+    fn add_group(&mut self, mut new_particles: Vec<Particle>) -> u32 {
+        let tag = self.next_tag;
+        self.next_tag += 1;
+
+        // Stamp tag and init material plastic state before insertion.
+        for p in &mut new_particles {
+            p.user_tag = tag;
+            if self.materials.is_registered(p.material_id) {
+                self.materials.get(p.material_id).init_particle(p);
+            }
+        }
+        let group_start = self.insert_active_particles(new_particles).start;
+        // An empty region still registers its (empty) group, like before.
+        self.tag_index.entry(tag).or_default();
+
+        // Scatter only particles in the spawn region + 3-cell margin.
+        // O(active_count) scan but O(local × stencil) grid work — fast for sparse spawns.
+        density::estimate_particle_volumes_local(
+            &mut self.particles,
+            &mut self.grid,
+            self.active_count,
+            group_start,
+            true,
+        );
+        self.spatial_hash
+            .rebuild(&self.particles.x, self.active_count);
+        tag
+    }
+
+    /// Append `new_particles` to the END of the active zone (ahead of every
+    /// sleeping particle) and register each under its own `user_tag` (and a
+    /// fresh particle ID, when enabled). Returns
+    /// their physical index range. Shared by `add_body` and emitters.
+    pub(super) fn insert_active_particles(
+        &mut self,
+        new_particles: Vec<Particle>,
+    ) -> std::ops::Range<usize> {
+        let old_active = self.active_count;
+        let old_len = self.particles.len();
+        // sleeping zone is [old_active..old_len] — new particles must land before it.
         for p in new_particles {
             self.particles.push(p);
         }
         let new_len = self.particles.len();
         let new_count = new_len - old_len;
         let sleeping_count = old_len - old_active;
-
-        // Stamp tag and init material plastic state (new particles still at [old_len..new_len]).
-        let mat_id = spawn.material_id;
-        for i in old_len..new_len {
-            self.particles.user_tag[i] = tag;
-            if self.materials.is_registered(mat_id) {
-                let mut p = self.particles.get(i);
-                self.materials.get(mat_id).init_particle(&mut p);
-                self.particles.set(i, p);
-            }
-        }
+        self.assign_particle_ids(old_len..new_len);
 
         // If sleeping particles sit between the active zone and the new particles, rotate new
         // particles before the sleeping zone so the partition invariant is maintained:
@@ -393,34 +502,51 @@ impl Simulation {
         if sleeping_count > 0 {
             self.particles.rotate_range(old_active, old_len, new_len);
             // Sleeping particles moved from [old_active+k] → [old_active+new_count+k].
-            // Update tag_index for each displaced sleeping particle.
+            // Update tag_index for each displaced sleeping particle: all removals
+            // before any insert -- the old and new ranges overlap whenever
+            // sleeping_count > new_count, and a per-particle replace would let a
+            // later removal delete an index an earlier same-tag insert just added.
+            for k in 0..sleeping_count {
+                let t = self.particles.user_tag[old_active + new_count + k];
+                if let Some(set) = self.tag_index.get_mut(&t) {
+                    set.remove(&(old_active + k));
+                }
+            }
             for k in 0..sleeping_count {
-                let old_pos = old_active + k;
                 let new_pos = old_active + new_count + k;
                 let t = self.particles.user_tag[new_pos];
-                Self::tag_index_replace(&mut self.tag_index, t, old_pos, new_pos);
+                self.tag_index.entry(t).or_default().insert(new_pos);
             }
         }
 
         // New particles are at [old_active..old_active+new_count].
-        let group_start = old_active;
-        let group_end = old_active + new_count;
-        self.tag_index
-            .insert(tag, (group_start..group_end).collect::<HashSet<usize>>());
-        self.active_count = group_end;
+        let group = old_active..old_active + new_count;
+        for i in group.clone() {
+            self.tag_index
+                .entry(self.particles.user_tag[i])
+                .or_default()
+                .insert(i);
+        }
+        self.active_count = group.end;
+        // Both the new particles and every displaced sleeper moved.
+        self.reindex_particle_ids(old_active..new_len);
+        for i in group.clone() {
+            self.record_event(i, ParticleEventKind::Added);
+        }
+        group
+    }
 
-        // Scatter only particles in the spawn region + 3-cell margin.
-        // O(active_count) scan but O(local × stencil) grid work — fast for sparse spawns.
-        density::estimate_particle_volumes_local(
-            &mut self.particles,
-            &mut self.grid,
-            self.active_count,
-            group_start,
-            true,
-        );
-        self.spatial_hash
-            .rebuild(&self.particles.x, self.active_count);
-        tag
+    /// Move particle `i` from its current tag group to `tag`.
+    pub(super) fn retag_particle(&mut self, i: usize, tag: u32) {
+        let old = self.particles.user_tag[i];
+        if old == tag {
+            return;
+        }
+        if let Some(s) = self.tag_index.get_mut(&old) {
+            s.remove(&i);
+        }
+        self.tag_index.entry(tag).or_default().insert(i);
+        self.particles.user_tag[i] = tag;
     }
 
     /// Attach a scalar diffusion field (pheromone, nutrients, morphogen).
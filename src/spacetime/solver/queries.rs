@@ -8,8 +8,11 @@
 use glam::Vec2;
 
 use super::Simulation;
+use super::contour::{Contour, ContourOptions, extract_contours};
 use super::query::{self, BodyState, body_state_of};
+use super::sampler::FieldSampler;
 use crate::diagnostics::{SimSnapshot, collect_snapshot};
+use crate::matter::rigid::{RigidBody, RigidBodyHandle};
 
 impl Simulation {
     pub fn diagnostics_snapshot(&self) -> SimSnapshot {
@@ -27,9 +30,37 @@ impl Simulation {
         snap.active_count = self.active_count;
         snap.sleeping_count = self.particles.len().saturating_sub(self.active_count);
         snap.timing = self.last_timing;
+        snap.emitted_count = self.last_emitted_count;
+        snap.sink_removed_count = self.last_sink_removed_count;
+        snap.sink_recycled_count = self.last_sink_recycled_count;
         snap
     }
 
+    /// Snapshot of the continuum fields for point queries — see
+    /// `solver::sampler`. Scalar field `k` is the `k`-th `attach_scalar_field`.
+    /// O(N · (1 + scalar fields)) to build; build once per frame, not per query.
+    pub fn field_sampler(&self) -> FieldSampler {
+        let getters: Vec<_> = self.scalar_fields.iter().map(|f| f.get).collect();
+        FieldSampler::new(&self.particles, self.grid.domain(), &getters)
+    }
+
+    /// Marching-squares outlines of the current density field — see
+    /// `solver::contour`. Includes sleeping particles. O(N + grid cells).
+    pub fn extract_contours(&self, options: &ContourOptions) -> Vec<Contour> {
+        extract_contours(&self.particles, self.grid.domain(), options)
+    }
+
+    // ── Rigid bodies ──────────────────────────────────────────────────────────
+
+    pub fn rigid_body(&self, handle: RigidBodyHandle) -> Option<&RigidBody> {
+        self.rigid_bodies.get(handle.0)
+    }
+
+    /// All rigid bodies, in `add_rigid_body` order (index = handle).
+    pub fn rigid_bodies(&self) -> &[RigidBody] {
+        &self.rigid_bodies
+    }
+
     // ── Tag-based group API ───────────────────────────────────────────────────
 
     /// Aggregate physics state for all particles with `tag`. O(group_size).
@@ -126,7 +157,12 @@ impl Simulation {
         let r2 = radius * radius;
         let mut s = query::BodyState::default();
         for i in self.spatial_hash.query(center, radius) {
-            if (self.particles.x[i] - center).length_squared() <= r2 {
+            if self
+                .spatial_hash
+                .offset(center, self.particles.x[i])
+                .length_squared()
+                <= r2
+            {
                 s.accumulate(
                     self.particles.x[i],
                     self.particles.v[i].length(),
@@ -143,12 +179,16 @@ impl Simulation {
     /// Iterate indices of active particles within `radius` grid-cells of `center`.
     ///
     /// Returns indices only — read particle data via `solver.particles().x[i]` etc.
-    /// O(candidates) via spatial hash, not O(N).
+    /// O(candidates) via spatial hash, not O(N). On periodic axes the radius
+    /// reaches across the seam (minimum-image distance), as in every query here.
     pub fn particles_near(&self, center: Vec2, radius: f32) -> impl Iterator<Item = usize> + '_ {
         let r2 = radius * radius;
-        self.spatial_hash
-            .query(center, radius)
-            .filter(move |&i| (self.particles.x[i] - center).length_squared() <= r2)
+        self.spatial_hash.query(center, radius).filter(move |&i| {
+            self.spatial_hash
+                .offset(center, self.particles.x[i])
+                .length_squared()
+                <= r2
+        })
     }
 
     /// Count active particles of a given material within `radius` of `center`.
@@ -159,7 +199,11 @@ impl Simulation {
             .query(center, radius)
             .filter(|&i| {
                 self.particles.material_id[i] == material_id
-                    && (self.particles.x[i] - center).length_squared() <= r2
+                    && self
+                        .spatial_hash
+                        .offset(center, self.particles.x[i])
+                        .length_squared()
+                        <= r2
             })
             .count()
     }
@@ -187,8 +231,7 @@ impl Simulation {
         if k == 0 || self.active_count == 0 {
             return Vec::new();
         }
-        let domain_diag =
-            self.config.grid_res as f32 * self.config.grid_cell_size * std::f32::consts::SQRT_2;
+        let domain_diag = self.config.grid_dims().as_vec2().length() * self.config.grid_cell_size;
         let mut radius = self.config.grid_cell_size * (k as f32).sqrt().max(1.0);
         let mut candidates: Vec<(usize, f32)>;
         loop {
@@ -196,7 +239,14 @@ impl Simulation {
             candidates = self
                 .spatial_hash
                 .query(center, radius)
-                .map(|i| (i, (self.particles.x[i] - center).length_squared()))
+                .map(|i| {
+                    (
+                        i,
+                        self.spatial_hash
+                            .offset(center, self.particles.x[i])
+                            .length_squared(),
+                    )
+                })
                 .filter(|&(_, d2)| d2 <= r2)
                 .collect();
             if candidates.len() >= k || radius >= domain_diag {
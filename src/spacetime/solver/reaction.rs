@@ -0,0 +1,302 @@
+//! Boundary reaction forces: how hard the material pushes on the walls and
+//! terrain.
+//!
+//! Boundaries act by overwriting grid node velocities in
+//! `apply_to_grid_velocity_in`, so the momentum a node loses or gains there,
+//! `m·(v_after − v_before)`, is the impulse the boundary applied to the
+//! material — and its negative is what the material applied to the boundary.
+//! With `Simulation::enable_boundary_reactions` the solver sums those impulses
+//! over each `step()` (all substeps, all boundaries together) and reports the
+//! mean force per wall, a normal-load profile along each wall, and the kinetic
+//! energy the boundaries removed.
+//!
+//! Impulses are attributed by node position: nodes in the outer
+//! `SimConfig::boundary_thickness` bands go to that box wall (a corner node
+//! gives its x impulse to the side wall and its y impulse to the floor or
+//! ceiling); every other node goes to `terrain` — `HeightmapBoundary` ground,
+//! `SdfColliderBoundary` shapes. Terrain loads and friction are measured
+//! against an upward normal, which is exact for a heightmap. Like events, the
+//! measurement is output, not state, and is not checkpointed.
+
+use glam::Vec2;
+
+use super::Simulation;
+use crate::boundary::wall_bands;
+use crate::grid::GridDomain;
+
+/// What the material did to one wall over the last step.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WallReaction {
+    /// Mean force the material exerted on the wall (mass · cells / s²).
+    pub force: Vec2,
+    /// Mean normal load pressing into the wall, per grid row (`left`, `right`)
+    /// or column (`bottom`, `top`, `terrain`): a force per cell of wall
+    /// length, i.e. the wall pressure profile. Negative where the wall pulls.
+    pub load_profile: Vec<f32>,
+    /// Kinetic energy friction removed from the tangential motion along the
+    /// wall during the step.
+    pub friction_dissipation: f32,
+}
+
+impl WallReaction {
+    fn new(len: usize) -> Self {
+        Self {
+            load_profile: vec![0.0; len],
+            ..Self::default()
+        }
+    }
+
+    /// Total normal load: the sum of `load_profile`.
+    pub fn normal_load(&self) -> f32 {
+        self.load_profile.iter().sum()
+    }
+}
+
+/// Per-wall reactions over the last `step()`. See the module doc.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BoundaryReactions {
+    pub left: WallReaction,
+    pub right: WallReaction,
+    pub bottom: WallReaction,
+    pub top: WallReaction,
+    /// Boundary geometry away from the box walls.
+    pub terrain: WallReaction,
+    /// Kinetic energy every boundary removed during the step, impact and
+    /// friction together.
+    pub dissipation: f32,
+}
+
+impl BoundaryReactions {
+    fn new(domain: GridDomain) -> Self {
+        let (width, height) = (domain.width(), domain.height());
+        Self {
+            left: WallReaction::new(height),
+            right: WallReaction::new(height),
+            bottom: WallReaction::new(width),
+            top: WallReaction::new(width),
+            terrain: WallReaction::new(width),
+            dissipation: 0.0,
+        }
+    }
+
+    /// Whether the load profiles are sized for `domain`.
+    fn fits(&self, domain: GridDomain) -> bool {
+        self.left.load_profile.len() == domain.height()
+            && self.bottom.load_profile.len() == domain.width()
+    }
+
+    /// Net force on every boundary together.
+    pub fn total_force(&self) -> Vec2 {
+        self.walls().iter().map(|w| w.force).sum()
+    }
+
+    /// Friction dissipation summed over every wall.
+    pub fn friction_dissipation(&self) -> f32 {
+        self.walls().iter().map(|w| w.friction_dissipation).sum()
+    }
+
+    fn walls(&self) -> [&WallReaction; 5] {
+        [
+            &self.left,
+            &self.right,
+            &self.bottom,
+            &self.top,
+            &self.terrain,
+        ]
+    }
+}
+
+/// Step-long accumulator behind `Simulation::boundary_reactions`. Holds no
+/// geometry of its own: the domain and wall thickness come from the
+/// simulation on every call, so a replaced grid cannot be indexed with stale
+/// extents.
+#[derive(Debug)]
+pub(super) struct ReactionRecorder {
+    /// Impulses and energies summed over the running step.
+    current: BoundaryReactions,
+    /// The last completed step, impulses turned into mean forces.
+    last: BoundaryReactions,
+}
+
+impl ReactionRecorder {
+    fn new(domain: GridDomain) -> Self {
+        Self {
+            current: BoundaryReactions::new(domain),
+            last: BoundaryReactions::new(domain),
+        }
+    }
+
+    /// Attribute node `cell_index`'s velocity change by the boundaries, with
+    /// walls `thickness` cells deep around `domain`.
+    pub(super) fn record(
+        &mut self,
+        domain: GridDomain,
+        thickness: usize,
+        cell_index: usize,
+        mass: f32,
+        before: Vec2,
+        after: Vec2,
+    ) {
+        let impulse = mass * (after - before);
+        if impulse == Vec2::ZERO {
+            return;
+        }
+        if !self.current.fits(domain) {
+            // The grid changed mid-step: what was summed so far belongs to the old one.
+            self.current = BoundaryReactions::new(domain);
+        }
+        let (x, y) = domain.cell_of(cell_index);
+        let bands = wall_bands(thickness, cell_index, domain);
+        let r = &mut self.current;
+        r.dissipation += 0.5 * mass * (before.length_squared() - after.length_squared());
+        // (wall, into-domain normal, index along the wall)
+        let side = if bands.left {
+            Some((&mut r.left, Vec2::X, y))
+        } else if bands.right {
+            Some((&mut r.right, -Vec2::X, y))
+        } else {
+            None
+        };
+        let cap = if bands.bottom {
+            Some((&mut r.bottom, Vec2::Y, x))
+        } else if bands.top {
+            Some((&mut r.top, -Vec2::Y, x))
+        } else {
+            None
+        };
+        match (side, cap) {
+            (Some((side, n_side, i_side)), Some((cap, n_cap, i_cap))) => {
+                // Corner: each wall takes its own axis; both are normal, so no friction.
+                add_load(side, n_side * impulse.dot(n_side), n_side, i_side);
+                add_load(cap, n_cap * impulse.dot(n_cap), n_cap, i_cap);
+            }
+            (Some((wall, n, i)), None) | (None, Some((wall, n, i))) => {
+                add_load(wall, impulse, n, i);
+                wall.friction_dissipation += tangential_loss(mass, before, after, n);
+            }
+            (None, None) => {
+                add_load(&mut r.terrain, impulse, Vec2::Y, x);
+                r.terrain.friction_dissipation += tangential_loss(mass, before, after, Vec2::Y);
+            }
+        }
+    }
+
+    /// Publish the running step's sums as mean forces over `elapsed` seconds
+    /// of simulated time and start the next step from zero on `domain`.
+    fn finish_step(&mut self, elapsed: f32, domain: GridDomain) {
+        let fresh = BoundaryReactions::new(domain);
+        let mut done = std::mem::replace(&mut self.current, fresh);
+        let rate = if elapsed > 0.0 { 1.0 / elapsed } else { 0.0 };
+        for wall in [
+            &mut done.left,
+            &mut done.right,
+            &mut done.bottom,
+            &mut done.top,
+            &mut done.terrain,
+        ] {
+            wall.force *= rate;
+            for load in &mut wall.load_profile {
+                *load *= rate;
+            }
+        }
+        self.last = done;
+    }
+}
+
+fn add_load(wall: &mut WallReaction, impulse: Vec2, normal: Vec2, along: usize) {
+    wall.force -= impulse;
+    wall.load_profile[along] += impulse.dot(normal);
+}
+
+/// Kinetic energy lost from the velocity component tangent to `normal`.
+fn tangential_loss(mass: f32, before: Vec2, after: Vec2, normal: Vec2) -> f32 {
+    let t = normal.perp();
+    0.5 * mass * (before.dot(t).powi(2) - after.dot(t).powi(2))
+}
+
+impl Simulation {
+    /// Start measuring boundary reactions; results appear after the next
+    /// `step()`. No-op when already enabled.
+    pub fn enable_boundary_reactions(&mut self) {
+        if self.reactions.is_none() {
+            self.reactions = Some(Box::new(ReactionRecorder::new(self.grid.domain())));
+        }
+    }
+
+    /// Builder-style [`Self::enable_boundary_reactions`].
+    pub fn with_boundary_reactions(mut self) -> Self {
+        self.enable_boundary_reactions();
+        self
+    }
+
+    pub fn disable_boundary_reactions(&mut self) {
+        self.reactions = None;
+    }
+
+    /// Start the measurement over (no-op while disabled), e.g. after a
+    /// checkpoint restore replaced the state the last step measured.
+    pub(super) fn reset_boundary_reactions(&mut self) {
+        if let Some(recorder) = &mut self.reactions {
+            **recorder = ReactionRecorder::new(self.grid.domain());
+        }
+    }
+
+    /// Reactions over the last completed step (all zero before the first
+    /// one), or `None` while disabled.
+    pub fn boundary_reactions(&self) -> Option<&BoundaryReactions> {
+        self.reactions.as_ref().map(|r| &r.last)
+    }
+
+    /// Close the running step (see `ReactionRecorder::finish_step`).
+    pub(super) fn finish_boundary_reactions(&mut self, elapsed: f32) {
+        if let Some(recorder) = &mut self.reactions {
+            recorder.finish_step(elapsed, self.grid.domain());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impulses_go_to_the_wall_whose_band_the_node_is_in() {
+        let domain = GridDomain::square(16);
+        let mut recorder = ReactionRecorder::new(domain);
+        let node = |x: usize, y: usize| x * 16 + y;
+        // Floor stops a falling node and friction halves its sliding speed.
+        recorder.record(
+            domain,
+            2,
+            node(8, 1),
+            2.0,
+            Vec2::new(4.0, -3.0),
+            Vec2::new(2.0, 0.0),
+        );
+        // Corner node: x impulse to the left wall, y impulse to the floor.
+        recorder.record(
+            domain,
+            2,
+            node(0, 0),
+            1.0,
+            Vec2::new(-1.0, -1.0),
+            Vec2::ZERO,
+        );
+        // Interior node: terrain.
+        recorder.record(domain, 2, node(8, 8), 1.0, Vec2::new(0.0, -2.0), Vec2::ZERO);
+        recorder.finish_step(0.5, domain);
+
+        let r = &recorder.last;
+        assert_eq!(r.bottom.force, Vec2::new(8.0, -14.0));
+        assert_eq!(r.bottom.load_profile[8], 12.0);
+        assert_eq!(r.bottom.load_profile[0], 2.0);
+        assert_eq!(r.bottom.friction_dissipation, 0.5 * 2.0 * (16.0 - 4.0));
+        assert_eq!(r.left.force, Vec2::new(-2.0, 0.0));
+        assert_eq!(r.left.load_profile[0], 2.0);
+        assert_eq!(r.terrain.force, Vec2::new(0.0, -4.0));
+        assert_eq!(r.terrain.normal_load(), 4.0);
+        assert_eq!(r.total_force(), Vec2::new(6.0, -18.0));
+        assert_eq!(r.dissipation, 0.5 * 2.0 * (25.0 - 4.0) + 1.0 + 2.0);
+        assert_eq!(recorder.current.total_force(), Vec2::ZERO);
+    }
+}
@@ -0,0 +1,600 @@
+//! Declarative scenes: a text description of a whole simulation setup.
+//!
+//! A [`Scene`] is plain data — config, named materials given by model name
+//! and SI parameters (converted with `FromSI` when built), boundaries, named
+//! force fields, thermal and scalar diffusion, threshold phase rules and
+//! bodies — so level authors can edit it without recompiling.
+//! `Scene::load(path)` parses a file and builds the `Simulation`;
+//! `Scene::save` writes the canonical text back, which parses to an equal
+//! `Scene`. Parse errors carry the 1-based line and column of the offending
+//! token.
+//!
+//! # Format
+//! Sections in brackets, `key = value` lines, `#` comments. Values are
+//! whitespace-separated: `gravity = 0 -0.3`.
+//! ```text
+//! [config]                 # optional; must come first
+//! grid_res = 64            # any SimConfig field; unset fields keep defaults
+//! dt = 0.05
+//! gravity = 0 -0.3
+//!
+//! [material jelly]         # ids follow file order: jelly = 0, sand = 1
+//! model = neo_hookean
+//! e_pa = 5000
+//! nu = 0.3
+//! rho_kg_m3 = 1000
+//!
+//! [material sand]
+//! model = drucker_prager
+//! e_pa = 5e5
+//! nu = 0.3
+//! rho_kg_m3 = 1600
+//! friction_angle_deg = 35
+//!
+//! [boundary]               # replaces the default slip walls
+//! kind = friction
+//! friction = 0.4
+//!
+//! [field wind]             # the name `Timeline::field_parameter` refers to
+//! kind = linear_drag
+//! target_velocity = 2 0
+//! drag_coefficient = 0.5
+//! materials = sand
+//!
+//! [phase_rule]
+//! from = jelly
+//! to = sand
+//! when = temperature > 400
+//!
+//! [body]
+//! material = jelly
+//! shape = disk 6
+//! center = 20 40
+//! spacing = 0.5
+//! mass = material          # particle mass from the material's density
+//! tag = 7
+//! ```
+//! Material models: `neo_hookean`, `corotated`, `viscoelastic` (plus
+//! `eta_pa_s`), `snow`, `drucker_prager` and `mu_i` (plus
+//! `friction_angle_deg`, optional `dilatancy_angle_deg`), `von_mises` (plus
+//! `yield_stress_pa`), `rankine` (plus `tensile_strength_pa`,
+//! `softening_rate`) — all with `e_pa`, `nu`, `rho_kg_m3` — and the fluids
+//! `newtonian` (`rho_kg_m3`, `eta_pa_s`, `bulk_modulus_pa`), `bingham` (plus
+//! `yield_stress_pa`) and `granular_fluid` (the `FluidGranular` fields).
+//!
+//! Boundary kinds: `slip`, `friction` (`friction`), `grip_friction`
+//! (`friction`, `grip_gain`), `ratchet_friction` (`mu_easy`, `mu_resist`,
+//! `easy_direction`), `predictive` (`wall_min`) and `heightmap` (`heights`,
+//! `friction`), each with an optional `thickness`.
+//!
+//! Field kinds: `linear_drag`, `gravity_well` (one `source = x y mass` line
+//! per source), `radial_confinement`, `aabb_confinement`, `buoyancy`,
+//! `n_body` and `uniform_electric` (one `charge = material q` line per
+//! charged material), with their constructors' parameter names. A field
+//! without a name gets `force_field_<n>`, as `add_force_field` would give it.
+//!
+//! `[thermal]` takes the `ThermalConfig` fields (`grid_cell_size` defaults to
+//! `dx_meters`); each `[scalar_field]` diffuses one particle `channel`
+//! (`temperature` or `activation`). A `[phase_rule]` moves particles of
+//! material `from` to `to` when `temperature`, `density`,
+//! `plastic_volume_ratio` or `activation` crosses the threshold.
+//!
+//! A `[body]` is a `SpawnRegion` (`shape`, `center`, `size`, `rotation`,
+//! `angular_velocity`, `spacing`, `jitter`, `velocity_scale`, `rng_seed`,
+//! `precompute_volumes`, `mass`) plus an initial `velocity` and
+//! `temperature`. A `shape` is `box`, `disk r`, `annulus inner outer`,
+//! `rect x y half_x half_y angle`, `capsule ax ay bx by r` or
+//! `polygon x y x y x y ...`, or `union`, `difference` or `intersection`
+//! followed by two shapes — `shape = difference box disk 3` is a block with a
+//! hole. A polygon takes at most 22 vertices, and a combined shape at most
+//! 44 numbers in all. Bodies get fresh tags in file order; `tag` sets one
+//! explicitly, and bodies sharing a tag form one group.
+
+mod text;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use glam::Vec2;
+
+use super::{SimConfig, Simulation, SpawnRegion};
+use crate::boundary::{
+    BoundaryCondition, FrictionBoundary, GripFrictionBoundary, HeightmapBoundary,
+    PredictiveBoundary, RatchetFrictionBoundary, SlipBoundary,
+};
+use crate::fields::{
+    AabbConfinementField, BuoyancyField, Field, GravityWellField, LinearDragField,
+    NBodyGravityField, RadialConfinementField, UniformElectricField,
+};
+use crate::materials::{
+    CorotatedMaterial, Elastic, Elastoplastic, Fluid, FluidGranular, FromSI, MaterialModel,
+    NeoHookeanMaterial, PlasticityModel, Viscoelastic,
+};
+use crate::particle::Particle;
+use crate::thermodynamics::{
+    ScalarDiffusionConfig, ScalarDiffusionField, ThermalConfig, ThermalDiffusion,
+};
+
+pub use text::SceneError;
+
+/// A whole simulation setup as data. See the module doc.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scene {
+    pub config: SimConfig,
+    /// Named materials; the position is the material ID.
+    pub materials: Vec<(String, SceneMaterial)>,
+    /// Boundary conditions, stacked in order. Empty keeps the default slip
+    /// walls.
+    pub boundaries: Vec<SceneBoundary>,
+    /// Force fields, registered under their names.
+    pub fields: Vec<(String, SceneField)>,
+    pub thermal: Option<ThermalConfig>,
+    pub scalar_fields: Vec<SceneScalarField>,
+    pub phase_rules: Vec<ScenePhaseRule>,
+    pub bodies: Vec<SceneBody>,
+}
+
+/// A material by model, in SI units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SceneMaterial {
+    NeoHookean(Elastic),
+    Corotated(Elastic),
+    Elastoplastic(Elastoplastic),
+    Viscoelastic(Viscoelastic),
+    /// Newtonian, or Bingham with a yield stress.
+    Fluid(Fluid),
+    FluidGranular(FluidGranular),
+}
+
+impl SceneMaterial {
+    /// The format's model name (`neo_hookean`, `drucker_prager`, ...).
+    pub fn model(&self) -> &'static str {
+        match self {
+            Self::NeoHookean(_) => "neo_hookean",
+            Self::Corotated(_) => "corotated",
+            Self::Viscoelastic(_) => "viscoelastic",
+            Self::Elastoplastic(props) => match props.model {
+                PlasticityModel::Snow => "snow",
+                PlasticityModel::Granular { .. } => "drucker_prager",
+                PlasticityModel::GranularRateDependent { .. } => "mu_i",
+                PlasticityModel::Ductile { .. } => "von_mises",
+                PlasticityModel::Brittle { .. } => "rankine",
+            },
+            Self::Fluid(props) => match props.yield_stress_pa {
+                None => "newtonian",
+                Some(_) => "bingham",
+            },
+            Self::FluidGranular(_) => "granular_fluid",
+        }
+    }
+
+    /// The grid-unit material for `config`.
+    pub fn material(&self, config: &SimConfig) -> Box<dyn MaterialModel> {
+        match self {
+            Self::NeoHookean(props) => Box::new(NeoHookeanMaterial::from_physical(props, config)),
+            Self::Corotated(props) => Box::new(CorotatedMaterial::from_physical(props, config)),
+            Self::Elastoplastic(props) => props.material(config),
+            Self::Viscoelastic(props) => props.material(config),
+            Self::Fluid(props) => props.material(config),
+            Self::FluidGranular(props) => props.material(config),
+        }
+    }
+
+    /// Particle mass for this material's density at `spacing`.
+    pub fn particle_mass(&self, spacing: f32, config: &SimConfig) -> f32 {
+        match self {
+            Self::NeoHookean(props) | Self::Corotated(props) => {
+                props.particle_mass(spacing, config)
+            }
+            Self::Elastoplastic(props) => props.particle_mass(spacing, config),
+            Self::Viscoelastic(props) => props.particle_mass(spacing, config),
+            Self::Fluid(props) => props.particle_mass(spacing, config),
+            Self::FluidGranular(props) => props.particle_mass(spacing, config),
+        }
+    }
+}
+
+/// A boundary condition. `thickness` is the wall band in cells.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneBoundary {
+    Slip {
+        thickness: usize,
+    },
+    Friction {
+        thickness: usize,
+        friction: f32,
+    },
+    GripFriction {
+        thickness: usize,
+        friction: f32,
+        grip_gain: f32,
+    },
+    RatchetFriction {
+        thickness: usize,
+        mu_easy: f32,
+        mu_resist: f32,
+        easy_direction: Vec2,
+    },
+    Predictive {
+        thickness: usize,
+        wall_min: f32,
+    },
+    Heightmap {
+        thickness: usize,
+        friction: f32,
+        heights: Vec<f32>,
+    },
+}
+
+impl SceneBoundary {
+    pub fn boundary(&self) -> Box<dyn BoundaryCondition> {
+        match *self {
+            Self::Slip { thickness } => Box::new(SlipBoundary::new(thickness)),
+            Self::Friction {
+                thickness,
+                friction,
+            } => Box::new(FrictionBoundary::new(thickness, friction)),
+            Self::GripFriction {
+                thickness,
+                friction,
+                grip_gain,
+            } => Box::new(GripFrictionBoundary::new(thickness, friction, grip_gain)),
+            Self::RatchetFriction {
+                thickness,
+                mu_easy,
+                mu_resist,
+                easy_direction,
+            } => Box::new(RatchetFrictionBoundary::new(
+                thickness,
+                mu_easy,
+                mu_resist,
+                easy_direction,
+            )),
+            Self::Predictive {
+                thickness,
+                wall_min,
+            } => Box::new(PredictiveBoundary::new(thickness, wall_min)),
+            Self::Heightmap {
+                thickness,
+                friction,
+                ref heights,
+            } => Box::new(HeightmapBoundary::new(heights.clone(), friction, thickness)),
+        }
+    }
+}
+
+/// A force field, with its constructor's parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneField {
+    LinearDrag {
+        target_velocity: Vec2,
+        drag_coefficient: f32,
+        /// `LinearDragField::material_mask`.
+        material_mask: u32,
+    },
+    GravityWell {
+        /// `(position, mass)` per source.
+        sources: Vec<(Vec2, f32)>,
+        gravitational_constant: f32,
+        softening: f32,
+        cutoff: Option<f32>,
+    },
+    RadialConfinement {
+        center: Vec2,
+        radius: f32,
+        stiffness: f32,
+    },
+    AabbConfinement {
+        min: Vec2,
+        max: Vec2,
+        stiffness: f32,
+    },
+    Buoyancy {
+        fluid_density: f32,
+        gravity: Vec2,
+        min_density: f32,
+    },
+    NBodyGravity {
+        gravitational_constant: f32,
+        softening: f32,
+        theta: f32,
+    },
+    UniformElectric {
+        field: Vec2,
+        /// `(material_id, charge)` per charged material.
+        charges: Vec<(u32, f32)>,
+    },
+}
+
+impl SceneField {
+    pub fn field(&self) -> Box<dyn Field> {
+        match *self {
+            Self::LinearDrag {
+                target_velocity,
+                drag_coefficient,
+                material_mask,
+            } => Box::new(LinearDragField::new(
+                target_velocity,
+                drag_coefficient,
+                material_mask,
+            )),
+            Self::GravityWell {
+                ref sources,
+                gravitational_constant,
+                softening,
+                cutoff,
+            } => {
+                let field =
+                    GravityWellField::new(sources.clone(), gravitational_constant, softening);
+                Box::new(match cutoff {
+                    Some(cutoff) => field.with_cutoff(cutoff),
+                    None => field,
+                })
+            }
+            Self::RadialConfinement {
+                center,
+                radius,
+                stiffness,
+            } => Box::new(RadialConfinementField::new(center, radius, stiffness)),
+            Self::AabbConfinement {
+                min,
+                max,
+                stiffness,
+            } => Box::new(AabbConfinementField::new(min, max, stiffness)),
+            Self::Buoyancy {
+                fluid_density,
+                gravity,
+                min_density,
+            } => {
+                let mut field = BuoyancyField::new(fluid_density, gravity);
+                field.min_density = min_density;
+                Box::new(field)
+            }
+            Self::NBodyGravity {
+                gravitational_constant,
+                softening,
+                theta,
+            } => Box::new(NBodyGravityField::new(
+                gravitational_constant,
+                softening,
+                theta,
+            )),
+            Self::UniformElectric { field, ref charges } => Box::new(UniformElectricField::new(
+                field,
+                charges.iter().copied().collect(),
+            )),
+        }
+    }
+}
+
+/// Particle quantity a scalar diffusion field transports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScalarChannel {
+    Temperature,
+    Activation,
+}
+
+impl ScalarChannel {
+    fn get(self) -> fn(&Particle) -> f32 {
+        match self {
+            Self::Temperature => |p| p.temperature,
+            Self::Activation => |p| p.activation,
+        }
+    }
+
+    /// Adds a change to the channel.
+    fn add(self) -> fn(&mut Particle, f32) {
+        match self {
+            Self::Temperature => |p, d| p.temperature += d,
+            Self::Activation => |p, d| p.activation += d,
+        }
+    }
+}
+
+/// A scalar diffusion field over one particle channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneScalarField {
+    pub channel: ScalarChannel,
+    pub config: ScalarDiffusionConfig,
+}
+
+/// Particle quantity a phase rule watches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhaseQuantity {
+    Temperature,
+    Density,
+    PlasticVolumeRatio,
+    Activation,
+}
+
+impl PhaseQuantity {
+    fn of(self, p: &Particle) -> f32 {
+        match self {
+            Self::Temperature => p.temperature,
+            Self::Density => p.density,
+            Self::PlasticVolumeRatio => p.plastic_volume_ratio,
+            Self::Activation => p.activation,
+        }
+    }
+}
+
+/// When a phase rule fires: strictly below or above a value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Threshold {
+    Below(f32),
+    Above(f32),
+}
+
+/// Move particles of material `from` to `to` when `quantity` crosses
+/// `threshold`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScenePhaseRule {
+    pub from: u32,
+    pub to: u32,
+    pub quantity: PhaseQuantity,
+    pub threshold: Threshold,
+}
+
+impl ScenePhaseRule {
+    /// The material `p` changes to, if any (an `add_phase_rule` rule).
+    pub fn apply(&self, p: &Particle) -> Option<u32> {
+        let value = self.quantity.of(p);
+        let fires = match self.threshold {
+            Threshold::Below(limit) => value < limit,
+            Threshold::Above(limit) => value > limit,
+        };
+        (p.material_id == self.from && fires).then_some(self.to)
+    }
+}
+
+/// A spawned body.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SceneBody {
+    pub spawn: SpawnRegion,
+    /// Take the particle mass from the material's density
+    /// (`SpawnRegion::mass_from`) instead of `spawn.mass_override`.
+    pub mass_from_material: bool,
+    /// Group tag; `None` takes the next fresh one.
+    pub tag: Option<u32>,
+    /// Added to each particle's spawned velocity, so `spawn.angular_velocity`
+    /// and `spawn.initial_velocity_scale` still apply.
+    pub velocity: Vec2,
+    pub temperature: Option<f32>,
+}
+
+impl SceneBody {
+    pub fn new(spawn: SpawnRegion) -> Self {
+        Self {
+            spawn,
+            mass_from_material: false,
+            tag: None,
+            velocity: Vec2::ZERO,
+            temperature: None,
+        }
+    }
+}
+
+impl Scene {
+    /// Parse scene text (see the module doc for the format).
+    pub fn parse(text: &str) -> Result<Self, SceneError> {
+        text::read(text)
+    }
+
+    /// The canonical text of this scene: `parse` gives back an equal scene.
+    pub fn to_text(&self) -> String {
+        text::write(self)
+    }
+
+    /// Set one `[config]` key from its value text, as a scene file would
+    /// (`set_config("dt", "0.025")`), for parameter sweeps.
+    pub fn set_config(&mut self, key: &str, value: &str) -> Result<(), SceneError> {
+        text::set_config(&mut self.config, key, value)
+    }
+
+    /// Read and parse a scene file. Parse errors come back as
+    /// `InvalidData`, prefixed with the path: `level.scene:12:5: ...`.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        Self::parse(&fs::read_to_string(path)?).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{err}", path.display()),
+            )
+        })
+    }
+
+    /// Read a scene file and build its simulation.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Simulation> {
+        Ok(Self::read(path)?.build())
+    }
+
+    /// Write the scene's text to `path` (created or truncated).
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    /// Whether `build` would get past `Simulation`'s own checks: the config
+    /// passes `SimConfig::check` and every body fits inside the domain walls.
+    /// `parse` guarantees both, but `set_config` and direct field edits can
+    /// change the domain under bodies placed for the old one.
+    pub fn check(&self) -> Result<(), String> {
+        self.config.check()?;
+        match (1..)
+            .zip(&self.bodies)
+            .find(|(_, body)| !body.spawn.fits_in_sim(&self.config))
+        {
+            Some((n, _)) => Err(format!("body {n} does not fit inside the domain walls")),
+            None => Ok(()),
+        }
+    }
+
+    /// Build the simulation. Panics where `Simulation` itself would: an
+    /// invalid config, or a body outside the domain (see `check`).
+    pub fn build(&self) -> Simulation {
+        let config = self.config;
+        let dims = config.grid_dims();
+        let mut sim = Simulation::empty(config);
+        for (id, (_, material)) in self.materials.iter().enumerate() {
+            sim.set_material(id as u32, material.material(&config));
+        }
+        if !self.boundaries.is_empty() {
+            sim.clear_boundaries();
+            for boundary in &self.boundaries {
+                sim.add_boundary_condition(boundary.boundary());
+            }
+        }
+        for (name, field) in &self.fields {
+            sim.add_named_force_field(name.clone(), field.field());
+        }
+        if let Some(thermal) = &self.thermal {
+            sim.set_thermal(ThermalDiffusion::with_dims(thermal.clone(), dims));
+        }
+        for scalar in &self.scalar_fields {
+            let field = ScalarDiffusionField::with_dims(
+                scalar.config.clone(),
+                scalar.channel.get(),
+                scalar.channel.add(),
+                dims,
+            );
+            sim.attach_scalar_field(field);
+        }
+        for &rule in &self.phase_rules {
+            sim.add_phase_rule(move |p| rule.apply(p));
+        }
+        for body in &self.bodies {
+            self.add_body(&mut sim, body);
+        }
+        sim
+    }
+
+    fn add_body(&self, sim: &mut Simulation, body: &SceneBody) {
+        let mut spawn = body.spawn;
+        if body.mass_from_material
+            && let Some((_, material)) = self.materials.get(spawn.material_id as usize)
+        {
+            spawn.mass_override = Some(material.particle_mass(spawn.spacing, &self.config));
+        }
+        let spawned = sim.add_body(spawn);
+        // Only this body's particles: an explicit tag may already hold others.
+        let indices: Vec<usize> = sim.particles_with_tag(spawned).collect();
+        let tag = body.tag.unwrap_or(spawned);
+        if tag != spawned {
+            for &i in &indices {
+                sim.retag_particle(i, tag);
+            }
+            sim.tag_index.remove(&spawned);
+            sim.next_tag = sim.next_tag.max(tag + 1);
+        }
+        if body.velocity != Vec2::ZERO {
+            for &i in &indices {
+                sim.particles.v[i] += body.velocity;
+            }
+        }
+        if let Some(temperature) = body.temperature {
+            for &i in &indices {
+                sim.particles.temperature[i] = temperature;
+            }
+        }
+    }
+}
@@ -0,0 +1,1649 @@
+//! The scene text format: splitting lines into sections, reading sections
+//! into a [`Scene`], and writing a scene back as canonical text.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use glam::{IVec2, Vec2};
+
+use super::{
+    PhaseQuantity, ScalarChannel, Scene, SceneBody, SceneBoundary, SceneField, SceneMaterial,
+    ScenePhaseRule, SceneScalarField, Threshold,
+};
+use crate::fields::{BuoyancyField, LinearDragField};
+use crate::materials::{
+    Elastic, Elastoplastic, Fluid, FluidGranular, PlasticityModel, Viscoelastic,
+};
+use crate::solver::config::{CompositeShape, Node, SimConfig, SpawnRegion, SpawnShape, Step};
+use crate::thermodynamics::{ScalarDiffusionConfig, ThermalConfig};
+
+const MATERIAL_MODELS: &str = "neo_hookean, corotated, viscoelastic, snow, drucker_prager, \
+                               mu_i, von_mises, rankine, newtonian, bingham, granular_fluid";
+const BOUNDARY_KINDS: &str =
+    "slip, friction, grip_friction, ratchet_friction, predictive, heightmap";
+const FIELD_KINDS: &str = "linear_drag, gravity_well, radial_confinement, aabb_confinement, \
+                           buoyancy, n_body, uniform_electric";
+
+/// What went wrong in scene text, and where (1-based line and column).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SceneError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/// One whitespace-separated word and where it starts.
+#[derive(Clone, Copy, Debug)]
+struct Token<'a> {
+    text: &'a str,
+    line: usize,
+    column: usize,
+}
+
+impl Token<'_> {
+    fn error(&self, message: impl Into<String>) -> SceneError {
+        SceneError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+}
+
+struct Entry<'a> {
+    key: Token<'a>,
+    values: Vec<Token<'a>>,
+    used: bool,
+}
+
+/// A `[kind]` or `[kind name]` header and its `key = value` lines. Keys are
+/// pulled out by the reader; any left over are unknown.
+struct Section<'a> {
+    kind: Token<'a>,
+    name: Option<Token<'a>>,
+    entries: Vec<Entry<'a>>,
+}
+
+/// The words of `part`, a slice of `line` (line number `number`).
+fn words<'a>(line: &'a str, number: usize, part: &'a str) -> Vec<Token<'a>> {
+    part.split_whitespace()
+        .map(|text| {
+            let offset = text.as_ptr() as usize - line.as_ptr() as usize;
+            Token {
+                text,
+                line: number,
+                column: line[..offset].chars().count() + 1,
+            }
+        })
+        .collect()
+}
+
+fn lex(text: &str) -> Result<Vec<Section<'_>>, SceneError> {
+    let mut sections: Vec<Section> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let content = line.split('#').next().unwrap_or_default();
+        let Some(&first) = words(line, number, content).first() else {
+            continue;
+        };
+        if let Some(header) = content.trim().strip_prefix('[') {
+            let Some(header) = header.strip_suffix(']') else {
+                return Err(first.error("section header is missing its closing `]`"));
+            };
+            let (kind, name) = match words(line, number, header)[..] {
+                [kind] => (kind, None),
+                [kind, name] => (kind, Some(name)),
+                [] => return Err(first.error("empty section header")),
+                [_, _, extra, ..] => {
+                    return Err(extra.error("a section header is `[kind]` or `[kind name]`"));
+                }
+            };
+            sections.push(Section {
+                kind,
+                name,
+                entries: Vec::new(),
+            });
+            continue;
+        }
+        let Some((key, value)) = content.split_once('=') else {
+            return Err(first.error("expected `key = value` or a `[section]` header"));
+        };
+        let key = match words(line, number, key)[..] {
+            [key] => key,
+            [] => return Err(first.error("missing key before `=`")),
+            [_, extra, ..] => return Err(extra.error("a key is a single word")),
+        };
+        let values = words(line, number, value);
+        if values.is_empty() {
+            return Err(key.error(format!("`{}` has no value", key.text)));
+        }
+        let Some(section) = sections.last_mut() else {
+            return Err(key.error(format!("`{}` comes before any [section]", key.text)));
+        };
+        section.entries.push(Entry {
+            key,
+            values,
+            used: false,
+        });
+    }
+    Ok(sections)
+}
+
+impl<'a> Section<'a> {
+    fn title(&self) -> String {
+        match self.name {
+            Some(name) => format!("[{} {}]", self.kind.text, name.text),
+            None => format!("[{}]", self.kind.text),
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> SceneError {
+        self.kind.error(message)
+    }
+
+    /// The values of `key`, marking it read. A key may appear once.
+    fn entry(&mut self, key: &str) -> Result<Option<Vec<Token<'a>>>, SceneError> {
+        let mut found = None;
+        for (i, entry) in self.entries.iter().enumerate() {
+            if entry.key.text != key {
+                continue;
+            }
+            if found.is_some() {
+                let message = format!("`{key}` is set twice in {}", self.title());
+                return Err(entry.key.error(message));
+            }
+            found = Some(i);
+        }
+        Ok(found.map(|i| {
+            self.entries[i].used = true;
+            self.entries[i].values.clone()
+        }))
+    }
+
+    /// The values of every `key` line, for keys that may repeat.
+    fn entries(&mut self, key: &str) -> Vec<Vec<Token<'a>>> {
+        self.entries
+            .iter_mut()
+            .filter(|entry| entry.key.text == key)
+            .map(|entry| {
+                entry.used = true;
+                entry.values.clone()
+            })
+            .collect()
+    }
+
+    fn get<T: Value>(&mut self, key: &str) -> Result<Option<T>, SceneError> {
+        self.entry(key)?.map(|values| T::read(&values)).transpose()
+    }
+
+    fn or<T: Value>(&mut self, key: &str, default: T) -> Result<T, SceneError> {
+        Ok(self.get(key)?.unwrap_or(default))
+    }
+
+    fn required<T: Value>(&mut self, key: &str) -> Result<T, SceneError> {
+        match self.get(key)? {
+            Some(value) => Ok(value),
+            None => Err(self.error(format!("{} needs `{key}`", self.title()))),
+        }
+    }
+
+    /// A one-word value (a model, kind or material name).
+    fn word(&mut self, key: &str) -> Result<Option<Token<'a>>, SceneError> {
+        self.entry(key)?
+            .map(|values| Ok(exactly::<1>(&values, "one word")?[0]))
+            .transpose()
+    }
+
+    fn required_word(&mut self, key: &str) -> Result<Token<'a>, SceneError> {
+        match self.word(key)? {
+            Some(word) => Ok(word),
+            None => Err(self.error(format!("{} needs `{key}`", self.title()))),
+        }
+    }
+
+    fn no_name(&self) -> Result<(), SceneError> {
+        match self.name {
+            Some(name) => Err(name.error(format!("[{}] takes no name", self.kind.text))),
+            None => Ok(()),
+        }
+    }
+
+    /// Fail on the first key nothing asked for.
+    fn finish(&self) -> Result<(), SceneError> {
+        match self.entries.iter().find(|entry| !entry.used) {
+            Some(entry) => Err(entry.key.error(format!(
+                "unknown key `{}` in {}",
+                entry.key.text,
+                self.title()
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// `values` as exactly `N` words, or an error describing `what` was expected.
+fn exactly<'t, 'a, const N: usize>(
+    values: &'t [Token<'a>],
+    what: &str,
+) -> Result<&'t [Token<'a>; N], SceneError> {
+    values.try_into().map_err(|_| {
+        // The lexer never yields a key without values.
+        let at = values.get(N).unwrap_or(&values[values.len() - 1]);
+        at.error(format!("expected {what}"))
+    })
+}
+
+fn number(token: &Token) -> Result<f32, SceneError> {
+    token
+        .text
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| token.error(format!("expected a number, found `{}`", token.text)))
+}
+
+fn integer<T: FromStr>(token: &Token) -> Result<T, SceneError> {
+    token
+        .text
+        .parse()
+        .map_err(|_| token.error(format!("expected a whole number, found `{}`", token.text)))
+}
+
+/// A value type of the format: how it is read from words and written back.
+trait Value: Sized {
+    fn read(values: &[Token]) -> Result<Self, SceneError>;
+    fn write(&self) -> String;
+}
+
+impl Value for f32 {
+    fn read(values: &[Token]) -> Result<Self, SceneError> {
+        number(&exactly::<1>(values, "one number")?[0])
+    }
+
+    fn write(&self) -> String {
+        // Debug is the shortest text that parses back to the same bits.
+        format!("{self:?}")
+    }
+}
+
+impl Value for u32 {
+    fn read(values: &[Token]) -> Result<Self, SceneError> {
+        integer(&exactly::<1>(values, "one whole number")?[0])
+    }
+
+    fn write(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Value for usize {
+    fn read(values: &[Token]) -> Result<Self, SceneError> {
+        integer(&exactly::<1>(values, "one whole number")?[0])
+    }
+
+    fn write(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Value for bool {
+    fn read(values: &[Token]) -> Result<Self, SceneError> {
+        let [token] = exactly::<1>(values, "`true` or `false`")?;
+        match token.text {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            other => Err(token.error(format!("expected `true` or `false`, found `{other}`"))),
+        }
+    }
+
+    fn write(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Value for Vec2 {
+    fn read(values: &[Token]) -> Result<Self, SceneError> {
+        let [x, y] = exactly::<2>(values, "two numbers `x y`")?;
+        Ok(Vec2::new(number(x)?, number(y)?))
+    }
+
+    fn write(&self) -> String {
+        format!("{} {}", self.x.write(), self.y.write())
+    }
+}
+
+impl Value for IVec2 {
+    fn read(values: &[Token]) -> Result<Self, SceneError> {
+        let [x, y] = exactly::<2>(values, "two whole numbers `x y`")?;
+        Ok(IVec2::new(integer(x)?, integer(y)?))
+    }
+
+    fn write(&self) -> String {
+        format!("{} {}", self.x, self.y)
+    }
+}
+
+/// `none` or a whole number.
+impl Value for Option<usize> {
+    fn read(values: &[Token]) -> Result<Self, SceneError> {
+        let [token] = exactly::<1>(values, "`none` or a whole number")?;
+        match token.text {
+            "none" => Ok(None),
+            _ => integer(token).map(Some),
+        }
+    }
+
+    fn write(&self) -> String {
+        self.map_or_else(|| "none".to_string(), |value| value.to_string())
+    }
+}
+
+/// A list of one or more numbers.
+impl Value for Vec<f32> {
+    fn read(values: &[Token]) -> Result<Self, SceneError> {
+        values.iter().map(number).collect()
+    }
+
+    fn write(&self) -> String {
+        let words: Vec<String> = self.iter().map(Value::write).collect();
+        words.join(" ")
+    }
+}
+
+/// A gravity source: `x y mass`.
+impl Value for (Vec2, f32) {
+    fn read(values: &[Token]) -> Result<Self, SceneError> {
+        let [x, y, mass] = exactly::<3>(values, "`x y mass`")?;
+        Ok((Vec2::new(number(x)?, number(y)?), number(mass)?))
+    }
+
+    fn write(&self) -> String {
+        format!("{} {}", self.0.write(), self.1.write())
+    }
+}
+
+/// Material name → ID, for references from fields, rules and bodies.
+type MaterialIds<'a> = HashMap<&'a str, u32>;
+
+fn material_id(token: &Token, ids: &MaterialIds) -> Result<u32, SceneError> {
+    ids.get(token.text).copied().ok_or_else(|| {
+        token.error(format!(
+            "unknown material `{}` (materials must be defined before they are used)",
+            token.text
+        ))
+    })
+}
+
+/// Set one `[config]` key from its value text, as a scene file would.
+pub(super) fn set_config(config: &mut SimConfig, key: &str, value: &str) -> Result<(), SceneError> {
+    let text = format!("[config]\n{key} = {value}");
+    let mut sections = lex(&text)?;
+    let [section] = &mut sections[..] else {
+        return Err(SceneError {
+            line: 1,
+            column: 1,
+            message: format!("`{key} = {value}` is not a single config entry"),
+        });
+    };
+    // Only a valid result replaces the config.
+    let mut updated = *config;
+    read_config(section, &mut updated)?;
+    section.finish()?;
+    *config = updated;
+    Ok(())
+}
+
+pub(super) fn read(text: &str) -> Result<Scene, SceneError> {
+    let mut scene = Scene::default();
+    let mut ids = MaterialIds::new();
+    for (index, mut section) in lex(text)?.into_iter().enumerate() {
+        let s = &mut section;
+        match s.kind.text {
+            "config" => {
+                s.no_name()?;
+                if index > 0 {
+                    return Err(s.error("[config] must be the first section"));
+                }
+                read_config(s, &mut scene.config)?;
+            }
+            "material" => {
+                let Some(name) = s.name else {
+                    return Err(s.error("a material needs a name, e.g. `[material sand]`"));
+                };
+                if ids
+                    .insert(name.text, scene.materials.len() as u32)
+                    .is_some()
+                {
+                    return Err(name.error(format!("material `{}` is defined twice", name.text)));
+                }
+                let material = read_material(s)?;
+                scene.materials.push((name.text.to_string(), material));
+            }
+            "boundary" => {
+                s.no_name()?;
+                scene.boundaries.push(read_boundary(s, &scene.config)?);
+            }
+            "field" => {
+                let name = match s.name {
+                    Some(name) => name.text.to_string(),
+                    None => format!("force_field_{}", scene.fields.len()),
+                };
+                if scene.fields.iter().any(|(other, _)| *other == name) {
+                    let at = s.name.unwrap_or(s.kind);
+                    return Err(at.error(format!("field `{name}` is defined twice")));
+                }
+                let field = read_field(s, &scene.config, &ids)?;
+                scene.fields.push((name, field));
+            }
+            "thermal" => {
+                s.no_name()?;
+                if scene.thermal.is_some() {
+                    return Err(s.error("only one [thermal] section is allowed"));
+                }
+                scene.thermal = Some(read_thermal(s, &scene.config)?);
+            }
+            "scalar_field" => {
+                s.no_name()?;
+                scene.scalar_fields.push(read_scalar_field(s)?);
+            }
+            "phase_rule" => {
+                s.no_name()?;
+                scene.phase_rules.push(read_phase_rule(s, &ids)?);
+            }
+            "body" => {
+                s.no_name()?;
+                scene.bodies.push(read_body(s, &scene.config, &ids)?);
+            }
+            other => return Err(s.kind.error(format!("unknown section `[{other}]`"))),
+        }
+        section.finish()?;
+    }
+    Ok(scene)
+}
+
+/// The `[config]` keys over `c`; the result must pass `SimConfig::check`.
+fn read_config(s: &mut Section, c: &mut SimConfig) -> Result<(), SceneError> {
+    c.grid_res = s.or("grid_res", c.grid_res)?;
+    c.grid_res_y = s.or("grid_res_y", c.grid_res_y)?;
+    c.grid_cell_size = s.or("grid_cell_size", c.grid_cell_size)?;
+    c.dt = s.or("dt", c.dt)?;
+    c.adaptive_timestep = s.or("adaptive_timestep", c.adaptive_timestep)?;
+    c.cfl_include_affine_speed = s.or("cfl_include_affine_speed", c.cfl_include_affine_speed)?;
+    c.cfl_coefficient = s.or("cfl_coefficient", c.cfl_coefficient)?;
+    c.material_cfl_coefficient = s.or("material_cfl_coefficient", c.material_cfl_coefficient)?;
+    c.viscous_timestep_coefficient = s.or(
+        "viscous_timestep_coefficient",
+        c.viscous_timestep_coefficient,
+    )?;
+    c.min_dt = s.or("min_dt", c.min_dt)?;
+    c.project_invalid_state = s.or("project_invalid_state", c.project_invalid_state)?;
+    c.projection_min_density = s.or("projection_min_density", c.projection_min_density)?;
+    c.projection_min_volume = s.or("projection_min_volume", c.projection_min_volume)?;
+    c.projection_min_deformation_j = s.or(
+        "projection_min_deformation_j",
+        c.projection_min_deformation_j,
+    )?;
+    c.gravity = s.or("gravity", c.gravity)?;
+    c.boundary_thickness = s.or("boundary_thickness", c.boundary_thickness)?;
+    c.default_initial_volume = s.or("default_initial_volume", c.default_initial_volume)?;
+    c.recompute_density_each_step =
+        s.or("recompute_density_each_step", c.recompute_density_each_step)?;
+    c.particle_mass = s.or("particle_mass", c.particle_mass)?;
+    c.max_substeps_per_step = s.or("max_substeps_per_step", c.max_substeps_per_step)?;
+    c.apic_blend = s.or("apic_blend", c.apic_blend)?;
+    c.j_max = s.or("j_max", c.j_max)?;
+    c.sleep_threshold = s.or("sleep_threshold", c.sleep_threshold)?;
+    c.contact_friction = s.or("contact_friction", c.contact_friction)?;
+    c.asflip_blend = s.or("asflip_blend", c.asflip_blend)?;
+    c.mixture_drag_coefficient = s.or("mixture_drag_coefficient", c.mixture_drag_coefficient)?;
+    c.mixture_pressure_iterations =
+        s.or("mixture_pressure_iterations", c.mixture_pressure_iterations)?;
+    c.parallel_p2g = s.or("parallel_p2g", c.parallel_p2g)?;
+    c.periodic_x = s.or("periodic_x", c.periodic_x)?;
+    c.periodic_y = s.or("periodic_y", c.periodic_y)?;
+    c.dx_meters = s.or("dx_meters", c.dx_meters)?;
+    c.dt_seconds = s.or("dt_seconds", c.dt_seconds)?;
+    c.check().map_err(|message| s.error(message))
+}
+
+fn read_elastic(s: &mut Section) -> Result<Elastic, SceneError> {
+    Ok(Elastic {
+        e_pa: s.required("e_pa")?,
+        nu: s.required("nu")?,
+        rho_kg_m3: s.required("rho_kg_m3")?,
+    })
+}
+
+fn read_material(s: &mut Section) -> Result<SceneMaterial, SceneError> {
+    let model = s.required_word("model")?;
+    let plastic = |elastic, model| SceneMaterial::Elastoplastic(Elastoplastic { elastic, model });
+    let fluid = |s: &mut Section, yield_stress_pa| -> Result<SceneMaterial, SceneError> {
+        Ok(SceneMaterial::Fluid(Fluid {
+            rho_kg_m3: s.required("rho_kg_m3")?,
+            eta_pa_s: s.required("eta_pa_s")?,
+            bulk_modulus_pa: s.required("bulk_modulus_pa")?,
+            yield_stress_pa,
+        }))
+    };
+    Ok(match model.text {
+        "neo_hookean" => SceneMaterial::NeoHookean(read_elastic(s)?),
+        "corotated" => SceneMaterial::Corotated(read_elastic(s)?),
+        "viscoelastic" => SceneMaterial::Viscoelastic(Viscoelastic {
+            elastic: read_elastic(s)?,
+            eta_pa_s: s.required("eta_pa_s")?,
+        }),
+        "snow" => plastic(read_elastic(s)?, PlasticityModel::Snow),
+        "drucker_prager" => plastic(
+            read_elastic(s)?,
+            PlasticityModel::Granular {
+                friction_angle_deg: s.required("friction_angle_deg")?,
+                dilatancy_angle_deg: s.or("dilatancy_angle_deg", 0.0)?,
+            },
+        ),
+        "mu_i" => plastic(
+            read_elastic(s)?,
+            PlasticityModel::GranularRateDependent {
+                friction_angle_deg: s.required("friction_angle_deg")?,
+                dilatancy_angle_deg: s.or("dilatancy_angle_deg", 0.0)?,
+            },
+        ),
+        "von_mises" => plastic(
+            read_elastic(s)?,
+            PlasticityModel::Ductile {
+                yield_stress_pa: s.required("yield_stress_pa")?,
+            },
+        ),
+        "rankine" => plastic(
+            read_elastic(s)?,
+            PlasticityModel::Brittle {
+                tensile_strength_pa: s.required("tensile_strength_pa")?,
+                softening_rate: s.required("softening_rate")?,
+            },
+        ),
+        "newtonian" => fluid(s, None)?,
+        "bingham" => {
+            let yield_stress_pa = s.required("yield_stress_pa")?;
+            fluid(s, Some(yield_stress_pa))?
+        }
+        "granular_fluid" => SceneMaterial::FluidGranular(FluidGranular {
+            rho_kg_m3: s.required("rho_kg_m3")?,
+            bulk_modulus_pa: s.required("bulk_modulus_pa")?,
+            e_pa: s.required("e_pa")?,
+            nu: s.required("nu")?,
+            compression_limit: s.required("compression_limit")?,
+            stretch_limit: s.required("stretch_limit")?,
+            hardening_exponent: s.required("hardening_exponent")?,
+        }),
+        other => {
+            return Err(model.error(format!(
+                "unknown material model `{other}` (expected one of {MATERIAL_MODELS})"
+            )));
+        }
+    })
+}
+
+fn read_boundary(s: &mut Section, config: &SimConfig) -> Result<SceneBoundary, SceneError> {
+    let kind = s.required_word("kind")?;
+    let thickness = s.or("thickness", config.boundary_thickness)?;
+    Ok(match kind.text {
+        "slip" => SceneBoundary::Slip { thickness },
+        "friction" => SceneBoundary::Friction {
+            thickness,
+            friction: s.required("friction")?,
+        },
+        "grip_friction" => SceneBoundary::GripFriction {
+            thickness,
+            friction: s.required("friction")?,
+            grip_gain: s.required("grip_gain")?,
+        },
+        "ratchet_friction" => SceneBoundary::RatchetFriction {
+            thickness,
+            mu_easy: s.required("mu_easy")?,
+            mu_resist: s.required("mu_resist")?,
+            easy_direction: s.required("easy_direction")?,
+        },
+        "predictive" => SceneBoundary::Predictive {
+            thickness,
+            wall_min: s.required("wall_min")?,
+        },
+        "heightmap" => SceneBoundary::Heightmap {
+            thickness,
+            friction: s.required("friction")?,
+            heights: s.required("heights")?,
+        },
+        other => {
+            return Err(kind.error(format!(
+                "unknown boundary kind `{other}` (expected one of {BOUNDARY_KINDS})"
+            )));
+        }
+    })
+}
+
+fn read_field(
+    s: &mut Section,
+    config: &SimConfig,
+    ids: &MaterialIds,
+) -> Result<SceneField, SceneError> {
+    let kind = s.required_word("kind")?;
+    Ok(match kind.text {
+        "linear_drag" => SceneField::LinearDrag {
+            target_velocity: s.required("target_velocity")?,
+            drag_coefficient: s.required("drag_coefficient")?,
+            material_mask: read_material_mask(s, ids)?,
+        },
+        "gravity_well" => {
+            let sources = s
+                .entries("source")
+                .iter()
+                .map(|values| <(Vec2, f32)>::read(values))
+                .collect::<Result<Vec<_>, _>>()?;
+            if sources.is_empty() {
+                return Err(s.error(format!("{} needs `source = x y mass`", s.title())));
+            }
+            SceneField::GravityWell {
+                sources,
+                gravitational_constant: s.required("gravitational_constant")?,
+                softening: s.required("softening")?,
+                cutoff: s.get("cutoff")?,
+            }
+        }
+        "radial_confinement" => SceneField::RadialConfinement {
+            center: s.required("center")?,
+            radius: s.required("radius")?,
+            stiffness: s.required("stiffness")?,
+        },
+        "aabb_confinement" => SceneField::AabbConfinement {
+            min: s.required("min")?,
+            max: s.required("max")?,
+            stiffness: s.required("stiffness")?,
+        },
+        "buoyancy" => SceneField::Buoyancy {
+            fluid_density: s.required("fluid_density")?,
+            gravity: s.or("gravity", config.gravity)?,
+            min_density: s.or(
+                "min_density",
+                BuoyancyField::new(0.0, Vec2::ZERO).min_density,
+            )?,
+        },
+        "n_body" => SceneField::NBodyGravity {
+            gravitational_constant: s.required("gravitational_constant")?,
+            softening: s.required("softening")?,
+            theta: s.required("theta")?,
+        },
+        "uniform_electric" => {
+            let mut charges = Vec::new();
+            for values in s.entries("charge") {
+                let [material, charge] = exactly::<2>(&values, "`material charge`")?;
+                charges.push((material_id(material, ids)?, number(charge)?));
+            }
+            SceneField::UniformElectric {
+                field: s.required("field")?,
+                charges,
+            }
+        }
+        other => {
+            return Err(kind.error(format!(
+                "unknown field kind `{other}` (expected one of {FIELD_KINDS})"
+            )));
+        }
+    })
+}
+
+/// `materials = all` (the default) or a list of material names.
+fn read_material_mask(s: &mut Section, ids: &MaterialIds) -> Result<u32, SceneError> {
+    let Some(values) = s.entry("materials")? else {
+        return Ok(LinearDragField::ALL_MATERIALS);
+    };
+    if let [all] = values[..]
+        && all.text == "all"
+    {
+        return Ok(LinearDragField::ALL_MATERIALS);
+    }
+    let mut mask = 0u32;
+    for token in &values {
+        let id = material_id(token, ids)?;
+        mask |= 1u32
+            .checked_shl(id)
+            .ok_or_else(|| token.error("material masks reach only the first 32 materials"))?;
+    }
+    Ok(mask)
+}
+
+fn read_thermal(s: &mut Section, config: &SimConfig) -> Result<ThermalConfig, SceneError> {
+    Ok(ThermalConfig {
+        conductivity: s.required("conductivity")?,
+        heat_capacity: s.required("heat_capacity")?,
+        ambient: s.or("ambient", 0.0)?,
+        grid_cell_size: s.or("grid_cell_size", config.dx_meters)?,
+        cooling_rate: s.or("cooling_rate", 0.0)?,
+    })
+}
+
+fn read_scalar_field(s: &mut Section) -> Result<SceneScalarField, SceneError> {
+    let channel = s.required_word("channel")?;
+    let channel = match channel.text {
+        "temperature" => ScalarChannel::Temperature,
+        "activation" => ScalarChannel::Activation,
+        other => {
+            return Err(channel.error(format!(
+                "unknown channel `{other}` (expected temperature or activation)"
+            )));
+        }
+    };
+    Ok(SceneScalarField {
+        channel,
+        config: ScalarDiffusionConfig {
+            diffusivity: s.or("diffusivity", 0.0)?,
+            decay_rate: s.or("decay_rate", 0.0)?,
+            ambient: s.or("ambient", 0.0)?,
+        },
+    })
+}
+
+fn read_phase_rule(s: &mut Section, ids: &MaterialIds) -> Result<ScenePhaseRule, SceneError> {
+    let from = material_id(&s.required_word("from")?, ids)?;
+    let to = material_id(&s.required_word("to")?, ids)?;
+    let Some(when) = s.entry("when")? else {
+        return Err(s.error(format!("{} needs `when`", s.title())));
+    };
+    let [quantity, op, limit] = exactly::<3>(&when, "`quantity < value` or `quantity > value`")?;
+    let quantity = match quantity.text {
+        "temperature" => PhaseQuantity::Temperature,
+        "density" => PhaseQuantity::Density,
+        "plastic_volume_ratio" => PhaseQuantity::PlasticVolumeRatio,
+        "activation" => PhaseQuantity::Activation,
+        other => {
+            return Err(quantity.error(format!(
+                "unknown quantity `{other}` (expected temperature, density, \
+                 plastic_volume_ratio or activation)"
+            )));
+        }
+    };
+    let limit = number(limit)?;
+    let threshold = match op.text {
+        "<" => Threshold::Below(limit),
+        ">" => Threshold::Above(limit),
+        other => return Err(op.error(format!("expected `<` or `>`, found `{other}`"))),
+    };
+    Ok(ScenePhaseRule {
+        from,
+        to,
+        quantity,
+        threshold,
+    })
+}
+
+fn read_body(
+    s: &mut Section,
+    config: &SimConfig,
+    ids: &MaterialIds,
+) -> Result<SceneBody, SceneError> {
+    let mut spawn = SpawnRegion::for_sim(config);
+    if let Some(material) = s.word("material")? {
+        spawn.material_id = material_id(&material, ids)?;
+    }
+    if let Some(words) = s.entry("shape")? {
+        let mut at = 0;
+        let shape = read_shape(&words, &mut at)?;
+        if let Some(extra) = words.get(at) {
+            return Err(extra.error(format!("unexpected `{}` after the shape", extra.text)));
+        }
+        spawn = spawn.shape(shape);
+    }
+    spawn.box_center = s.or("center", spawn.box_center)?;
+    spawn.box_size = s.or("size", spawn.box_size)?;
+    spawn.rotation = s.or("rotation", spawn.rotation)?;
+    spawn.angular_velocity = s.or("angular_velocity", spawn.angular_velocity)?;
+    spawn.spacing = s.or("spacing", spawn.spacing)?;
+    spawn.position_jitter = s.or("jitter", spawn.position_jitter)?;
+    spawn.initial_velocity_scale = s.or("velocity_scale", spawn.initial_velocity_scale)?;
+    spawn.rng_seed = s.or("rng_seed", spawn.rng_seed)?;
+    spawn.precompute_initial_volumes =
+        s.or("precompute_volumes", spawn.precompute_initial_volumes)?;
+    let mut mass_from_material = false;
+    if let Some(mass) = s.entry("mass")? {
+        match mass[..] {
+            [word] if word.text == "material" => mass_from_material = true,
+            _ => spawn.mass_override = Some(f32::read(&mass)?),
+        }
+    }
+    if !spawn.fits_in_sim(config) {
+        return Err(s.error(
+            "the body does not fit inside the domain walls (check `center`, `size` and \
+             `spacing`)",
+        ));
+    }
+    let tag = match s.entry("tag")? {
+        // The next fresh tag after it would not fit in a u32.
+        Some(values) => match u32::read(&values)? {
+            u32::MAX => return Err(values[0].error(format!("`tag` must be below {}", u32::MAX))),
+            tag => Some(tag),
+        },
+        None => None,
+    };
+    Ok(SceneBody {
+        spawn,
+        mass_from_material,
+        tag,
+        velocity: s.or("velocity", Vec2::ZERO)?,
+        temperature: s.get("temperature")?,
+    })
+}
+
+/// One `shape` in prefix form starting at `words[*at]`, e.g.
+/// `difference rect 0 0 8 6 0 disk 3`; advances `at` past it.
+fn read_shape(words: &[Token], at: &mut usize) -> Result<SpawnShape, SceneError> {
+    // The lexer never yields a key without values.
+    let last = words[words.len() - 1];
+    let word = *words
+        .get(*at)
+        .ok_or_else(|| last.error("expected another shape"))?;
+    *at += 1;
+    let shape = match word.text {
+        "box" => SpawnShape::Box,
+        "disk" => match shape_numbers(words, at, word, 1)?[..] {
+            [radius] if radius > 0.0 => SpawnShape::Disk { radius },
+            _ => return Err(word.error("`disk` needs a positive radius")),
+        },
+        "annulus" => match shape_numbers(words, at, word, 2)?[..] {
+            [inner, outer] if inner >= 0.0 && outer > inner => SpawnShape::annulus(inner, outer),
+            _ => return Err(word.error("`annulus` needs 0 <= inner radius < outer radius")),
+        },
+        "rect" => match shape_numbers(words, at, word, 5)?[..] {
+            [x, y, hx, hy, angle] if hx > 0.0 && hy > 0.0 => {
+                SpawnShape::rect(Vec2::new(x, y), Vec2::new(hx, hy), angle)
+            }
+            _ => return Err(word.error("`rect` needs positive half-extents")),
+        },
+        "capsule" => match shape_numbers(words, at, word, 5)?[..] {
+            [ax, ay, bx, by, radius] if radius > 0.0 => {
+                SpawnShape::capsule(Vec2::new(ax, ay), Vec2::new(bx, by), radius)
+            }
+            _ => return Err(word.error("`capsule` needs a positive radius")),
+        },
+        "polygon" => {
+            // Vertices run until the next word that is not a number.
+            let count = words[*at..]
+                .iter()
+                .take_while(|token| number(token).is_ok())
+                .count();
+            if count < 6 || count % 2 != 0 {
+                return Err(word.error("`polygon` needs at least 3 `x y` vertices"));
+            }
+            let values = shape_numbers(words, at, word, count)?;
+            let vertices: Vec<Vec2> = values.chunks_exact(2).map(Vec2::from_slice).collect();
+            SpawnShape::try_polygon(&vertices).map_err(|err| word.error(err))?
+        }
+        "union" | "difference" | "intersection" => {
+            let op = match word.text {
+                "union" => Node::Union,
+                "difference" => Node::Difference,
+                _ => Node::Intersection,
+            };
+            let (a, b) = (read_shape(words, at)?, read_shape(words, at)?);
+            a.combine(op, b).map_err(|err| word.error(err))?
+        }
+        other => {
+            return Err(word.error(format!(
+                "unknown shape `{other}` (expected box, disk, annulus, rect, capsule, polygon, \
+                 union, difference or intersection)"
+            )));
+        }
+    };
+    Ok(shape)
+}
+
+/// The `count` numbers after shape `word`, advancing `at` past them.
+fn shape_numbers(
+    words: &[Token],
+    at: &mut usize,
+    word: Token,
+    count: usize,
+) -> Result<Vec<f32>, SceneError> {
+    let values = words.get(*at..*at + count).ok_or_else(|| {
+        words[words.len() - 1].error(format!("`{}` takes {count} numbers", word.text))
+    })?;
+    *at += count;
+    values.iter().map(number).collect()
+}
+
+/// `shape` in the prefix form `read_shape` takes.
+fn shape_words(shape: &SpawnShape) -> String {
+    let numbers = |values: &[f32]| {
+        values
+            .iter()
+            .map(Value::write)
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    match shape {
+        SpawnShape::Box => "box".into(),
+        SpawnShape::Disk { radius } => format!("disk {}", radius.write()),
+        SpawnShape::Annulus {
+            inner_radius,
+            outer_radius,
+        } => format!("annulus {}", numbers(&[*inner_radius, *outer_radius])),
+        SpawnShape::Rect {
+            center,
+            half,
+            angle,
+        } => format!(
+            "rect {}",
+            numbers(&[center.x, center.y, half.x, half.y, *angle])
+        ),
+        SpawnShape::Capsule { a, b, radius } => {
+            format!("capsule {}", numbers(&[a.x, a.y, b.x, b.y, *radius]))
+        }
+        SpawnShape::Composite(shape) => composite_words(shape, numbers),
+    }
+}
+
+/// Postfix nodes back to prefix words: each combinator takes the last two.
+fn composite_words(shape: &CompositeShape, numbers: impl Fn(&[f32]) -> String) -> String {
+    let mut stack: Vec<String> = Vec::new();
+    for step in shape.steps() {
+        let words = match step {
+            Step::Shape(leaf) => shape_words(&leaf),
+            Step::Polygon(vertices) => format!("polygon {}", numbers(vertices)),
+            Step::Combine(op) => {
+                let b = stack.pop().expect("a combinator follows two shapes");
+                let a = stack.pop().expect("a combinator follows two shapes");
+                let op = match op {
+                    Node::Union => "union",
+                    Node::Difference => "difference",
+                    _ => "intersection",
+                };
+                format!("{op} {a} {b}")
+            }
+        };
+        stack.push(words);
+    }
+    stack.pop().expect("a composite holds at least one shape")
+}
+
+/// Builds the canonical text, one section at a time.
+#[derive(Default)]
+struct Writer {
+    out: String,
+}
+
+impl Writer {
+    fn section(&mut self, kind: &str, name: Option<&str>) {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        match name {
+            Some(name) => self.out.push_str(&format!("[{kind} {name}]\n")),
+            None => self.out.push_str(&format!("[{kind}]\n")),
+        }
+    }
+
+    fn words(&mut self, key: &str, words: &str) {
+        self.out.push_str(&format!("{key} = {words}\n"));
+    }
+
+    fn key(&mut self, key: &str, value: &impl Value) {
+        self.words(key, &value.write());
+    }
+
+    fn key_if<T: Value + PartialEq>(&mut self, key: &str, value: &T, default: &T) {
+        if value != default {
+            self.key(key, value);
+        }
+    }
+}
+
+pub(super) fn write(scene: &Scene) -> String {
+    let mut w = Writer::default();
+    let name_of = |id: u32| {
+        scene
+            .materials
+            .get(id as usize)
+            .map(|(name, _)| name.as_str())
+    };
+
+    if scene.config != SimConfig::default() {
+        w.section("config", None);
+        write_config(&mut w, &scene.config);
+    }
+    for (name, material) in &scene.materials {
+        w.section("material", Some(name));
+        write_material(&mut w, material);
+    }
+    for boundary in &scene.boundaries {
+        w.section("boundary", None);
+        write_boundary(&mut w, boundary);
+    }
+    for (name, field) in &scene.fields {
+        w.section("field", Some(name));
+        write_field(&mut w, field, &name_of);
+    }
+    if let Some(thermal) = &scene.thermal {
+        w.section("thermal", None);
+        w.key("conductivity", &thermal.conductivity);
+        w.key("heat_capacity", &thermal.heat_capacity);
+        w.key("ambient", &thermal.ambient);
+        w.key("grid_cell_size", &thermal.grid_cell_size);
+        w.key("cooling_rate", &thermal.cooling_rate);
+    }
+    for scalar in &scene.scalar_fields {
+        w.section("scalar_field", None);
+        let channel = match scalar.channel {
+            ScalarChannel::Temperature => "temperature",
+            ScalarChannel::Activation => "activation",
+        };
+        w.words("channel", channel);
+        w.key("diffusivity", &scalar.config.diffusivity);
+        w.key("decay_rate", &scalar.config.decay_rate);
+        w.key("ambient", &scalar.config.ambient);
+    }
+    for rule in &scene.phase_rules {
+        w.section("phase_rule", None);
+        if let (Some(from), Some(to)) = (name_of(rule.from), name_of(rule.to)) {
+            w.words("from", from);
+            w.words("to", to);
+        }
+        let quantity = match rule.quantity {
+            PhaseQuantity::Temperature => "temperature",
+            PhaseQuantity::Density => "density",
+            PhaseQuantity::PlasticVolumeRatio => "plastic_volume_ratio",
+            PhaseQuantity::Activation => "activation",
+        };
+        let (op, limit) = match rule.threshold {
+            Threshold::Below(limit) => ('<', limit),
+            Threshold::Above(limit) => ('>', limit),
+        };
+        w.words("when", &format!("{quantity} {op} {}", limit.write()));
+    }
+    for body in &scene.bodies {
+        w.section("body", None);
+        write_body(&mut w, body, &name_of);
+    }
+    w.out
+}
+
+fn write_config(w: &mut Writer, c: &SimConfig) {
+    let d = SimConfig::default();
+    w.key_if("grid_res", &c.grid_res, &d.grid_res);
+    w.key_if("grid_res_y", &c.grid_res_y, &d.grid_res_y);
+    w.key_if("grid_cell_size", &c.grid_cell_size, &d.grid_cell_size);
+    w.key_if("dt", &c.dt, &d.dt);
+    w.key_if(
+        "adaptive_timestep",
+        &c.adaptive_timestep,
+        &d.adaptive_timestep,
+    );
+    w.key_if(
+        "cfl_include_affine_speed",
+        &c.cfl_include_affine_speed,
+        &d.cfl_include_affine_speed,
+    );
+    w.key_if("cfl_coefficient", &c.cfl_coefficient, &d.cfl_coefficient);
+    w.key_if(
+        "material_cfl_coefficient",
+        &c.material_cfl_coefficient,
+        &d.material_cfl_coefficient,
+    );
+    w.key_if(
+        "viscous_timestep_coefficient",
+        &c.viscous_timestep_coefficient,
+        &d.viscous_timestep_coefficient,
+    );
+    w.key_if("min_dt", &c.min_dt, &d.min_dt);
+    w.key_if(
+        "project_invalid_state",
+        &c.project_invalid_state,
+        &d.project_invalid_state,
+    );
+    w.key_if(
+        "projection_min_density",
+        &c.projection_min_density,
+        &d.projection_min_density,
+    );
+    w.key_if(
+        "projection_min_volume",
+        &c.projection_min_volume,
+        &d.projection_min_volume,
+    );
+    w.key_if(
+        "projection_min_deformation_j",
+        &c.projection_min_deformation_j,
+        &d.projection_min_deformation_j,
+    );
+    w.key_if("gravity", &c.gravity, &d.gravity);
+    w.key_if(
+        "boundary_thickness",
+        &c.boundary_thickness,
+        &d.boundary_thickness,
+    );
+    w.key_if(
+        "default_initial_volume",
+        &c.default_initial_volume,
+        &d.default_initial_volume,
+    );
+    w.key_if(
+        "recompute_density_each_step",
+        &c.recompute_density_each_step,
+        &d.recompute_density_each_step,
+    );
+    w.key_if("particle_mass", &c.particle_mass, &d.particle_mass);
+    w.key_if(
+        "max_substeps_per_step",
+        &c.max_substeps_per_step,
+        &d.max_substeps_per_step,
+    );
+    w.key_if("apic_blend", &c.apic_blend, &d.apic_blend);
+    w.key_if("j_max", &c.j_max, &d.j_max);
+    w.key_if("sleep_threshold", &c.sleep_threshold, &d.sleep_threshold);
+    w.key_if("contact_friction", &c.contact_friction, &d.contact_friction);
+    w.key_if("asflip_blend", &c.asflip_blend, &d.asflip_blend);
+    w.key_if(
+        "mixture_drag_coefficient",
+        &c.mixture_drag_coefficient,
+        &d.mixture_drag_coefficient,
+    );
+    w.key_if(
+        "mixture_pressure_iterations",
+        &c.mixture_pressure_iterations,
+        &d.mixture_pressure_iterations,
+    );
+    w.key_if("parallel_p2g", &c.parallel_p2g, &d.parallel_p2g);
+    w.key_if("periodic_x", &c.periodic_x, &d.periodic_x);
+    w.key_if("periodic_y", &c.periodic_y, &d.periodic_y);
+    w.key_if("dx_meters", &c.dx_meters, &d.dx_meters);
+    w.key_if("dt_seconds", &c.dt_seconds, &d.dt_seconds);
+}
+
+fn write_elastic(w: &mut Writer, elastic: &Elastic) {
+    w.key("e_pa", &elastic.e_pa);
+    w.key("nu", &elastic.nu);
+    w.key("rho_kg_m3", &elastic.rho_kg_m3);
+}
+
+fn write_material(w: &mut Writer, material: &SceneMaterial) {
+    w.words("model", material.model());
+    match material {
+        SceneMaterial::NeoHookean(elastic) | SceneMaterial::Corotated(elastic) => {
+            write_elastic(w, elastic);
+        }
+        SceneMaterial::Viscoelastic(props) => {
+            write_elastic(w, &props.elastic);
+            w.key("eta_pa_s", &props.eta_pa_s);
+        }
+        SceneMaterial::Elastoplastic(props) => {
+            write_elastic(w, &props.elastic);
+            match props.model {
+                PlasticityModel::Snow => {}
+                PlasticityModel::Granular {
+                    friction_angle_deg,
+                    dilatancy_angle_deg,
+                }
+                | PlasticityModel::GranularRateDependent {
+                    friction_angle_deg,
+                    dilatancy_angle_deg,
+                } => {
+                    w.key("friction_angle_deg", &friction_angle_deg);
+                    w.key("dilatancy_angle_deg", &dilatancy_angle_deg);
+                }
+                PlasticityModel::Ductile { yield_stress_pa } => {
+                    w.key("yield_stress_pa", &yield_stress_pa);
+                }
+                PlasticityModel::Brittle {
+                    tensile_strength_pa,
+                    softening_rate,
+                } => {
+                    w.key("tensile_strength_pa", &tensile_strength_pa);
+                    w.key("softening_rate", &softening_rate);
+                }
+            }
+        }
+        SceneMaterial::Fluid(fluid) => {
+            w.key("rho_kg_m3", &fluid.rho_kg_m3);
+            w.key("eta_pa_s", &fluid.eta_pa_s);
+            w.key("bulk_modulus_pa", &fluid.bulk_modulus_pa);
+            if let Some(yield_stress_pa) = fluid.yield_stress_pa {
+                w.key("yield_stress_pa", &yield_stress_pa);
+            }
+        }
+        SceneMaterial::FluidGranular(props) => {
+            w.key("rho_kg_m3", &props.rho_kg_m3);
+            w.key("bulk_modulus_pa", &props.bulk_modulus_pa);
+            w.key("e_pa", &props.e_pa);
+            w.key("nu", &props.nu);
+            w.key("compression_limit", &props.compression_limit);
+            w.key("stretch_limit", &props.stretch_limit);
+            w.key("hardening_exponent", &props.hardening_exponent);
+        }
+    }
+}
+
+fn write_boundary(w: &mut Writer, boundary: &SceneBoundary) {
+    match boundary {
+        SceneBoundary::Slip { thickness } => {
+            w.words("kind", "slip");
+            w.key("thickness", thickness);
+        }
+        SceneBoundary::Friction {
+            thickness,
+            friction,
+        } => {
+            w.words("kind", "friction");
+            w.key("thickness", thickness);
+            w.key("friction", friction);
+        }
+        SceneBoundary::GripFriction {
+            thickness,
+            friction,
+            grip_gain,
+        } => {
+            w.words("kind", "grip_friction");
+            w.key("thickness", thickness);
+            w.key("friction", friction);
+            w.key("grip_gain", grip_gain);
+        }
+        SceneBoundary::RatchetFriction {
+            thickness,
+            mu_easy,
+            mu_resist,
+            easy_direction,
+        } => {
+            w.words("kind", "ratchet_friction");
+            w.key("thickness", thickness);
+            w.key("mu_easy", mu_easy);
+            w.key("mu_resist", mu_resist);
+            w.key("easy_direction", easy_direction);
+        }
+        SceneBoundary::Predictive {
+            thickness,
+            wall_min,
+        } => {
+            w.words("kind", "predictive");
+            w.key("thickness", thickness);
+            w.key("wall_min", wall_min);
+        }
+        SceneBoundary::Heightmap {
+            thickness,
+            friction,
+            heights,
+        } => {
+            w.words("kind", "heightmap");
+            w.key("thickness", thickness);
+            w.key("friction", friction);
+            w.key("heights", heights);
+        }
+    }
+}
+
+fn write_field<'a>(w: &mut Writer, field: &SceneField, name_of: &impl Fn(u32) -> Option<&'a str>) {
+    match field {
+        SceneField::LinearDrag {
+            target_velocity,
+            drag_coefficient,
+            material_mask,
+        } => {
+            w.words("kind", "linear_drag");
+            w.key("target_velocity", target_velocity);
+            w.key("drag_coefficient", drag_coefficient);
+            if *material_mask != LinearDragField::ALL_MATERIALS {
+                let names: Vec<&str> = (0..32)
+                    .filter(|bit| material_mask & (1 << bit) != 0)
+                    .filter_map(name_of)
+                    .collect();
+                w.words("materials", &names.join(" "));
+            }
+        }
+        SceneField::GravityWell {
+            sources,
+            gravitational_constant,
+            softening,
+            cutoff,
+        } => {
+            w.words("kind", "gravity_well");
+            for source in sources {
+                w.key("source", source);
+            }
+            w.key("gravitational_constant", gravitational_constant);
+            w.key("softening", softening);
+            if let Some(cutoff) = cutoff {
+                w.key("cutoff", cutoff);
+            }
+        }
+        SceneField::RadialConfinement {
+            center,
+            radius,
+            stiffness,
+        } => {
+            w.words("kind", "radial_confinement");
+            w.key("center", center);
+            w.key("radius", radius);
+            w.key("stiffness", stiffness);
+        }
+        SceneField::AabbConfinement {
+            min,
+            max,
+            stiffness,
+        } => {
+            w.words("kind", "aabb_confinement");
+            w.key("min", min);
+            w.key("max", max);
+            w.key("stiffness", stiffness);
+        }
+        SceneField::Buoyancy {
+            fluid_density,
+            gravity,
+            min_density,
+        } => {
+            w.words("kind", "buoyancy");
+            w.key("fluid_density", fluid_density);
+            w.key("gravity", gravity);
+            w.key("min_density", min_density);
+        }
+        SceneField::NBodyGravity {
+            gravitational_constant,
+            softening,
+            theta,
+        } => {
+            w.words("kind", "n_body");
+            w.key("gravitational_constant", gravitational_constant);
+            w.key("softening", softening);
+            w.key("theta", theta);
+        }
+        SceneField::UniformElectric { field, charges } => {
+            w.words("kind", "uniform_electric");
+            w.key("field", field);
+            for &(material, charge) in charges {
+                if let Some(name) = name_of(material) {
+                    w.words("charge", &format!("{name} {}", charge.write()));
+                }
+            }
+        }
+    }
+}
+
+fn write_body<'a>(w: &mut Writer, body: &SceneBody, name_of: &impl Fn(u32) -> Option<&'a str>) {
+    let spawn = &body.spawn;
+    let d = SpawnRegion::default();
+    if let Some(name) = name_of(spawn.material_id) {
+        w.words("material", name);
+    }
+    w.words("shape", &shape_words(&spawn.shape));
+    w.key("center", &spawn.box_center);
+    w.key("size", &spawn.box_size);
+    w.key_if("rotation", &spawn.rotation, &d.rotation);
+    w.key_if(
+        "angular_velocity",
+        &spawn.angular_velocity,
+        &d.angular_velocity,
+    );
+    w.key("spacing", &spawn.spacing);
+    w.key_if("jitter", &spawn.position_jitter, &d.position_jitter);
+    w.key_if(
+        "velocity_scale",
+        &spawn.initial_velocity_scale,
+        &d.initial_velocity_scale,
+    );
+    w.key_if("rng_seed", &spawn.rng_seed, &d.rng_seed);
+    w.key_if(
+        "precompute_volumes",
+        &spawn.precompute_initial_volumes,
+        &d.precompute_initial_volumes,
+    );
+    if body.mass_from_material {
+        w.words("mass", "material");
+    } else if let Some(mass) = spawn.mass_override {
+        w.key("mass", &mass);
+    }
+    if let Some(tag) = body.tag {
+        w.key("tag", &tag);
+    }
+    w.key_if("velocity", &body.velocity, &Vec2::ZERO);
+    if let Some(temperature) = body.temperature {
+        w.key("temperature", &temperature);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENE: &str = "\
+# Jelly and sand; comments and blank lines are ignored.
+[config]
+grid_res = 48
+dt = 0.05
+gravity = 0 -0.3   # trailing comments too
+
+[material jelly]
+model = neo_hookean
+e_pa = 5000
+nu = 0.3
+rho_kg_m3 = 1000
+
+[material sand]
+model = drucker_prager
+e_pa = 5e5
+nu = 0.3
+rho_kg_m3 = 1600
+friction_angle_deg = 35
+
+[boundary]
+kind = heightmap
+friction = 0.4
+heights = 4 4 5 6
+
+[field wind]
+kind = linear_drag
+target_velocity = 2 0
+drag_coefficient = 0.5
+materials = sand
+
+[field]
+kind = gravity_well
+source = 10 10 2
+source = 30 10 1
+gravitational_constant = 1
+softening = 0.5
+
+[thermal]
+conductivity = 0.6
+heat_capacity = 4000
+
+[scalar_field]
+channel = activation
+diffusivity = 0.5
+
+[phase_rule]
+from = jelly
+to = sand
+when = temperature > 400
+
+[body]
+material = jelly
+shape = disk 6
+center = 20 30
+spacing = 0.5
+mass = material
+tag = 7
+velocity = 1 0
+
+[body]
+material = sand
+size = 12 6
+center = 24 10
+temperature = 300
+";
+
+    #[test]
+    fn text_round_trips_through_the_scene() {
+        let scene = Scene::parse(SCENE).unwrap();
+        assert_eq!(scene.config.grid_res, 48);
+        assert_eq!(scene.config.gravity, Vec2::new(0.0, -0.3));
+        assert_eq!(scene.materials[1].0, "sand");
+        assert_eq!(scene.fields[1].0, "force_field_1");
+        assert_eq!(
+            scene.fields[0].1,
+            SceneField::LinearDrag {
+                target_velocity: Vec2::new(2.0, 0.0),
+                drag_coefficient: 0.5,
+                material_mask: 1 << 1,
+            }
+        );
+        assert_eq!(
+            scene.thermal.as_ref().unwrap().grid_cell_size,
+            scene.config.dx_meters
+        );
+        let disk = &scene.bodies[0];
+        assert_eq!(disk.spawn.shape, SpawnShape::Disk { radius: 6.0 });
+        assert_eq!(disk.spawn.box_size, IVec2::splat(13));
+        assert!(disk.mass_from_material);
+        assert_eq!((disk.tag, scene.bodies[1].spawn.material_id), (Some(7), 1));
+        assert_eq!(scene.phase_rules[0].threshold, Threshold::Above(400.0));
+
+        let text = scene.to_text();
+        assert_eq!(Scene::parse(&text).unwrap(), scene, "{text}");
+        assert_eq!(Scene::parse("").unwrap().to_text(), "");
+
+        let mut swept = Scene::default();
+        swept.set_config("gravity", "0 -2").unwrap();
+        assert_eq!(swept.config.gravity, Vec2::new(0.0, -2.0));
+        assert!(swept.set_config("dtt", "0.1").is_err());
+        assert!(swept.set_config("dt", "0.1\n[body]").is_err());
+        assert!(swept.set_config("grid_res", "2").is_err());
+        assert_eq!(swept.config.grid_res, SimConfig::default().grid_res);
+    }
+
+    #[test]
+    fn bodies_sharing_a_tag_keep_their_own_velocity_and_temperature() {
+        let scene = Scene::parse(
+            "[body]\n\
+             center = 12 24\n\
+             velocity_scale = 0\n\
+             tag = 3\n\
+             velocity = 1 0\n\
+             temperature = 300\n\
+             [body]\n\
+             center = 36 24\n\
+             velocity_scale = 0\n\
+             tag = 3\n",
+        )
+        .unwrap();
+        let sim = scene.build();
+        let particles = sim.particles();
+        let (left, right): (Vec<usize>, Vec<usize>) = sim
+            .particles_with_tag(3)
+            .partition(|&i| particles.x[i].x < 24.0);
+        assert_eq!(left.len() + right.len(), particles.len());
+        assert!(!left.is_empty() && !right.is_empty());
+        for i in left {
+            assert_eq!(particles.v[i], Vec2::new(1.0, 0.0));
+            assert_eq!(particles.temperature[i], 300.0);
+        }
+        for i in right {
+            assert_eq!(particles.v[i], Vec2::ZERO);
+            assert_ne!(particles.temperature[i], 300.0);
+        }
+    }
+
+    #[test]
+    fn shapes_nest_in_prefix_form() {
+        let scene = Scene::parse(
+            "[body]\n\
+             shape = difference difference union rect 0 0 8 2 0 capsule -8 0 -8 6 2 \
+             polygon 4 0 6 3 4 6 annulus 0 1\n\
+             center = 24 24\n\
+             rotation = 0.5\n\
+             angular_velocity = -1\n",
+        )
+        .unwrap();
+        let spawn = &scene.bodies[0].spawn;
+        let expected = SpawnShape::rect(Vec2::ZERO, Vec2::new(8.0, 2.0), 0.0)
+            .union(SpawnShape::capsule(
+                Vec2::new(-8.0, 0.0),
+                Vec2::new(-8.0, 6.0),
+                2.0,
+            ))
+            .difference(SpawnShape::polygon(&[
+                Vec2::new(4.0, 0.0),
+                Vec2::new(6.0, 3.0),
+                Vec2::new(4.0, 6.0),
+            ]))
+            .difference(SpawnShape::annulus(0.0, 1.0));
+        assert_eq!(spawn.shape, expected);
+        assert_eq!(spawn.box_size, IVec2::new(21, 17));
+        assert_eq!((spawn.rotation, spawn.angular_velocity), (0.5, -1.0));
+        assert_eq!(Scene::parse(&scene.to_text()).unwrap(), scene);
+
+        let error = |shape: &str| {
+            Scene::parse(&format!("[body]\nshape = {shape}\n"))
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(error("union box"), "2:15: expected another shape");
+        assert_eq!(error("rect 0 0 1"), "2:18: `rect` takes 5 numbers");
+        assert_eq!(
+            error("polygon 0 0 1 1"),
+            "2:9: `polygon` needs at least 3 `x y` vertices"
+        );
+        assert_eq!(
+            error("disk 2 box"),
+            "2:16: unexpected `box` after the shape"
+        );
+        // Shapes are stored inline, so their size is capped.
+        let many = ["1 0 0 1 -1 0"; 8].join(" ");
+        assert_eq!(
+            error(&format!("polygon {many}")),
+            "2:9: polygon has 24 vertices, at most 22 fit in a spawn shape"
+        );
+        let most = ["1 0 0 1 -1 0"; 7].join(" ");
+        assert_eq!(
+            error(&format!("union polygon {most} rect 0 0 1 1 0")),
+            "2:9: spawn shape needs more than 44 numbers"
+        );
+
+        // A combinator on the right still writes back in the same nesting.
+        let scene =
+            Scene::parse("[body]\nshape = difference disk 5 union disk 1 annulus 2 3\n").unwrap();
+        let shape = scene.bodies[0].spawn.shape;
+        assert!(shape.contains(Vec2::new(1.5, 0.0)) && !shape.contains(Vec2::new(2.5, 0.0)));
+        let text = scene.to_text();
+        assert!(
+            text.contains("shape = difference disk 5.0 union disk 1.0 annulus 2.0 3.0"),
+            "{text}"
+        );
+        assert_eq!(Scene::parse(&scene.to_text()).unwrap(), scene);
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_token() {
+        let error = |text: &str| Scene::parse(text).unwrap_err();
+        let at = |e: SceneError| (e.line, e.column);
+
+        assert_eq!(at(error("[config]\n  dt = fast\n")), (2, 8));
+        assert_eq!(at(error("[config]\ngravity = 0\n")), (2, 11));
+        assert_eq!(at(error("[config]\ndtt = 0.1\n")), (2, 1));
+        assert_eq!(at(error("[body]\n[config]\n")), (2, 2));
+        assert_eq!(at(error("[material]\n")), (1, 2));
+        assert_eq!(at(error("[material m]\nmodel = jelly\n")), (2, 9));
+        assert_eq!(at(error("[body]\nmaterial = rock\n")), (2, 12));
+        assert_eq!(at(error("[body]\ncenter = 1 1\n")), (1, 2));
+        assert_eq!(at(error("[body]\ntag = 4294967295\n")), (2, 7));
+        assert_eq!(at(error("dt = 1\n")), (1, 1));
+        assert_eq!(at(error("[config\n")), (1, 1));
+
+        let e = error("[config]\ndt = 0\n");
+        assert_eq!(e.to_string(), "1:2: dt must be positive");
+        let e = error("[material m]\nmodel = corotated\ne_pa = 1\nnu = 0.3\n");
+        assert_eq!(e.to_string(), "1:2: [material m] needs `rho_kg_m3`");
+        let e = error("[field]\nkind = buoyancy\nfluid_density = 1\nfluid_density = 2\n");
+        assert_eq!(at(e), (4, 1));
+    }
+}
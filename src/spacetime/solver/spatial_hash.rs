@@ -1,6 +1,9 @@
-use glam::Vec2;
+use glam::{IVec2, UVec2, Vec2};
 use std::collections::HashMap;
 
+use super::SimConfig;
+use crate::grid::GridDomain;
+
 /// Flat spatial hash over active particles.
 ///
 /// Particles live in grid-coordinate space (same units as `SimConfig::grid_cell_size`).
@@ -9,11 +12,19 @@ use std::collections::HashMap;
 ///
 /// Rebuild once per substep after G2P (positions are final for that substep).
 /// Query with `query(center, radius)` — iterates candidate indices; caller does
-/// exact distance filtering.
+/// exact distance filtering, measuring with `offset` so periodic domains use the
+/// minimum-image distance across the seam.
 pub(crate) struct SpatialHash {
     inv_cell: f32,
     /// (cx, cy) → particle indices.
     table: HashMap<(i32, i32), Vec<usize>>,
+    /// Domain extent and the axes on which it wraps (`SimConfig::periodic_x`/
+    /// `periodic_y`); only consulted on periodic axes.
+    domain: GridDomain,
+    /// Buckets spanning the domain per axis — bucket coordinates wrap modulo
+    /// this on periodic axes. The last bucket is partial when the domain isn't
+    /// a whole number of buckets.
+    wrap_cells: IVec2,
 }
 
 impl SpatialHash {
@@ -22,7 +33,26 @@ impl SpatialHash {
         Self {
             inv_cell: 1.0 / cell_size,
             table: HashMap::new(),
+            domain: GridDomain::new(UVec2::ZERO),
+            wrap_cells: IVec2::ZERO,
+        }
+    }
+
+    /// Hash sized and wrapped for `config`'s domain.
+    pub fn for_config(config: &SimConfig) -> Self {
+        Self::for_domain(config.grid_cell_size, config.grid_domain())
+    }
+
+    /// Hash with `cell_size` buckets, wrapped on `domain`'s periodic axes.
+    pub fn for_domain(cell_size: f32, domain: GridDomain) -> Self {
+        let mut hash = Self::new(cell_size);
+        if domain.periodic.any() {
+            hash.domain = domain;
+            hash.wrap_cells = (hash.domain.dims.as_vec2() * hash.inv_cell)
+                .ceil()
+                .as_ivec2();
         }
+        hash
     }
 
     /// Rebuild from the active partition `positions[0..active_count]`.
@@ -35,29 +65,59 @@ impl SpatialHash {
             v.clear();
         }
         for (i, &pos) in positions.iter().enumerate().take(active_count) {
-            let c = self.cell_of(pos);
+            let c = self.wrap(self.cell_of(pos));
             self.table.entry(c).or_default().push(i);
         }
     }
 
     /// Iterate candidate particle indices within `radius` of `center`.
     ///
-    /// Covers all cells whose bounding box overlaps the query circle.
+    /// Covers all cells whose bounding box overlaps the query circle, wrapping
+    /// across the seam on periodic axes. Each particle is yielded at most once.
     /// Caller is responsible for exact distance filtering.
     pub fn query(&self, center: Vec2, radius: f32) -> impl Iterator<Item = usize> + '_ {
         let r_cells = (radius * self.inv_cell).ceil() as i32;
         let (cx, cy) = self.cell_of(center);
+        let periodic = self.domain.periodic;
+        let (x0, x1) = Self::axis_range(cx, r_cells, periodic.x.then_some(self.wrap_cells.x));
+        let (y0, y1) = Self::axis_range(cy, r_cells, periodic.y.then_some(self.wrap_cells.y));
         SpatialHashIter {
             hash: self,
-            cx,
-            cy,
-            r_cells,
-            gx: -r_cells,
-            gy: -r_cells,
+            x1,
+            y0,
+            y1,
+            gx: x0,
+            gy: y0,
             bucket_pos: 0,
         }
     }
 
+    /// Displacement from `center` to `p` — the shortest one across the seam on
+    /// periodic axes (minimum image), the plain difference otherwise.
+    pub fn offset(&self, center: Vec2, p: Vec2) -> Vec2 {
+        if !self.domain.periodic.any() {
+            return p - center;
+        }
+        self.domain.nearest_image(p - center)
+    }
+
+    /// Inclusive bucket range along one axis around bucket `c`. On a periodic
+    /// axis (`wrap_cells` buckets per period) the range is widened by one bucket
+    /// (a partial last bucket can put a particle's seam image one bucket further
+    /// than its own), and capped at one full period so wrapped buckets are never
+    /// visited twice.
+    fn axis_range(c: i32, r_cells: i32, wrap_cells: Option<i32>) -> (i32, i32) {
+        let Some(wrap_cells) = wrap_cells else {
+            return (c - r_cells, c + r_cells);
+        };
+        let r = r_cells + 1;
+        if 2 * r + 1 >= wrap_cells {
+            (0, wrap_cells - 1)
+        } else {
+            (c - r, c + r)
+        }
+    }
+
     #[inline(always)]
     fn cell_of(&self, p: Vec2) -> (i32, i32) {
         (
@@ -65,13 +125,30 @@ impl SpatialHash {
             (p.y * self.inv_cell).floor() as i32,
         )
     }
+
+    /// Bucket key for a (possibly out-of-period) bucket coordinate.
+    #[inline(always)]
+    fn wrap(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        (
+            if self.domain.periodic.x {
+                x.rem_euclid(self.wrap_cells.x)
+            } else {
+                x
+            },
+            if self.domain.periodic.y {
+                y.rem_euclid(self.wrap_cells.y)
+            } else {
+                y
+            },
+        )
+    }
 }
 
 struct SpatialHashIter<'a> {
     hash: &'a SpatialHash,
-    cx: i32,
-    cy: i32,
-    r_cells: i32,
+    x1: i32,
+    y0: i32,
+    y1: i32,
     gx: i32,
     gy: i32,
     bucket_pos: usize,
@@ -82,10 +159,10 @@ impl<'a> Iterator for SpatialHashIter<'a> {
 
     fn next(&mut self) -> Option<usize> {
         loop {
-            if self.gx > self.r_cells {
+            if self.gx > self.x1 {
                 return None;
             }
-            let cell = (self.cx + self.gx, self.cy + self.gy);
+            let cell = self.hash.wrap((self.gx, self.gy));
             if let Some(bucket) = self.hash.table.get(&cell)
                 && self.bucket_pos < bucket.len()
             {
@@ -96,10 +173,42 @@ impl<'a> Iterator for SpatialHashIter<'a> {
             // Advance to next cell.
             self.bucket_pos = 0;
             self.gy += 1;
-            if self.gy > self.r_cells {
-                self.gy = -self.r_cells;
+            if self.gy > self.y1 {
+                self.gy = self.y0;
                 self.gx += 1;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn periodic_query_finds_neighbours_across_the_seam_once() {
+        let config = SimConfig {
+            grid_res: 32,
+            periodic_x: true,
+            ..SimConfig::default()
+        };
+        let positions = [
+            Vec2::new(0.5, 10.0),
+            Vec2::new(31.5, 10.0),
+            Vec2::new(16.0, 10.0),
+        ];
+        let mut hash = SpatialHash::for_config(&config);
+        hash.rebuild(&positions, positions.len());
+
+        let mut near: Vec<usize> = hash
+            .query(Vec2::new(31.8, 10.0), 1.5)
+            .filter(|&i| hash.offset(Vec2::new(31.8, 10.0), positions[i]).length() <= 1.5)
+            .collect();
+        near.sort_unstable();
+        assert_eq!(near, vec![0, 1]);
+
+        // A query wider than the domain visits every bucket exactly once.
+        let all: Vec<usize> = hash.query(Vec2::new(16.0, 10.0), 40.0).collect();
+        assert_eq!(all.len(), positions.len());
+    }
+}
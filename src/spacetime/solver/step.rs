@@ -8,13 +8,17 @@
 
 use glam::{Mat2, Vec2};
 
+use super::events::ParticleEventKind;
+use super::hook::{SubstepHook, SubstepInfo};
+use super::reaction::ReactionRecorder;
 use super::{MaterialRegistry, SimConfig, Simulation};
 use crate::boundary::BoundaryCondition;
-use crate::grid::Grid;
-use crate::particle::Particles;
-use crate::solver::density::estimate_particle_volumes;
+use crate::grid::{Grid, GridDomain};
+use crate::particle::{PIN_KINEMATIC, Particles};
+use crate::solver::density::{estimate_particle_volumes, estimate_particle_volumes_parallel};
 use crate::transfer::{
-    G2PParams, gather_contact_point_cloud, gather_grid_to_particles, scatter_particles_to_grid,
+    G2PParams, gather_contact_point_cloud, gather_contact_point_cloud_parallel,
+    gather_grid_to_particles, scatter_particles_to_grid, scatter_particles_to_grid_parallel,
 };
 
 impl Simulation {
@@ -26,12 +30,23 @@ impl Simulation {
         // Without this loop, the FixedStepController accounts for config.dt per call but the
         // simulation only advances sub_dt — causing it to run orders of magnitude too slowly.
         let step_start = std::time::Instant::now();
+        self.run_emitters();
+        self.wake_constrained_particles();
         let mut remaining = self.config.dt;
         let mut substeps_taken = 0;
         self.last_vel_clamp_count = 0;
         self.last_j_projection_count = 0;
         self.last_timing = crate::diagnostics::StepTiming::default();
+        for body in &mut self.rigid_bodies {
+            body.begin_step();
+        }
         while remaining > f32::EPSILON && substeps_taken < self.config.max_substeps_per_step {
+            // Scripted parameters and kinematic targets first, so this substep's
+            // dt choice sees them too.
+            self.apply_timeline();
+            if !self.kinematic_targets.is_empty() {
+                self.steer_kinematic_targets(remaining);
+            }
             // Cap sub-step at remaining time so we don't overshoot the configured frame dt.
             let t_cfl = std::time::Instant::now();
             let sub_dt = choose_substep_dt(
@@ -42,13 +57,23 @@ impl Simulation {
                 remaining,
             );
             self.last_timing.cfl_us += t_cfl.elapsed().as_micros() as u64;
-            self.do_substep(sub_dt);
+            self.do_substep(sub_dt, substeps_taken);
+            self.advance_timeline(sub_dt);
             remaining -= sub_dt;
             self.last_step_dt = sub_dt;
             substeps_taken += 1;
         }
         self.last_substeps = substeps_taken;
         self.last_sim_time_dropped = remaining.max(0.0);
+        self.finish_kinematic_targets();
+        self.finish_boundary_reactions(self.config.dt - self.last_sim_time_dropped);
+        for body in &mut self.rigid_bodies {
+            body.end_step(self.config.dt - self.last_sim_time_dropped);
+        }
+        self.run_sinks();
+        self.tracers.record_history();
+        self.record_stresses();
+        self.detect_fractures();
         // Rebuild once per step, not per substep — LP queries happen between step() calls,
         // never mid-substep, so one rebuild after the loop is sufficient and correct.
         let t_hash = std::time::Instant::now();
@@ -59,7 +84,13 @@ impl Simulation {
         self.frame_index = self.frame_index.saturating_add(1);
     }
 
-    fn do_substep(&mut self, sub_dt: f32) {
+    fn do_substep(&mut self, sub_dt: f32, substep: usize) {
+        // Moving boundaries (kinematic colliders) step their pose first, so this
+        // substep's grid BCs and position clamps see the pose it actually ends at.
+        for boundary in &self.boundaries {
+            boundary.advance(sub_dt);
+        }
+
         // Project invalid particle state before it can corrupt the grid scatter.
         // Running pre-P2G (not post) means a bad particle from a previous substep is
         // fixed before its momentum enters the grid — no NaN cascade possible.
@@ -68,6 +99,7 @@ impl Simulation {
             for i in 0..self.active_count {
                 if project_particle_state_to_admissible(&mut self.particles, i, &self.config) {
                     self.last_j_projection_count += 1;
+                    self.record_event(i, ParticleEventKind::Projected);
                 }
             }
         }
@@ -78,30 +110,62 @@ impl Simulation {
         // Manual override via config.recompute_density_each_step for edge cases.
         let t_density = std::time::Instant::now();
         if self.config.recompute_density_each_step || self.materials.any_needs_density_recompute() {
-            estimate_particle_volumes(
-                &mut self.particles,
-                &mut self.grid,
-                self.active_count,
-                false,
-            );
+            if self.config.parallel_p2g {
+                estimate_particle_volumes_parallel(
+                    &mut self.particles,
+                    &mut self.grid,
+                    &mut self.p2g_scratch,
+                    self.active_count,
+                    false,
+                );
+            } else {
+                estimate_particle_volumes(
+                    &mut self.particles,
+                    &mut self.grid,
+                    self.active_count,
+                    false,
+                );
+            }
         }
         self.last_timing.density_us += t_density.elapsed().as_micros() as u64;
 
+        // A moving rigid body has no grid mass of its own, so the active-cell wake
+        // scan below can't see it coming: wake sleeping material in its path first.
+        if !self.rigid_bodies.is_empty() && self.active_count < self.particles.len() {
+            self.wake_particles_near_moving_bodies();
+        }
+
+        self.run_substep_hooks(sub_dt, substep, |hook, particles, _, info| {
+            hook.pre_p2g(particles, info)
+        });
+
         // ── P2G ──────────────────────────────────────────────────────────────
         let t0 = std::time::Instant::now();
         self.grid.clear();
-        scatter_particles_to_grid(
-            &self.particles,
-            &mut self.grid,
-            &self.materials,
-            sub_dt,
-            self.active_count,
-        );
         // Second particle pass for the contact-normal point cloud (see
-        // `gather_contact_point_cloud` doc) -- must run after the above, since
+        // `gather_contact_point_cloud` doc) -- must run after the scatter, since
         // contact-active nodes aren't fully known until every grip particle's mass
         // has been scattered. No-op when `contact_group` is unused anywhere.
-        gather_contact_point_cloud(&self.particles, &mut self.grid, self.active_count);
+        if self.config.parallel_p2g {
+            scatter_particles_to_grid_parallel(
+                &self.particles,
+                &mut self.grid,
+                &mut self.p2g_scratch,
+                &self.materials,
+                sub_dt,
+                self.active_count,
+            );
+            gather_contact_point_cloud_parallel(&self.particles, &mut self.grid, self.active_count);
+        } else {
+            scatter_particles_to_grid(
+                &self.particles,
+                &mut self.grid,
+                &self.materials,
+                sub_dt,
+                self.active_count,
+            );
+            gather_contact_point_cloud(&self.particles, &mut self.grid, self.active_count);
+        }
         self.last_timing.p2g_us += t0.elapsed().as_micros() as u64;
 
         // Wake any sleeping particle whose kernel overlaps an active grid cell.
@@ -146,10 +210,15 @@ impl Simulation {
             self.grid.update_velocities(sub_dt, self.config.gravity);
             None
         };
-        let grid_res = self.grid.resolution();
-        for boundary in &self.boundaries {
-            apply_boundary_conditions_to_grid(&mut self.grid, grid_res, boundary.as_ref());
-        }
+        let domain = self.grid.domain();
+        apply_boundary_conditions_to_grid(
+            &mut self.grid,
+            domain,
+            &self.boundaries,
+            self.reactions
+                .as_deref_mut()
+                .map(|recorder| (recorder, self.config.boundary_thickness)),
+        );
         // Clamp grid velocity before G2P — bounds both v_p and C_p at the source.
         // Post-G2P clamping misses C_p: large C_p → F = (I + dt·C)·F blows up → J→0.
         let vel_limit = self.config.grid_cell_size / sub_dt;
@@ -189,7 +258,22 @@ impl Simulation {
             self.config.grid_cell_size,
             self.config.mixture_pressure_iterations,
         );
+        // Rigid-body coupling last, so bodies exchange momentum with the final
+        // grid velocity G2P will read. Bodies integrate right after, at the pose
+        // the grid was just made consistent with.
+        if !self.rigid_bodies.is_empty() {
+            let domain = self.grid.domain();
+            let lo = Vec2::splat(self.config.boundary_thickness as f32);
+            let hi = domain.dims.as_vec2() - lo;
+            for body in &mut self.rigid_bodies {
+                body.couple_to_grid(&mut self.grid, sub_dt, self.config.gravity);
+                body.integrate(sub_dt, lo, hi, domain);
+            }
+        }
         self.last_timing.grid_update_us += t1.elapsed().as_micros() as u64;
+        self.run_substep_hooks(sub_dt, substep, |hook, _, grid, info| {
+            hook.post_grid_update(grid, info)
+        });
 
         // ── G2P ──────────────────────────────────────────────────────────────
         let t2 = std::time::Instant::now();
@@ -207,8 +291,22 @@ impl Simulation {
                 pre_force_snapshot: asflip_snapshot.as_ref(),
             },
         );
+        for body in &self.rigid_bodies {
+            body.project_particles(&mut self.particles, self.active_count, self.grid.domain());
+        }
         self.last_timing.g2p_us += t2.elapsed().as_micros() as u64;
 
+        // Tracers read the same post-update grid velocities G2P just gathered.
+        if !self.tracers.is_empty() {
+            let t_tracers = std::time::Instant::now();
+            self.tracers
+                .advect(&self.grid, sub_dt, self.config.boundary_thickness);
+            self.last_timing.tracers_us += t_tracers.elapsed().as_micros() as u64;
+        }
+        self.run_substep_hooks(sub_dt, substep, |hook, particles, _, info| {
+            hook.post_g2p(particles, info)
+        });
+
         // ── Force fields ──────────────────────────────────────────────────────
         // External body force fields: v += dt × acceleration(p) per particle.
         // Applied after G2P so each field sees the fully gathered particle state.
@@ -217,7 +315,10 @@ impl Simulation {
         // momentum — the clamp re-asserts the CFL contract after external perturbation.
         // prepare() is called first so stateful fields (e.g. Barnes-Hut tree) can
         // rebuild their internal state from the current particle snapshot.
-        if !self.force_fields.is_empty() {
+        //
+        // Constraint springs (`solver::constraint`) are forces too and go through
+        // the same clamp.
+        if !self.force_fields.is_empty() || !self.constraints.is_empty() {
             let t3 = std::time::Instant::now();
             let mut fields = std::mem::take(&mut self.force_fields);
             for (_, field) in &mut fields {
@@ -244,6 +345,7 @@ impl Simulation {
                 self.particles.v[i] += sub_dt * dv;
             }
             self.force_fields = fields;
+            self.apply_constraints(sub_dt);
             // Re-clamp velocity after force fields — large external impulses (explosions,
             // creature bursts, planetary impacts) must not enter P2G with >1 cell/substep.
             let vel_limit = self.config.grid_cell_size / sub_dt;
@@ -279,6 +381,13 @@ impl Simulation {
                 for rule in &rules {
                     if let Some(new_id) = rule(&p) {
                         self.particles.material_id[i] = new_id;
+                        self.record_event(
+                            i,
+                            ParticleEventKind::PhaseChanged {
+                                from: p.material_id,
+                                to: new_id,
+                            },
+                        );
                         let latent_heat = self.materials.get(new_id).latent_heat();
                         if let (true, Some(cp)) = (latent_heat != 0.0, heat_capacity) {
                             self.particles.temperature[i] -= latent_heat / cp;
@@ -302,7 +411,10 @@ impl Simulation {
             self.scratch_indices.clear();
             self.scratch_indices
                 .extend((0..self.active_count).filter(|&i| {
+                    // A kinematic particle at rest is waiting for its next
+                    // prescribed velocity, not settled.
                     self.particles.activation[i] == 0.0
+                        && self.particles.pinned[i] != PIN_KINEMATIC
                         && self.particles.v[i].length_squared() < threshold_sq
                 }));
             // Descending order: sleep_particle swaps i↔last_active (high end of active zone).
@@ -314,6 +426,56 @@ impl Simulation {
             }
         }
         self.last_timing.phase_sleep_us += t5.elapsed().as_micros() as u64;
+        self.run_substep_hooks(sub_dt, substep, |hook, particles, _, info| {
+            hook.end_of_substep(particles, info)
+        });
+    }
+
+    /// Call `stage` on every substep hook, in registration order.
+    fn run_substep_hooks(
+        &mut self,
+        sub_dt: f32,
+        substep: usize,
+        mut stage: impl FnMut(&mut dyn SubstepHook, &mut Particles, &mut Grid, SubstepInfo),
+    ) {
+        if self.substep_hooks.is_empty() {
+            return;
+        }
+        let t = std::time::Instant::now();
+        let info = SubstepInfo {
+            sub_dt,
+            substep,
+            active_count: self.active_count,
+        };
+        for hook in &mut self.substep_hooks {
+            stage(hook.as_mut(), &mut self.particles, &mut self.grid, info);
+        }
+        self.last_timing.hooks_us += t.elapsed().as_micros() as u64;
+    }
+
+    fn wake_particles_near_moving_bodies(&mut self) {
+        // Same margin as the wake scan's kernel reach: 2 cells past the surface.
+        const WAKE_MARGIN: f32 = 2.0;
+        let threshold = self.config.sleep_threshold;
+        let domain = self.grid.domain();
+        self.scratch_indices.clear();
+        for body in self.rigid_bodies.iter().filter(|b| b.is_moving(threshold)) {
+            for i in self.active_count..self.particles.len() {
+                let x = body.image_near(self.particles.x[i], domain);
+                if body.may_touch(x, WAKE_MARGIN) && body.distance_and_normal(x).0 < WAKE_MARGIN {
+                    self.scratch_indices.push(i);
+                }
+            }
+        }
+        // Ascending, deduplicated (bodies can overlap): waking swaps `i` with the
+        // first sleeping slot, which is below every not-yet-visited candidate --
+        // same reasoning as `wake_tag`.
+        self.scratch_indices.sort_unstable();
+        self.scratch_indices.dedup();
+        for j in 0..self.scratch_indices.len() {
+            let i = self.scratch_indices[j];
+            self.wake_particle(i);
+        }
     }
 
     pub fn effective_dt(&self) -> f32 {
@@ -324,6 +486,11 @@ impl Simulation {
         self.last_substeps
     }
 
+    /// Total frames stepped since creation (or since the restored checkpoint's frame).
+    pub fn frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
     pub fn step_n(&mut self, steps: usize) {
         for _ in 0..steps {
             self.step();
@@ -331,14 +498,24 @@ impl Simulation {
     }
 }
 
+/// Every boundary, in order, on each massive node. With `reactions` (and the
+/// wall thickness), also records the velocity change they made together (see
+/// `solver::reaction`).
 fn apply_boundary_conditions_to_grid(
     grid: &mut Grid,
-    grid_res: usize,
-    boundary: &dyn BoundaryCondition,
+    domain: GridDomain,
+    boundaries: &[Box<dyn BoundaryCondition>],
+    mut reactions: Option<(&mut ReactionRecorder, usize)>,
 ) {
     for (i, cell) in grid.active_cells_with_index_mut() {
         if cell.mass > 0.0 {
-            boundary.apply_to_grid_velocity(i, grid_res, &mut cell.momentum);
+            let before = cell.momentum;
+            for boundary in boundaries {
+                boundary.apply_to_grid_velocity_in(i, domain, &mut cell.momentum);
+            }
+            if let Some((recorder, thickness)) = &mut reactions {
+                recorder.record(domain, *thickness, i, cell.mass, before, cell.momentum);
+            }
         }
     }
 }
@@ -350,15 +527,19 @@ fn project_particle_state_to_admissible(
     config: &SimConfig,
 ) -> bool {
     let mut projected = false;
-    let min = config.boundary_thickness.saturating_sub(1) as f32;
-    let max = config.grid_res.saturating_sub(config.boundary_thickness) as f32;
-    let domain_center = Vec2::splat((min + max) * 0.5);
+    let min = Vec2::splat(config.boundary_thickness.saturating_sub(1) as f32);
+    let max = config
+        .grid_dims()
+        .saturating_sub(glam::UVec2::splat(config.boundary_thickness as u32))
+        .as_vec2();
+    let domain_center = (min + max) * 0.5;
 
     if !particles.x[i].is_finite() {
         particles.x[i] = domain_center;
         projected = true;
     } else {
-        particles.x[i] = particles.x[i].clamp(Vec2::splat(min), Vec2::splat(max));
+        let clamped = particles.x[i].clamp(min, max);
+        particles.x[i] = Vec2::select(config.periodic_axes(), particles.x[i], clamped);
     }
 
     if !particles.v[i].is_finite() {
@@ -0,0 +1,214 @@
+//! Per-particle stress and strain export.
+//!
+//! P2G evaluates every particle's Kirchhoff stress each substep and keeps only
+//! the grid forces. With `Simulation::enable_stress_export` the solver also
+//! records each particle's Cauchy stress at the end of every `step()`, from the
+//! same `combined_kirchhoff_stress` P2G uses (passive + active). It is the
+//! stress of the particles' state at the end of the step, after the last
+//! G2P: the stress the next substep's P2G starts from, not the one that drove
+//! the step just taken.
+//!
+//! Cauchy stress is force per current area: `σ = τ·V_s / (V₀·J)`, where `V_s`
+//! is the material's `stress_volume` (normally `V₀`, giving the textbook
+//! `σ = τ/J`). Units are the simulation's stress units, same scale as λ/µ.
+//! [`StressState`] derives the usual invariants and the strain measures that go
+//! with them. Off by default; like events, the export is output rather than
+//! state and is not checkpointed.
+
+use glam::{IVec2, Mat2};
+use rayon::prelude::*;
+
+use super::Simulation;
+use crate::grid::GridDomain;
+use crate::grid::kernel::quadratic_weights;
+use crate::materials::MaterialModel;
+use crate::materials::svd::svd2;
+use crate::materials::utils::hencky_strains;
+use crate::particle::Particles;
+use crate::transfer::combined_kirchhoff_stress;
+
+/// One particle's stress and strain, derived from its Cauchy stress,
+/// deformation gradient and plastic volume ratio.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StressState {
+    pub cauchy: Mat2,
+    /// Mean compressive stress `−tr(σ)/2`; positive in compression.
+    pub pressure: f32,
+    /// `√(3/2 · s:s)` with `s` the 2D deviator `σ − tr(σ)/2·I`. Zero under
+    /// pure pressure, `√3·τ` under pure shear `τ`.
+    pub von_mises: f32,
+    /// Largest principal stress; positive in tension (what brittle fracture
+    /// criteria compare against a tensile strength).
+    pub max_principal: f32,
+    /// `ln J` of the (elastic) deformation gradient: Hencky volumetric strain.
+    pub elastic_volumetric_strain: f32,
+    /// `|dev(ε)|` of the Hencky strain of `F` — the measure J2 plasticity
+    /// (`VonMisesMaterial`) yields on.
+    pub elastic_deviatoric_strain: f32,
+    /// `ln Jₚ` from `plastic_volume_ratio`: negative after plastic compaction.
+    pub plastic_volumetric_strain: f32,
+}
+
+impl StressState {
+    pub fn new(cauchy: Mat2, deformation_gradient: Mat2, plastic_volume_ratio: f32) -> Self {
+        let (xx, yy) = (cauchy.x_axis.x, cauchy.y_axis.y);
+        let xy = 0.5 * (cauchy.y_axis.x + cauchy.x_axis.y);
+        let mean = 0.5 * (xx + yy);
+        // Radius of Mohr's circle: half the principal stress difference.
+        let radius = (0.25 * (xx - yy).powi(2) + xy * xy).sqrt();
+        let (_, sigma, _) = svd2(deformation_gradient);
+        let strain = hencky_strains(sigma);
+        Self {
+            cauchy,
+            pressure: -mean,
+            von_mises: 3f32.sqrt() * radius,
+            max_principal: mean + radius,
+            elastic_volumetric_strain: strain.x + strain.y,
+            elastic_deviatoric_strain: (strain.x - strain.y).abs()
+                * std::f32::consts::FRAC_1_SQRT_2,
+            plastic_volumetric_strain: plastic_volume_ratio.max(f32::MIN_POSITIVE).ln(),
+        }
+    }
+}
+
+/// Cauchy stress of particle `i` under `material`. See the module doc.
+pub(crate) fn cauchy_stress(material: &dyn MaterialModel, particles: &Particles, i: usize) -> Mat2 {
+    let tau = combined_kirchhoff_stress(material, particles, i);
+    let current_volume =
+        particles.initial_volume[i] * particles.deformation_gradient[i].determinant();
+    if current_volume.abs() > f32::EPSILON {
+        tau * (material.stress_volume(particles, i) / current_volume)
+    } else {
+        Mat2::ZERO
+    }
+}
+
+/// Mass-weighted node stress `Σ w·m·σ / Σ w·m` on `domain`, laid out like
+/// `compute_density_grid_in` (`x * height + y`); zero away from material.
+/// `stresses[i]` belongs to particle `i`; extra particles are skipped.
+pub fn rasterize_stress(particles: &Particles, stresses: &[Mat2], domain: GridDomain) -> Vec<Mat2> {
+    let cells = domain.cell_count();
+    let mut mass = vec![0.0f32; cells];
+    let mut weighted = vec![Mat2::ZERO; cells];
+    for (i, &stress) in stresses.iter().enumerate().take(particles.len()) {
+        let w = quadratic_weights(particles.x[i]);
+        for gx in 0..3 {
+            for gy in 0..3 {
+                let cell = w.base_cell + IVec2::new(gx as i32 - 1, gy as i32 - 1);
+                let Some(idx) = domain.flat_index(cell) else {
+                    continue;
+                };
+                let wm = w.wx[gx] * w.wy[gy] * particles.mass[i];
+                mass[idx as usize] += wm;
+                weighted[idx as usize] += stress * wm;
+            }
+        }
+    }
+    weighted
+        .into_iter()
+        .zip(mass)
+        .map(|(s, m)| {
+            if m > f32::EPSILON {
+                s * (1.0 / m)
+            } else {
+                Mat2::ZERO
+            }
+        })
+        .collect()
+}
+
+impl Simulation {
+    /// Record every particle's Cauchy stress at the end of each `step()`,
+    /// starting with the current state. No-op when already enabled.
+    pub fn enable_stress_export(&mut self) {
+        if self.stresses.is_none() {
+            self.stresses = Some(Vec::new());
+            self.record_stresses();
+        }
+    }
+
+    /// Builder-style [`Self::enable_stress_export`].
+    pub fn with_stress_export(mut self) -> Self {
+        self.enable_stress_export();
+        self
+    }
+
+    pub fn disable_stress_export(&mut self) {
+        self.stresses = None;
+    }
+
+    pub fn stress_export_enabled(&self) -> bool {
+        self.stresses.is_some()
+    }
+
+    /// Cauchy stress per particle (active and sleeping) as of the last step,
+    /// indexed like `particles()`. Empty while the export is disabled. Adding,
+    /// removing or waking particles between steps shifts indices until the
+    /// next step refreshes it.
+    pub fn particle_stresses(&self) -> &[Mat2] {
+        self.stresses.as_deref().unwrap_or_default()
+    }
+
+    /// Stress invariants and strain measures of particle `i`, or `None` when
+    /// the export is disabled or `i` was added since the last step.
+    pub fn particle_stress(&self, i: usize) -> Option<StressState> {
+        let cauchy = *self.stresses.as_ref()?.get(i)?;
+        Some(StressState::new(
+            cauchy,
+            self.particles.deformation_gradient[i],
+            self.particles.plastic_volume_ratio[i],
+        ))
+    }
+
+    /// The recorded stresses rasterized onto the simulation grid (see
+    /// [`rasterize_stress`]), or `None` when the export is disabled.
+    pub fn stress_grid(&self) -> Option<Vec<Mat2>> {
+        let stresses = self.stresses.as_ref()?;
+        Some(rasterize_stress(
+            &self.particles,
+            stresses,
+            self.grid.domain(),
+        ))
+    }
+
+    /// Refresh the export from the current particle state (no-op while disabled).
+    pub(super) fn record_stresses(&mut self) {
+        let Some(stresses) = &mut self.stresses else {
+            return;
+        };
+        let particles = &self.particles;
+        let materials = &self.materials;
+        (0..particles.len())
+            .into_par_iter()
+            .map(|i| cauchy_stress(materials.get(particles.material_id[i]), particles, i))
+            .collect_into_vec(stresses);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+
+    #[test]
+    fn invariants_separate_pressure_from_shear() {
+        let pressure =
+            StressState::new(Mat2::from_diagonal(Vec2::splat(-3.0)), Mat2::IDENTITY, 1.0);
+        assert!((pressure.pressure - 3.0).abs() < 1e-6);
+        assert!(pressure.von_mises.abs() < 1e-6);
+        assert!((pressure.max_principal + 3.0).abs() < 1e-6);
+
+        let shear = Mat2::from_cols(Vec2::new(0.0, 2.0), Vec2::new(2.0, 0.0));
+        let shear = StressState::new(shear, Mat2::IDENTITY, 1.0);
+        assert!(shear.pressure.abs() < 1e-6);
+        assert!((shear.von_mises - 2.0 * 3f32.sqrt()).abs() < 1e-5);
+        assert!((shear.max_principal - 2.0).abs() < 1e-6);
+
+        // Uniform 10% stretch in x: ln(1.1) volumetric, ln(1.1)/√2 deviatoric.
+        let stretched = StressState::new(Mat2::ZERO, Mat2::from_diagonal(Vec2::new(1.1, 1.0)), 0.8);
+        let e = 1.1f32.ln();
+        assert!((stretched.elastic_volumetric_strain - e).abs() < 1e-5);
+        assert!((stretched.elastic_deviatoric_strain - e / 2f32.sqrt()).abs() < 1e-5);
+        assert!((stretched.plastic_volumetric_strain - 0.8f32.ln()).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,395 @@
+//! Keyframed parameter animation: scripted gravity, config, thermal, force
+//! field and material changes.
+//!
+//! A [`Timeline`] is a set of [`Track`]s, each bound to one simulation
+//! parameter. The simulation owns one (`Simulation::set_timeline`,
+//! `with_timeline`) and evaluates it at the start of every substep, before the
+//! substep's CFL choice, at the timeline's own clock; the clock then advances
+//! by the substep's `dt`. A day/night cycle, an earthquake or a tide is then
+//! part of the scene rather than of the game loop, and replays identically
+//! from the same start.
+//!
+//! Tracks hold `(time, value)` keys in seconds of simulated time. Before the
+//! first key a track holds the first value, after the last it holds the last,
+//! and a [`Track::looping`] track repeats with its period. Channels whose
+//! target is missing (no thermal model, a force field that was removed) do
+//! nothing. A misspelt name is not a missing target:
+//! `Simulation::check_timeline` reports field names that match no registered
+//! field, and a parameter name the field rejects fails a debug assertion when
+//! the channel is applied. A timeline overwrites its parameters every substep, so a value set
+//! by hand on an animated parameter lasts until the next substep. Like force
+//! fields, the tracks are configuration and are not checkpointed; the clock
+//! is, and restoring a checkpoint seeks it to the saved time.
+
+use std::ops::{Add, Mul};
+
+use glam::Vec2;
+
+use super::Simulation;
+use super::config::SimConfig;
+use crate::materials::MaterialModel;
+
+/// How a track moves between two keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Hold each key's value until the next key.
+    Step,
+    #[default]
+    Linear,
+    /// Ease in and out of every key (`3s² − 2s³`): no velocity jump at keys.
+    Smoothstep,
+}
+
+impl Interpolation {
+    /// Blend weight of the next key at fraction `s` of the way there.
+    fn weight(self, s: f32) -> f32 {
+        match self {
+            Self::Step => 0.0,
+            Self::Linear => s,
+            Self::Smoothstep => s * s * (3.0 - 2.0 * s),
+        }
+    }
+}
+
+/// Keyframes of one parameter over time (`f32` or `Vec2`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<T> {
+    /// Sorted by time; keys at equal times keep `key` order (a jump).
+    keys: Vec<(f32, T)>,
+    interpolation: Interpolation,
+    period: Option<f32>,
+}
+
+impl<T> Track<T>
+where
+    T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+{
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            keys: Vec::new(),
+            interpolation,
+            period: None,
+        }
+    }
+
+    /// Add a key. Keys may be added in any order; a second key at the same
+    /// time makes the value jump there.
+    pub fn key(mut self, time: f32, value: T) -> Self {
+        let at = self.keys.partition_point(|&(t, _)| t <= time);
+        self.keys.insert(at, (time, value));
+        self
+    }
+
+    /// Repeat every `period` seconds: the track is sampled at `time mod period`.
+    pub fn looping(mut self, period: f32) -> Self {
+        assert!(period > 0.0, "loop period must be positive, got {period}");
+        self.period = Some(period);
+        self
+    }
+
+    /// The track's value at `time`, or `None` when it has no keys.
+    pub fn sample(&self, time: f32) -> Option<T> {
+        self.sample_clock(f64::from(time))
+    }
+
+    /// [`Self::sample`] at the timeline's `f64` clock. The loop wraps before
+    /// narrowing, so a long-running loop keeps sub-step resolution.
+    fn sample_clock(&self, time: f64) -> Option<T> {
+        let time = self
+            .period
+            .map_or(time, |period| time.rem_euclid(f64::from(period))) as f32;
+        let next = self.keys.partition_point(|&(t, _)| t <= time);
+        let (t1, v1) = *self.keys.get(next).or(self.keys.last())?;
+        let Some(&(t0, v0)) = next.checked_sub(1).map(|i| &self.keys[i]) else {
+            return Some(v1);
+        };
+        if next == self.keys.len() || t1 <= t0 {
+            return Some(v0);
+        }
+        let w = self.interpolation.weight((time - t0) / (t1 - t0));
+        Some(v0 * (1.0 - w) + v1 * w)
+    }
+}
+
+type ConfigSetter = Box<dyn Fn(&mut SimConfig, f32) + Send + Sync>;
+type MaterialBuilder = Box<dyn Fn(f32) -> Box<dyn MaterialModel> + Send + Sync>;
+
+/// One animated parameter.
+enum Channel {
+    Gravity(Track<Vec2>),
+    Config(Track<f32>, ConfigSetter),
+    ThermalAmbient(Track<f32>),
+    FieldParameter {
+        field: String,
+        parameter: String,
+        track: Track<f32>,
+    },
+    Material {
+        material_id: u32,
+        track: Track<f32>,
+        build: MaterialBuilder,
+        /// Value the registered material was last built from.
+        built: Option<f32>,
+    },
+}
+
+/// Tracks bound to simulation parameters, and the clock they run on. See the
+/// module doc.
+#[derive(Default)]
+pub struct Timeline {
+    channels: Vec<Channel>,
+    /// `f64` so that adding a small `dt` still moves it after days of
+    /// simulated time.
+    time: f64,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Animate `SimConfig::gravity`.
+    pub fn gravity(mut self, track: Track<Vec2>) -> Self {
+        self.channels.push(Channel::Gravity(track));
+        self
+    }
+
+    /// Animate any `SimConfig` scalar: `apply` writes the track's value, e.g.
+    /// `|config, mu| config.contact_friction = mu`.
+    pub fn config(
+        mut self,
+        track: Track<f32>,
+        apply: impl Fn(&mut SimConfig, f32) + Send + Sync + 'static,
+    ) -> Self {
+        self.channels.push(Channel::Config(track, Box::new(apply)));
+        self
+    }
+
+    /// Animate the thermal model's `ThermalConfig::ambient`.
+    pub fn thermal_ambient(mut self, track: Track<f32>) -> Self {
+        self.channels.push(Channel::ThermalAmbient(track));
+        self
+    }
+
+    /// Animate `parameter` of the force field registered as `field` (see
+    /// `Field::set_parameter` for the names).
+    pub fn field_parameter(
+        mut self,
+        field: impl Into<String>,
+        parameter: impl Into<String>,
+        track: Track<f32>,
+    ) -> Self {
+        self.channels.push(Channel::FieldParameter {
+            field: field.into(),
+            parameter: parameter.into(),
+            track,
+        });
+        self
+    }
+
+    /// Animate a material parameter: whenever the track's value changes,
+    /// `build` makes the material for it and replaces `material_id`, e.g.
+    /// `|mu| Box::new(NeoHookeanMaterial::new(lambda, mu))`.
+    pub fn material(
+        mut self,
+        material_id: u32,
+        track: Track<f32>,
+        build: impl Fn(f32) -> Box<dyn MaterialModel> + Send + Sync + 'static,
+    ) -> Self {
+        self.channels.push(Channel::Material {
+            material_id,
+            track,
+            build: Box::new(build),
+            built: None,
+        });
+        self
+    }
+
+    /// Seconds of simulated time since the timeline started.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Move the clock to `time`; the next substep evaluates there.
+    pub fn seek(&mut self, time: f64) {
+        self.time = time;
+    }
+
+    /// Whether a material channel replaces `material_id`.
+    pub(super) fn animates_material(&self, material_id: u32) -> bool {
+        self.channels.iter().any(|channel| {
+            matches!(channel, Channel::Material { material_id: id, .. } if *id == material_id)
+        })
+    }
+
+    /// Number of animated parameters.
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+}
+
+impl Simulation {
+    /// The simulation's timeline. Empty by default.
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    pub fn timeline_mut(&mut self) -> &mut Timeline {
+        &mut self.timeline
+    }
+
+    /// Replace the timeline; it takes effect from the next substep.
+    pub fn set_timeline(&mut self, timeline: Timeline) {
+        self.timeline = timeline;
+    }
+
+    /// Builder-style [`Self::set_timeline`].
+    pub fn with_timeline(mut self, timeline: Timeline) -> Self {
+        self.set_timeline(timeline);
+        self
+    }
+
+    /// Check that every field-parameter channel names a registered force
+    /// field. Call it once the fields are added (a timeline may be set before
+    /// them); the error lists the unknown names.
+    pub fn check_timeline(&self) -> Result<(), String> {
+        let unknown: Vec<&str> = self
+            .timeline
+            .channels
+            .iter()
+            .filter_map(|channel| match channel {
+                Channel::FieldParameter { field, .. }
+                    if !self.force_fields.iter().any(|(n, _)| n == field) =>
+                {
+                    Some(field.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "timeline animates unregistered force fields {unknown:?} (registered: {:?})",
+                self.force_field_names()
+            ))
+        }
+    }
+
+    /// Write every animated parameter for the timeline's current time.
+    pub(super) fn apply_timeline(&mut self) {
+        let time = self.timeline.time;
+        for channel in &mut self.timeline.channels {
+            match channel {
+                Channel::Gravity(track) => {
+                    if let Some(gravity) = track.sample_clock(time) {
+                        self.config.gravity = gravity;
+                    }
+                }
+                Channel::Config(track, apply) => {
+                    if let Some(value) = track.sample_clock(time) {
+                        apply(&mut self.config, value);
+                    }
+                }
+                Channel::ThermalAmbient(track) => {
+                    if let (Some(ambient), Some(thermal)) =
+                        (track.sample_clock(time), &mut self.thermal)
+                    {
+                        thermal.config.ambient = ambient;
+                    }
+                }
+                Channel::FieldParameter {
+                    field,
+                    parameter,
+                    track,
+                } => {
+                    let Some(value) = track.sample_clock(time) else {
+                        continue;
+                    };
+                    if let Some((_, target)) =
+                        self.force_fields.iter_mut().find(|(n, _)| n == field)
+                    {
+                        let accepted = target.set_parameter(parameter, value);
+                        debug_assert!(
+                            accepted,
+                            "timeline: force field `{field}` has no parameter `{parameter}`"
+                        );
+                    }
+                }
+                Channel::Material {
+                    material_id,
+                    track,
+                    build,
+                    built,
+                } => {
+                    if let Some(value) = track.sample_clock(time)
+                        && *built != Some(value)
+                    {
+                        self.materials.insert(*material_id, build(value));
+                        *built = Some(value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Run the timeline's clock forward over a finished substep.
+    pub(super) fn advance_timeline(&mut self, dt: f32) {
+        self.timeline.time += f64::from(dt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_hold_ends_jump_ease_and_loop() {
+        let linear = Track::new(Interpolation::Linear)
+            .key(2.0, 10.0)
+            .key(0.0, 0.0)
+            .key(2.0, 20.0)
+            .key(3.0, 30.0);
+        assert_eq!(linear.sample(-1.0), Some(0.0));
+        assert_eq!(linear.sample(1.5), Some(7.5));
+        // Two keys at t = 2: arrive at the first, leave from the second.
+        assert_eq!(linear.sample(2.0), Some(20.0));
+        assert_eq!(linear.sample(2.5), Some(25.0));
+        assert_eq!(linear.sample(9.0), Some(30.0));
+
+        let step = Track::new(Interpolation::Step).key(0.0, 1.0).key(1.0, 2.0);
+        assert_eq!(step.sample(0.99), Some(1.0));
+        assert_eq!(step.sample(1.0), Some(2.0));
+
+        let eased = Track::new(Interpolation::Smoothstep)
+            .key(0.0, Vec2::ZERO)
+            .key(1.0, Vec2::ONE)
+            .looping(2.0);
+        assert_eq!(eased.sample(0.25), Some(Vec2::splat(0.15625)));
+        assert_eq!(eased.sample(4.5), Some(Vec2::splat(0.5)));
+        assert_eq!(eased.sample(-0.5), Some(Vec2::ONE));
+
+        assert_eq!(Track::<f32>::new(Interpolation::Linear).sample(0.0), None);
+    }
+
+    #[test]
+    fn clock_keeps_resolution_after_a_long_run() {
+        let saw = Track::new(Interpolation::Linear)
+            .key(0.0, 0.0)
+            .key(1.0, 1.0)
+            .looping(1.0);
+        // 1e7 + 0.25 s has no f32 representation closer than a whole second.
+        assert_eq!(saw.sample_clock(1e7 + 0.25), Some(0.25));
+
+        let mut sim = Simulation::empty(SimConfig::default());
+        sim.timeline_mut().seek(1e7);
+        for _ in 0..4 {
+            sim.advance_timeline(0.0625);
+        }
+        assert_eq!(sim.timeline().time(), 1e7 + 0.25);
+    }
+}
@@ -0,0 +1,54 @@
+//! Spawn, removal and readback of passive tracers (see `matter::tracer`).
+
+use std::ops::Range;
+
+use glam::Vec2;
+
+use super::Simulation;
+use crate::matter::tracer::Tracers;
+
+impl Simulation {
+    /// Add a tracer at each of `positions`, all with drag lag `response_time`
+    /// (seconds, 0 = follow the flow exactly). Returns their index range.
+    pub fn add_tracers(
+        &mut self,
+        positions: impl IntoIterator<Item = Vec2>,
+        response_time: f32,
+    ) -> Range<usize> {
+        let start = self.tracers.len();
+        for x in positions {
+            self.tracers.push(x, response_time);
+        }
+        start..self.tracers.len()
+    }
+
+    /// Remove every tracer whose position satisfies `predicate` (e.g. it left
+    /// the region of interest). Stable: the rest keep their order. Returns the
+    /// count removed.
+    pub fn remove_tracers(&mut self, predicate: impl Fn(Vec2) -> bool) -> usize {
+        let before = self.tracers.len();
+        self.tracers.retain(|x| !predicate(x));
+        before - self.tracers.len()
+    }
+
+    pub fn clear_tracers(&mut self) {
+        self.tracers.clear();
+    }
+
+    /// Tracer positions, velocities and history.
+    pub fn tracers(&self) -> &Tracers {
+        &self.tracers
+    }
+
+    /// Record the last `len` positions of every tracer, one per `step()`, for
+    /// drawing streaklines (0 turns history off). Resets what was recorded.
+    pub fn set_tracer_history_len(&mut self, len: usize) {
+        self.tracers.set_history_len(len);
+    }
+
+    /// Builder-style [`Self::set_tracer_history_len`].
+    pub fn with_tracer_history(mut self, len: usize) -> Self {
+        self.set_tracer_history_len(len);
+        self
+    }
+}
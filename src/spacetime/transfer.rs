@@ -21,8 +21,9 @@ pub use g2p::{
     G2PParams, f_update_vjp, g2p_affine_vjp, g2p_velocity_vjp, gather_grid_to_particles,
 };
 pub use p2g::{
-    P2GParticleState, gather_contact_point_cloud, p2g_position_vjp, p2g_stress_vjp,
-    scatter_particle_mass, scatter_particles_to_grid,
+    P2GParticleState, P2GScratch, gather_contact_point_cloud, gather_contact_point_cloud_parallel,
+    p2g_position_vjp, p2g_stress_vjp, scatter_particle_mass, scatter_particle_mass_parallel,
+    scatter_particles_to_grid, scatter_particles_to_grid_parallel,
 };
 
 // The two test modules' `use super::*;` see every item re-exported above
@@ -5,7 +5,7 @@ use crate::boundary::BoundaryCondition;
 use crate::grid::Grid;
 use crate::grid::kernel::quadratic_weights;
 use crate::materials::registry::MaterialRegistry;
-use crate::particle::Particles;
+use crate::particle::{PIN_KINEMATIC, Particles};
 use crate::solver::config::KERNEL_D_INVERSE;
 
 pub struct G2PParams<'a> {
@@ -163,7 +163,10 @@ pub fn gather_grid_to_particles(
         asflip_blend,
         pre_force_snapshot,
     } = params;
-    let grid_res = grid.resolution();
+    let domain = grid.domain();
+    // Periodic axes (`SimConfig::periodic_x`/`periodic_y`): boundaries skip their
+    // walls there and the advected position wraps back into the domain instead.
+    let any_periodic = domain.periodic.any();
 
     // Phase 1 (parallel): grid gather -> v, velocity_gradient, position advance + boundary
     // position clamp. Pure math over read-only grid/boundary state, writing only the calling
@@ -254,9 +257,25 @@ pub fn gather_grid_to_particles(
                 // neither — position is deliberately left completely untouched, not just
                 // re-clamped to itself, avoiding any float drift from a v=0*dt add-then-
                 // reclamp round trip.
+                //
+                // A kinematic particle (`PIN_KINEMATIC`) takes the same branch but keeps
+                // its prescribed `v` and moves with it, through the same boundary clamp
+                // and periodic wrap as a free particle -- no speed cap, the CFL scan
+                // already sized the substep for it.
                 if pinned != 0 {
-                    *v = Vec2::ZERO;
                     *vg = Mat2::ZERO;
+                    if pinned == PIN_KINEMATIC {
+                        let mut new_pos = *x + *v * dt;
+                        for boundary in boundaries.iter() {
+                            new_pos = boundary.clamp_particle_position_in(new_pos, domain);
+                        }
+                        if any_periodic {
+                            new_pos = domain.wrap_position(new_pos);
+                        }
+                        *x = new_pos;
+                    } else {
+                        *v = Vec2::ZERO;
+                    }
                     return 0;
                 }
 
@@ -316,7 +335,10 @@ pub fn gather_grid_to_particles(
                 // Apply all boundaries' position clamp (pure function, no particle-struct access).
                 let mut new_pos = *x + v_position * dt;
                 for boundary in boundaries.iter() {
-                    new_pos = boundary.clamp_particle_position(new_pos, grid_res);
+                    new_pos = boundary.clamp_particle_position_in(new_pos, domain);
+                }
+                if any_periodic {
+                    new_pos = domain.wrap_position(new_pos);
                 }
 
                 *v = v_store;
@@ -335,7 +357,7 @@ pub fn gather_grid_to_particles(
         let material = materials.get(material_id);
         material.update_particle(particles, i, dt);
         for boundary in boundaries.iter() {
-            boundary.post_g2p_particle(particles, i, grid_res, dt);
+            boundary.post_g2p_particle(particles, i, domain.width(), dt);
         }
     }
 
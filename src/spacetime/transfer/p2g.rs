@@ -1,4 +1,7 @@
+use std::ops::Range;
+
 use glam::{IVec2, Mat2, Vec2};
+use rayon::prelude::*;
 
 use crate::grid::Grid;
 use crate::grid::kernel::{axis_weights_derivative, quadratic_weights};
@@ -8,19 +11,60 @@ use crate::solver::config::KERNEL_D_INVERSE;
 
 use super::combined_kirchhoff_stress;
 
+/// Particles per chunk in the parallel P2G path. A fixed constant, NOT derived
+/// from the rayon thread count: each chunk is scattered sequentially into its own
+/// scratch grid and chunks are merged in index order, so the per-cell summation
+/// grouping depends only on this value and `active_count` -- results are
+/// bit-identical across runs AND across thread counts. Large enough that the
+/// serial merge (≈ one hash op per touched cell per chunk, vs. 9 per particle for
+/// the scatter itself) stays a small fraction of the work.
+const P2G_CHUNK_PARTICLES: usize = 4096;
+
+/// Reusable per-chunk scratch grids for the parallel P2G path — owned by the
+/// caller (`Simulation` keeps one) so a substep never allocates fresh hash maps.
+/// Same role as `Simulation::scratch_indices`.
+#[derive(Debug, Default)]
+pub struct P2GScratch {
+    grids: Vec<Grid>,
+}
+
+impl P2GScratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cleared chunk grids for `count` particles on `grid`'s domain, growing the pool
+    /// (or rebuilding it on a domain change) as needed.
+    fn prepare(&mut self, count: usize, grid: &Grid) -> &mut [Grid] {
+        let domain = grid.domain();
+        let chunks = count.div_ceil(P2G_CHUNK_PARTICLES);
+        if self.grids.first().is_some_and(|g| g.domain() != domain) {
+            self.grids.clear();
+        }
+        if self.grids.len() < chunks {
+            self.grids.resize_with(chunks, || Grid::with_domain(domain));
+        }
+        let grids = &mut self.grids[..chunks];
+        for g in grids.iter_mut() {
+            g.clear();
+        }
+        grids
+    }
+}
+
 /// P2G: scatter particle mass, momentum, and stress forces onto the grid (MLS-MPM, Hu 2018 §4).
 ///
 /// Stress is pre-integrated as a momentum impulse so the grid needs one accumulation pass.
 /// The APIC affine term conserves angular momentum without a correction step.
 ///
-/// NOT parallelized (unlike G2P below): multiple particles write to the same grid cell (3×3
-/// B-spline stencils overlap), so summing their contributions requires either a shared mutable
-/// map (unsound across threads — `HashMap::entry()` can trigger a resize) or a thread-local
-/// fold/reduce merge. The latter was attempted and reverted 2026-06-20: it's safe and compiles
-/// clean, but changes floating-point summation order across particles sharing a cell, and that
-/// shifted results enough to break `fluid_spreads_more_than_elastic_under_gravity` (a 600-step
-/// chaotic simulation) — confirmed by isolated A/B, not assumed. Reverted rather than accepted
-/// the correctness risk for an unmeasured gain.
+/// Sequential reference path. Multiple particles write to the same grid cell (3×3 B-spline
+/// stencils overlap), so any parallel version has to change the floating-point summation
+/// order for cells shared across work units. A thread-local fold/reduce was attempted and
+/// reverted 2026-06-20: its grouping followed rayon's work splitting, and the shifted sums
+/// broke `fluid_spreads_more_than_elastic_under_gravity` (a 600-step chaotic simulation) —
+/// confirmed by isolated A/B, not assumed. `scatter_particles_to_grid_parallel` below is
+/// the deterministic replacement, opt-in via `SimConfig::parallel_p2g`; this path stays the
+/// default so existing scenes keep their exact trajectories.
 pub fn scatter_particles_to_grid(
     particles: &Particles,
     grid: &mut Grid,
@@ -28,7 +72,47 @@ pub fn scatter_particles_to_grid(
     dt: f32,
     active_count: usize,
 ) {
-    for i in 0..active_count {
+    scatter_range(particles, grid, materials, dt, 0..active_count);
+}
+
+/// Parallel P2G: same result as `scatter_particles_to_grid` up to per-cell summation
+/// grouping. Particles are split into fixed-size chunks (`P2G_CHUNK_PARTICLES`), each
+/// chunk scatters into its own scratch grid on a rayon worker, then the chunk grids are
+/// merged into `grid` in chunk order (`Grid::merge_from`). Deterministic for any thread
+/// count, and the merged grid's active-cell order matches the sequential path exactly.
+/// Covers the contact (grip) and mixture scatters too — they live in the same per-particle
+/// loop and their accumulators are merged alongside the ordinary cells.
+pub fn scatter_particles_to_grid_parallel(
+    particles: &Particles,
+    grid: &mut Grid,
+    scratch: &mut P2GScratch,
+    materials: &MaterialRegistry,
+    dt: f32,
+    active_count: usize,
+) {
+    if active_count <= P2G_CHUNK_PARTICLES {
+        scatter_particles_to_grid(particles, grid, materials, dt, active_count);
+        return;
+    }
+    let chunks = scratch.prepare(active_count, grid);
+    chunks.par_iter_mut().enumerate().for_each(|(c, local)| {
+        let start = c * P2G_CHUNK_PARTICLES;
+        let end = (start + P2G_CHUNK_PARTICLES).min(active_count);
+        scatter_range(particles, local, materials, dt, start..end);
+    });
+    for local in chunks.iter() {
+        grid.merge_from(local);
+    }
+}
+
+fn scatter_range(
+    particles: &Particles,
+    grid: &mut Grid,
+    materials: &MaterialRegistry,
+    dt: f32,
+    range: Range<usize>,
+) {
+    for i in range {
         let material_id = particles.material_id[i];
         let material = materials.get(material_id);
         let x = particles.x[i];
@@ -105,6 +189,51 @@ pub fn gather_contact_point_cloud(particles: &Particles, grid: &mut Grid, active
     }
 }
 
+/// Parallel `gather_contact_point_cloud`. Which nodes are contact-active is already
+/// fixed by the time this runs, so each chunk decides read-only which of its points
+/// to keep, and the kept points are appended chunk by chunk in particle order —
+/// every node's point cloud comes out identical to the sequential pass, element for
+/// element (the logistic-regression fit is sensitive to point order, so this matters).
+pub fn gather_contact_point_cloud_parallel(
+    particles: &Particles,
+    grid: &mut Grid,
+    active_count: usize,
+) {
+    if !grid.has_contact_activity() {
+        return;
+    }
+    let shared: &Grid = grid;
+    let per_chunk: Vec<Vec<(u32, Vec2, f32)>> = (0..active_count)
+        .into_par_iter()
+        .step_by(P2G_CHUNK_PARTICLES)
+        .map(|start| {
+            let end = (start + P2G_CHUNK_PARTICLES).min(active_count);
+            let mut points = Vec::new();
+            for i in start..end {
+                let x = particles.x[i];
+                let label = if particles.contact_group[i] != 0 {
+                    1.0
+                } else {
+                    -1.0
+                };
+                let weights = quadratic_weights(x);
+                for gx in 0i32..3 {
+                    for gy in 0i32..3 {
+                        let cell_pos = weights.base_cell + IVec2::new(gx - 1, gy - 1);
+                        if let Some(idx) = shared.contact_cell_index(cell_pos) {
+                            points.push((idx, x, label));
+                        }
+                    }
+                }
+            }
+            points
+        })
+        .collect();
+    for (idx, x, label) in per_chunk.into_iter().flatten() {
+        grid.push_contact_point(idx, x, label);
+    }
+}
+
 /// Analytic adjoint of P2G's stress→force scatter contribution w.r.t. the
 /// particle's own Kirchhoff stress tensor -- the second real piece of
 /// differentiable stepping, after `NeoHookeanMaterial::kirchhoff_stress_vjp`.
@@ -228,7 +357,11 @@ pub fn p2g_position_vjp(
 }
 
 pub fn scatter_particle_mass(particles: &Particles, grid: &mut Grid, active_count: usize) {
-    for i in 0..active_count {
+    scatter_mass_range(particles, grid, 0..active_count);
+}
+
+fn scatter_mass_range(particles: &Particles, grid: &mut Grid, range: Range<usize>) {
+    for i in range {
         let x = particles.x[i];
         let mass = particles.mass[i];
         let weights = quadratic_weights(x);
@@ -241,3 +374,26 @@ pub fn scatter_particle_mass(particles: &Particles, grid: &mut Grid, active_coun
         }
     }
 }
+
+/// Parallel `scatter_particle_mass`, chunked and merged exactly like
+/// `scatter_particles_to_grid_parallel`.
+pub fn scatter_particle_mass_parallel(
+    particles: &Particles,
+    grid: &mut Grid,
+    scratch: &mut P2GScratch,
+    active_count: usize,
+) {
+    if active_count <= P2G_CHUNK_PARTICLES {
+        scatter_particle_mass(particles, grid, active_count);
+        return;
+    }
+    let chunks = scratch.prepare(active_count, grid);
+    chunks.par_iter_mut().enumerate().for_each(|(c, local)| {
+        let start = c * P2G_CHUNK_PARTICLES;
+        let end = (start + P2G_CHUNK_PARTICLES).min(active_count);
+        scatter_mass_range(particles, local, start..end);
+    });
+    for local in chunks.iter() {
+        grid.merge_from(local);
+    }
+}
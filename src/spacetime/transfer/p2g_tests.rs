@@ -367,3 +367,132 @@ mod p2g_position_vjp_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod parallel_p2g_tests {
+    use super::*;
+    use crate::materials::NeoHookeanMaterial;
+    use crate::materials::registry::MaterialRegistry;
+    use crate::particle::Particle;
+
+    /// Enough particles for several chunks, scattered irregularly (LCG positions,
+    /// velocities, affine C) so chunks genuinely share cells — the case where the
+    /// parallel path's summation grouping differs from the sequential one.
+    fn scattered_particles(n: usize) -> Particles {
+        let mut state = 12345u32;
+        let mut next = || {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            state as f32 / u32::MAX as f32
+        };
+        let mut out = Vec::with_capacity(n);
+        for i in 0..n {
+            let mut p = Particle::zeroed();
+            p.x = Vec2::new(4.0 + next() * 56.0, 4.0 + next() * 56.0);
+            p.v = Vec2::new(next() - 0.5, next() - 0.5);
+            p.velocity_gradient = Mat2::from_cols_array(&[next(), next(), next(), next()]) * 0.1;
+            p.deformation_gradient =
+                Mat2::IDENTITY + Mat2::from_cols_array(&[next(), next(), next(), next()]) * 0.05;
+            p.mass = 1.0;
+            p.initial_volume = 1.0;
+            p.volume = 1.0;
+            p.density = 1.0;
+            p.contact_group = (i % 7 == 0) as u32;
+            out.push(p);
+        }
+        Particles::from(out)
+    }
+
+    fn registry() -> MaterialRegistry {
+        MaterialRegistry::with_default(Box::new(NeoHookeanMaterial::new(10.0, 20.0)))
+    }
+
+    fn parallel_grid(particles: &Particles, threads: usize) -> Grid {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let materials = registry();
+        let mut grid = Grid::new(64);
+        let mut scratch = P2GScratch::new();
+        pool.install(|| {
+            scatter_particles_to_grid_parallel(
+                particles,
+                &mut grid,
+                &mut scratch,
+                &materials,
+                0.01,
+                particles.len(),
+            );
+            gather_contact_point_cloud_parallel(particles, &mut grid, particles.len());
+        });
+        grid
+    }
+
+    fn cell_bits(grid: &Grid) -> Vec<[u32; 3]> {
+        grid.active_cells()
+            .map(|c| {
+                [
+                    c.mass.to_bits(),
+                    c.momentum.x.to_bits(),
+                    c.momentum.y.to_bits(),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parallel_scatter_is_bit_identical_across_thread_counts() {
+        let particles = scattered_particles(20_000);
+        let reference = cell_bits(&parallel_grid(&particles, 1));
+        for threads in [2, 3, 8] {
+            assert_eq!(
+                cell_bits(&parallel_grid(&particles, threads)),
+                reference,
+                "parallel P2G result changed with {threads} threads"
+            );
+        }
+    }
+
+    #[test]
+    fn parallel_scatter_matches_sequential_cell_order_and_sums() {
+        let particles = scattered_particles(20_000);
+        let parallel = parallel_grid(&particles, 4);
+        let mut sequential = Grid::new(64);
+        scatter_particles_to_grid(
+            &particles,
+            &mut sequential,
+            &registry(),
+            0.01,
+            particles.len(),
+        );
+
+        assert_eq!(parallel.active_cell_count(), sequential.active_cell_count());
+        assert!(parallel.has_contact_activity());
+        for (a, b) in parallel.active_cells().zip(sequential.active_cells()) {
+            // Same first-touch order, so this zip pairs up the same cells; only the
+            // per-cell summation grouping may differ.
+            let scale = b.mass.abs().max(1.0);
+            assert!((a.mass - b.mass).abs() / scale < 1.0e-5);
+            let m_scale = b.momentum.length().max(1.0);
+            assert!((a.momentum - b.momentum).length() / m_scale < 1.0e-4);
+        }
+    }
+
+    #[test]
+    fn parallel_mass_scatter_matches_sequential() {
+        let particles = scattered_particles(10_000);
+        let mut parallel = Grid::new(64);
+        scatter_particle_mass_parallel(
+            &particles,
+            &mut parallel,
+            &mut P2GScratch::new(),
+            particles.len(),
+        );
+        let mut sequential = Grid::new(64);
+        scatter_particle_mass(&particles, &mut sequential, particles.len());
+        assert_eq!(parallel.active_cell_count(), sequential.active_cell_count());
+        for (a, b) in parallel.active_cells().zip(sequential.active_cells()) {
+            assert!((a.mass - b.mass).abs() < 1.0e-4 * b.mass.max(1.0));
+        }
+    }
+}
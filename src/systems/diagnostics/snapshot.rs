@@ -1,8 +1,11 @@
-use glam::Vec2;
+use glam::{UVec2, Vec2};
 use std::collections::HashMap;
 
 use crate::solver::config::SimConfig;
-use crate::{grid::Grid, particle::Particles};
+use crate::{
+    grid::Grid,
+    particle::{PIN_KINEMATIC, Particles},
+};
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct SimSnapshot {
@@ -66,13 +69,20 @@ pub struct SimSnapshot {
     /// elastic one — unbounded growth with no external force driving it is a real bug,
     /// not just "high energy."
     pub total_kinetic_energy: f32,
-    /// Max speed among particles with `Particle::pinned != 0`. Should read exactly 0.0
+    /// Max speed among anchored particles (`Particle::pinned` nonzero and not
+    /// `PIN_KINEMATIC` -- kinematic particles move on purpose). Should read exactly 0.0
     /// for any scene using pinned/Dirichlet anchors — G2P forces `v=0` on pinned
     /// particles every substep (see `transfer.rs`). Nonzero here means the pinning
     /// mechanism itself is broken (a real engine bug), not a scene-tuning issue —
     /// added specifically so this class of bug is directly observable instead of
     /// inferred indirectly from a body slowly drifting.
     pub max_pinned_particle_speed: f32,
+    /// Particles created by emitters during the last `step()` call.
+    pub emitted_count: usize,
+    /// Particles deleted by `SinkAction::Delete` sinks during the last `step()` call.
+    pub sink_removed_count: usize,
+    /// Particles re-emitted by `SinkAction::Recycle` sinks during the last `step()` call.
+    pub sink_recycled_count: usize,
 }
 
 /// Wall-clock timing breakdown for one `step()` call (sum of all substeps).
@@ -100,6 +110,10 @@ pub struct StepTiming {
     pub project_us: u64,
     /// Density recompute via P2G volume estimation (only when fluid materials present).
     pub density_us: u64,
+    /// Substep hooks, all stages. Zero if none registered.
+    pub hooks_us: u64,
+    /// Passive tracer advection. Zero if there are no tracers.
+    pub tracers_us: u64,
     /// Total wall time for the step (includes overhead not captured in individual phases).
     pub total_us: u64,
 }
@@ -127,8 +141,7 @@ pub fn collect_snapshot_particles_only(
         avg_elastic_hardening: 1.0,
         ..Default::default()
     };
-    let min_bound = config.boundary_thickness.saturating_sub(1) as f32;
-    let max_bound = config.grid_res.saturating_sub(config.boundary_thickness) as f32;
+    let (min_bound, max_bound) = admissible_bounds(config);
     let mut jp_sum = 0.0f32;
     let mut h_sum = 0.0f32;
 
@@ -138,14 +151,14 @@ pub fn collect_snapshot_particles_only(
         snap.total_particle_momentum += p.mass * p.v;
         snap.max_particle_speed = snap.max_particle_speed.max(p.v.length());
         snap.total_kinetic_energy += 0.5 * p.mass * p.v.length_squared();
-        if p.pinned != 0 {
+        if p.pinned != 0 && p.pinned != PIN_KINEMATIC {
             snap.max_pinned_particle_speed = snap.max_pinned_particle_speed.max(p.v.length());
         }
         if deformation_j.is_finite() {
             snap.min_deformation_j = snap.min_deformation_j.min(deformation_j);
             snap.max_deformation_j = snap.max_deformation_j.max(deformation_j);
         }
-        if p.x.x < min_bound || p.x.x > max_bound || p.x.y < min_bound || p.x.y > max_bound {
+        if !in_bounds(p.x, min_bound, max_bound) {
             snap.out_of_bounds_particles += 1;
         }
         let jp = p.plastic_volume_ratio;
@@ -244,8 +257,7 @@ pub fn collect_snapshot(
     let mut h_sum = 0.0f32;
     let mut material_cells = HashMap::<usize, MaterialCellState>::new();
 
-    let min_bound = config.boundary_thickness.saturating_sub(1) as f32;
-    let max_bound = config.grid_res.saturating_sub(config.boundary_thickness) as f32;
+    let (min_bound, max_bound) = admissible_bounds(config);
 
     for i in particles.indices() {
         let mass = particles.mass[i];
@@ -260,7 +272,7 @@ pub fn collect_snapshot(
         snapshot.total_particle_momentum += mass * v;
         snapshot.max_particle_speed = snapshot.max_particle_speed.max(v.length());
         snapshot.total_kinetic_energy += 0.5 * mass * v.length_squared();
-        if particles.pinned[i] != 0 {
+        if particles.pinned[i] != 0 && particles.pinned[i] != PIN_KINEMATIC {
             snapshot.max_pinned_particle_speed = snapshot.max_pinned_particle_speed.max(v.length());
         }
 
@@ -269,11 +281,11 @@ pub fn collect_snapshot(
             snapshot.max_deformation_j = snapshot.max_deformation_j.max(deformation_j);
         }
 
-        if x.x < min_bound || x.x > max_bound || x.y < min_bound || x.y > max_bound {
+        if !in_bounds(x, min_bound, max_bound) {
             snapshot.out_of_bounds_particles += 1;
         }
 
-        if let Some(cell_index) = particle_cell_index(x, config.grid_res) {
+        if let Some(cell_index) = particle_cell_index(x, config.grid_dims()) {
             let entry = material_cells
                 .entry(cell_index)
                 .or_insert_with(|| MaterialCellState {
@@ -368,7 +380,18 @@ pub fn collect_snapshot(
     snapshot
 }
 
-fn particle_cell_index(position: Vec2, grid_res: usize) -> Option<usize> {
+/// Per-axis `[min, max]` a particle may occupy inside the boundary walls.
+fn admissible_bounds(config: &SimConfig) -> (Vec2, Vec2) {
+    let min = Vec2::splat(config.boundary_thickness.saturating_sub(1) as f32);
+    let max = config.grid_dims().as_vec2() - config.boundary_thickness as f32;
+    (min, max)
+}
+
+fn in_bounds(x: Vec2, min: Vec2, max: Vec2) -> bool {
+    x.cmpge(min).all() && x.cmple(max).all()
+}
+
+fn particle_cell_index(position: Vec2, dims: UVec2) -> Option<usize> {
     if !position.is_finite() {
         return None;
     }
@@ -379,10 +402,11 @@ fn particle_cell_index(position: Vec2, grid_res: usize) -> Option<usize> {
     }
     let ux = ix as usize;
     let uy = iy as usize;
-    if ux >= grid_res || uy >= grid_res {
+    let (width, height) = (dims.x as usize, dims.y as usize);
+    if ux >= width || uy >= height {
         return None;
     }
-    Some(ux * grid_res + uy)
+    Some(ux * height + uy)
 }
 
 fn count_non_finite_particle_values(particles: &Particles, i: usize) -> usize {
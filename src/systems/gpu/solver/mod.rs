@@ -296,6 +296,9 @@ impl GpuSimulation {
     /// zero-readback [`crate::render::Renderer::render_gpu`] path. `new()` creates its
     /// own headless device instead, which is correct for compute-only or CPU-readback
     /// workflows but cannot share GPU buffers with another device.
+    ///
+    /// Panics if `config` asks for a non-square or periodic grid: the GPU
+    /// kernels only know the square, walled domain.
     pub fn with_device(
         device: Arc<wgpu::Device>,
         queue: Arc<wgpu::Queue>,
@@ -303,6 +306,14 @@ impl GpuSimulation {
         particles: Vec<Particle>,
         registry: MaterialRegistry,
     ) -> Self {
+        assert!(
+            config.grid_res_y.is_none(),
+            "GpuSimulation supports square grids only (grid_res_y must be None)"
+        );
+        assert!(
+            !config.periodic_x && !config.periodic_y,
+            "GpuSimulation supports walled grids only (periodic_x and periodic_y must be false)"
+        );
         let material_params = registry.all_params();
 
         // Run init_particle before uploading. Mirrors Simulation::spawn_region().
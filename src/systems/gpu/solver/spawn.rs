@@ -28,7 +28,7 @@ impl GpuSimulation {
             spawn.material_id
         );
         let mut rng = LcgRng::new(spawn.rng_seed);
-        let new_particles = initialize_particles(&self.config, spawn, &mut rng);
+        let new_particles = initialize_particles(&self.config, &spawn, &mut rng);
         self.particles.extend(new_particles);
 
         // Recompute initial volumes for the combined particle set using a temporary grid.
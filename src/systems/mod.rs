@@ -6,7 +6,8 @@
 //! collection: engineering observability, not physics. `gpu`
 //! [feature = "gpu"] — `GpuSimulation` + WGSL compute shaders: backend
 //! plumbing. `render` [feature = "render"] — instanced particle debug draw:
-//! pipeline setup, not the physics it visualizes.
+//! pipeline setup, not the physics it visualizes. `vtk` — VTU/VTI/PVD file
+//! export for ParaView: a file format, not physics.
 //!
 //! Part of the emerge/LP domain taxonomy (matter/forces/energy/information/
 //! spacetime/organism/systems) -- see `project_domain_taxonomy` design notes.
@@ -21,3 +22,4 @@ pub mod diagnostics;
 pub mod gpu;
 #[cfg(feature = "render")]
 pub mod render;
+pub mod vtk;
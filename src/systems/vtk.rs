@@ -0,0 +1,361 @@
+//! VTK export for offline analysis in ParaView or VisIt: particles as `.vtu`
+//! point clouds, grid fields as `.vti` images, and `.pvd` collections that
+//! tie one file per frame into a time series.
+//!
+//! Files use the VTK XML formats with raw appended binary data
+//! (little-endian, `UInt64` block headers) — no compression or base64, so
+//! the writer needs nothing beyond `std` and `bytemuck`, and a 100k-particle
+//! frame stays a few MB. Coordinates are grid units, like `Particle::x`;
+//! z is 0.
+//!
+//! [`write_vtu`] stores one vertex per particle, active and sleeping, with
+//! point arrays `velocity`, `J` (`det F`), `plastic_volume_ratio`,
+//! `temperature`, `scalar_field`, `activation`, `mass`, `density`,
+//! `material` and `tag`. [`write_vti`] stores a [`FieldSampler`]'s node
+//! fields — `mass`, `momentum`, `velocity`, `temperature` and one
+//! `scalar_<n>` per attached scalar field — on the simulation grid, nodes
+//! at cell centres.
+//!
+//! ```ignore
+//! let mut series = PvdCollection::new();
+//! for frame in 0..frames {
+//!     sim.step();
+//!     let file = format!("particles_{frame:05}.vtu");
+//!     vtk::save_vtu(sim.particles(), out.join(&file))?;
+//!     series.add(frame as f32 * sim.config().dt, file);
+//! }
+//! series.save(out.join("particles.pvd"))?;
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use bytemuck::Pod;
+use glam::Vec2;
+
+use crate::particle::Particles;
+use crate::solver::FieldSampler;
+
+/// Element types a `DataArray` can hold.
+trait Scalar: Pod {
+    const NAME: &'static str;
+}
+
+impl Scalar for f32 {
+    const NAME: &'static str = "Float32";
+}
+
+impl Scalar for u32 {
+    const NAME: &'static str = "UInt32";
+}
+
+impl Scalar for i64 {
+    const NAME: &'static str = "Int64";
+}
+
+impl Scalar for u8 {
+    const NAME: &'static str = "UInt8";
+}
+
+/// `VTK_VERTEX` cell type.
+const VERTEX: u8 = 1;
+
+/// One file being assembled: the XML declares each array with its offset
+/// into the appended block, and the block collects the bytes in the same
+/// order.
+#[derive(Default)]
+struct Document {
+    xml: String,
+    appended: Vec<u8>,
+}
+
+impl Document {
+    fn new(kind: &str) -> Self {
+        let mut doc = Self::default();
+        doc.line(r#"<?xml version="1.0"?>"#);
+        doc.line(&format!(
+            r#"<VTKFile type="{kind}" version="1.0" byte_order="LittleEndian" header_type="UInt64">"#
+        ));
+        doc
+    }
+
+    fn line(&mut self, line: &str) {
+        self.xml.push_str(line);
+        self.xml.push('\n');
+    }
+
+    /// Declare an array of `components`-tuples and append its data.
+    fn array<T: Scalar>(&mut self, name: &str, components: usize, values: &[T]) {
+        let bytes: &[u8] = bytemuck::cast_slice(values);
+        self.line(&format!(
+            r#"<DataArray type="{}" Name="{name}" NumberOfComponents="{components}" format="appended" offset="{}"/>"#,
+            T::NAME,
+            self.appended.len()
+        ));
+        self.appended
+            .extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        self.appended.extend_from_slice(bytes);
+    }
+
+    /// Declare a vector array, padded to the 3 components VTK filters expect.
+    fn vectors(&mut self, name: &str, values: &[Vec2]) {
+        let values: Vec<[f32; 3]> = values.iter().map(|v| [v.x, v.y, 0.0]).collect();
+        self.array::<f32>(name, 3, bytemuck::cast_slice(&values));
+    }
+
+    fn finish<W: Write>(mut self, w: &mut W) -> io::Result<()> {
+        self.line(r#"<AppendedData encoding="raw">"#);
+        w.write_all(self.xml.as_bytes())?;
+        w.write_all(b"_")?;
+        w.write_all(&self.appended)?;
+        w.write_all(b"\n</AppendedData>\n</VTKFile>\n")
+    }
+}
+
+/// Write every particle as a `.vtu` vertex cloud (see the module doc).
+pub fn write_vtu<W: Write>(particles: &Particles, w: &mut W) -> io::Result<()> {
+    let n = particles.len();
+    let mut doc = Document::new("UnstructuredGrid");
+    doc.line("<UnstructuredGrid>");
+    doc.line(&format!(
+        r#"<Piece NumberOfPoints="{n}" NumberOfCells="{n}">"#
+    ));
+
+    doc.line(r#"<PointData Scalars="J" Vectors="velocity">"#);
+    doc.vectors("velocity", &particles.v);
+    let j: Vec<f32> = particles
+        .deformation_gradient
+        .iter()
+        .map(|f| f.determinant())
+        .collect();
+    doc.array("J", 1, &j);
+    doc.array("plastic_volume_ratio", 1, &particles.plastic_volume_ratio);
+    doc.array("temperature", 1, &particles.temperature);
+    doc.array("scalar_field", 1, &particles.scalar_field);
+    doc.array("activation", 1, &particles.activation);
+    doc.array("mass", 1, &particles.mass);
+    doc.array("density", 1, &particles.density);
+    doc.array("material", 1, &particles.material_id);
+    doc.array("tag", 1, &particles.user_tag);
+    doc.line("</PointData>");
+
+    doc.line("<Points>");
+    doc.vectors("Points", &particles.x);
+    doc.line("</Points>");
+
+    doc.line("<Cells>");
+    let connectivity: Vec<i64> = (0..n as i64).collect();
+    let offsets: Vec<i64> = (1..=n as i64).collect();
+    doc.array("connectivity", 1, &connectivity);
+    doc.array("offsets", 1, &offsets);
+    doc.array("types", 1, &vec![VERTEX; n]);
+    doc.line("</Cells>");
+
+    doc.line("</Piece>");
+    doc.line("</UnstructuredGrid>");
+    doc.finish(w)
+}
+
+/// Write a sampler's node fields as a `.vti` image (see the module doc).
+pub fn write_vti<W: Write>(sampler: &FieldSampler, w: &mut W) -> io::Result<()> {
+    let domain = sampler.domain();
+    let (width, height) = (domain.width(), domain.height());
+    // Grid storage is column-major (`x * height + y`); VTK images run x fastest.
+    let order: Vec<usize> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| x * height + y))
+        .collect();
+    let image = |values: &[f32]| -> Vec<f32> { order.iter().map(|&i| values[i]).collect() };
+    let image_vectors = |values: &[Vec2]| order.iter().map(|&i| values[i]).collect::<Vec<_>>();
+
+    let extent = format!("0 {} 0 {} 0 0", width - 1, height - 1);
+    let mut doc = Document::new("ImageData");
+    doc.line(&format!(
+        r#"<ImageData WholeExtent="{extent}" Origin="0.5 0.5 0" Spacing="1 1 1">"#
+    ));
+    doc.line(&format!(r#"<Piece Extent="{extent}">"#));
+    doc.line(r#"<PointData Scalars="mass" Vectors="velocity">"#);
+    doc.array("mass", 1, &image(sampler.node_mass()));
+    doc.vectors("momentum", &image_vectors(sampler.node_momentum()));
+    doc.vectors("velocity", &image_vectors(&sampler.node_velocity()));
+    doc.array("temperature", 1, &image(&sampler.node_temperature()));
+    for field in 0..sampler.scalar_count() {
+        let values = sampler.node_scalar(field).unwrap_or_default();
+        doc.array(&format!("scalar_{field}"), 1, &image(&values));
+    }
+    doc.line("</PointData>");
+    doc.line("</Piece>");
+    doc.line("</ImageData>");
+    doc.finish(w)
+}
+
+/// [`write_vtu`] to `path` (created or truncated).
+pub fn save_vtu(particles: &Particles, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    write_vtu(particles, &mut w)?;
+    w.flush()
+}
+
+/// [`write_vti`] to `path` (created or truncated).
+pub fn save_vti(sampler: &FieldSampler, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    write_vti(sampler, &mut w)?;
+    w.flush()
+}
+
+/// A `.pvd` time series: one dataset file per time, for ParaView's time
+/// controls. Paths are stored as given, so keep them relative to the
+/// directory the `.pvd` is saved in.
+#[derive(Clone, Debug, Default)]
+pub struct PvdCollection {
+    datasets: Vec<(f32, String)>,
+}
+
+impl PvdCollection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the dataset `file` at simulation time `time`.
+    pub fn add(&mut self, time: f32, file: impl Into<String>) {
+        self.datasets.push((time, file.into()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.datasets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.datasets.is_empty()
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, r#"<?xml version="1.0"?>"#)?;
+        writeln!(
+            w,
+            r#"<VTKFile type="Collection" version="1.0" byte_order="LittleEndian">"#
+        )?;
+        writeln!(w, "<Collection>")?;
+        for (time, file) in &self.datasets {
+            writeln!(
+                w,
+                r#"<DataSet timestep="{time:?}" part="0" file="{}"/>"#,
+                escape(file)
+            )?;
+        }
+        writeln!(w, "</Collection>")?;
+        writeln!(w, "</VTKFile>")
+    }
+
+    /// Write the collection to `path`. Saving again after each `add` keeps
+    /// the series loadable while a run is still going.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write(&mut w)?;
+        w.flush()
+    }
+}
+
+/// `text` with the XML attribute specials escaped.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridDomain;
+    use crate::particle::Particle;
+    use glam::UVec2;
+
+    /// The appended blocks of a written file, in declaration order.
+    fn blocks(file: &[u8]) -> Vec<&[u8]> {
+        let marker = b"<AppendedData encoding=\"raw\">\n_";
+        let start = file
+            .windows(marker.len())
+            .position(|w| w == marker)
+            .unwrap()
+            + marker.len();
+        let mut rest = &file[start..];
+        let mut out = Vec::new();
+        while rest.len() > 8 && !rest.starts_with(b"\n</AppendedData>") {
+            let len = u64::from_le_bytes(rest[..8].try_into().unwrap()) as usize;
+            out.push(&rest[8..8 + len]);
+            rest = &rest[8 + len..];
+        }
+        out
+    }
+
+    fn floats(block: &[u8]) -> Vec<f32> {
+        block
+            .chunks(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn vtu_blocks_follow_the_declared_arrays() {
+        let particles = Particles::from(vec![
+            Particle {
+                x: Vec2::new(1.0, 2.0),
+                v: Vec2::new(3.0, 4.0),
+                temperature: 300.0,
+                material_id: 2,
+                ..Particle::zeroed()
+            },
+            Particle {
+                x: Vec2::new(5.0, 6.0),
+                user_tag: 9,
+                ..Particle::zeroed()
+            },
+        ]);
+        let mut file = Vec::new();
+        write_vtu(&particles, &mut file).unwrap();
+        let text = String::from_utf8_lossy(&file);
+        assert!(text.contains(r#"<Piece NumberOfPoints="2" NumberOfCells="2">"#));
+
+        let blocks = blocks(&file);
+        assert_eq!(blocks.len(), 14);
+        assert_eq!(floats(blocks[0]), [3.0, 4.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(floats(blocks[3]), [300.0, 0.0]);
+        assert_eq!(blocks[8], bytemuck::cast_slice::<u32, u8>(&[2, 0]));
+        assert_eq!(blocks[9], bytemuck::cast_slice::<u32, u8>(&[0, 9]));
+        assert_eq!(floats(blocks[10]), [1.0, 2.0, 0.0, 5.0, 6.0, 0.0]);
+        assert_eq!(blocks[13], [VERTEX, VERTEX]);
+    }
+
+    #[test]
+    fn vti_runs_x_fastest_and_pvd_lists_frames() {
+        // One particle on the centre of node (2, 1) of a 4 × 3 grid.
+        let particles = Particles::from(vec![Particle {
+            x: Vec2::new(2.5, 1.5),
+            mass: 1.0,
+            temperature: 7.0,
+            ..Particle::zeroed()
+        }]);
+        let sampler = FieldSampler::new(&particles, GridDomain::new(UVec2::new(4, 3)), &[]);
+        let mut file = Vec::new();
+        write_vti(&sampler, &mut file).unwrap();
+        assert!(String::from_utf8_lossy(&file).contains(r#"WholeExtent="0 3 0 2 0 0""#));
+        let mass = floats(blocks(&file)[0]);
+        assert_eq!(mass.len(), 12);
+        let peak = (0..12)
+            .max_by(|&a, &b| mass[a].total_cmp(&mass[b]))
+            .unwrap();
+        assert_eq!(peak, 4 + 2);
+        assert_eq!(floats(blocks(&file)[3])[peak], 7.0);
+
+        let mut series = PvdCollection::new();
+        series.add(0.0, "frame_0.vtu");
+        series.add(0.5, "frame \"1\".vtu");
+        let mut pvd = Vec::new();
+        series.write(&mut pvd).unwrap();
+        let pvd = String::from_utf8(pvd).unwrap();
+        assert!(
+            pvd.contains(r#"<DataSet timestep="0.5" part="0" file="frame &quot;1&quot;.vtu"/>"#)
+        );
+    }
+}
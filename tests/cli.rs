@@ -0,0 +1,166 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn emerge(args: &[&str], dir: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_emerge"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("failed to start emerge")
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("emerge-cli-{name}-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+const SETTLING: &str = "\
+[config]
+grid_res = 32
+dt = 0.1
+adaptive_timestep = true
+
+[body]
+center = 16 12
+size = 8 8
+spacing = 0.5
+";
+
+// No particles at all: `evaluate_stability` flags the first frame.
+const EMPTY: &str = "\
+[config]
+grid_res = 32
+";
+
+#[test]
+fn run_writes_outputs_and_stops_on_the_first_unhealthy_frame() {
+    let dir = scratch_dir("run");
+    fs::write(dir.join("settling.scene"), SETTLING).unwrap();
+    fs::write(dir.join("empty.scene"), EMPTY).unwrap();
+
+    let run = emerge(
+        &[
+            "run",
+            "settling.scene",
+            "--steps",
+            "4",
+            "--log",
+            "run.ndjson",
+            "--log-every",
+            "2",
+            "--out",
+            "out",
+            "--dump-every",
+            "2",
+            "--checkpoint-every",
+            "4",
+            "--vtk-every",
+            "2",
+        ],
+        &dir,
+    );
+    assert!(run.status.success(), "{run:?}");
+    let log = fs::read_to_string(dir.join("run.ndjson")).unwrap();
+    assert_eq!(log.lines().count(), 2);
+    assert!(log.starts_with("{\"frame\":2,"), "{log}");
+    let dump = fs::read_to_string(dir.join("out/particles_000004.csv")).unwrap();
+    assert!(dump.starts_with("x,y,vx,vy,mass,material,tag,temperature,j\n"));
+    assert_eq!(dump.lines().count(), 1 + 16 * 16);
+    let series = fs::read_to_string(dir.join("out/particles.pvd")).unwrap();
+    assert!(series.contains("file=\"particles_000004.vtu\""), "{series}");
+    let frame = fs::read(dir.join("out/grid_000002.vti")).unwrap();
+    assert!(frame.starts_with(b"<?xml"));
+
+    let inspect = emerge(
+        &[
+            "inspect",
+            "settling.scene",
+            "--checkpoint",
+            "out/checkpoint_000004.ckpt",
+        ],
+        &dir,
+    );
+    let summary = String::from_utf8_lossy(&inspect.stdout);
+    assert!(inspect.status.success(), "{inspect:?}");
+    assert!(
+        summary.contains("256 in 1 bodies") && summary.contains("frame 4"),
+        "{summary}"
+    );
+
+    let failed = emerge(&["run", "empty.scene"], &dir);
+    assert_eq!(failed.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&failed.stderr);
+    assert!(
+        stderr.contains("step 1 unhealthy [particle_count]"),
+        "{stderr}"
+    );
+
+    let sweep = emerge(
+        &[
+            "sweep",
+            "settling.scene",
+            "--steps",
+            "3",
+            "--set",
+            "dt=0.1,50",
+        ],
+        &dir,
+    );
+    let lines = String::from_utf8_lossy(&sweep.stdout);
+    assert_eq!(sweep.status.code(), Some(1));
+    // A 50-unit frame outruns the CFL limit and gets its velocities clamped.
+    assert!(
+        lines.contains("settling.scene dt=0.1: ok, 3 steps"),
+        "{lines}"
+    );
+    assert!(
+        lines.contains("settling.scene dt=50: unhealthy at step 1"),
+        "{lines}"
+    );
+
+    // Values the scene cannot take fail their own run; the rest still run.
+    let shrunk = emerge(
+        &[
+            "sweep",
+            "settling.scene",
+            "--steps",
+            "1",
+            "--set",
+            "grid_res=2,16,32",
+        ],
+        &dir,
+    );
+    let lines = String::from_utf8_lossy(&shrunk.stdout);
+    assert_eq!(shrunk.status.code(), Some(2), "{shrunk:?}");
+    assert!(
+        lines.contains("grid_res=2: failed: grid_res must be >= 4"),
+        "{lines}"
+    );
+    assert!(
+        lines.contains("grid_res=16: failed: body 1 does not fit inside the domain walls"),
+        "{lines}"
+    );
+    assert!(lines.contains("grid_res=32: ok, 1 steps"), "{lines}");
+
+    // So does a scene file that cannot be read.
+    let missing = emerge(
+        &["sweep", "missing.scene", "settling.scene", "--steps", "1"],
+        &dir,
+    );
+    let lines = String::from_utf8_lossy(&missing.stdout);
+    assert_eq!(missing.status.code(), Some(2), "{missing:?}");
+    assert!(
+        lines.contains("missing.scene: failed: missing.scene: "),
+        "{lines}"
+    );
+    assert!(lines.contains("settling.scene: ok, 1 steps"), "{lines}");
+
+    let misuse = emerge(&["run", "settling.scene", "--stpes", "4"], &dir);
+    assert_eq!(misuse.status.code(), Some(2));
+    let bad_key = emerge(&["sweep", "settling.scene", "--set", "dtt=1"], &dir);
+    assert_eq!(bad_key.status.code(), Some(2));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -18,6 +18,7 @@
 //! - `BoundaryCondition`  (custom grid boundary)
 //! - `DiagnosticsPlugin`  (custom observation)
 //! - phase rules          (closure-based matter state change)
+//! - `SubstepHook`        (user code at named substep stages)
 
 extern crate emerge_engine as emerge;
 
@@ -336,3 +337,72 @@ fn external_phase_rule_transitions_matter() {
         "external phase rule never fired -- closure seam not evaluated during stepping"
     );
 }
+
+// ─── 6. Substep hooks ────────────────────────────────────────────────────────
+
+/// Counts every stage and pushes grid nodes sideways after the grid update --
+/// a grid-level force no particle `Field` can express.
+struct ExternalHook {
+    stage_calls: [&'static AtomicU32; 4],
+    grid_push: f32,
+}
+
+impl SubstepHook for ExternalHook {
+    fn pre_p2g(&mut self, particles: &mut Particles, info: SubstepInfo) {
+        self.stage_calls[0].fetch_add(1, Ordering::Relaxed);
+        assert!(info.active_count <= particles.len());
+        assert!(info.sub_dt > 0.0 && info.sub_dt <= DT);
+        assert!(info.substep < config().max_substeps_per_step);
+    }
+
+    fn post_grid_update(&mut self, grid: &mut Grid, info: SubstepInfo) {
+        self.stage_calls[1].fetch_add(1, Ordering::Relaxed);
+        for (_, cell) in grid.active_cells_with_index_mut() {
+            cell.momentum.x += self.grid_push * info.sub_dt;
+        }
+    }
+
+    fn post_g2p(&mut self, _particles: &mut Particles, _info: SubstepInfo) {
+        self.stage_calls[2].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn end_of_substep(&mut self, _particles: &mut Particles, _info: SubstepInfo) {
+        self.stage_calls[3].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn external_substep_hook_runs_every_stage_of_every_substep() {
+    let run = |grid_push: f32| {
+        let stage_calls = [counter(), counter(), counter(), counter()];
+        let cfg = config();
+        let mut solver = Simulation::new(cfg, spawn(&cfg))
+            .with_default_material(Box::new(NeoHookeanMaterial::new(30.0, 20.0)))
+            .with_substep_hook(Box::new(ExternalHook {
+                stage_calls,
+                grid_push,
+            }));
+        let mut substeps = 0;
+        for _ in 0..30 {
+            solver.step();
+            substeps += solver.last_substeps() as u32;
+        }
+        for (stage, calls) in stage_calls.iter().enumerate() {
+            assert_eq!(
+                calls.load(Ordering::Relaxed),
+                substeps,
+                "stage {stage} must run exactly once per substep"
+            );
+        }
+        let particles = solver.particles();
+        (0..particles.len()).map(|i| particles.x[i].x).sum::<f32>() / particles.len() as f32
+    };
+
+    let baseline_x = run(0.0);
+    let pushed_x = run(0.6);
+    assert!(
+        pushed_x > baseline_x + 0.5,
+        "grid edits in post_grid_update never reached the particles: \
+         baseline com_x={baseline_x:.3}, pushed com_x={pushed_x:.3}"
+    );
+}
@@ -1,6 +1,7 @@
 extern crate emerge_engine as emerge;
 
 use std::collections::HashMap;
+use std::f32::consts::FRAC_PI_4;
 
 use emerge::fields::{
     AabbConfinementField, CoulombField, GravityWellField, LinearDragField, RadialConfinementField,
@@ -11,11 +12,14 @@ use emerge::thermodynamics::{
     ScalarDiffusionConfig, ScalarDiffusionField, ThermalConfig, ThermalDiffusion, saturating_uptake,
 };
 use emerge::{
-    DruckerPragerMaterial, Elastic, Field, MixturePhase, MuIRheologyMaterial, NaccMaterial,
-    NeoHookeanMaterial, NewtonianFluidMaterial, RankineMaterial, SimConfig, Simulation,
-    SlipBoundary, SpawnRegion, StomakhinMaterial, VonMisesMaterial, WithMixturePhase,
+    Bitmap, BoundaryCondition, Constraint, ConstraintSet, ContourOptions, DruckerPragerMaterial,
+    Elastic, Field, FragmentOptions, FrictionBoundary, HeightmapBoundary, Interpolation,
+    MixturePhase, MuIRheologyMaterial, NaccMaterial, NeoHookeanMaterial, NewtonianFluidMaterial,
+    Palette, PointSet, RankineMaterial, RaycastOptions, Scene, SimConfig, Simulation, SlipBoundary,
+    SpawnRegion, SpawnShape, Spring, StomakhinMaterial, Timeline, Track, VonMisesMaterial,
+    WithMixturePhase, build_particles, build_particles_from_points, surface_height,
 };
-use glam::{IVec2, Vec2};
+use glam::{IVec2, Mat2, Vec2};
 
 // --- helpers ---
 
@@ -55,6 +59,23 @@ fn step_keeps_particles_inside_domain() {
     }
 }
 
+#[test]
+fn jittered_body_against_the_wall_is_accepted() {
+    let config = SimConfig::standard(64, 0.1, Vec2::new(0.0, -0.3));
+    let spawn = SpawnRegion {
+        box_size: IVec2::new(60, 4),
+        box_center: Vec2::new(32.0, 4.0),
+        position_jitter: 0.3,
+        ..SpawnRegion::for_sim(&config).spacing(0.5)
+    };
+    assert!(spawn.fits_in_sim(&config));
+    let mut solver = Simulation::empty(config);
+    let tag = solver.add_body(spawn);
+    assert!(solver.particles_with_tag(tag).count() > 0);
+    solver.step_n(5);
+    assert!(solver.particles().x.iter().all(|x| x.is_finite()));
+}
+
 #[test]
 fn precomputed_volumes_are_positive() {
     let spawn = SpawnRegion {
@@ -1973,3 +1994,1796 @@ fn higher_drag_relaxes_solid_fluid_relative_velocity_faster() {
          lower drag over the same real time: low={low_relative:.4} high={high_relative:.4}"
     );
 }
+
+// --- sdf colliders ---
+
+#[test]
+fn sdf_collider_holds_material_out_and_moving_paddle_pushes_it() {
+    use emerge::{SdfCollider, SdfColliderBoundary, SdfShape};
+    use std::sync::Arc;
+
+    let obstacle = Vec2::new(16.0, 8.0);
+    let colliders = Arc::new(SdfColliderBoundary::new(vec![
+        SdfCollider::new(SdfShape::circle(4.0), obstacle).friction(0.3),
+    ]));
+    let mut sim = Simulation::new(small_solver_config(), small_spawn_config(16.0))
+        .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)))
+        .with_boundary(Box::new(colliders.clone()));
+    sim.step_n(60);
+    for p in sim.particles() {
+        assert!(
+            (p.x - obstacle).length() > 4.0 - 0.05,
+            "particle at {:?} penetrated the static collider",
+            p.x
+        );
+    }
+
+    // Sweep a paddle in from the left at 2 cells/s: the body must be shoved +X.
+    let mean_x = |sim: &Simulation| {
+        sim.particles().iter().map(|p| p.x.x).sum::<f32>() / sim.particles().len() as f32
+    };
+    let before = mean_x(&sim);
+    colliders.add_collider(
+        SdfCollider::new(SdfShape::rect(Vec2::new(1.0, 8.0)), Vec2::new(6.0, 12.0))
+            .velocity(Vec2::new(2.0, 0.0)),
+    );
+    sim.step_n(30);
+    let paddle_x = colliders.collider(1).unwrap().position.x;
+    assert!(
+        (paddle_x - 12.0).abs() < 0.05,
+        "paddle pose advances per substep"
+    );
+    assert!(
+        mean_x(&sim) > before + 0.5,
+        "moving paddle should push the body: {before} -> {}",
+        mean_x(&sim)
+    );
+    for p in sim.particles() {
+        assert!(p.x.is_finite() && p.v.is_finite());
+    }
+}
+
+#[test]
+fn moving_collider_poses_carry_across_a_checkpoint_through_a_snapshot() {
+    use emerge::{SdfCollider, SdfColliderBoundary, SdfShape};
+    use std::sync::Arc;
+
+    let build = || {
+        let paddle = SdfCollider::new(SdfShape::rect(Vec2::new(1.0, 8.0)), Vec2::new(6.0, 12.0))
+            .velocity(Vec2::new(2.0, 0.0));
+        let colliders = Arc::new(SdfColliderBoundary::new(vec![paddle]));
+        let sim = Simulation::new(small_solver_config(), small_spawn_config(16.0))
+            .with_boundary(Box::new(colliders.clone()));
+        (sim, colliders)
+    };
+    let (mut original, colliders) = build();
+    original.step_n(10);
+    let mut bytes = Vec::new();
+    original.write_checkpoint(&mut bytes).unwrap();
+    let snapshot = colliders.colliders();
+
+    let (mut restored, restored_colliders) = build();
+    restored.read_checkpoint(&mut bytes.as_slice()).unwrap();
+    restored_colliders.set_colliders(snapshot);
+    original.step_n(10);
+    restored.step_n(10);
+    assert_eq!(colliders.collider(0), restored_colliders.collider(0));
+    for (a, b) in original.particles().iter().zip(restored.particles()) {
+        assert_eq!(bytemuck::bytes_of(&a), bytemuck::bytes_of(&b));
+    }
+}
+
+// --- rigid bodies ---
+
+#[test]
+fn rigid_body_lands_on_sleeping_sand_and_is_held_up_by_it() {
+    use emerge::{RigidBody, SdfShape};
+    // Sleep enabled: the slab settles and sleeps long before the boulder
+    // arrives, so this also covers waking material in a moving body's path.
+    let config = SimConfig {
+        gravity: Vec2::new(0.0, -2.0),
+        sleep_threshold: 0.05,
+        ..small_solver_config()
+    };
+    let slab = SpawnRegion {
+        box_size: IVec2::new(26, 8),
+        box_center: Vec2::new(16.0, 6.0),
+        ..small_spawn_config(0.0)
+    };
+    let mut sim = Simulation::new(config, slab)
+        .with_default_material(Box::new(DruckerPragerMaterial::new(1_000.0, 500.0)));
+    let radius = 3.0;
+    let body = sim.add_rigid_body(
+        RigidBody::new(SdfShape::circle(radius), Vec2::new(16.0, 20.0), 4.0).friction(0.5),
+    );
+    let weight = sim.rigid_body(body).unwrap().mass() * 2.0;
+
+    sim.step_n(200);
+    let mut mean_support = 0.0;
+    for _ in 0..50 {
+        sim.step();
+        mean_support += sim.rigid_body(body).unwrap().continuum_force().y / 50.0;
+    }
+
+    let b = sim.rigid_body(body).unwrap();
+    let bottom = b.position.y - radius;
+    assert!(
+        bottom > 8.0 && bottom < 11.0,
+        "boulder should rest on the slab top (y = 10), bottom at {bottom}"
+    );
+    assert!(
+        b.linear_velocity.length() < 0.2,
+        "boulder should be at rest"
+    );
+    assert!(
+        (mean_support - weight).abs() < 0.15 * weight,
+        "sand must carry the boulder's weight: {mean_support} vs {weight}"
+    );
+    let min_y = sim
+        .particles()
+        .iter()
+        .map(|p| p.x.y)
+        .fold(f32::MAX, f32::min);
+    assert!(min_y >= 1.0, "sand must not be driven through the floor");
+    for p in sim.particles() {
+        assert!(
+            b.distance_and_normal(p.x).0 > -0.05,
+            "particle inside the boulder"
+        );
+    }
+}
+
+// --- emitters and sinks ---
+
+/// `tag_index` agrees with `user_tag` for `tags`, and the sleep partition holds.
+fn assert_bookkeeping_consistent(sim: &Simulation, tags: &[u32]) {
+    let particles = sim.particles();
+    for i in 0..particles.len() {
+        assert_eq!(
+            particles.sleeping[i],
+            i >= sim.active_count(),
+            "sleep partition broken at {i}"
+        );
+    }
+    for &tag in tags {
+        let indexed: Vec<usize> = sim.particles_with_tag(tag).collect();
+        let expected = particles.user_tag.iter().filter(|&&t| t == tag).count();
+        assert_eq!(indexed.len(), expected, "tag {tag} index size");
+        assert!(indexed.iter().all(|&i| particles.user_tag[i] == tag));
+    }
+}
+
+#[test]
+fn add_body_ahead_of_a_larger_sleeping_group_keeps_the_tag_index() {
+    let mut sim = Simulation::empty(small_solver_config());
+    let sleeper = sim.add_body(small_spawn_config(16.0));
+    sim.sleep_tag(sleeper);
+    // Fewer new particles than sleepers: the rotated sleeping range overlaps
+    // its old place, the case a per-particle index update used to corrupt.
+    let small = sim.add_body(SpawnRegion {
+        box_size: IVec2::new(2, 2),
+        ..small_spawn_config(8.0)
+    });
+    assert!(sim.group_count(small) < sim.group_count(sleeper));
+    assert_eq!(sim.active_count(), sim.group_count(small));
+    assert_bookkeeping_consistent(&sim, &[sleeper, small]);
+}
+
+#[test]
+fn emitter_emits_at_its_rate_into_a_sleeping_scene() {
+    use emerge::{EmissionRate, Emitter, RegionShape};
+    let config = SimConfig {
+        sleep_threshold: 0.05,
+        ..small_solver_config()
+    };
+    let mut sim = Simulation::new(config, small_spawn_config(8.0))
+        .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)));
+    sim.step_n(30);
+    assert!(
+        sim.active_count() < sim.particles().len(),
+        "block should sleep"
+    );
+
+    // 25 particles/s at dt = 0.1: 2.5 per step, so exactly 50 over 20 steps.
+    let tap = sim.add_emitter(
+        Emitter::new(
+            RegionShape::Disk { radius: 1.0 },
+            Vec2::new(22.0, 24.0),
+            EmissionRate::ParticlesPerSecond(25.0),
+        )
+        .velocity(Vec2::new(0.0, -1.0))
+        .temperature(5.0),
+    );
+    let tag = sim.emitter(tap).unwrap().tag.unwrap();
+    let mut emitted = 0;
+    for _ in 0..20 {
+        sim.step();
+        emitted += sim.diagnostics_snapshot().emitted_count;
+        assert_bookkeeping_consistent(&sim, &[0, tag]);
+    }
+    assert_eq!(emitted, 50);
+    assert_eq!(sim.particles_with_tag(tag).count(), 50);
+    assert!(
+        sim.particles_with_tag(tag)
+            .all(|i| sim.particles().temperature[i] == 5.0)
+    );
+
+    sim.emitter_mut(tap).unwrap().enabled = false;
+    sim.step_n(5);
+    assert_eq!(sim.particles_with_tag(tag).count(), 50);
+}
+
+#[test]
+fn emitter_stays_inside_the_walls() {
+    use emerge::{EmissionRate, Emitter, RegionShape};
+    let config = small_solver_config();
+    let tap = |center| {
+        Emitter::new(
+            RegionShape::Box {
+                half_extents: Vec2::new(3.0, 1.0),
+            },
+            center,
+            EmissionRate::ParticlesPerSecond(50.0),
+        )
+    };
+    assert!(tap(Vec2::new(16.0, 16.0)).fits_in_sim(&config));
+    assert!(!tap(Vec2::new(4.0, 16.0)).fits_in_sim(&config));
+    let refused = std::panic::catch_unwind(|| {
+        Simulation::empty(config).add_emitter(tap(Vec2::new(4.0, 16.0)))
+    });
+    assert!(refused.is_err());
+
+    // Moved into the left wall at run time: emission lands on its edge.
+    let mut sim = Simulation::empty(config);
+    let handle = sim.add_emitter(tap(Vec2::new(16.0, 16.0)));
+    sim.emitter_mut(handle).unwrap().center = Vec2::new(1.0, 16.0);
+    sim.step();
+    let wall = config.boundary_thickness as f32;
+    assert_eq!(sim.particles().len(), 5);
+    assert!(
+        sim.particles().x.iter().all(|x| x.x >= wall),
+        "{:?}",
+        sim.particles().x
+    );
+}
+
+#[test]
+fn delete_and_recycle_sinks_keep_bookkeeping_consistent() {
+    use emerge::{EmissionRate, Emitter, RegionShape, Sink};
+    let config = SimConfig {
+        gravity: Vec2::new(0.0, -2.0),
+        sleep_threshold: 0.05,
+        ..small_solver_config()
+    };
+    let mut sim = Simulation::new(config, small_spawn_config(8.0))
+        .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)));
+    sim.step_n(30);
+    let resting = sim.particles().len();
+
+    // A pour onto the right half of the floor, drained by a delete sink there.
+    let pour = sim.add_emitter(Emitter::new(
+        RegionShape::Box {
+            half_extents: Vec2::new(2.0, 1.0),
+        },
+        Vec2::new(24.0, 20.0),
+        EmissionRate::MassPerSecond(40.0),
+    ));
+    let pour_tag = sim.emitter(pour).unwrap().tag.unwrap();
+    sim.add_sink(Sink::delete(
+        RegionShape::Box {
+            half_extents: Vec2::new(6.0, 4.0),
+        },
+        Vec2::new(24.0, 4.0),
+    ));
+    let (mut emitted, mut removed) = (0, 0);
+    for _ in 0..60 {
+        sim.step();
+        let snap = sim.diagnostics_snapshot();
+        emitted += snap.emitted_count;
+        removed += snap.sink_removed_count;
+        assert_bookkeeping_consistent(&sim, &[0, pour_tag]);
+    }
+    assert_eq!(emitted, 240, "40 mass/s at particle_mass 1 over 6 s");
+    assert!(removed > 0, "the pour must reach the drain");
+    assert_eq!(sim.particles().len(), resting + emitted - removed);
+
+    // Recycle the sleeping block back into the (paused) pour: count is kept,
+    // every block particle wakes and joins the pour's group.
+    sim.emitter_mut(pour).unwrap().enabled = false;
+    let before = sim.particles().len();
+    let block = sim.particles_with_tag(0).count();
+    sim.add_sink(Sink::recycle(
+        RegionShape::Box {
+            half_extents: Vec2::new(6.0, 6.0),
+        },
+        Vec2::new(8.0, 8.0),
+        pour,
+    ));
+    sim.step();
+    let snap = sim.diagnostics_snapshot();
+    assert_eq!(snap.sink_recycled_count, block);
+    assert_eq!(sim.particles().len(), before - snap.sink_removed_count);
+    assert_eq!(sim.particles_with_tag(0).count(), 0);
+    assert_bookkeeping_consistent(&sim, &[0, pour_tag]);
+}
+
+// --- parallel P2G ---
+
+fn run_parallel_p2g_scene() -> Vec<Particle> {
+    let config = SimConfig {
+        grid_res: 64,
+        parallel_p2g: true,
+        ..SimConfig::standard(64, 0.1, Vec2::new(0.0, -0.3))
+    };
+    // 60x40 cells at spacing 0.5 → 9600 particles, three P2G chunks.
+    let spawn = SpawnRegion {
+        spacing: 0.5,
+        box_size: IVec2::new(60, 40),
+        box_center: Vec2::new(32.0, 24.0),
+        precompute_initial_volumes: true,
+        ..SpawnRegion::default()
+    };
+    let mut sim = Simulation::new(config, spawn)
+        .with_default_material(Box::new(NewtonianFluidMaterial::low_viscosity(1.0, 10.0)));
+    sim.step_n(5);
+    sim.particles().to_vec()
+}
+
+#[test]
+fn parallel_p2g_runs_are_reproducible_and_finite() {
+    let a = run_parallel_p2g_scene();
+    let b = run_parallel_p2g_scene();
+    assert!(a.len() > 8192, "scene must span multiple P2G chunks");
+    for (pa, pb) in a.iter().zip(&b) {
+        assert!(pa.x.is_finite() && pa.v.is_finite());
+        assert_eq!(bytemuck::bytes_of(pa), bytemuck::bytes_of(pb));
+    }
+}
+
+// --- checkpoint ---
+
+fn build_checkpoint_scene() -> (Simulation, u32) {
+    let config = SimConfig {
+        sleep_threshold: 0.02,
+        ..small_solver_config()
+    };
+    let mut sim = Simulation::new(config, small_spawn_config(16.0))
+        .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)))
+        .with_thermal(ThermalDiffusion::new(
+            ThermalConfig {
+                conductivity: 0.6,
+                heat_capacity: 4182.0,
+                ambient: 20.0,
+                grid_cell_size: 1.0,
+                cooling_rate: 0.0,
+            },
+            32,
+        ));
+    let body = sim.add_body(small_spawn_config(10.0).material(0));
+    (sim, body)
+}
+
+#[test]
+fn checkpoint_restore_continues_bit_exactly() {
+    let (mut original, body) = build_checkpoint_scene();
+    original.step_n(15);
+    let mut bytes = Vec::new();
+    original.write_checkpoint(&mut bytes).unwrap();
+
+    let (mut restored, _) = build_checkpoint_scene();
+    // Diverge the target first so the restore has something to overwrite.
+    restored.step_n(2);
+    restored.read_checkpoint(&mut bytes.as_slice()).unwrap();
+    assert_eq!(restored.frame_index(), original.frame_index());
+    assert_eq!(restored.active_count(), original.active_count());
+    let mut tagged_a: Vec<usize> = original.particles_with_tag(body).collect();
+    let mut tagged_b: Vec<usize> = restored.particles_with_tag(body).collect();
+    tagged_a.sort_unstable();
+    tagged_b.sort_unstable();
+    assert_eq!(tagged_a, tagged_b);
+
+    original.step_n(10);
+    restored.step_n(10);
+    let a = original.particles().to_vec();
+    let b = restored.particles().to_vec();
+    assert_eq!(a.len(), b.len());
+    for (pa, pb) in a.iter().zip(&b) {
+        assert_eq!(
+            bytemuck::bytes_of(pa),
+            bytemuck::bytes_of(pb),
+            "restored run diverged from the original"
+        );
+    }
+    assert_eq!(original.active_count(), restored.active_count());
+}
+
+#[test]
+fn checkpoint_rejects_mismatched_materials_and_leaves_sim_untouched() {
+    let (mut original, _) = build_checkpoint_scene();
+    original.step_n(3);
+    let mut bytes = Vec::new();
+    original.write_checkpoint(&mut bytes).unwrap();
+
+    let mut other = Simulation::new(small_solver_config(), small_spawn_config(16.0))
+        .with_default_material(Box::new(NewtonianFluidMaterial::low_viscosity(1.0, 10.0)));
+    let before = other.particles().len();
+    let err = other.read_checkpoint(&mut bytes.as_slice()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(other.particles().len(), before);
+    assert_eq!(other.frame_index(), 0);
+
+    // dt sits after the 20-byte header, grid_res (u64) and grid_cell_size.
+    let mut zero_dt = bytes.clone();
+    zero_dt[32..36].copy_from_slice(&0.0f32.to_le_bytes());
+    let err = original
+        .read_checkpoint(&mut zero_dt.as_slice())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), "dt must be positive");
+    assert_eq!(original.frame_index(), 3);
+
+    assert!(
+        other
+            .read_checkpoint(&mut &b"not a checkpoint"[..])
+            .is_err()
+    );
+}
+
+#[test]
+fn checkpoint_rejects_corrupt_dims_materials_and_ids() {
+    let restore = |bytes: &[u8], target: &mut Simulation| {
+        let err = target.read_checkpoint(&mut &bytes[..]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        err.to_string()
+    };
+
+    // A thermal grid that is not the simulation grid would size its buffers
+    // from the file alone.
+    let oversized = Simulation::new(small_solver_config(), small_spawn_config(16.0)).with_thermal(
+        ThermalDiffusion::new(
+            ThermalConfig {
+                conductivity: 0.6,
+                heat_capacity: 4182.0,
+                ambient: 20.0,
+                grid_cell_size: 1.0,
+                cooling_rate: 0.0,
+            },
+            4096,
+        ),
+    );
+    let mut bytes = Vec::new();
+    oversized.write_checkpoint(&mut bytes).unwrap();
+    let (mut target, _) = build_checkpoint_scene();
+    assert!(restore(&bytes, &mut target).contains("thermal model"));
+
+    // Particles made of a material the target never registered.
+    let mut two_materials = Simulation::new(small_solver_config(), small_spawn_config(16.0))
+        .with_material(1, Box::new(NeoHookeanMaterial::new(10.0, 20.0)));
+    let _ = two_materials.add_body(small_spawn_config(10.0).material(1));
+    bytes.clear();
+    two_materials.write_checkpoint(&mut bytes).unwrap();
+    let mut one_material = Simulation::new(small_solver_config(), small_spawn_config(16.0));
+    assert!(restore(&bytes, &mut one_material).contains("uses material 1"));
+
+    // Two particles sharing an ID. With no tracers the file ends in the
+    // tracer count and the timeline clock, right after the last two IDs.
+    let mut with_ids =
+        Simulation::new(small_solver_config(), small_spawn_config(16.0)).with_particle_ids();
+    bytes.clear();
+    with_ids.write_checkpoint(&mut bytes).unwrap();
+    let n = bytes.len();
+    let last_id: [u8; 8] = bytes[n - 24..n - 16].try_into().unwrap();
+    bytes[n - 32..n - 24].copy_from_slice(&last_id);
+    assert!(restore(&bytes, &mut with_ids).contains("duplicated"));
+    assert_eq!(with_ids.frame_index(), 0);
+}
+
+#[test]
+fn checkpoint_restore_onto_a_larger_domain_resets_reactions_and_stresses() {
+    let wide = SimConfig {
+        grid_res: 48,
+        grid_res_y: Some(40),
+        gravity: Vec2::new(0.0, -2.0),
+        ..small_solver_config()
+    };
+    // Resting on the floor beyond x = 32, where the old grid ends.
+    let spawn = SpawnRegion {
+        box_center: Vec2::new(40.0, 6.0),
+        ..small_spawn_config(0.0)
+    };
+    let mut original = Simulation::new(wide, spawn);
+    original.step_n(3);
+    let mut bytes = Vec::new();
+    original.write_checkpoint(&mut bytes).unwrap();
+
+    let mut restored = Simulation::new(small_solver_config(), small_spawn_config(16.0))
+        .with_boundary_reactions()
+        .with_stress_export();
+    restored.step_n(2);
+    restored.read_checkpoint(&mut bytes.as_slice()).unwrap();
+    assert_eq!(
+        restored.particle_stresses().len(),
+        restored.particles().len()
+    );
+    // Walls away from the old 32x32 box must index the new 48x40 grid.
+    restored.step_n(10);
+    let r = restored.boundary_reactions().unwrap();
+    assert_eq!(r.left.load_profile.len(), 40);
+    assert_eq!(r.bottom.load_profile.len(), 48);
+    assert!(r.bottom.load_profile[32..].iter().sum::<f32>() > 0.0);
+    assert_eq!(
+        restored.particle_stresses().len(),
+        restored.particles().len()
+    );
+}
+
+// --- periodic boundaries ---
+
+#[test]
+fn x_periodic_stream_wraps_around_and_keeps_its_floor() {
+    let config = SimConfig {
+        periodic_x: true,
+        ..small_solver_config()
+    };
+    let spawn = SpawnRegion {
+        box_center: Vec2::new(16.0, 6.0),
+        ..small_spawn_config(16.0)
+    };
+    let mut sim = Simulation::new(config, spawn)
+        .with_default_material(Box::new(NewtonianFluidMaterial::low_viscosity(1.0, 10.0)));
+    let count = sim.particles().len();
+    sim.apply_group_impulse(0, Vec2::new(2.0, 0.0), None);
+    let res = config.grid_res as f32;
+    let floor = config.boundary_thickness.saturating_sub(1) as f32;
+
+    let mut wrapped = false;
+    for _ in 0..120 {
+        sim.step();
+        for &x in &sim.particles().x {
+            assert!(x.is_finite());
+            assert!(
+                (0.0..res).contains(&x.x),
+                "x={} left the periodic span",
+                x.x
+            );
+            assert!(x.y >= floor, "y={} fell through the floor", x.y);
+        }
+        // Spawned in [12, 20]; only a wrap can put material near x = 0.
+        wrapped |= sim.particles().x.iter().any(|x| x.x < 4.0);
+    }
+    assert!(wrapped, "stream never re-entered on the left edge");
+    assert_eq!(
+        sim.particles().len(),
+        count,
+        "wrapping must not lose particles"
+    );
+    let mean_vx = sim.particles().v.iter().map(|v| v.x).sum::<f32>() / count as f32;
+    assert!(
+        mean_vx > 1.0,
+        "no side walls: the stream should keep most of its momentum, mean vx={mean_vx}"
+    );
+
+    // Neighbour queries see across the seam (minimum-image distance).
+    let center = Vec2::new(0.25, 3.0);
+    let radius = 2.0;
+    let mut expected = 0;
+    for x in &sim.particles().x[..sim.active_count()] {
+        let mut d = *x - center;
+        d.x -= (d.x / res).round() * res;
+        if d.length_squared() <= radius * radius {
+            expected += 1;
+        }
+    }
+    assert!(expected > 0, "scene should have material near the seam");
+    assert_eq!(sim.particles_near(center, radius).count(), expected);
+}
+
+// --- rectangular grids ---
+
+#[test]
+fn wide_shallow_channel_bounds_each_axis_and_exports_full_density() {
+    let config = SimConfig {
+        grid_res: 64,
+        grid_res_y: Some(16),
+        ..small_solver_config()
+    };
+    let spawn = SpawnRegion {
+        box_center: Vec2::new(8.0, 7.0),
+        ..small_spawn_config(0.0)
+    };
+    let mut sim = Simulation::new(config, spawn)
+        .with_default_material(Box::new(NewtonianFluidMaterial::low_viscosity(1.0, 10.0)))
+        .with_thermal(ThermalDiffusion::with_dims(
+            ThermalConfig {
+                conductivity: 0.6,
+                heat_capacity: 4182.0,
+                ambient: 20.0,
+                grid_cell_size: 1.0,
+                cooling_rate: 0.0,
+            },
+            config.grid_dims(),
+        ));
+    let count = sim.particles().len();
+    let dims = config.grid_dims().as_vec2();
+    let floor = config.boundary_thickness.saturating_sub(1) as f32;
+
+    for _ in 0..120 {
+        sim.step();
+        for &x in &sim.particles().x {
+            assert!(x.is_finite());
+            assert!(
+                x.cmpge(Vec2::splat(floor)).all() && x.cmple(dims - floor).all(),
+                "{x} left the 64x16 domain"
+            );
+        }
+    }
+    assert_eq!(sim.particles().len(), count);
+    let front = sim.particles().x.iter().map(|x| x.x).fold(0.0, f32::max);
+    assert!(
+        front > 20.0,
+        "dam break should run down a channel wider than it is tall, front at x={front}"
+    );
+    assert!(sim.particles().temperature.iter().all(|t| t.is_finite()));
+
+    let density = emerge::compute_density_grid_in(sim.particles(), config.grid_domain());
+    assert_eq!(density.len(), 64 * 16);
+    let grid_mass: f32 = density.iter().sum();
+    let particle_mass: f32 = sim.particles().mass.iter().sum();
+    assert!(
+        (grid_mass - particle_mass).abs() <= 1.0e-4 * particle_mass,
+        "density export lost mass: grid {grid_mass} vs particles {particle_mass}"
+    );
+}
+
+// --- stable particle ids ---
+
+/// Every particle's ID resolves back to its own index, IDs are unique, and
+/// each particle still carries the temperature it was stamped with (= its ID).
+fn assert_ids_track_particles(sim: &Simulation) {
+    let particles = sim.particles();
+    let mut seen = std::collections::HashSet::new();
+    for i in 0..particles.len() {
+        let id = sim.id_of(i).expect("ids are enabled");
+        assert!(seen.insert(id), "{id:?} issued twice");
+        assert_eq!(sim.index_of(id), Some(i));
+        assert_eq!(
+            particles.temperature[i], id.0 as f32,
+            "{id:?} lost its particle"
+        );
+    }
+}
+
+fn stamp_ids_as_temperature(sim: &mut Simulation) {
+    let ids: Vec<_> = (0..sim.particles().len())
+        .map(|i| sim.id_of(i).unwrap())
+        .collect();
+    for id in ids {
+        sim.update_particle_by_id(id, |p| p.temperature = id.0 as f32);
+    }
+}
+
+#[test]
+fn particle_ids_follow_particles_through_sleep_insert_remove_split_and_checkpoint() {
+    let config = SimConfig {
+        sleep_threshold: 0.05,
+        ..small_solver_config()
+    };
+    let build = || {
+        Simulation::new(config, small_spawn_config(8.0))
+            .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)))
+    };
+    let mut sim = build();
+    assert!(!sim.particle_ids_enabled());
+    assert_eq!(sim.id_of(0), None);
+    sim.enable_particle_ids();
+    stamp_ids_as_temperature(&mut sim);
+    assert_ids_track_particles(&sim);
+
+    sim.step_n(30);
+    assert!(
+        sim.active_count() < sim.particles().len(),
+        "block should sleep"
+    );
+    assert_ids_track_particles(&sim);
+
+    // Inserted ahead of the sleeping zone: every sleeper is rotated back.
+    let body = sim.add_body(small_spawn_config(22.0));
+    stamp_ids_as_temperature(&mut sim);
+    assert_ids_track_particles(&sim);
+
+    let doomed: Vec<_> = (0..sim.particles().len())
+        .filter(|&i| sim.particles().x[i].x < 7.0)
+        .map(|i| sim.id_of(i).unwrap())
+        .collect();
+    assert!(!doomed.is_empty());
+    sim.remove_particles(|p| p.x.x < 7.0);
+    assert!(doomed.iter().all(|&id| sim.index_of(id).is_none()));
+    assert_ids_track_particles(&sim);
+
+    // Split the new body: first children keep the parent IDs, second children
+    // get fresh ones above every ID issued so far.
+    let parents: Vec<_> = sim
+        .particles_with_tag(body)
+        .map(|i| sim.id_of(i).unwrap())
+        .collect();
+    let max_before = (0..sim.particles().len())
+        .map(|i| sim.id_of(i).unwrap())
+        .max()
+        .unwrap();
+    sim.split_particles(|p| p.user_tag == body, 0.1);
+    assert_eq!(sim.particles_with_tag(body).count(), 2 * parents.len());
+    let fresh = sim
+        .particles_with_tag(body)
+        .filter(|&i| sim.id_of(i).unwrap() > max_before)
+        .count();
+    assert_eq!(fresh, parents.len());
+    for &id in &parents {
+        assert_eq!(sim.particle_by_id(id).unwrap().mass, 0.5);
+    }
+    stamp_ids_as_temperature(&mut sim);
+    assert_ids_track_particles(&sim);
+
+    // A user_tag edit through the ID accessor moves the particle's group.
+    sim.update_particle_by_id(parents[0], |p| p.user_tag = 0);
+    assert!(
+        sim.particles_with_tag(0)
+            .any(|i| sim.id_of(i) == Some(parents[0]))
+    );
+
+    // Restored into a simulation without IDs: they come back, and the counter
+    // continues where the original left off.
+    let mut bytes = Vec::new();
+    sim.write_checkpoint(&mut bytes).unwrap();
+    let mut restored = build();
+    restored.read_checkpoint(&mut bytes.as_slice()).unwrap();
+    assert!(restored.particle_ids_enabled());
+    assert_ids_track_particles(&restored);
+    sim.step_n(5);
+    restored.step_n(5);
+    let next = sim.add_body(small_spawn_config(16.0));
+    let next_restored = restored.add_body(small_spawn_config(16.0));
+    let ids_of = |s: &Simulation, tag| {
+        let mut ids: Vec<_> = s.particles_with_tag(tag).map(|i| s.id_of(i)).collect();
+        ids.sort_unstable();
+        ids
+    };
+    assert_eq!(ids_of(&sim, next), ids_of(&restored, next_restored));
+}
+
+// --- particle events ---
+
+fn count_events(events: &[emerge::ParticleEvent], kind: emerge::ParticleEventKind) -> usize {
+    events.iter().filter(|e| e.kind == kind).count()
+}
+
+#[test]
+fn particle_events_report_lifecycle_sleep_projection_and_phase_changes() {
+    use emerge::ParticleEventKind::*;
+    let config = SimConfig {
+        sleep_threshold: 0.05,
+        project_invalid_state: true,
+        ..small_solver_config()
+    };
+    let mut sim = Simulation::new(config, small_spawn_config(8.0))
+        .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)));
+    let n = sim.particles().len();
+    assert!(!sim.particle_events_enabled());
+    sim.enable_particle_events();
+    sim.enable_particle_ids();
+
+    // A non-finite velocity: the pre-P2G projection repairs it.
+    sim.particles_mut().v[3] = Vec2::NAN;
+    sim.step();
+    let events = sim.particle_events();
+    assert_eq!(count_events(events, Projected), 1);
+    assert!(events.iter().all(|e| e.frame == 0 && e.id.is_some()));
+
+    sim.step_n(30);
+    let events = sim.drain_particle_events();
+    let slept = count_events(&events, Slept) - count_events(&events, Woke);
+    assert_eq!(slept, n - sim.active_count());
+    assert!(slept > 0, "block should sleep");
+
+    sim.apply_impulse(Vec2::splat(8.0), 1.0, Vec2::new(0.0, 1.0));
+    let woke = sim.drain_particle_events();
+    assert!(!woke.is_empty() && woke.iter().all(|e| e.kind == Woke && e.tag == 0));
+
+    let body = sim.add_body(small_spawn_config(22.0));
+    let added = sim.drain_particle_events();
+    assert_eq!(
+        count_events(&added, Added),
+        sim.particles_with_tag(body).count()
+    );
+    assert!(added.iter().all(|e| e.tag == body));
+
+    let doomed: Vec<Vec2> = sim
+        .particles()
+        .x
+        .iter()
+        .copied()
+        .filter(|x| x.x < 7.0)
+        .collect();
+    let removed = sim.remove_particles(|p| p.x.x < 7.0);
+    let events = sim.drain_particle_events();
+    assert_eq!(events.len(), removed);
+    assert!(
+        events
+            .iter()
+            .all(|e| e.kind == Removed && doomed.contains(&e.position))
+    );
+
+    let parents = sim.particles_with_tag(body).count();
+    sim.split_particles(|p| p.user_tag == body, 0.1);
+    assert_eq!(count_events(&sim.drain_particle_events(), Split), parents);
+
+    sim.register_material(Box::new(NewtonianFluidMaterial::low_viscosity(1.0, 10.0)));
+    sim.add_phase_rule(move |p| (p.user_tag == body && p.material_id == 0).then_some(1));
+    sim.step();
+    let events = sim.drain_particle_events();
+    assert_eq!(
+        count_events(&events, PhaseChanged { from: 0, to: 1 }),
+        2 * parents
+    );
+
+    sim.disable_particle_events();
+    sim.step();
+    assert!(sim.drain_particle_events().is_empty());
+}
+
+// --- field sampling ---
+
+#[test]
+fn field_sampler_reads_flow_and_attached_scalar_fields_between_steps() {
+    // An elastic block falling as a unit: near-uniform flow inside it.
+    let config = SimConfig {
+        gravity: Vec2::new(0.0, -2.0),
+        ..small_solver_config()
+    };
+    let mut sim = Simulation::new(config, small_spawn_config(16.0))
+        .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)))
+        .with_scalar_field(ScalarDiffusionField::new(
+            ScalarDiffusionConfig::default(),
+            |p| p.scalar_field,
+            |p, d| p.scalar_field += d,
+            32,
+        ));
+    for v in &mut sim.particles_mut().scalar_field {
+        *v = 3.0;
+    }
+    sim.step_n(5);
+    let sampler = sim.field_sampler();
+
+    let center = sim.group_centroid(0);
+    let near: Vec<usize> = (0..sim.particles().len())
+        .filter(|&i| sim.particles().x[i].distance(center) < 1.5)
+        .collect();
+    let mean_v = near.iter().map(|&i| sim.particles().v[i]).sum::<Vec2>() / near.len() as f32;
+    let sample = sampler.sample(center);
+    assert!(sample.density > 0.0 && sample.material == Some(0));
+    assert!(
+        mean_v.y < -0.5 && (sample.velocity - mean_v).length() < 0.1 * mean_v.length(),
+        "sampled {} vs particle mean {mean_v}",
+        sample.velocity
+    );
+    assert!((sampler.scalar(0, center).unwrap() - 3.0).abs() < 0.1);
+    assert_eq!(sampler.sample(Vec2::new(28.0, 28.0)).density, 0.0);
+}
+
+// --- passive tracers ---
+
+#[test]
+fn tracers_follow_the_flow_without_touching_it() {
+    let config = SimConfig {
+        gravity: Vec2::new(0.0, -2.0),
+        ..small_solver_config()
+    };
+    let build = || {
+        Simulation::new(config, small_spawn_config(16.0))
+            .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)))
+    };
+    let mut plain = build();
+    let mut sim = build().with_tracer_history(4);
+    let start = Vec2::new(16.0, 16.0);
+    let ideal = sim.add_tracers([start], 0.0);
+    let lagged = sim.add_tracers([start], 2.0);
+    let outside = sim.add_tracers([Vec2::new(4.0, 28.0)], 0.0);
+    let centroid_before = sim.group_centroid(0);
+
+    sim.step_n(6);
+    plain.step_n(6);
+    for (a, b) in sim
+        .particles()
+        .to_vec()
+        .iter()
+        .zip(plain.particles().to_vec())
+    {
+        assert_eq!(
+            bytemuck::bytes_of(a),
+            bytemuck::bytes_of(&b),
+            "tracers must be massless"
+        );
+    }
+
+    let drop = centroid_before.y - sim.group_centroid(0).y;
+    let tracers = sim.tracers();
+    let ideal_drop = start.y - tracers.x()[ideal.start].y;
+    let lagged_drop = start.y - tracers.x()[lagged.start].y;
+    assert!(drop > 0.3, "block should fall, dropped {drop}");
+    assert!(
+        (ideal_drop - drop).abs() < 0.1 * drop,
+        "{ideal_drop} vs {drop}"
+    );
+    assert!(lagged_drop > 0.0 && lagged_drop < 0.7 * ideal_drop);
+    assert_eq!(tracers.x()[outside.start], Vec2::new(4.0, 28.0));
+
+    let trail: Vec<Vec2> = tracers.history(ideal.start).collect();
+    assert_eq!(trail.len(), 4);
+    assert_eq!(trail[3], tracers.x()[ideal.start]);
+    assert!(trail.windows(2).all(|w| w[1].y < w[0].y));
+
+    // Tracers are checkpoint state; history restarts on restore.
+    let mut bytes = Vec::new();
+    sim.write_checkpoint(&mut bytes).unwrap();
+    let mut restored = build().with_tracer_history(4);
+    restored.read_checkpoint(&mut bytes.as_slice()).unwrap();
+    assert_eq!(restored.tracers().x(), sim.tracers().x());
+    assert_eq!(restored.tracers().v(), sim.tracers().v());
+    assert_eq!(restored.tracers().history(0).count(), 0);
+
+    assert_eq!(sim.remove_tracers(|x| x.y > 20.0), 1);
+    assert_eq!(sim.tracers().len(), 2);
+}
+
+// --- surface contours ---
+
+#[test]
+fn contours_outline_each_body_and_measure_its_surface_height() {
+    let mut sim = Simulation::new(small_solver_config(), small_spawn_config(10.0))
+        .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)));
+    let right = sim.add_body(SpawnRegion {
+        box_center: Vec2::new(22.0, 10.0),
+        ..small_spawn_config(10.0)
+    });
+    sim.step_n(5);
+
+    let interior = emerge::compute_density_grid(sim.particles(), 32)
+        .into_iter()
+        .fold(0.0, f32::max);
+    let options = ContourOptions::new(0.5 * interior);
+    let both = sim.extract_contours(&options);
+    assert_eq!(both.len(), 2, "one closed outline per block");
+    assert!(both.iter().all(|c| c.closed && c.signed_area() > 0.0));
+
+    let only_right = sim.extract_contours(&options.tag(right).smoothing(2).simplify(0.05));
+    assert_eq!(only_right.len(), 1);
+    let outline = &only_right[0];
+    assert!(outline.points.iter().all(|p| p.x > 16.0));
+    assert_eq!(outline.points.len(), outline.normals.len());
+
+    // The free surface sits just above the top row of particles.
+    let x = sim.group_centroid(right).x;
+    let top = (0..sim.particles().len())
+        .filter(|&i| sim.particles().user_tag[i] == right)
+        .map(|i| sim.particles().x[i].y)
+        .fold(f32::MIN, f32::max);
+    let height = surface_height(&only_right, x).unwrap();
+    assert!(
+        height > top - 0.25 && height < top + 0.75,
+        "surface {height} vs top particle {top}"
+    );
+    assert_eq!(surface_height(&only_right, 4.0), None);
+    assert!(
+        sim.extract_contours(&options.material(7)).is_empty(),
+        "no particles of an unused material"
+    );
+}
+
+// --- ray casting ---
+
+#[test]
+fn rays_stop_at_the_first_body_and_report_its_surface() {
+    let mut sim = Simulation::new(small_solver_config(), small_spawn_config(10.0))
+        .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)));
+    let far = sim.add_body(SpawnRegion {
+        box_center: Vec2::new(22.0, 10.0),
+        ..small_spawn_config(10.0)
+    });
+    sim.step_n(3);
+    let left_face = (0..sim.particles().len())
+        .filter(|&i| sim.particles().user_tag[i] == 0)
+        .map(|i| sim.particles().x[i].x)
+        .fold(f32::MAX, f32::min);
+    let origin = Vec2::new(2.0, 10.0);
+
+    let disc = RaycastOptions::new(30.0);
+    let hit = sim.raycast(origin, Vec2::X, &disc).unwrap();
+    assert_eq!(hit.user_tag, 0);
+    assert!((hit.position.x - (left_face - 0.5)).abs() < 0.3, "{hit:?}");
+    assert!(hit.normal.x < -0.5);
+    assert_eq!(hit.position, origin + Vec2::X * hit.distance);
+
+    // The near body can be made transparent, and a ray can see through both.
+    let through = sim.raycast(origin, Vec2::X, &disc.ignore_tag(0)).unwrap();
+    assert_eq!(through.user_tag, far);
+    let all = sim.raycast_all(origin, Vec2::X, &disc);
+    assert!(all.windows(2).all(|w| w[0].distance <= w[1].distance));
+    assert_eq!(all[0], hit);
+    assert!(all.iter().any(|h| h.user_tag == far));
+    let mut seen: Vec<usize> = all.iter().map(|h| h.index).collect();
+    seen.sort_unstable();
+    seen.dedup();
+    assert_eq!(seen.len(), all.len(), "each particle is hit once");
+
+    // Density rays hit the smoothed surface with a clean normal.
+    let interior = emerge::compute_density_grid(sim.particles(), 32)
+        .into_iter()
+        .fold(0.0, f32::max);
+    let smooth = RaycastOptions::new(30.0).density_threshold(0.5 * interior);
+    let surface = sim.raycast(origin, Vec2::X, &smooth).unwrap();
+    assert_eq!(surface.user_tag, 0);
+    assert!((surface.position.x - left_face).abs() < 0.75, "{surface:?}");
+    assert!(surface.normal.dot(-Vec2::X) > 0.95, "{surface:?}");
+    let entries = sim.raycast_all(origin, Vec2::X, &smooth);
+    assert_eq!(entries.len(), 2, "one entry per body");
+    assert_eq!(entries[1].user_tag, far);
+
+    assert_eq!(sim.raycast(origin, -Vec2::X, &disc), None);
+    assert_eq!(
+        sim.raycast(origin, Vec2::X, &RaycastOptions::new(2.0)),
+        None
+    );
+
+    let fan = sim.raycast_fan(origin, Vec2::X, 2.0 * std::f32::consts::FRAC_PI_3, 9, &disc);
+    assert_eq!(fan.len(), 9);
+    assert_eq!(fan[4], Some(hit), "the middle ray looks straight ahead");
+    assert!(
+        fan[0].is_none() && fan[8].is_none(),
+        "edge rays miss at ±60°"
+    );
+}
+
+// --- stress export ---
+
+#[test]
+fn resting_block_is_compressed_more_at_its_base() {
+    let config = SimConfig {
+        gravity: Vec2::new(0.0, -2.0),
+        ..small_solver_config()
+    };
+    let spawn = SpawnRegion {
+        box_center: Vec2::new(16.0, 8.0),
+        ..small_spawn_config(16.0)
+    };
+    let mut sim = Simulation::new(config, spawn)
+        .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)));
+    assert!(sim.particle_stress(0).is_none() && sim.stress_grid().is_none());
+    sim.enable_stress_export();
+    assert_eq!(sim.particle_stresses().len(), sim.particles().len());
+    sim.step_n(30);
+
+    let n = sim.particles().len();
+    let ys: Vec<f32> = sim.particles().x.iter().map(|x| x.y).collect();
+    let (bottom, top) = ys
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(lo, hi), &y| (lo.min(y), hi.max(y)));
+    let mean_pressure = |near: f32| {
+        let rows: Vec<usize> = (0..n).filter(|&i| (ys[i] - near).abs() < 1.0).collect();
+        rows.iter()
+            .map(|&i| sim.particle_stress(i).unwrap().pressure)
+            .sum::<f32>()
+            / rows.len() as f32
+    };
+    let (base, crown) = (mean_pressure(bottom), mean_pressure(top));
+    assert!(
+        base > 0.0 && base > 2.0 * crown.abs(),
+        "base {base} vs crown {crown}"
+    );
+    let state = sim.particle_stress(0).unwrap();
+    assert!(state.von_mises >= 0.0 && state.elastic_volumetric_strain < 0.1);
+    assert_eq!(
+        state.plastic_volumetric_strain, 0.0,
+        "elastic: no plastic flow"
+    );
+
+    // The rasterized field agrees with the particles around the block's middle.
+    let grid = sim.stress_grid().unwrap();
+    let centroid = sim.group_centroid(0).floor();
+    let node = grid[centroid.x as usize * 32 + centroid.y as usize];
+    assert!(-0.5 * (node.x_axis.x + node.y_axis.y) > 0.0);
+    assert_eq!(grid[31 * 32 + 31], glam::Mat2::ZERO);
+
+    sim.disable_stress_export();
+    assert!(sim.particle_stresses().is_empty() && sim.particle_stress(0).is_none());
+}
+
+// --- boundary reactions ---
+
+/// Total particle momentum.
+fn momentum(sim: &Simulation) -> Vec2 {
+    let p = sim.particles();
+    p.mass.iter().zip(&p.v).map(|(&m, &v)| m * v).sum()
+}
+
+#[test]
+fn boundary_reactions_balance_the_blocks_momentum_against_its_weight() {
+    let config = SimConfig {
+        gravity: Vec2::new(0.0, -2.0),
+        ..small_solver_config()
+    };
+    let spawn = |y: f32| SpawnRegion {
+        box_center: Vec2::new(16.0, y),
+        ..small_spawn_config(16.0)
+    };
+    // Drops a block onto the floor (or terrain) and bounces it for 10 s:
+    // returns the mean reactions and the weight.
+    let settle = |sim: &mut Simulation| {
+        sim.step_n(10);
+        let weight = sim.particles().mass.iter().sum::<f32>() * 2.0;
+        let before = momentum(sim);
+        let (mut floor, mut terrain, mut walls, mut profile) =
+            (Vec2::ZERO, Vec2::ZERO, Vec2::ZERO, vec![0.0; 32]);
+        for _ in 0..100 {
+            sim.step();
+            let r = sim.boundary_reactions().unwrap();
+            floor += r.bottom.force / 100.0;
+            terrain += r.terrain.force / 100.0;
+            walls += (r.left.force + r.right.force + r.top.force) / 100.0;
+            for (sum, load) in profile.iter_mut().zip(&r.bottom.load_profile) {
+                *sum += load / 100.0;
+            }
+        }
+        // Impulse balance over the 10 s: what the boundaries pushed back with,
+        // minus gravity, is exactly the momentum the block gained.
+        let gained = momentum(sim) - before;
+        let pushed = -(floor + terrain + walls) * 10.0 - Vec2::Y * weight * 10.0;
+        assert!(
+            (gained - pushed).length() < 1e-3 * weight * 10.0,
+            "gained {gained} vs boundary + gravity impulse {pushed}"
+        );
+        (floor, terrain, walls, profile, weight)
+    };
+
+    let mut sim = Simulation::new(config, spawn(7.0))
+        .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)))
+        .with_boundary_reactions();
+    assert_eq!(sim.boundary_reactions().unwrap().total_force(), Vec2::ZERO);
+    let (floor, terrain, walls, profile, weight) = settle(&mut sim);
+    // The block still bounces, so the 10 s mean is only near its weight.
+    assert!(
+        (floor.y + weight).abs() < 0.25 * weight,
+        "floor carries {} of {weight}",
+        -floor.y
+    );
+    assert!(terrain == Vec2::ZERO && walls.length() < 0.05 * weight);
+    let loaded: Vec<usize> = (0..32).filter(|&x| profile[x] > 1e-3 * weight).collect();
+    assert!(
+        !loaded.is_empty() && loaded.iter().all(|&x| (10..=22).contains(&x)),
+        "only the columns under the block: {loaded:?}"
+    );
+
+    // The same block on heightmap ground at y = 6 loads the terrain instead.
+    let mut sim = Simulation::new(config, spawn(11.0))
+        .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)))
+        .with_boundary(Box::new(HeightmapBoundary::flat_floor(32, 6.0, 0.0)))
+        .with_boundary_reactions();
+    let (floor, terrain, _, _, weight) = settle(&mut sim);
+    assert!((terrain.y + weight).abs() < 0.25 * weight, "{terrain}");
+    assert_eq!(floor, Vec2::ZERO);
+
+    // Sliding on a rough floor dissipates energy through friction; slip does not.
+    let slide = |boundary: Box<dyn BoundaryCondition>| {
+        let mut sim = Simulation::new(config, spawn(7.0))
+            .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)))
+            .with_boundary_reactions();
+        sim.set_boundary_condition(boundary);
+        sim.step_n(20);
+        for v in &mut sim.particles_mut().v {
+            v.x = 3.0;
+        }
+        sim.step();
+        sim.boundary_reactions()
+            .unwrap()
+            .bottom
+            .friction_dissipation
+    };
+    let rough = slide(Box::new(FrictionBoundary::new(2, 0.5)));
+    let smooth = slide(Box::new(SlipBoundary::new(2)));
+    assert!(
+        rough > 0.0 && smooth.abs() < 1e-3 * rough,
+        "{rough} vs {smooth}"
+    );
+    sim.disable_boundary_reactions();
+    assert!(sim.boundary_reactions().is_none());
+}
+
+// --- fragments ---
+
+#[test]
+fn cut_body_splits_into_retagged_fragments_once() {
+    // Untagged particles underneath, cut by the same gap: never a body.
+    let floor = SpawnRegion {
+        box_size: IVec2::new(16, 4),
+        box_center: Vec2::new(16.0, 6.0),
+        ..small_spawn_config(16.0)
+    };
+    let mut solver = Simulation::new(small_solver_config(), floor)
+        .with_fracture_detection(FragmentOptions::default().min_fragment_size(4));
+    let body = solver.add_body(SpawnRegion {
+        box_size: IVec2::new(16, 6),
+        box_center: Vec2::new(16.0, 16.0),
+        ..small_spawn_config(16.0)
+    });
+    assert_eq!(body, 1);
+    // Cut a 4-cell gap through the middle: one tag, two pieces.
+    solver.remove_particles(|p| (p.x.x - 16.0).abs() < 2.0);
+    let total = solver.group_count(body);
+    let untagged = solver.group_count(0);
+
+    let labels = solver.label_fragments(1.0);
+    assert_eq!(labels.fragments.len(), 4);
+    assert_eq!(labels.fragments.iter().filter(|f| f.tag == body).count(), 2);
+
+    let dry_run = solver.split_fragments(&FragmentOptions::default().retag(false));
+    assert_eq!(dry_run.len(), 1);
+    assert_eq!(dry_run[0].tag, body);
+    assert_eq!(solver.group_count(body), total);
+
+    solver.step();
+    let splits = solver.drain_body_splits();
+    assert_eq!(splits.len(), 1, "{splits:?}");
+    let split = &splits[0];
+    assert_eq!(split.tag, body);
+    assert_eq!(split.frame, 0);
+    let [kept, broken] = split.fragments[..] else {
+        panic!("expected two fragments: {split:?}");
+    };
+    assert_eq!(kept.tag, body);
+    assert_eq!(broken.tag, 2);
+    assert_eq!(kept.size + broken.size, total);
+    assert_eq!(solver.group_count(body), kept.size);
+    assert_eq!(solver.group_count(2), broken.size);
+    assert_eq!(solver.group_count(0), untagged);
+    let gap = (kept.centroid.x - broken.centroid.x).abs();
+    assert!((gap - 10.0).abs() < 1.0, "centroids {kept:?} {broken:?}");
+    assert!((solver.group_centroid(2) - broken.centroid).length() < 0.5);
+
+    // Already separated: nothing further to report.
+    solver.step_n(3);
+    assert!(solver.body_splits().is_empty());
+    assert_eq!(solver.label_fragments(1.0).fragments.len(), 4);
+}
+
+// --- kinematic particles ---
+
+#[test]
+fn kinematic_paddle_follows_its_velocity_and_pushes_the_block() {
+    let mut solver = Simulation::empty(small_solver_config()).with_force_field(Box::new(
+        LinearDragField::new(Vec2::ZERO, 0.5, LinearDragField::ALL_MATERIALS),
+    ));
+    let block = solver.add_body(small_spawn_config(14.0));
+    let paddle = solver.add_body(SpawnRegion {
+        box_size: IVec2::new(2, 12),
+        box_center: Vec2::new(6.0, 14.0),
+        // Heavy, so its momentum dominates the nodes it shares with the block.
+        mass_override: Some(20.0 * small_solver_config().particle_mass),
+        ..small_spawn_config(0.0)
+    });
+    let velocity = Vec2::new(2.0, 0.0);
+    solver.set_kinematic_velocity(paddle, velocity);
+    let paddle_start = solver.group_centroid(paddle);
+    let block_start = solver.group_centroid(block);
+
+    solver.step_n(30);
+    // Neither gravity, drag nor the block's resistance deflects it.
+    for i in solver.particles_with_tag(paddle) {
+        assert_eq!(solver.particles().v[i], velocity);
+        assert_eq!(solver.particles().pinned[i], emerge::PIN_KINEMATIC);
+    }
+    let travelled = solver.group_centroid(paddle) - paddle_start;
+    assert!(
+        (travelled - Vec2::new(6.0, 0.0)).length() < 1e-3,
+        "{travelled}"
+    );
+    // Pushed ahead of the paddle rather than passed through.
+    let pushed = solver.group_centroid(block).x - block_start.x;
+    assert!(pushed > 0.5, "block moved {pushed}");
+    let leading = |tag: u32, pick: fn(f32, f32) -> f32, init: f32| {
+        solver
+            .particles_with_tag(tag)
+            .map(|i| solver.particles().x[i].x)
+            .fold(init, pick)
+    };
+    assert!(leading(block, f32::min, f32::MAX) > leading(paddle, f32::max, f32::MIN));
+    assert_eq!(solver.diagnostics_snapshot().max_pinned_particle_speed, 0.0);
+
+    // Position target: lands on it after one step and stops there.
+    let target = solver.group_centroid(paddle) + Vec2::new(-1.0, 0.5);
+    solver.set_kinematic_target(paddle, target);
+    solver.step();
+    assert!((solver.group_centroid(paddle) - target).length() < 1e-3);
+    solver.step_n(3);
+    assert!((solver.group_centroid(paddle) - target).length() < 1e-3);
+    for i in solver.particles_with_tag(paddle) {
+        assert_eq!(solver.particles().v[i], Vec2::ZERO);
+        assert_eq!(solver.particles().pinned[i], emerge::PIN_KINEMATIC);
+    }
+
+    // Released: free again, so drag and the block start slowing it.
+    let held = Vec2::new(-10.0, 5.0);
+    solver.set_kinematic_velocity(paddle, held);
+    solver.release_kinematic(paddle);
+    solver.step_n(2);
+    for i in solver.particles_with_tag(paddle) {
+        assert_eq!(solver.particles().pinned[i], 0);
+        assert!(solver.particles().v[i].length() < held.length());
+    }
+}
+
+#[test]
+fn kinematic_particle_target_is_reached_across_substeps_and_held() {
+    let config = SimConfig {
+        gravity: Vec2::ZERO,
+        ..small_solver_config()
+    };
+    let mut solver = Simulation::empty(config).with_particle_ids();
+    let tag = solver.add_body(small_spawn_config(16.0));
+    let i = solver.particles_with_tag(tag).next().unwrap();
+    let id = solver.id_of(i).unwrap();
+    // Far enough that the speed it needs splits the step into substeps.
+    let target = solver.particles().x[i] + Vec2::new(-3.0, 1.0);
+    assert!(solver.set_particle_kinematic_target(id, target));
+    solver.step();
+    assert!(solver.last_substeps() > 1, "{}", solver.last_substeps());
+    let landed = solver.particle_by_id(id).unwrap();
+    assert!((landed.x - target).length() < 1e-3, "{}", landed.x);
+    assert_eq!(landed.v, Vec2::ZERO);
+    solver.step_n(3);
+    assert!((solver.particle_by_id(id).unwrap().x - target).length() < 1e-3);
+}
+
+// --- constraints ---
+
+#[test]
+fn springs_drag_a_block_and_a_rope_ties_two_together() {
+    let config = SimConfig {
+        gravity: Vec2::ZERO,
+        ..small_solver_config()
+    };
+    // No `with_particle_ids`: the constraint setters turn IDs on, plain
+    // access does not.
+    let mut solver = Simulation::empty(config)
+        .with_default_material(Box::new(NeoHookeanMaterial::new(10.0, 20.0)));
+    let _ = solver.constraints_mut();
+    assert!(!solver.particle_ids_enabled());
+    solver.set_constraints(ConstraintSet::new());
+    assert!(solver.particle_ids_enabled());
+    let left = solver.add_body(small_spawn_config(10.0));
+    let right = solver.add_body(small_spawn_config(22.0));
+    let nearest = |solver: &Simulation, tag: u32, p: Vec2| {
+        let i = solver
+            .particles_with_tag(tag)
+            .min_by(|&a, &b| {
+                let da = solver.particles().x[a].distance(p);
+                let db = solver.particles().x[b].distance(p);
+                da.total_cmp(&db)
+            })
+            .unwrap();
+        solver.id_of(i).unwrap()
+    };
+    // Rope between the facing sides, 3 cells shorter than their gap.
+    let a = nearest(&solver, left, Vec2::new(14.0, 10.0));
+    let b = nearest(&solver, right, Vec2::new(18.0, 22.0));
+    let gap = |solver: &Simulation| {
+        let x = |id| solver.particle_by_id(id).unwrap().x;
+        x(a).distance(x(b))
+    };
+    let start_gap = gap(&solver);
+    let rope = solver.add_constraint(Constraint::DistanceLimit {
+        a,
+        b,
+        max_length: start_gap - 3.0,
+        spring: Spring::new(20.0, 2.0),
+    });
+    let momentum = |solver: &Simulation| -> Vec2 {
+        let p = solver.particles();
+        (0..p.len()).map(|i| p.mass[i] * p.v[i]).sum()
+    };
+    let (left_start, right_start) = (solver.group_centroid(left), solver.group_centroid(right));
+    solver.step_n(20);
+    assert!(
+        gap(&solver) < start_gap - 1.0,
+        "{start_gap} → {}",
+        gap(&solver)
+    );
+    assert!(solver.group_centroid(left).x > left_start.x);
+    assert!(solver.group_centroid(right).x < right_start.x);
+    assert!(momentum(&solver).length() < 1e-2, "{}", momentum(&solver));
+
+    // Drag the right block by a handle of springs around its centre.
+    solver.constraints_mut().remove(rope);
+    let centre = solver.group_centroid(right);
+    let target_shift = Vec2::new(4.0, 0.0);
+    let handles: Vec<_> = solver
+        .particles_near(centre, 2.0)
+        .filter_map(|i| {
+            let id = solver.id_of(i)?;
+            Some((id, solver.particles().x[i]))
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|(particle, x)| {
+            solver.constraints_mut().add(Constraint::PointSpring {
+                particle,
+                target: x + target_shift,
+                spring: Spring::new(20.0, 2.0),
+            })
+        })
+        .collect();
+    assert!(handles.len() > 4);
+    solver.step_n(40);
+    let moved = solver.group_centroid(right) - centre;
+    assert!((moved.x - target_shift.x).abs() < 1.5, "moved {moved}");
+    assert!(moved.y.abs() < 0.5, "moved {moved}");
+    for h in handles {
+        solver.constraints_mut().remove(h);
+    }
+    assert!(solver.constraints().is_empty());
+}
+
+#[test]
+fn timeline_switches_on_wind_and_gravity_at_their_keys() {
+    let config = SimConfig {
+        gravity: Vec2::ZERO,
+        ..small_solver_config()
+    };
+    let builds = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = builds.clone();
+    let step_at_one = |before: f32, after: f32| {
+        Track::new(Interpolation::Step)
+            .key(0.0, before)
+            .key(1.0, after)
+    };
+    let timeline = Timeline::new()
+        .field_parameter("wind", "drag_coefficient", step_at_one(0.0, 2.0))
+        .gravity(
+            Track::new(Interpolation::Linear)
+                .key(0.0, Vec2::ZERO)
+                .key(1.0, Vec2::ZERO)
+                .key(2.0, Vec2::new(0.0, -1.0)),
+        )
+        .config(step_at_one(0.0, 0.5), |config, mu| {
+            config.contact_friction = mu
+        })
+        .material(0, step_at_one(10.0, 20.0), move |mu| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Box::new(NeoHookeanMaterial::new(10.0, mu))
+        });
+    let mut solver = Simulation::empty(config).with_timeline(timeline);
+    solver.add_named_force_field(
+        "wind",
+        Box::new(LinearDragField::new(
+            Vec2::new(2.0, 0.0),
+            1.0,
+            LinearDragField::ALL_MATERIALS,
+        )),
+    );
+    solver.check_timeline().unwrap();
+    let tag = solver.add_body(small_spawn_config(16.0));
+    let velocity = |solver: &Simulation| {
+        let indices: Vec<usize> = solver.particles_with_tag(tag).collect();
+        let sum: Vec2 = indices.iter().map(|&i| solver.particles().v[i]).sum();
+        sum / indices.len() as f32
+    };
+
+    // Before t = 1 the wind's coefficient is keyed to zero.
+    solver.step_n(9);
+    assert!(velocity(&solver).length() < 1e-4, "{}", velocity(&solver));
+    assert_eq!(solver.config().contact_friction, 0.0);
+    assert_eq!(solver.config().gravity, Vec2::ZERO);
+
+    solver.step_n(11);
+    let v = velocity(&solver);
+    assert!((solver.timeline().time() - 2.0).abs() < 1e-4);
+    assert!(v.x > 1.0, "{v}");
+    assert_eq!(solver.config().contact_friction, 0.5);
+    // Gravity ramps in from t = 1 and was last sampled just before t = 2.
+    assert!(
+        solver.config().gravity.y < -0.8,
+        "{}",
+        solver.config().gravity
+    );
+    assert!(v.y < 0.0, "{v}");
+    // The material was rebuilt once per distinct value, not every substep.
+    assert_eq!(builds.load(std::sync::atomic::Ordering::Relaxed), 2);
+}
+
+#[test]
+fn timeline_reports_misspelt_field_and_parameter_names() {
+    let track = || Track::new(Interpolation::Step).key(0.0, 1.0);
+    let mut sim = Simulation::empty(small_solver_config())
+        .with_timeline(Timeline::new().field_parameter("wnd", "drag_coefficient", track()));
+    sim.add_named_force_field(
+        "wind",
+        Box::new(LinearDragField::new(
+            Vec2::X,
+            1.0,
+            LinearDragField::ALL_MATERIALS,
+        )),
+    );
+    let err = sim.check_timeline().unwrap_err();
+    assert!(err.contains("\"wnd\"") && err.contains("\"wind\""), "{err}");
+    // An unknown field is a missing target: nothing is applied.
+    sim.step();
+
+    if cfg!(debug_assertions) {
+        sim.set_timeline(Timeline::new().field_parameter("wind", "drag_coeficient", track()));
+        sim.check_timeline().unwrap();
+        let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| sim.step()));
+        assert!(
+            panic.is_err(),
+            "a rejected parameter name must not pass silently"
+        );
+    }
+}
+
+#[test]
+fn checkpoint_restores_the_timeline_clock_under_an_animated_material() {
+    let build = || {
+        let timeline = Timeline::new().material(
+            0,
+            Track::new(Interpolation::Step)
+                .key(0.0, 10.0)
+                .key(1.0, 20.0),
+            |mu| Box::new(NeoHookeanMaterial::new(10.0, mu)),
+        );
+        let mut sim = Simulation::empty(small_solver_config()).with_timeline(timeline);
+        let _ = sim.add_body(small_spawn_config(16.0));
+        sim
+    };
+    let mut original = build();
+    original.step_n(15);
+    assert!(original.timeline().time() > 1.0);
+    let mut bytes = Vec::new();
+    original.write_checkpoint(&mut bytes).unwrap();
+
+    // Material 0 is still the fallback here; the channel owns it, so the
+    // params mismatch is not an error.
+    let mut restored = build();
+    restored.read_checkpoint(&mut bytes.as_slice()).unwrap();
+    assert_eq!(restored.timeline().time(), original.timeline().time());
+
+    original.step_n(5);
+    restored.step_n(5);
+    for (pa, pb) in original
+        .particles()
+        .to_vec()
+        .iter()
+        .zip(&restored.particles().to_vec())
+    {
+        assert_eq!(bytemuck::bytes_of(pa), bytemuck::bytes_of(pb));
+    }
+}
+
+#[test]
+fn scene_file_saves_loads_and_builds_its_bodies() {
+    let text = "\
+[config]
+grid_res = 32
+dt = 0.1
+adaptive_timestep = true
+
+[material jelly]
+model = neo_hookean
+e_pa = 5000
+nu = 0.3
+rho_kg_m3 = 1000
+
+[material water]
+model = newtonian
+rho_kg_m3 = 1000
+eta_pa_s = 0.001
+bulk_modulus_pa = 2e5
+
+[field wind]
+kind = linear_drag
+target_velocity = 1 0
+drag_coefficient = 0.1
+materials = water
+
+[thermal]
+conductivity = 0.6
+heat_capacity = 4000
+
+[body]
+material = jelly
+shape = disk 3
+center = 10 16
+spacing = 0.5
+mass = material
+tag = 7
+velocity = 0.5 0
+angular_velocity = 0.2
+
+[body]
+material = water
+center = 22 10
+size = 6 6
+spacing = 0.5
+temperature = 300
+";
+    let scene = Scene::parse(text).unwrap();
+    let path = std::env::temp_dir().join(format!("emerge-scene-{}.scene", std::process::id()));
+    scene.save(&path).unwrap();
+    assert_eq!(Scene::read(&path).unwrap(), scene);
+    let mut solver = Scene::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(solver.force_field_names(), ["wind"]);
+    let jelly: Vec<usize> = solver.particles_with_tag(7).collect();
+    let water: Vec<usize> = solver.particles_with_tag(8).collect();
+    assert!(!jelly.is_empty() && !water.is_empty());
+    assert_eq!(jelly.len() + water.len(), solver.particles().len());
+    let particles = solver.particles();
+    assert!(jelly.iter().all(|&i| particles.material_id[i] == 0));
+    // The body velocity adds to the spin instead of replacing it.
+    let mean = jelly.iter().map(|&i| particles.v[i]).sum::<Vec2>() / jelly.len() as f32;
+    assert!(mean.distance(Vec2::new(0.5, 0.0)) < 1e-3, "{mean}");
+    assert!(jelly.iter().all(|&i| {
+        let spin = 0.2 * (particles.x[i] - Vec2::new(10.0, 16.0)).perp();
+        (particles.v[i] - Vec2::new(0.5, 0.0)).distance(spin) < 0.1
+    }));
+    assert!(
+        water
+            .iter()
+            .all(|&i| particles.material_id[i] == 1 && particles.temperature[i] == 300.0)
+    );
+
+    solver.step_n(5);
+    assert!(solver.particles().x.iter().all(|x| x.is_finite()));
+
+    let bad = std::env::temp_dir().join(format!("emerge-bad-{}.scene", std::process::id()));
+    std::fs::write(&bad, "[config]\ndt = soon\n").unwrap();
+    let err = Scene::read(&bad).unwrap_err();
+    std::fs::remove_file(&bad).unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(
+        err.to_string()
+            .ends_with(".scene:2:6: expected a number, found `soon`"),
+        "{err}"
+    );
+}
+
+#[test]
+fn imported_points_and_painted_bitmaps_become_tagged_bodies() {
+    let config = small_solver_config();
+    let mut solver = Simulation::empty(config)
+        .with_default_material(Box::new(NeoHookeanMaterial::new(50.0, 100.0)))
+        .with_material(
+            1,
+            Box::new(NewtonianFluidMaterial::new(4.0, 0.1, 10.0, 4.0)),
+        );
+
+    // 4x2 mask: water over jelly, right column empty (unmapped white).
+    let [jelly, water, air] = [[200, 120, 40, 255], [40, 80, 255, 255], [255; 4]];
+    let bitmap = Bitmap::new(
+        4,
+        2,
+        vec![water, water, water, air, jelly, jelly, jelly, air],
+    );
+    let palette = Palette::new().with(0xc87828, 0).with(0x2850ff, 1);
+    let spawn = SpawnRegion::for_sim(&config).spacing(0.5);
+    let painted = PointSet::from_bitmap(&bitmap, &palette, Vec2::new(8.0, 4.0), 2.0, 0.5);
+    let terrain = solver.add_particles(build_particles_from_points(&config, spawn, &painted));
+    let counts = solver.material_particle_counts();
+    assert_eq!((counts[&0], counts[&1]), (48, 48));
+    let particles = solver.particles();
+    assert!(solver.particles_with_tag(terrain).all(|i| {
+        let expected = if particles.x[i].y > 6.0 { 1 } else { 0 };
+        particles.material_id[i] == expected && particles.x[i].x < 14.0
+    }));
+
+    // An `emerge run` particle dump reads straight back in.
+    let dump = "x,y,vx,vy,mass,material,tag,temperature,j\n\
+                0,0,1,0,2,0,5,300,1\n0.5,0,1,0,2,0,5,300,1\n\
+                0,0.5,1,0,2,0,5,300,1\n0.5,0.5,1,0,2,0,5,300,1\n";
+    let points = PointSet::read_csv(dump.as_bytes())
+        .unwrap()
+        .centered_at(Vec2::new(24.0, 20.0));
+    let rock = solver.add_particles(build_particles_from_points(&config, spawn, &points));
+    let rock: Vec<usize> = solver.particles_with_tag(rock).collect();
+    assert_eq!(rock.len(), 4);
+    let particles = solver.particles();
+    assert!(rock.iter().all(|&i| particles.v[i] == Vec2::X
+        && particles.mass[i] == 2.0
+        && particles.temperature[i] == 300.0
+        && particles.x[i].distance(Vec2::new(24.0, 20.0)) < 0.5));
+
+    solver.step_n(5);
+    assert!(solver.particles().x.iter().all(|x| x.is_finite()));
+
+    // Points off the grid or of an unregistered material are reported, not panicked on.
+    let check = |csv: &str| {
+        let points = PointSet::read_csv(csv.as_bytes()).unwrap();
+        let particles =
+            build_particles_from_points(&config, SpawnRegion::for_sim(&config), &points);
+        solver.check_particles(&particles)
+    };
+    assert!(check("x,y\n16,16\n").is_ok());
+    assert!(
+        check("x,y\n16,40\n")
+            .unwrap_err()
+            .contains("inside the simulation domain")
+    );
+    assert!(
+        check("x,y,material\n16,16,7\n")
+            .unwrap_err()
+            .contains("uses material 7")
+    );
+}
+
+#[test]
+fn spawn_shapes_turn_spin_and_combine() {
+    let config = small_solver_config();
+    let center = Vec2::new(16.0, 16.0);
+    let region = SpawnRegion::for_sim(&config).at(center).spacing(0.5);
+
+    // A plank tilted 45°: same lattice as the level one, turned into place.
+    let plank = region.box_of(IVec2::new(16, 2));
+    let tilted = build_particles(&config, plank.rotation(FRAC_PI_4));
+    assert_eq!(tilted.len(), build_particles(&config, plank).len());
+    let along = Vec2::from_angle(FRAC_PI_4);
+    assert!(tilted.iter().all(|p| {
+        let r = p.x - center;
+        r.dot(along).abs() <= 8.0 + 1e-4 && along.perp_dot(r).abs() <= 1.0 + 1e-4
+    }));
+
+    // A spinning disk moves rigidly, and its APIC gradient carries the spin.
+    let spinning = build_particles(&config, region.disk(6.0).angular_velocity(2.0));
+    let spin = Mat2::from_cols(Vec2::new(0.0, 2.0), Vec2::new(-2.0, 0.0));
+    assert!(spinning.iter().all(|p| {
+        (p.v - 2.0 * (p.x - center).perp()).length() < 1e-5 && p.velocity_gradient == spin
+    }));
+
+    // An L-shaped (concave) polygon leaves its notch empty.
+    let l_shape = SpawnShape::polygon(
+        &[(0, 0), (6, 0), (6, 2), (2, 2), (2, 6), (0, 6)]
+            .map(|(x, y)| Vec2::new(x as f32 - 3.0, y as f32 - 3.0)),
+    );
+    let l_particles = build_particles(&config, region.shape(l_shape));
+    assert!(!l_particles.is_empty());
+    assert!(
+        l_particles
+            .iter()
+            .all(|p| p.x.x - center.x <= -1.0 || p.x.y - center.y <= -1.0)
+    );
+
+    // A cup — a box minus a raised inner box — holds water in its hollow.
+    let cup = SpawnShape::rect(Vec2::ZERO, Vec2::new(8.0, 6.0), 0.0).difference(SpawnShape::rect(
+        Vec2::new(0.0, 1.0),
+        Vec2::new(6.0, 5.0),
+        0.0,
+    ));
+    let cup = region.shape(cup);
+    assert_eq!(cup.box_size, IVec2::new(17, 13));
+    let mut solver = Simulation::empty(config)
+        .with_default_material(Box::new(NeoHookeanMaterial::new(50.0, 100.0)))
+        .with_material(
+            1,
+            Box::new(NewtonianFluidMaterial::new(4.0, 0.1, 10.0, 4.0)),
+        );
+    let cup = solver.add_body(cup);
+    let water = solver.add_body(
+        region
+            .at(center + Vec2::new(0.0, 1.0))
+            .box_of(IVec2::new(10, 6))
+            .material(1),
+    );
+    let particles = solver.particles();
+    assert!(solver.particles_with_tag(cup).all(|i| {
+        let r = particles.x[i] - center;
+        r.x.abs() >= 6.0 || r.y <= -4.0
+    }));
+    assert_eq!(solver.particles_with_tag(water).count(), 240);
+    solver.step_n(5);
+    assert!(solver.particles().x.iter().all(|x| x.is_finite()));
+}